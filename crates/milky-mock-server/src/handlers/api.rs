@@ -15,7 +15,8 @@ pub async fn api_handler(
     let broadcast_message = payload.to_string();
 
     let clients = state.clients.lock().unwrap();
-    for tx in clients.values().cloned() {
+    for tx in clients.values() {
+        let tx = tx.clone();
         let broadcast_message = broadcast_message.clone();
         tokio::spawn(async move {
             if tx.send(broadcast_message.clone()).await.is_err() {
@@ -1,32 +1,26 @@
 use std::sync::Arc;
 
 use log::{LevelFilter, error, info};
+use milky_rust_sdk::client::OverflowPolicy;
 use milky_rust_sdk::prelude::*;
 use milky_rust_sdk::utils::get_plain_text_from_segments;
 use milky_rust_sdk::{Communication, MilkyClient, Result};
 use milky_rust_sdk::{WebSocketConfig, logger};
-use tokio::sync::mpsc;
-
-// 辅助函数，用于创建文本消息段
-fn text_segment(text: &str) -> OutgoingSegment {
-    OutgoingSegment::Text(TextData {
-        text: text.to_string(),
-    })
-}
 
 #[tokio::main]
 async fn main() -> Result<()> {
     logger::init_logger(Some(LevelFilter::Info)); // 初始化日志
 
-    // 创建事件通道
-    let (event_tx, mut event_rx) = mpsc::channel::<Event>(100);
-
-    // 初始化 MilkyClient
+    // 初始化 MilkyClient，同时创建事件通道
     // 示例中使用的是WebSocket的通信方式，如果你想通过WebHook方式与服务端通信，可以参考下面的代码
     // let wh_config = WebHookConfig::new(None, 8080, "http://127.0.0.1:3000".to_string(), None);
-    // let client = MilkyClient::new(Communication::WebHook(wh_config), event_tx)?;
+    // let (client, mut event_rx) = MilkyClient::builder(Communication::WebHook(wh_config)).build()?;
     let ws_config = WebSocketConfig::new("ws://127.0.0.1:3002".to_string(), None);
-    let client = MilkyClient::new(Communication::WebSocket(ws_config), event_tx)?;
+    let (client, mut event_rx) = MilkyClient::builder(Communication::WebSocket(ws_config))
+        // 事件处理跟不上接收速度时丢弃最早的事件，而不是暂停接收
+        .channel_capacity(256)
+        .overflow_policy(OverflowPolicy::DropOldest)
+        .build()?;
     let client = Arc::new(client);
 
     // 连接到件流
@@ -49,20 +43,23 @@ async fn main() -> Result<()> {
                 } => {
                     match message_event {
                         MessageEvent::Friend(friend_msg) => {
-                            let plain_text = get_plain_text_from_segments(&friend_msg.message.segments);
+                            let plain_text =
+                                get_plain_text_from_segments(&friend_msg.message.segments);
                             info!(
                                 "收到好友消息: {} (QQ: {}) - {}",
-                                friend_msg.friend.remark,
-                                friend_msg.message.sender_id,
-                                plain_text
+                                friend_msg.friend.remark, friend_msg.message.sender_id, plain_text
                             );
 
                             // 示例：复读
                             if plain_text.starts_with("/echo") {
-                                let reply_segments =
-                                    vec![text_segment(plain_text.replace("/echo", "").trim())];
+                                let reply_segments = vec![OutgoingSegment::text(
+                                    plain_text.replace("/echo", "").trim(),
+                                )];
                                 match client_for_task
-                                    .send_private_message(friend_msg.message.sender_id, reply_segments)
+                                    .send_private_message(
+                                        friend_msg.message.sender_id,
+                                        reply_segments,
+                                    )
                                     .await
                                 {
                                     Ok(resp) => info!("自动回复成功: seq={}", resp.message_seq),
@@ -71,7 +68,8 @@ async fn main() -> Result<()> {
                             }
                         }
                         MessageEvent::Group(group_msg) => {
-                            let plain_text = get_plain_text_from_segments(&group_msg.message.segments);
+                            let plain_text =
+                                get_plain_text_from_segments(&group_msg.message.segments);
                             info!(
                                 "收到群消息: [{}] {} (QQ: {}) - {}",
                                 group_msg.group.group_name,
@@ -81,11 +79,11 @@ async fn main() -> Result<()> {
                             );
                         }
                         MessageEvent::Temp(temp_msg) => {
-                            let plain_text = get_plain_text_from_segments(&temp_msg.message.segments);
+                            let plain_text =
+                                get_plain_text_from_segments(&temp_msg.message.segments);
                             info!(
                                 "收到临时消息: QQ: {} - {}",
-                                temp_msg.message.sender_id,
-                                plain_text
+                                temp_msg.message.sender_id, plain_text
                             );
                         }
                     }
@@ -121,7 +119,9 @@ async fn main() -> Result<()> {
 
     // 发送私聊消息 (请替换为有效的 user_id)
     let user_id_to_send: i64 = 123456789; // 示例QQ号
-    let message_to_send = vec![text_segment("你好，这是一个来自 Vivian SDK 的测试消息！")];
+    let message_to_send = vec![OutgoingSegment::text(
+        "你好，这是一个来自 Vivian SDK 的测试消息！",
+    )];
     match client
         .send_private_message(user_id_to_send, message_to_send)
         .await
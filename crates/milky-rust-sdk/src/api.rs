@@ -1,5 +1,29 @@
+//! 协议 API 的请求参数、响应数据与客户端方法
+//!
+//! 启用 `wasm` 特性编译到 wasm32 时，不依赖本地文件系统与 tokio 的 API 方法同样提供给
+//! `wasm::WasmClient`，文件下载、上传与头像缓存等功能只在原生平台可用。
+
+pub mod action;
+#[cfg(feature = "client")]
+pub mod avatar;
+#[cfg(feature = "client")]
+pub mod download;
 pub mod file;
+#[cfg(feature = "client")]
+pub mod file_tree;
 pub mod friend;
 pub mod group;
 pub mod message;
+#[cfg(feature = "schemars")]
+pub mod schema;
 pub mod system;
+#[cfg(feature = "client")]
+pub mod upload;
+
+/// 提供 API 方法的客户端类型
+#[cfg(feature = "client")]
+pub(crate) type ApiClient = crate::MilkyClient;
+
+/// 提供 API 方法的客户端类型
+#[cfg(not(feature = "client"))]
+pub(crate) type ApiClient = crate::wasm::WasmClient;
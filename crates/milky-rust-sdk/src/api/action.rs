@@ -0,0 +1,116 @@
+//! 以类型描述 API 操作，便于在下游 crate 中扩展 SDK 尚未封装的接口
+//!
+//! 为自定义的类型实现 [`ApiAction`] 后，即可通过 [`MilkyClient::call`](crate::MilkyClient::call) 以强类型的参数与响应调用该接口：
+//!
+//! ```no_run
+//! use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::api::action::ApiAction;
+//! use serde::{Deserialize, Serialize};
+//!
+//! /// 获取自定义表情的 URL 列表
+//! struct GetCustomFaceUrlList;
+//!
+//! #[derive(Serialize)]
+//! struct GetCustomFaceUrlListRequest {}
+//!
+//! #[derive(Deserialize)]
+//! struct GetCustomFaceUrlListResponse {
+//!     urls: Vec<String>,
+//! }
+//!
+//! impl ApiAction for GetCustomFaceUrlList {
+//!     const NAME: &'static str = "get_custom_face_url_list";
+//!     type Params = GetCustomFaceUrlListRequest;
+//!     type Response = GetCustomFaceUrlListResponse;
+//! }
+//!
+//! # async fn run(client: MilkyClient) -> milky_rust_sdk::Result<()> {
+//! let faces = client
+//!     .call::<GetCustomFaceUrlList>(GetCustomFaceUrlListRequest {})
+//!     .await?;
+//! println!("共有 {} 个自定义表情", faces.urls.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::ApiClient;
+use crate::error::Result;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 一个 API 操作的名称、请求参数与响应数据类型
+pub trait ApiAction {
+    /// API 操作的名称，例如 "get_login_info"
+    const NAME: &'static str;
+    /// 请求参数的类型
+    type Params: Serialize;
+    /// 响应数据的类型
+    type Response: DeserializeOwned;
+}
+
+impl ApiClient {
+    /// 调用由 [`ApiAction`] 描述的 API
+    ///
+    /// # 参数
+    /// * `params`: 请求参数
+    ///
+    /// # 返回
+    /// 成功则返回反序列化后的响应数据
+    pub async fn call<A: ApiAction>(&self, params: A::Params) -> Result<A::Response> {
+        self.send_request(A::NAME, params).await
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use axum::Router;
+    use axum::routing::post;
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+
+    /// 将请求参数中的文本原样返回的测试接口
+    struct Echo;
+
+    #[derive(Serialize)]
+    struct EchoRequest {
+        text: String,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct EchoResponse {
+        text: String,
+    }
+
+    impl ApiAction for Echo {
+        const NAME: &'static str = "echo";
+        type Params = EchoRequest;
+        type Response = EchoResponse;
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let app = Router::new().route(
+            "/api/echo",
+            post(|axum::Json(params): axum::Json<Value>| async move {
+                axum::Json(json!({"status": "ok", "retcode": 0, "data": params}))
+            }),
+        );
+        let (client, _rx) = test_util::client(test_util::serve(app).await);
+
+        let response = client
+            .call::<Echo>(EchoRequest {
+                text: "hello".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            EchoResponse {
+                text: "hello".to_string()
+            }
+        );
+    }
+}
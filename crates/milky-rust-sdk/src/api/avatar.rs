@@ -0,0 +1,152 @@
+//! 提供了获取用户与群组头像图片的辅助功能
+//!
+//! 头像通过 QQ 的公开头像服务获取，获取到的图片会在内存中缓存一小时，
+//! 适合在生成欢迎卡片、排行榜图片等场景中频繁使用。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+
+use bytes::Bytes;
+use std::time::Duration;
+
+/// 头像图片的缓存时长
+const AVATAR_TTL: Duration = Duration::from_secs(3600);
+
+/// 用户头像服务的地址
+const USER_AVATAR_HOST: &str = "https://q1.qlogo.cn";
+
+/// 群组头像服务的地址
+const GROUP_AVATAR_HOST: &str = "https://p.qlogo.cn";
+
+/// 用户头像的边长，头像服务只提供以下几种尺寸
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AvatarSize {
+    /// 40 像素
+    Small,
+    /// 100 像素
+    Medium,
+    /// 140 像素
+    Large,
+    /// 640 像素
+    #[default]
+    Original,
+}
+
+impl AvatarSize {
+    /// 头像的边长（像素）
+    pub fn pixels(self) -> u32 {
+        match self {
+            AvatarSize::Small => 40,
+            AvatarSize::Medium => 100,
+            AvatarSize::Large => 140,
+            AvatarSize::Original => 640,
+        }
+    }
+}
+
+impl MilkyClient {
+    /// 获取用户头像图片
+    ///
+    /// # 参数
+    /// * `user_id`: 用户QQ号
+    /// * `size`: 头像边长
+    ///
+    /// # 返回
+    /// 成功则返回头像图片的原始字节
+    pub async fn get_user_avatar(&self, user_id: i64, size: AvatarSize) -> Result<Bytes> {
+        let host = self.avatar_base_url.as_deref().unwrap_or(USER_AVATAR_HOST);
+        let url = format!("{host}/g?b=qq&nk={user_id}&s={}", size.pixels());
+        self.fetch_avatar(url).await
+    }
+
+    /// 获取群组头像图片
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    ///
+    /// # 返回
+    /// 成功则返回 640 像素头像图片的原始字节
+    pub async fn get_group_avatar(&self, group_id: i64) -> Result<Bytes> {
+        let host = self.avatar_base_url.as_deref().unwrap_or(GROUP_AVATAR_HOST);
+        let url = format!("{host}/gh/{group_id}/{group_id}/640/");
+        self.fetch_avatar(url).await
+    }
+
+    /// 下载头像图片，优先使用缓存
+    async fn fetch_avatar(&self, url: String) -> Result<Bytes> {
+        if let Some(image) = self.avatar_cache.get(&url) {
+            return Ok(image);
+        }
+        let response = self.http_client().get(&url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(MilkyError::HttpApiError {
+                status,
+                message: format!("获取头像 {url} 失败"),
+            });
+        }
+        let image = response.bytes().await?;
+        self.avatar_cache.insert(url, image.clone(), AVATAR_TTL);
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use axum::Router;
+    use axum::extract::Query;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_avatar_cache_and_errors() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new()
+            .route(
+                "/g",
+                get(
+                    move |Query(query): Query<HashMap<String, String>>| async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        format!("{}@{}", query["nk"], query["s"])
+                    },
+                ),
+            )
+            .route(
+                "/gh/{group_id}/{same}/640/",
+                get(|| async { StatusCode::NOT_FOUND }),
+            );
+        let addr = test_util::serve(app).await;
+        let (mut client, _rx) = test_util::client(test_util::OFFLINE);
+        client.avatar_base_url = Some(format!("http://{addr}"));
+
+        let avatar = client
+            .get_user_avatar(10000, AvatarSize::Large)
+            .await
+            .unwrap();
+        assert_eq!(avatar, Bytes::from("10000@140"));
+        // 第二次请求直接使用缓存，不再访问头像服务
+        let cached = client
+            .get_user_avatar(10000, AvatarSize::Large)
+            .await
+            .unwrap();
+        assert_eq!(cached, avatar);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        // 不同尺寸是不同的头像
+        client
+            .get_user_avatar(10000, AvatarSize::Small)
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        let err = client.get_group_avatar(123456).await.unwrap_err();
+        assert!(
+            matches!(err, MilkyError::HttpApiError { status, .. } if status == StatusCode::NOT_FOUND)
+        );
+    }
+}
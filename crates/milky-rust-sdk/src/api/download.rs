@@ -0,0 +1,393 @@
+//! 提供了将消息资源或文件下载到本地磁盘的辅助功能
+
+use crate::api::file_tree::GroupFileEntry;
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+use crate::logger::{debug, info, warn};
+use crate::runtime;
+use crate::utils::hash::{to_hex, tri_sha1_file};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt, stream};
+use milky_types::group::GroupFile;
+use sha1::{Digest, Sha1};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// 下载完成后的文件信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadedFile {
+    /// 写入磁盘的字节数
+    pub size: u64,
+    /// 文件内容的 SHA1 哈希值（小写十六进制）
+    pub sha1: String,
+}
+
+/// 批量下载群文件夹时的并发数
+const FOLDER_DOWNLOAD_CONCURRENCY: usize = 4;
+
+/// 批量下载群文件夹时单个文件的最大重试次数
+const FOLDER_DOWNLOAD_RETRIES: u32 = 2;
+
+/// 批量下载中成功保存的单个群文件
+#[derive(Debug, Clone)]
+pub struct SavedGroupFile {
+    /// 文件在群文件系统中相对于所下载文件夹的路径
+    pub remote_path: String,
+    /// 文件保存到的本地路径
+    pub local_path: PathBuf,
+    /// 群文件信息
+    pub file: GroupFile,
+    /// 下载结果
+    pub download: DownloadedFile,
+}
+
+/// 批量下载群文件夹的清单
+#[derive(Debug, Default)]
+pub struct FolderManifest {
+    /// 成功保存的文件
+    pub saved: Vec<SavedGroupFile>,
+    /// 重试后仍下载失败的文件及最后一次的错误
+    pub failed: Vec<(GroupFile, MilkyError)>,
+}
+
+/// 下载进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// 已下载的字节数
+    pub downloaded: u64,
+    /// 文件总字节数，服务器未返回 `Content-Length` 时为 `None`
+    pub total: Option<u64>,
+}
+
+impl MilkyClient {
+    /// 将资源下载并保存到本地文件
+    ///
+    /// 传入 `http(s)://` 链接时直接下载；否则视为资源ID，先通过 [`MilkyClient::get_resource_temp_url`] 获取临时下载链接。
+    /// 私聊文件和群文件可先通过 [`MilkyClient::get_private_file_download_url`] 或
+    /// [`MilkyClient::get_group_file_download_url`] 获取下载链接后传入。
+    ///
+    /// 响应内容以流的方式写入磁盘，不会整体读入内存。下载失败时会删除已写入的部分文件。
+    ///
+    /// # 参数
+    /// * `resource`: 资源ID或下载链接
+    /// * `path`: 保存的目标文件路径，已存在时会被覆盖
+    ///
+    /// # 返回
+    /// 成功则返回包含文件大小和哈希值的 [`DownloadedFile`]
+    pub async fn download_to(
+        &self,
+        resource: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadedFile> {
+        self.download_to_with_progress(resource, path, |_| ControlFlow::Continue(()))
+            .await
+    }
+
+    /// 将资源下载并保存到本地文件，并在每收到一块数据时报告进度
+    ///
+    /// # 参数
+    /// * `resource`: 资源ID或下载链接，规则同 [`MilkyClient::download_to`]
+    /// * `path`: 保存的目标文件路径，已存在时会被覆盖
+    /// * `on_progress`: 进度回调，返回 `ControlFlow::Break(())` 时中止下载
+    ///
+    /// # 返回
+    /// 成功则返回包含文件大小和哈希值的 [`DownloadedFile`]；被回调中止时返回 [`MilkyError::Cancelled`]
+    pub async fn download_to_with_progress<F>(
+        &self,
+        resource: &str,
+        path: impl AsRef<Path>,
+        on_progress: F,
+    ) -> Result<DownloadedFile>
+    where
+        F: FnMut(DownloadProgress) -> ControlFlow<()> + Send + 'static,
+    {
+        let path = path.as_ref();
+        let result = async {
+            let chunks = self.download_stream(resource, on_progress).await?;
+            debug!("正在下载 {resource} 至 {}", path.display());
+            write_chunks_to(chunks, path).await
+        }
+        .await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        result
+    }
+
+    /// 下载资源并校验其 TriSHA1 哈希值
+    ///
+    /// 适用于 [`FriendFileUpload`](milky_types::EventKind::FriendFileUpload) 事件或文件消息段中带有 `file_hash` 的文件。
+    ///
+    /// # 参数
+    /// * `resource`: 资源ID或下载链接，规则同 [`MilkyClient::download_to`]
+    /// * `path`: 保存的目标文件路径，已存在时会被覆盖
+    /// * `file_hash`: 协议端报告的 TriSHA1 哈希值
+    ///
+    /// # 返回
+    /// 成功则返回 [`DownloadedFile`]；哈希值不一致时删除已下载的文件并返回 [`MilkyError::HashMismatch`]
+    pub async fn download_verified(
+        &self,
+        resource: &str,
+        path: impl AsRef<Path>,
+        file_hash: &str,
+    ) -> Result<DownloadedFile> {
+        let path = path.as_ref();
+        let file = self.download_to(resource, path).await?;
+        let actual = tri_sha1_file(path).await?;
+        if !actual.eq_ignore_ascii_case(file_hash) {
+            let _ = tokio::fs::remove_file(path).await;
+            return Err(MilkyError::HashMismatch {
+                expected: file_hash.to_string(),
+                actual,
+            });
+        }
+        Ok(file)
+    }
+
+    /// 以数据块流的形式下载资源，适合下载较大的群文件或需要显示进度的场景
+    ///
+    /// 丢弃返回的流即可随时中止下载。
+    ///
+    /// # 参数
+    /// * `resource`: 资源ID或下载链接，规则同 [`MilkyClient::download_to`]
+    /// * `on_progress`: 进度回调，每收到一块数据调用一次，返回 `ControlFlow::Break(())` 时流会产出
+    ///   [`MilkyError::Cancelled`] 并结束
+    ///
+    /// # 返回
+    /// 成功则返回按顺序产出数据块的流
+    pub async fn download_stream<F>(
+        &self,
+        resource: &str,
+        on_progress: F,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static>
+    where
+        F: FnMut(DownloadProgress) -> ControlFlow<()> + Send + 'static,
+    {
+        let mut url = self.resolve_download_url(resource).await?;
+        let mut response = self.http_client().get(&url).send().await?;
+        if response.status().is_client_error() && url != resource {
+            // 缓存的临时链接可能已失效，重新获取后再试一次
+            self.invalidate_temp_url(resource);
+            url = self.resolve_download_url(resource).await?;
+            response = self.http_client().get(&url).send().await?;
+        }
+        let status = response.status();
+        if !status.is_success() {
+            return Err(MilkyError::HttpApiError {
+                status,
+                message: format!("下载 {url} 失败"),
+            });
+        }
+
+        let total = response.content_length();
+        let state = Some((response, 0u64, on_progress));
+        Ok(stream::unfold(state, move |state| async move {
+            let (mut response, downloaded, mut on_progress) = state?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    let downloaded = downloaded + chunk.len() as u64;
+                    if on_progress(DownloadProgress { downloaded, total }).is_break() {
+                        return Some((Err(MilkyError::Cancelled), None));
+                    }
+                    Some((Ok(chunk), Some((response, downloaded, on_progress))))
+                }
+                Ok(None) => None,
+                Err(e) => Some((Err(e.into()), None)),
+            }
+        }))
+    }
+
+    /// 将群文件夹（包括子文件夹）中的所有文件下载到本地目录
+    ///
+    /// 子文件夹会在本地目录中按原有的层级创建。文件以有限的并发数下载，
+    /// 临时性失败（如网络错误、服务端 5xx）会进行重试，单个文件失败不会中断其余文件的下载。
+    ///
+    /// # 参数
+    /// * `group_id`: 文件所属群组的群号
+    /// * `folder_id`: 要下载的文件夹ID，为 `None` 时下载整个群文件
+    /// * `dest_dir`: 保存文件的本地目录，不存在时会自动创建
+    ///
+    /// # 返回
+    /// 成功则返回包含每个文件下载结果的 [`FolderManifest`]；仅在无法列出文件时返回错误
+    pub async fn download_group_folder(
+        &self,
+        group_id: i64,
+        folder_id: Option<String>,
+        dest_dir: impl AsRef<Path>,
+    ) -> Result<FolderManifest> {
+        let dest_dir = dest_dir.as_ref();
+        let entries = self
+            .list_group_folder_recursive(group_id, folder_id)
+            .await?;
+        info!(
+            "开始下载群 {group_id} 的 {} 个文件至 {}",
+            entries.len(),
+            dest_dir.display()
+        );
+
+        let results: Vec<_> = stream::iter(entries)
+            .map(|entry| async move {
+                let local_path = local_path_for(dest_dir, &entry.path);
+                let result = self
+                    .download_group_file_with_retry(&entry, &local_path)
+                    .await;
+                (entry, local_path, result)
+            })
+            .buffer_unordered(FOLDER_DOWNLOAD_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut manifest = FolderManifest::default();
+        for (entry, local_path, result) in results {
+            match result {
+                Ok(download) => manifest.saved.push(SavedGroupFile {
+                    remote_path: entry.path,
+                    local_path,
+                    file: entry.file,
+                    download,
+                }),
+                Err(e) => {
+                    warn!("下载群文件 {} 失败: {e}", entry.path);
+                    manifest.failed.push((entry.file, e));
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// 下载单个群文件，遇到临时性失败时按指数退避重试
+    async fn download_group_file_with_retry(
+        &self,
+        entry: &GroupFileEntry,
+        local_path: &Path,
+    ) -> Result<DownloadedFile> {
+        if let Some(parent) = local_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut delay = Duration::from_secs(1);
+        let mut attempt = 0;
+        loop {
+            let result = async {
+                let url = self
+                    .get_group_file_download_url(entry.file.group_id, entry.file.file_id.clone())
+                    .await?
+                    .download_url;
+                self.download_to(&url, local_path).await
+            }
+            .await;
+            match result {
+                Err(e) if attempt < FOLDER_DOWNLOAD_RETRIES && e.is_retryable() => {
+                    attempt += 1;
+                    warn!(
+                        "下载群文件 {} 失败，{delay:?} 后进行第 {attempt} 次重试: {e}",
+                        entry.path
+                    );
+                    runtime::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 将资源ID解析为下载链接，已经是链接时原样返回
+    pub(crate) async fn resolve_download_url(&self, resource: &str) -> Result<String> {
+        if resource.starts_with("http://") || resource.starts_with("https://") {
+            Ok(resource.to_string())
+        } else {
+            Ok(self.get_resource_temp_url(resource).await?.url)
+        }
+    }
+}
+
+/// 将群文件路径转换为本地目录下的路径，忽略可能越出目标目录的路径片段
+fn local_path_for(dest_dir: &Path, remote_path: &str) -> PathBuf {
+    let mut path = dest_dir.to_path_buf();
+    for component in remote_path.split('/') {
+        let component = component.replace('\\', "_");
+        if !component.is_empty() && component != "." && component != ".." {
+            path.push(component);
+        }
+    }
+    path
+}
+
+/// 将数据块流写入文件并计算哈希值
+async fn write_chunks_to(
+    chunks: impl Stream<Item = Result<Bytes>>,
+    path: &Path,
+) -> Result<DownloadedFile> {
+    let mut chunks = std::pin::pin!(chunks);
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha1::new();
+    let mut size = 0u64;
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+        size += chunk.len() as u64;
+    }
+    file.flush().await?;
+
+    Ok(DownloadedFile {
+        size,
+        sha1: to_hex(&hasher.finalize()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+    use axum::Router;
+    use axum::routing::get;
+
+    #[test]
+    fn test_local_path_for() {
+        let dest = Path::new("/backup");
+        assert_eq!(
+            local_path_for(dest, "/资料/2024/课件.pdf"),
+            Path::new("/backup/资料/2024/课件.pdf")
+        );
+        assert_eq!(
+            local_path_for(dest, "/../..\\etc/passwd"),
+            Path::new("/backup/.._etc/passwd")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_with_progress() {
+        let app = Router::new().route("/file", get(|| async { "hello milky" }));
+        let url = format!("http://{}/file", test_util::serve(app).await);
+        let (client, _rx) = test_util::client(OFFLINE);
+        let path = std::env::temp_dir().join(format!("milky-download-{}", uuid::Uuid::new_v4()));
+
+        let file = client
+            .download_to_with_progress(&url, &path, |progress| {
+                assert_eq!(progress.total, Some(11));
+                ControlFlow::Continue(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(file.size, 11);
+        assert_eq!(file.sha1, "22c6a4035980f804df84f429c2e8d56cc4939938");
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"hello milky");
+
+        let mismatch = client.download_verified(&url, &path, "00").await;
+        assert!(matches!(mismatch, Err(MilkyError::HashMismatch { .. })));
+        assert!(!path.exists());
+        client
+            .download_verified(&url, &path, &file.sha1.to_uppercase())
+            .await
+            .unwrap();
+
+        let cancelled = client
+            .download_to_with_progress(&url, &path, |_| ControlFlow::Break(()))
+            .await;
+        assert!(matches!(cancelled, Err(MilkyError::Cancelled)));
+        assert!(!path.exists());
+    }
+}
@@ -1,27 +1,30 @@
 //! 提供了与文件操作相关的API接口功能，包括私聊文件和群文件的上传、下载、管理等
 
-use crate::client::MilkyClient;
+use crate::api::ApiClient;
 use crate::error::Result;
+#[cfg(feature = "client")]
+use crate::utils::infer_file_name;
+#[cfg(not(feature = "client"))]
+use crate::wasm::infer_file_name;
+use milky_types::common::FileUri;
 use milky_types::group::{GroupFile, GroupFolder};
 use serde::{Deserialize, Serialize};
 
 /// 上传私聊文件的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UploadPrivateFileRequest {
     /// 接收文件的好友QQ号
     pub user_id: i64,
-    /// 文件的统一资源标识符 (URI)
-    /// 支持:
-    /// - `file:///path/to/file` (本地文件),
-    /// - `http(s)://example.com/file` (网络URL),
-    /// - `base64://<BASE64编码的文件数据>` (Base64编码内容)
-    pub file_uri: String,
+    /// 文件的统一资源标识符，详见 [`FileUri`]
+    pub file_uri: FileUri,
     /// 文件名称
     pub file_name: String,
 }
 
 /// 上传私聊文件的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UploadPrivateFileResponse {
     /// 文件ID
     pub file_id: String,
@@ -29,23 +32,21 @@ pub struct UploadPrivateFileResponse {
 
 /// 上传群文件的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UploadGroupFileRequest {
     /// 文件要上传到的目标群组的群号
     pub group_id: i64,
     /// 目标文件夹ID
     pub parent_folder_id: String,
-    /// 文件的统一资源标识符 (URI)
-    /// 支持:
-    /// - `file:///path/to/file` (本地文件),
-    /// - `http(s)://example.com/file` (网络URL),
-    /// - `base64://<BASE64编码的文件数据>` (Base64编码内容)
-    pub file_uri: String,
+    /// 文件的统一资源标识符，详见 [`FileUri`]
+    pub file_uri: FileUri,
     /// 文件名称
     pub file_name: String,
 }
 
 /// 上传群文件的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UploadGroupFileResponse {
     /// 上传成功后，文件在服务器上的唯一ID
     pub file_id: String,
@@ -53,6 +54,7 @@ pub struct UploadGroupFileResponse {
 
 /// 获取私聊文件下载链接的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetPrivateFileDownloadUrlRequest {
     /// 文件所属好友的QQ号
     pub user_id: i64,
@@ -64,6 +66,7 @@ pub struct GetPrivateFileDownloadUrlRequest {
 
 /// 获取私聊文件下载链接的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetPrivateFileDownloadUrlResponse {
     /// 文件的可直接访问的下载链接
     pub download_url: String,
@@ -71,6 +74,7 @@ pub struct GetPrivateFileDownloadUrlResponse {
 
 /// 获取群文件下载链接的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupFileDownloadUrlRequest {
     /// 文件所属群组的群号
     pub group_id: i64,
@@ -80,6 +84,7 @@ pub struct GetGroupFileDownloadUrlRequest {
 
 /// 获取群文件下载链接的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupFileDownloadUrlResponse {
     /// 文件的可直接访问的下载链接
     pub download_url: String,
@@ -87,6 +92,7 @@ pub struct GetGroupFileDownloadUrlResponse {
 
 /// 获取群文件列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupFilesRequest {
     /// 要查询的群组的群号
     pub group_id: i64,
@@ -96,6 +102,7 @@ pub struct GetGroupFilesRequest {
 
 /// 获取群文件列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupFilesResponse {
     /// 获取到的文件列表
     pub files: Vec<GroupFile>,
@@ -105,6 +112,7 @@ pub struct GetGroupFilesResponse {
 
 /// 移动群文件的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MoveGroupFileRequest {
     /// 文件所属群组的群号
     pub group_id: i64,
@@ -118,6 +126,7 @@ pub struct MoveGroupFileRequest {
 
 /// 重命名群文件的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RenameGroupFileRequest {
     /// 文件所属群组的群号
     pub group_id: i64,
@@ -131,6 +140,7 @@ pub struct RenameGroupFileRequest {
 
 /// 删除群文件的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DeleteGroupFileRequest {
     /// 文件所属群组的群号
     pub group_id: i64,
@@ -140,6 +150,7 @@ pub struct DeleteGroupFileRequest {
 
 /// 创建群文件夹的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateGroupFolderRequest {
     /// 要在其中创建文件夹的群组的群号
     pub group_id: i64,
@@ -149,6 +160,7 @@ pub struct CreateGroupFolderRequest {
 
 /// 创建群文件夹的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateGroupFolderResponse {
     /// 创建成功后，新文件夹的唯一ID
     pub folder_id: String,
@@ -156,6 +168,7 @@ pub struct CreateGroupFolderResponse {
 
 /// 重命名群文件夹的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RenameGroupFolderRequest {
     /// 文件夹所属群组的群号
     pub group_id: i64,
@@ -167,6 +180,7 @@ pub struct RenameGroupFolderRequest {
 
 /// 删除群文件夹的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DeleteGroupFolderRequest {
     /// 文件夹所属群组的群号
     pub group_id: i64,
@@ -174,22 +188,26 @@ pub struct DeleteGroupFolderRequest {
     pub folder_id: String,
 }
 
-impl MilkyClient {
+impl ApiClient {
     /// 上传私聊文件到指定好友
     ///
     /// # 参数
     /// * `user_id`: 接收文件的好友QQ号
-    /// * `file_uri`: 文件的URI，支持 `file://`, `http(s)://`, `base64://` 格式
-    /// * `file_name`: 文件名称
+    /// * `file_uri`: 文件的URI，可以是本地路径、网络URL或文件内容
+    /// * `file_name`: 文件名称，为 `None` 时根据路径、链接或文件内容自动推断
     ///
     /// # 返回
     /// 成功则返回包含文件ID的 [`UploadPrivateFileResponse`]
     pub async fn upload_private_file(
         &self,
         user_id: i64,
-        file_uri: String,
-        file_name: String,
+        file_uri: FileUri,
+        file_name: Option<String>,
     ) -> Result<UploadPrivateFileResponse> {
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => infer_file_name(&file_uri).await,
+        };
         let params = UploadPrivateFileRequest {
             user_id,
             file_uri,
@@ -204,7 +222,7 @@ impl MilkyClient {
     /// * `group_id`: 文件要上传到的目标群组的群号
     /// * `parent_folder_id`: 目标文件夹 ID
     /// * `file_uri`: 文件的URI，格式同上
-    /// * `file_name`: 文件名称
+    /// * `file_name`: 文件名称，为 `None` 时根据路径、链接或文件内容自动推断
     ///
     /// # 返回
     /// 成功则返回包含文件ID的 [`UploadGroupFileResponse`]
@@ -212,9 +230,13 @@ impl MilkyClient {
         &self,
         group_id: i64,
         parent_folder_id: Option<String>,
-        file_uri: String,
-        file_name: String,
+        file_uri: FileUri,
+        file_name: Option<String>,
     ) -> Result<UploadGroupFileResponse> {
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => infer_file_name(&file_uri).await,
+        };
         let parent_folder_id = parent_folder_id.unwrap_or("/".to_string());
         let params = UploadGroupFileRequest {
             parent_folder_id,
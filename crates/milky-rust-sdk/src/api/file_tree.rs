@@ -0,0 +1,331 @@
+//! 提供了遍历与搜索群文件目录树的辅助功能
+
+use crate::client::MilkyClient;
+use crate::error::Result;
+
+use chrono::Utc;
+use milky_types::group::GroupFile;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// 群文件及其在群文件系统中的完整路径
+#[derive(Debug, Clone)]
+pub struct GroupFileEntry {
+    /// 文件的完整路径，例如 `/资料/2024/课件.pdf`
+    pub path: String,
+    /// 文件信息
+    pub file: GroupFile,
+}
+
+/// 群文件的搜索条件
+///
+/// 所有条件需同时满足，未设置的条件不做限制。
+///
+/// ```no_run
+/// # use milky_rust_sdk::api::file_tree::GroupFileFilter;
+/// # use std::time::Duration;
+/// // 查找 90 天前上传的所有 zip 文件
+/// let filter = GroupFileFilter::new()
+///     .name_glob("*.zip")
+///     .older_than(Duration::from_secs(90 * 24 * 3600));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GroupFileFilter {
+    /// 文件名通配符，支持 `*` 与 `?`
+    name_glob: Option<String>,
+    /// 文件名需包含的子串
+    name_contains: Option<String>,
+    /// 上传者QQ号
+    uploader_id: Option<i64>,
+    /// 最小文件大小（字节）
+    min_size: Option<i64>,
+    /// 最大文件大小（字节）
+    max_size: Option<i64>,
+    /// 上传时间距今至少经过的时长
+    older_than: Option<Duration>,
+    /// 距离过期不超过的时长
+    expires_within: Option<Duration>,
+}
+
+impl GroupFileFilter {
+    /// 创建一个不做任何限制的搜索条件
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按通配符匹配文件名，`*` 匹配任意字符串，`?` 匹配单个字符，不区分大小写
+    pub fn name_glob(mut self, pattern: impl Into<String>) -> Self {
+        self.name_glob = Some(pattern.into());
+        self
+    }
+
+    /// 文件名需包含指定子串，不区分大小写
+    pub fn name_contains(mut self, needle: impl Into<String>) -> Self {
+        self.name_contains = Some(needle.into());
+        self
+    }
+
+    /// 仅匹配指定用户上传的文件
+    pub fn uploader(mut self, uploader_id: i64) -> Self {
+        self.uploader_id = Some(uploader_id);
+        self
+    }
+
+    /// 限制文件大小的范围（字节），传入 `None` 表示该侧不限制
+    pub fn size_range(mut self, min: Option<i64>, max: Option<i64>) -> Self {
+        self.min_size = min;
+        self.max_size = max;
+        self
+    }
+
+    /// 仅匹配上传时间距今超过指定时长的文件
+    pub fn older_than(mut self, age: Duration) -> Self {
+        self.older_than = Some(age);
+        self
+    }
+
+    /// 仅匹配将在指定时长内过期的文件，永久文件不会被匹配
+    pub fn expires_within(mut self, within: Duration) -> Self {
+        self.expires_within = Some(within);
+        self
+    }
+
+    /// 判断文件是否满足搜索条件
+    ///
+    /// # 参数
+    /// * `file`: 群文件信息
+    /// * `now`: 当前的Unix时间戳（秒）
+    pub fn matches(&self, file: &GroupFile, now: i64) -> bool {
+        let name = file.file_name.to_lowercase();
+        if let Some(pattern) = &self.name_glob
+            && !glob_match(&pattern.to_lowercase(), &name)
+        {
+            return false;
+        }
+        if let Some(needle) = &self.name_contains
+            && !name.contains(&needle.to_lowercase())
+        {
+            return false;
+        }
+        if self.uploader_id.is_some_and(|id| id != file.uploader_id)
+            || self.min_size.is_some_and(|min| file.file_size < min)
+            || self.max_size.is_some_and(|max| file.file_size > max)
+        {
+            return false;
+        }
+        if let Some(age) = self.older_than
+            && now - file.uploaded_time < age.as_secs() as i64
+        {
+            return false;
+        }
+        if let Some(within) = self.expires_within
+            && (file.expire_time <= 0 || file.expire_time - now > within.as_secs() as i64)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// 通配符匹配，`*` 匹配任意字符串，`?` 匹配单个字符
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bt)) => {
+                    p = bp + 1;
+                    t = bt + 1;
+                    backtrack = Some((bp, bt + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl MilkyClient {
+    /// 递归获取群组中所有文件夹下的文件
+    ///
+    /// 从根目录开始逐层调用 [`MilkyClient::get_group_files`]，返回所有文件的扁平列表。
+    ///
+    /// # 参数
+    /// * `group_id`: 要查询的群组的群号
+    ///
+    /// # 返回
+    /// 成功则返回按目录层级排列的 [`GroupFileEntry`] 列表
+    pub async fn list_group_files_recursive(&self, group_id: i64) -> Result<Vec<GroupFileEntry>> {
+        self.list_group_folder_recursive(group_id, None).await
+    }
+
+    /// 递归获取指定文件夹及其子文件夹下的文件
+    ///
+    /// # 参数
+    /// * `group_id`: 要查询的群组的群号
+    /// * `folder_id`: 起始文件夹的ID，为 `None` 时从根目录开始
+    ///
+    /// # 返回
+    /// 成功则返回 [`GroupFileEntry`] 列表，其中的路径相对于起始文件夹
+    pub async fn list_group_folder_recursive(
+        &self,
+        group_id: i64,
+        folder_id: Option<String>,
+    ) -> Result<Vec<GroupFileEntry>> {
+        let mut entries = Vec::new();
+        let mut pending = VecDeque::from([(folder_id, String::new())]);
+
+        while let Some((folder_id, folder_path)) = pending.pop_front() {
+            let resp = self.get_group_files(group_id, folder_id).await?;
+            for file in resp.files {
+                entries.push(GroupFileEntry {
+                    path: format!("{folder_path}/{}", file.file_name),
+                    file,
+                });
+            }
+            for folder in resp.folder {
+                pending.push_back((
+                    Some(folder.folder_id),
+                    format!("{folder_path}/{}", folder.folder_name),
+                ));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 在群组的所有文件夹中搜索满足条件的文件
+    ///
+    /// # 参数
+    /// * `group_id`: 要搜索的群组的群号
+    /// * `filter`: 搜索条件
+    ///
+    /// # 返回
+    /// 成功则返回所有满足条件的 [`GroupFileEntry`]
+    pub async fn find_group_files(
+        &self,
+        group_id: i64,
+        filter: &GroupFileFilter,
+    ) -> Result<Vec<GroupFileEntry>> {
+        let now = Utc::now().timestamp();
+        let mut entries = self.list_group_files_recursive(group_id).await?;
+        entries.retain(|entry| filter.matches(&entry.file, now));
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use axum::Router;
+    use axum::routing::post;
+    use milky_types::group::GroupFolder;
+    use serde_json::{Value, json};
+
+    const DAY: u64 = 24 * 3600;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.zip", "资料.zip"));
+        assert!(glob_match("a?c*", "abcdef"));
+        assert!(glob_match("*b*b", "abxbb"));
+        assert!(!glob_match("*.zip", "资料.zip.txt"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let now = 100 * DAY as i64;
+        let file = GroupFile {
+            file_name: "Backup.ZIP".to_string(),
+            file_size: 2048,
+            uploaded_time: now - 91 * DAY as i64,
+            expire_time: now + DAY as i64,
+            uploader_id: 10001,
+            ..Default::default()
+        };
+
+        let filter = GroupFileFilter::new()
+            .name_glob("*.zip")
+            .older_than(Duration::from_secs(90 * DAY));
+        assert!(filter.matches(&file, now));
+        assert!(!filter.clone().uploader(10002).matches(&file, now));
+        assert!(!filter.size_range(Some(4096), None).matches(&file, now));
+
+        let filter = GroupFileFilter::new().name_contains("backup");
+        assert!(
+            filter
+                .clone()
+                .expires_within(Duration::from_secs(2 * DAY))
+                .matches(&file, now)
+        );
+        assert!(
+            !filter
+                .expires_within(Duration::from_secs(3600))
+                .matches(&file, now)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_group_files_recursive() {
+        let file = |name: &str| GroupFile {
+            file_name: name.to_string(),
+            ..Default::default()
+        };
+        let folder = |id: &str, name: &str| GroupFolder {
+            folder_id: id.to_string(),
+            folder_name: name.to_string(),
+            ..Default::default()
+        };
+        // 根目录下有文件夹 “资料”，其中又有子文件夹 “2024”
+        let app = Router::new().route(
+            "/api/get_group_files",
+            post(move |axum::Json(params): axum::Json<Value>| async move {
+                assert_eq!(params["group_id"], 123);
+                let (files, folders) = match params["parent_folder_id"].as_str().unwrap() {
+                    "/" => (vec![file("readme.txt")], vec![folder("f1", "资料")]),
+                    "f1" => (vec![file("大纲.docx")], vec![folder("f2", "2024")]),
+                    "f2" => (vec![file("课件.pdf")], vec![]),
+                    other => panic!("{other}"),
+                };
+                axum::Json(json!({
+                    "status": "ok",
+                    "retcode": 0,
+                    "data": {"files": files, "folder": folders},
+                }))
+            }),
+        );
+        let (client, _rx) = test_util::client(test_util::serve(app).await);
+
+        let paths = |entries: Vec<GroupFileEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| {
+                    assert!(entry.path.ends_with(&entry.file.file_name));
+                    entry.path
+                })
+                .collect::<Vec<_>>()
+        };
+        let entries = client.list_group_files_recursive(123).await.unwrap();
+        assert_eq!(
+            paths(entries),
+            ["/readme.txt", "/资料/大纲.docx", "/资料/2024/课件.pdf"]
+        );
+        let entries = client
+            .list_group_folder_recursive(123, Some("f1".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(paths(entries), ["/大纲.docx", "/2024/课件.pdf"]);
+    }
+}
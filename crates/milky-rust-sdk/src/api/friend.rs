@@ -1,11 +1,13 @@
-//! 提供了与好友互动相关的API接口功能，例如发送戳一戳和点赞
+//! 提供了与好友互动相关的API接口功能，例如发送戳一戳、点赞和管理好友
 
-use crate::{MilkyClient, error::Result};
+use crate::api::ApiClient;
+use crate::error::Result;
 use milky_types::friend::FriendRequest;
 use serde::{Deserialize, Serialize};
 
 /// 发送好友戳一戳的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendFriendNudgeRequest {
     /// 要戳一戳的好友的QQ号
     pub user_id: i64,
@@ -16,6 +18,7 @@ pub struct SendFriendNudgeRequest {
 
 /// 发送资料卡点赞的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendProfileLikeRequest {
     /// 要点赞的好友的QQ号
     pub user_id: i64,
@@ -26,6 +29,7 @@ pub struct SendProfileLikeRequest {
 
 /// 获取好友请求列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendRequestsRequest {
     /// 获取的最大请求数量，默认`20`
     pub limit: i32,
@@ -35,6 +39,7 @@ pub struct GetFriendRequestsRequest {
 
 /// 获取好友请求列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendRequestsResponse {
     /// 好友请求列表
     pub requests: Vec<FriendRequest>,
@@ -42,6 +47,7 @@ pub struct GetFriendRequestsResponse {
 
 /// 接受好友请求的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AcceptFriendRequestRequest {
     /// 请求发起者的UID
     pub initiator_uid: i64,
@@ -51,6 +57,7 @@ pub struct AcceptFriendRequestRequest {
 
 /// 拒绝好友请求的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RejectFriendRequestRequest {
     /// 请求发起者的UID
     pub initiator_uid: i64,
@@ -60,7 +67,27 @@ pub struct RejectFriendRequestRequest {
     pub reason: String,
 }
 
-impl MilkyClient {
+/// 删除好友的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeleteFriendRequest {
+    /// 要删除的好友的QQ号
+    pub user_id: i64,
+    /// 是否同时将其加入黑名单
+    pub block: bool,
+}
+
+/// 设置好友黑名单的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SetFriendBlockRequest {
+    /// 目标用户的QQ号
+    pub user_id: i64,
+    /// `true` 为加入黑名单，`false` 为移出黑名单
+    pub is_block: bool,
+}
+
+impl ApiClient {
     /// 发送好友戳一戳（Nudge）
     ///
     /// # 参数
@@ -150,4 +177,32 @@ impl MilkyClient {
         };
         self.send_request("reject_friend_request", params).await
     }
+
+    /// 删除好友
+    ///
+    /// # 参数
+    /// * `user_id`: 要删除的好友的QQ号
+    /// * `block`: 是否同时将其加入黑名单，加入后对方无法再发起好友请求
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn delete_friend(&self, user_id: i64, block: bool) -> Result<()> {
+        let params = DeleteFriendRequest { user_id, block };
+        self.send_request("delete_friend", params).await
+    }
+
+    /// 将用户加入或移出黑名单
+    ///
+    /// 与 [`delete_friend`](Self::delete_friend) 不同，加入黑名单不会删除好友关系，仅屏蔽对方的消息与请求。
+    ///
+    /// # 参数
+    /// * `user_id`: 目标用户的QQ号
+    /// * `is_block`: `true` 为加入黑名单，`false` 为移出黑名单
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn set_friend_block(&self, user_id: i64, is_block: bool) -> Result<()> {
+        let params = SetFriendBlockRequest { user_id, is_block };
+        self.send_request("set_friend_block", params).await
+    }
 }
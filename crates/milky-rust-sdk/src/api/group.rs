@@ -1,13 +1,18 @@
 //! 提供了与群组管理和互动相关的API接口功能
 //!
 //! 这包括设置群信息、管理群成员、处理群公告、以及发送群内互动（如戳一戳、表情回应）等操作
-//! 所有功能均通过 [`MilkyClient`] 的方法暴露
+//! 所有功能均通过 [`MilkyClient`](crate::MilkyClient) 的方法暴露
 
-use crate::{MilkyClient, error::Result};
+use crate::api::ApiClient;
+use crate::error::Result;
+use milky_types::common::FileUri;
 use milky_types::group::{GroupAnnouncement, GroupEssenceMessage, GroupNotification};
+use milky_types::message::reaction::Reaction;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum GroupNotificationType {
     JoinRequest,
@@ -16,6 +21,7 @@ pub enum GroupNotificationType {
 
 /// 设置群名称的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupNameRequest {
     /// 要操作的目标群组的群号
     pub group_id: i64,
@@ -25,19 +31,17 @@ pub struct SetGroupNameRequest {
 
 /// 设置群头像的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupAvatarRequest {
     /// 群号
     pub group_id: i64,
-    /// 图像文件的统一资源标识符 (URI)
-    /// 支持:
-    /// - `file:///path/to/image` (本地文件),
-    /// - `http(s)://example.com/image` (网络URL),
-    /// - `base64://<BASE64编码的图像数据>` (Base64编码内容)
-    pub image_uri: String,
+    /// 图像文件的统一资源标识符，详见 [`FileUri`]
+    pub image_uri: FileUri,
 }
 
 /// 设置群成员名片（备注）的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupMemberCardRequest {
     /// 群号
     pub group_id: i64,
@@ -47,8 +51,19 @@ pub struct SetGroupMemberCardRequest {
     pub card: String,
 }
 
+/// 设置群备注的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SetGroupRemarkRequest {
+    /// 群号
+    pub group_id: i64,
+    /// 要设置的新的群备注如果设置为空字符串，通常表示删除备注
+    pub remark: String,
+}
+
 /// 设置群成员专属头衔的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupMemberSpecialTitleRequest {
     /// 群号
     pub group_id: i64,
@@ -60,6 +75,7 @@ pub struct SetGroupMemberSpecialTitleRequest {
 
 /// 设置群成员管理员权限的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupMemberAdminRequest {
     /// 群号
     pub group_id: i64,
@@ -79,6 +95,7 @@ fn default_true() -> bool {
 
 /// 设置群成员禁言的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupMemberMuteRequest {
     /// 群号
     pub group_id: i64,
@@ -90,8 +107,69 @@ pub struct SetGroupMemberMuteRequest {
     pub duration: i64,
 }
 
+/// 群成员禁言的时长，用于 [`MilkyClient::mute_group_member`](crate::MilkyClient::mute_group_member)
+///
+/// 协议以整数秒表示禁言时长，不足一秒的部分向上取整，以免短时间的禁言被当作解除禁言。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mute {
+    /// 禁言时长（秒），`0` 表示解除禁言
+    seconds: i64,
+}
+
+impl Mute {
+    /// 禁言指定的时长
+    pub fn for_duration(duration: Duration) -> Self {
+        let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+        Self {
+            seconds: i64::try_from(seconds).unwrap_or(i64::MAX),
+        }
+    }
+
+    /// 禁言指定的秒数
+    pub fn for_seconds(seconds: u64) -> Self {
+        Self::for_duration(Duration::from_secs(seconds))
+    }
+
+    /// 禁言指定的分钟数
+    pub fn for_minutes(minutes: u64) -> Self {
+        Self::for_seconds(minutes.saturating_mul(60))
+    }
+
+    /// 禁言指定的小时数
+    pub fn for_hours(hours: u64) -> Self {
+        Self::for_seconds(hours.saturating_mul(3600))
+    }
+
+    /// 禁言指定的天数
+    pub fn for_days(days: u64) -> Self {
+        Self::for_seconds(days.saturating_mul(86400))
+    }
+
+    /// 解除禁言
+    pub fn unmute() -> Self {
+        Self::default()
+    }
+
+    /// 是否表示解除禁言
+    pub fn is_unmute(&self) -> bool {
+        self.seconds == 0
+    }
+
+    /// 获取禁言时长
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.seconds as u64)
+    }
+}
+
+impl From<Duration> for Mute {
+    fn from(duration: Duration) -> Self {
+        Self::for_duration(duration)
+    }
+}
+
 /// 设置全群禁言的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupWholeMuteRequest {
     /// 群号
     pub group_id: i64,
@@ -103,6 +181,7 @@ pub struct SetGroupWholeMuteRequest {
 
 /// 踢出群成员的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct KickGroupMemberRequest {
     /// 群号
     pub group_id: i64,
@@ -116,6 +195,7 @@ pub struct KickGroupMemberRequest {
 
 /// 获取群公告列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupAnnouncementListRequest {
     /// 群号
     pub group_id: i64,
@@ -123,6 +203,7 @@ pub struct GetGroupAnnouncementListRequest {
 
 /// 获取群公告列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupAnnouncementListResponse {
     /// 获取到的群公告列表
     pub announcements: Vec<GroupAnnouncement>,
@@ -130,21 +211,19 @@ pub struct GetGroupAnnouncementListResponse {
 
 /// 发送（发布）群公告的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendGroupAnnouncementRequest {
     /// 目标群组的群号
     pub group_id: i64,
     /// 公告的文本内容
     pub content: String,
-    /// 公告附带的图像文件URI（可选，若不需要图片则可传入空字符串或根据API具体要求处理）
-    /// 支持:
-    /// - `file:///path/to/image` (本地文件),
-    /// - `http(s)://example.com/image` (网络URL),
-    /// - `base64://<BASE64编码的图像数据>` (Base64编码内容)
-    pub image_uri: Option<String>,
+    /// 公告附带的图像文件URI（可选），详见 [`FileUri`]
+    pub image_uri: Option<FileUri>,
 }
 
 /// 删除群公告的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DeleteGroupAnnouncementRequest {
     /// 目标群组的群号
     pub group_id: i64,
@@ -154,6 +233,7 @@ pub struct DeleteGroupAnnouncementRequest {
 
 /// 获取群精华消息列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupEssenceMessagesRequest {
     /// 群号
     pub group_id: i64,
@@ -165,6 +245,7 @@ pub struct GetGroupEssenceMessagesRequest {
 
 /// 获取群精华消息列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupEssenceMessagesResponse {
     /// 精华消息列表
     pub messages: Vec<GroupEssenceMessage>,
@@ -174,6 +255,7 @@ pub struct GetGroupEssenceMessagesResponse {
 
 /// 设置群精华消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetGroupEssenceMessageRequest {
     /// 群号
     pub group_id: i64,
@@ -187,6 +269,7 @@ pub struct SetGroupEssenceMessageRequest {
 
 /// 退出群组的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct QuitGroupRequest {
     /// 要退出的群组的群号
     pub group_id: i64,
@@ -194,13 +277,14 @@ pub struct QuitGroupRequest {
 
 /// 发送群消息表情回应的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendGroupMessageReactionRequest {
     /// 群号
     pub group_id: i64,
     /// 要回应的目标消息的序列号 (`message_seq`)
     pub message_seq: i64,
-    /// 要发送的表情回应的ID
-    pub reaction: String,
+    /// 要发送的表情回应
+    pub reaction: Reaction,
     /// 操作类型，`true` 为添加表情回应，`false` 为取消表情回应
     /// 默认为 `true`
     #[serde(default = "default_true")]
@@ -209,6 +293,7 @@ pub struct SendGroupMessageReactionRequest {
 
 /// 发送群内戳一戳的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendGroupNudgeRequest {
     /// 目标群组的群号
     pub group_id: i64,
@@ -216,8 +301,17 @@ pub struct SendGroupNudgeRequest {
     pub user_id: i64,
 }
 
+/// 群打卡的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SendGroupSignRequest {
+    /// 要打卡的群组的群号
+    pub group_id: i64,
+}
+
 /// 获取群组通知的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupNotificationsRequest {
     /// 起始通知序列号
     pub start_notification_seq: Option<i64>,
@@ -230,6 +324,7 @@ pub struct GetGroupNotificationsRequest {
 
 /// 获取群组通知的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupNotificationResponse {
     /// 通知列表
     pub notifications: Vec<GroupNotification>,
@@ -239,6 +334,7 @@ pub struct GetGroupNotificationResponse {
 
 /// 同意入群/邀请他人入群请求的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AcceptGroupRequestRequest {
     /// 请求对应的通知序列号
     pub notification_seq: String,
@@ -252,6 +348,7 @@ pub struct AcceptGroupRequestRequest {
 
 /// 拒绝入群/邀请他人入群请求的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RejectGroupRequestRequest {
     /// 请求对应的通知序列号
     pub notification_seq: String,
@@ -267,6 +364,7 @@ pub struct RejectGroupRequestRequest {
 
 /// 同意他人邀请自身入群的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AcceptGroupInvitationRequest {
     /// 群号
     pub group_id: i64,
@@ -276,6 +374,7 @@ pub struct AcceptGroupInvitationRequest {
 
 /// 拒绝他人邀请自身入群的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RejectGroupInvitationRequest {
     /// 群号
     pub group_id: i64,
@@ -283,7 +382,7 @@ pub struct RejectGroupInvitationRequest {
     pub invitation_seq: String,
 }
 
-impl MilkyClient {
+impl ApiClient {
     /// 设置指定群组的名称
     ///
     /// # 参数
@@ -304,11 +403,11 @@ impl MilkyClient {
     ///
     /// # 参数
     /// * `group_id`: 目标群组的群号
-    /// * `image_uri`: 图像文件的URI，支持 `file://`, `http(s)://`, `base64://` 格式
+    /// * `image_uri`: 图像文件的URI，可以是本地路径、网络URL或图像内容
     ///
     /// # 返回
     /// 成功则返回 `Ok(())`
-    pub async fn set_group_avatar(&self, group_id: i64, image_uri: String) -> Result<()> {
+    pub async fn set_group_avatar(&self, group_id: i64, image_uri: FileUri) -> Result<()> {
         let params = SetGroupAvatarRequest {
             group_id,
             image_uri,
@@ -339,6 +438,21 @@ impl MilkyClient {
         self.send_request("set_group_member_card", params).await
     }
 
+    /// 设置机器人自身对指定群组的备注
+    ///
+    /// 群备注仅对机器人自身可见，不会影响群名称。
+    ///
+    /// # 参数
+    /// * `group_id`: 目标群组的群号
+    /// * `remark`: 新的群备注内容空字符串通常用于清除备注
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn set_group_remark(&self, group_id: i64, remark: String) -> Result<()> {
+        let params = SetGroupRemarkRequest { group_id, remark };
+        self.send_request("set_group_remark", params).await
+    }
+
     /// 设置指定群组成员的专属头衔
     ///
     /// # 参数
@@ -411,6 +525,29 @@ impl MilkyClient {
         self.send_request("set_group_member_mute", params).await
     }
 
+    /// 对指定群组成员进行禁言或解除禁言，参见 [`MilkyClient::set_group_member_mute`](crate::MilkyClient::set_group_member_mute)
+    ///
+    /// # 参数
+    /// * `group_id`: 目标群组的群号
+    /// * `user_id`: 目标成员的QQ号
+    /// * `mute`: 禁言时长，例如 `Mute::for_minutes(10)`、`Duration::from_secs(60)` 或 `Mute::unmute()`
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn mute_group_member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        mute: impl Into<Mute>,
+    ) -> Result<()> {
+        let params = SetGroupMemberMuteRequest {
+            group_id,
+            user_id,
+            duration: mute.into().seconds,
+        };
+        self.send_request("set_group_member_mute", params).await
+    }
+
     /// 对指定群组开启或关闭全员禁言
     ///
     /// # 参数
@@ -470,7 +607,7 @@ impl MilkyClient {
     /// # 参数
     /// * `group_id`: 目标群组的群号
     /// * `content`: 公告的文本内容
-    /// * `image_uri`: 公告附带的图片URI如果不需要图片，处理方式需参照API具体要求（可能为空字符串或省略）
+    /// * `image_uri`: 公告附带的图片URI，不需要图片时传入 `None`
     ///
     /// # 返回
     /// 成功则返回 `Ok(())`
@@ -478,7 +615,7 @@ impl MilkyClient {
         &self,
         group_id: i64,
         content: String,
-        image_uri: Option<String>,
+        image_uri: Option<FileUri>,
     ) -> Result<()> {
         let params = SendGroupAnnouncementRequest {
             group_id,
@@ -572,7 +709,7 @@ impl MilkyClient {
     ///
     /// # 参数
     /// * `message_seq`: 要回应的目标消息的序列号
-    /// * `reaction`: 要发送的表情回应的ID
+    /// * `reaction`: 要发送的表情回应，可以是 [`Reaction`]、[`Face`](milky_types::message::face::Face) 或表情回应的ID
     /// * `is_add`: 可选参数，`Some(true)` 为添加表情回应，`Some(false)` 为取消若为 `None`，则默认为 `true` (添加回应)
     ///
     /// # 返回
@@ -581,14 +718,14 @@ impl MilkyClient {
         &self,
         group_id: i64,
         message_seq: i64,
-        reaction: String,
+        reaction: impl Into<Reaction>,
         is_add: Option<bool>,
     ) -> Result<()> {
         let is_add = is_add.unwrap_or(true); // 默认为 true
         let params = SendGroupMessageReactionRequest {
             group_id,
             message_seq,
-            reaction,
+            reaction: reaction.into(),
             is_add,
         };
         self.send_request("send_group_message_reaction", params)
@@ -608,6 +745,18 @@ impl MilkyClient {
         self.send_request("send_group_nudge", params).await
     }
 
+    /// 在群内进行每日打卡
+    ///
+    /// # 参数
+    /// * `group_id`: 要打卡的群组的群号
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn send_group_sign(&self, group_id: i64) -> Result<()> {
+        let params = SendGroupSignRequest { group_id };
+        self.send_request("send_group_sign", params).await
+    }
+
     /// 获取群组通知列表
     ///
     /// # 参数
@@ -728,3 +877,18 @@ impl MilkyClient {
         self.send_request("reject_group_invitation", params).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mute_duration() {
+        assert_eq!(Mute::for_minutes(10).seconds, 600);
+        assert_eq!(Mute::from(Duration::from_millis(1500)).seconds, 2);
+        assert_eq!(Mute::for_duration(Duration::from_millis(1)).seconds, 1);
+        assert!(Mute::unmute().is_unmute());
+        assert!(!Mute::for_seconds(1).is_unmute());
+        assert_eq!(Mute::for_days(u64::MAX).seconds, i64::MAX);
+    }
+}
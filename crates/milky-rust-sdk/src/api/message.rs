@@ -1,13 +1,21 @@
 //! 提供了与消息处理相关的API接口功能
 
-use crate::{MilkyClient, error::Result};
+use crate::api::ApiClient;
+#[cfg(feature = "client")]
+use crate::client::MilkyClient;
+use crate::error::Result;
 use milky_types::common::MessageScene;
 use milky_types::message::in_coming::IncomingMessage;
 use milky_types::message::out_going::OutgoingSegment;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "client")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "client")]
+use url::Url;
 
 /// 发送私聊消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendPrivateMessageRequest {
     /// 接收消息的好友的QQ号
     pub user_id: i64,
@@ -17,6 +25,7 @@ pub struct SendPrivateMessageRequest {
 
 /// 发送私聊消息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[allow(dead_code)] // 允许未使用代码，因为此结构体主要用于反序列化API响应
 pub struct SendPrivateMessageResponse {
     /// 消息序列号
@@ -27,6 +36,7 @@ pub struct SendPrivateMessageResponse {
 
 /// 发送群聊消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendGroupMessageRequest {
     /// 接收消息的群组的群号
     pub group_id: i64,
@@ -36,6 +46,7 @@ pub struct SendGroupMessageRequest {
 
 /// 发送群聊消息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SendGroupMessageResponse {
     /// 消息序列号
     pub message_seq: i64,
@@ -43,8 +54,31 @@ pub struct SendGroupMessageResponse {
     pub time: i64,
 }
 
+/// 发送群临时会话消息的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SendTempMessageRequest {
+    /// 发起临时会话所在的群组的群号
+    pub group_id: i64,
+    /// 接收消息的群成员的QQ号
+    pub user_id: i64,
+    /// 要发送的消息内容，由一个或多个 [`OutgoingSegment`] 组成
+    pub message: Vec<OutgoingSegment>,
+}
+
+/// 发送群临时会话消息的响应数据
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SendTempMessageResponse {
+    /// 消息序列号
+    pub message_seq: i64,
+    /// 消息发送时间（Unix时间戳，秒）
+    pub time: i64,
+}
+
 /// 撤回私聊消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RecallPrivateMessageRequest {
     /// 好友 QQ 号
     pub user_id: i64,
@@ -54,6 +88,7 @@ pub struct RecallPrivateMessageRequest {
 
 /// 撤回群聊消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RecallGroupMessageRequest {
     /// 消息所属群组的群号
     pub group_id: i64,
@@ -63,6 +98,7 @@ pub struct RecallGroupMessageRequest {
 
 /// 获取单条消息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetMessageRequest {
     /// 消息场景
     pub message_scene: MessageScene,
@@ -74,6 +110,7 @@ pub struct GetMessageRequest {
 
 /// 获取单条消息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetMessageResponse {
     /// 获取到的消息内容
     pub message: IncomingMessage,
@@ -81,6 +118,7 @@ pub struct GetMessageResponse {
 
 /// 获取历史消息记录的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetHistoryMessageRequest {
     /// 消息所属的场景
     pub message_scene: MessageScene,
@@ -96,6 +134,7 @@ pub struct GetHistoryMessageRequest {
 
 /// 获取历史消息记录的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[allow(dead_code)] // 允许未使用代码
 pub struct GetHistoryMessageResponse {
     /// 获取到的消息列表
@@ -107,6 +146,7 @@ pub struct GetHistoryMessageResponse {
 
 /// 获取消息中资源（如图片、语音、文件）的临时下载链接的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetResourceTempUrlRequest {
     /// 资源ID
     pub resource_id: String,
@@ -114,6 +154,7 @@ pub struct GetResourceTempUrlRequest {
 
 /// 获取消息中资源的临时下载链接的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetResourceTempUrlResponse {
     /// 获取到的临时下载链接此链接通常有有效期
     pub url: String,
@@ -121,6 +162,7 @@ pub struct GetResourceTempUrlResponse {
 
 /// 获取合并转发消息内容的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetForwardedMessagesRequest {
     /// 转发消息ID
     pub forward_id: String,
@@ -128,6 +170,7 @@ pub struct GetForwardedMessagesRequest {
 
 /// 获取合并转发消息内容的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetForwardedMessagesResponse {
     /// 合并转发消息中包含的原始消息列表
     pub messages: Vec<IncomingMessage>,
@@ -135,6 +178,7 @@ pub struct GetForwardedMessagesResponse {
 
 /// 标记消息为已读的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MarkMessageAsReadRequest {
     /// 消息所属的场景
     pub message_scene: MessageScene,
@@ -144,7 +188,7 @@ pub struct MarkMessageAsReadRequest {
     pub message_seq: i64,
 }
 
-impl MilkyClient {
+impl ApiClient {
     /// 发送私聊消息给指定好友
     ///
     /// # 参数
@@ -158,6 +202,7 @@ impl MilkyClient {
         user_id: i64,
         message: Vec<OutgoingSegment>,
     ) -> Result<SendPrivateMessageResponse> {
+        let message = self.prepare_outgoing(message).await;
         let params = SendPrivateMessageRequest { user_id, message };
         self.send_request("send_private_message", params).await
     }
@@ -175,10 +220,38 @@ impl MilkyClient {
         group_id: i64,
         message: Vec<OutgoingSegment>,
     ) -> Result<SendGroupMessageResponse> {
+        let message = self.prepare_outgoing(message).await;
         let params = SendGroupMessageRequest { group_id, message };
         self.send_request("send_group_message", params).await
     }
 
+    /// 通过群临时会话向群成员发送消息
+    ///
+    /// 可用于回复 [`TempMessage`](milky_types::message::in_coming::TempMessage)，
+    /// 其中的 `group` 字段即为发起临时会话所在的群组。
+    ///
+    /// # 参数
+    /// * `group_id`: 发起临时会话所在的群组的群号
+    /// * `user_id`: 接收消息的群成员的QQ号
+    /// * `message`: 由一个或多个 [`OutgoingSegment`] 组成的消息内容
+    ///
+    /// # 返回
+    /// 成功则返回包含消息回执信息（如 `message_seq`）的 [`SendTempMessageResponse`]
+    pub async fn send_temp_message(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        message: Vec<OutgoingSegment>,
+    ) -> Result<SendTempMessageResponse> {
+        let message = self.prepare_outgoing(message).await;
+        let params = SendTempMessageRequest {
+            group_id,
+            user_id,
+            message,
+        };
+        self.send_request("send_temp_message", params).await
+    }
+
     /// 获取指定场景下的单条消息内容
     ///
     /// # 参数
@@ -229,23 +302,6 @@ impl MilkyClient {
         self.send_request("get_history_messages", params).await
     }
 
-    /// 获取消息中特定资源（如图片、语音）的临时下载URL
-    ///
-    /// # 参数
-    /// * `resource_id`: 资源的唯一标识符，通常从消息段中获得
-    ///
-    /// # 返回
-    /// 成功则返回包含临时URL的 [`GetResourceTempUrlResponse`]
-    pub async fn get_resource_temp_url(
-        &self,
-        resource_id: &str,
-    ) -> Result<GetResourceTempUrlResponse> {
-        let params = GetResourceTempUrlRequest {
-            resource_id: resource_id.to_string(),
-        };
-        self.send_request("get_resource_temp_url", params).await
-    }
-
     /// 获取合并转发消息的具体内容
     ///
     /// # 参数
@@ -317,3 +373,122 @@ impl MilkyClient {
         self.send_request("mark_message_as_read", params).await
     }
 }
+
+#[cfg(feature = "client")]
+impl MilkyClient {
+    /// 获取消息中特定资源（如图片、语音）的临时下载URL
+    ///
+    /// 获取到的链接会按资源ID缓存，在有效期内再次获取时直接返回缓存的链接，过期后自动重新获取。
+    /// 若确认缓存的链接已失效，可调用 [`MilkyClient::invalidate_temp_url`](crate::MilkyClient::invalidate_temp_url) 清除。
+    ///
+    /// # 参数
+    /// * `resource_id`: 资源的唯一标识符，通常从消息段中获得
+    ///
+    /// # 返回
+    /// 成功则返回包含临时URL的 [`GetResourceTempUrlResponse`]
+    pub async fn get_resource_temp_url(
+        &self,
+        resource_id: &str,
+    ) -> Result<GetResourceTempUrlResponse> {
+        if let Some(url) = self.temp_url_cache.get(&resource_id.to_string()) {
+            return Ok(GetResourceTempUrlResponse { url });
+        }
+
+        let params = GetResourceTempUrlRequest {
+            resource_id: resource_id.to_string(),
+        };
+        let resp: GetResourceTempUrlResponse =
+            self.send_request("get_resource_temp_url", params).await?;
+        if let Some(ttl) = temp_url_ttl(&resp.url, self.temp_url_ttl) {
+            self.temp_url_cache
+                .insert(resource_id.to_string(), resp.url.clone(), ttl);
+        }
+        Ok(resp)
+    }
+
+    /// 清除指定资源已缓存的临时下载URL，下次获取时会重新请求
+    ///
+    /// # 参数
+    /// * `resource_id`: 资源的唯一标识符
+    pub fn invalidate_temp_url(&self, resource_id: &str) {
+        self.temp_url_cache.remove(&resource_id.to_string());
+    }
+}
+
+#[cfg(not(feature = "client"))]
+impl ApiClient {
+    /// 获取消息中特定资源（如图片、语音）的临时下载URL，浏览器中不缓存获取到的链接
+    ///
+    /// # 参数
+    /// * `resource_id`: 资源的唯一标识符，通常从消息段中获得
+    ///
+    /// # 返回
+    /// 成功则返回包含临时URL的 [`GetResourceTempUrlResponse`]
+    pub async fn get_resource_temp_url(
+        &self,
+        resource_id: &str,
+    ) -> Result<GetResourceTempUrlResponse> {
+        let params = GetResourceTempUrlRequest {
+            resource_id: resource_id.to_string(),
+        };
+        self.send_request("get_resource_temp_url", params).await
+    }
+}
+
+/// 计算临时下载链接的缓存时长
+///
+/// 链接带有 `expires` 参数（Unix 时间戳，秒）时，提前 30 秒过期，且不超过默认时长。
+/// 返回 `None` 表示不应缓存。
+#[cfg(feature = "client")]
+fn temp_url_ttl(url: &str, default: Duration) -> Option<Duration> {
+    let expires_at = Url::parse(url).ok().and_then(|url| {
+        url.query_pairs()
+            .find(|(key, _)| key.eq_ignore_ascii_case("expires"))
+            .and_then(|(_, value)| value.parse::<u64>().ok())
+    });
+    let ttl = match expires_at {
+        Some(expires_at) => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+            let remaining = Duration::from_secs(expires_at.checked_sub(now + 30)?);
+            remaining.min(default)
+        }
+        None => default,
+    };
+    (!ttl.is_zero()).then_some(ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_url_ttl() {
+        let default = Duration::from_secs(600);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(
+            temp_url_ttl("https://example.com/a.png", default),
+            Some(default)
+        );
+        assert_eq!(
+            temp_url_ttl(
+                &format!("https://example.com/a.png?Expires={}", now + 130),
+                default
+            ),
+            Some(Duration::from_secs(100))
+        );
+        assert_eq!(
+            temp_url_ttl(
+                &format!("https://example.com/a.png?expires={}", now + 10),
+                default
+            ),
+            None
+        );
+        assert_eq!(
+            temp_url_ttl("https://example.com/a.png", Duration::ZERO),
+            None
+        );
+    }
+}
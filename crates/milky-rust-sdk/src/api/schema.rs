@@ -0,0 +1,159 @@
+//! 导出 API 请求参数与响应数据的 JSON Schema
+//!
+//! 启用 `schemars` 特性后可用。除本模块列出的 API 类型外，还包含
+//! [`milky_types::schema::schemas`] 中事件、消息段等协议类型的 Schema。
+//!
+//! ```no_run
+//! milky_rust_sdk::api::schema::dump_schemas("schemas").unwrap();
+//! ```
+
+use schemars::{Schema, schema_for};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// 将各模块中类型的 Schema 以类型名称为键插入到映射中
+macro_rules! insert_schemas {
+    ($schemas:expr, $($module:ident::{$($ty:ident),* $(,)?}),* $(,)?) => {
+        $($($schemas.insert(stringify!($ty), schema_for!(super::$module::$ty));)*)*
+    };
+}
+
+/// 获取协议类型与所有 API 请求参数、响应数据的 JSON Schema
+///
+/// # 返回
+/// 以类型名称（例如 `Event`、`SendGroupMessageRequest`）为键的映射，每个 Schema 都是独立完整的，可以单独使用
+pub fn schemas() -> BTreeMap<&'static str, Schema> {
+    let mut schemas = milky_types::schema::schemas();
+    insert_schemas!(
+        schemas,
+        file::{
+            UploadPrivateFileRequest,
+            UploadPrivateFileResponse,
+            UploadGroupFileRequest,
+            UploadGroupFileResponse,
+            GetPrivateFileDownloadUrlRequest,
+            GetPrivateFileDownloadUrlResponse,
+            GetGroupFileDownloadUrlRequest,
+            GetGroupFileDownloadUrlResponse,
+            GetGroupFilesRequest,
+            GetGroupFilesResponse,
+            MoveGroupFileRequest,
+            RenameGroupFileRequest,
+            DeleteGroupFileRequest,
+            CreateGroupFolderRequest,
+            CreateGroupFolderResponse,
+            RenameGroupFolderRequest,
+            DeleteGroupFolderRequest,
+        },
+        friend::{
+            SendFriendNudgeRequest,
+            SendProfileLikeRequest,
+            GetFriendRequestsRequest,
+            GetFriendRequestsResponse,
+            AcceptFriendRequestRequest,
+            RejectFriendRequestRequest,
+            DeleteFriendRequest,
+            SetFriendBlockRequest,
+        },
+        group::{
+            SetGroupNameRequest,
+            SetGroupAvatarRequest,
+            SetGroupMemberCardRequest,
+            SetGroupRemarkRequest,
+            SetGroupMemberSpecialTitleRequest,
+            SetGroupMemberAdminRequest,
+            SetGroupMemberMuteRequest,
+            SetGroupWholeMuteRequest,
+            KickGroupMemberRequest,
+            GetGroupAnnouncementListRequest,
+            GetGroupAnnouncementListResponse,
+            SendGroupAnnouncementRequest,
+            DeleteGroupAnnouncementRequest,
+            GetGroupEssenceMessagesRequest,
+            GetGroupEssenceMessagesResponse,
+            SetGroupEssenceMessageRequest,
+            QuitGroupRequest,
+            SendGroupMessageReactionRequest,
+            SendGroupNudgeRequest,
+            SendGroupSignRequest,
+            GetGroupNotificationsRequest,
+            GetGroupNotificationResponse,
+            AcceptGroupRequestRequest,
+            RejectGroupRequestRequest,
+            AcceptGroupInvitationRequest,
+            RejectGroupInvitationRequest,
+        },
+        message::{
+            SendPrivateMessageRequest,
+            SendPrivateMessageResponse,
+            SendGroupMessageRequest,
+            SendGroupMessageResponse,
+            SendTempMessageRequest,
+            SendTempMessageResponse,
+            RecallPrivateMessageRequest,
+            RecallGroupMessageRequest,
+            GetMessageRequest,
+            GetMessageResponse,
+            GetHistoryMessageRequest,
+            GetHistoryMessageResponse,
+            GetResourceTempUrlRequest,
+            GetResourceTempUrlResponse,
+            GetForwardedMessagesRequest,
+            GetForwardedMessagesResponse,
+            MarkMessageAsReadRequest,
+        },
+        system::{
+            GetLoginInfoRequest,
+            GetLoginInfoResponse,
+            GetImplInfoRequest,
+            GetImplInfoResponse,
+            GetUserProfileRequest,
+            GetUserProfileResponse,
+            GetFriendListRequest,
+            GetFriendListResponse,
+            GetFriendInfoRequest,
+            GetFriendInfoResponse,
+            GetGroupListRequest,
+            GetGroupListResponse,
+            GetGroupInfoRequest,
+            GetGroupInfoResponse,
+            GetGroupMemberListRequest,
+            GetGroupMemberListResponse,
+            GetGroupMemberInfoRequest,
+            GetGroupMemberInfoResponse,
+            GetCookiesRequest,
+            GetCookiesResponse,
+            GetCsrfTokenRequest,
+            GetCsrfTokenResponse,
+            GetRecentContactsRequest,
+            GetRecentContactsResponse,
+            CleanCacheRequest,
+        },
+    );
+    schemas
+}
+
+/// 将 [`schemas`] 中的所有 Schema 写入目录
+///
+/// # 参数
+/// * `dir`: 输出目录，不存在时会自动创建；每个类型写入一个 `<类型名称>.json` 文件
+pub fn dump_schemas(dir: impl AsRef<Path>) -> io::Result<()> {
+    milky_types::schema::write_schemas(dir, &schemas())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_schemas() {
+        let schemas = schemas();
+        assert!(schemas.contains_key("Event"));
+        let request = serde_json::to_value(&schemas["SendGroupMessageRequest"]).unwrap();
+        assert_eq!(
+            request["required"],
+            serde_json::json!(["group_id", "message"])
+        );
+    }
+}
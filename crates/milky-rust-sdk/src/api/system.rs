@@ -1,9 +1,9 @@
 //! 提供了与系统信息查询相关的API接口功能
 
-use crate::client::MilkyClient;
+use crate::api::ApiClient;
 use crate::error::Result;
 use milky_types::{
-    common::{Platform, Sex},
+    common::{MessageScene, Platform, Sex},
     friend::Friend,
     group::{Group, GroupMember},
 };
@@ -11,10 +11,12 @@ use serde::{Deserialize, Serialize};
 
 /// 获取当前登录账号信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetLoginInfoRequest {}
 
 /// 获取当前登录账号信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetLoginInfoResponse {
     /// 当前登录的QQ号 (UIN)
     pub uin: i64,
@@ -24,10 +26,12 @@ pub struct GetLoginInfoResponse {
 
 /// 获取协议端信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetImplInfoRequest {}
 
 /// 获取协议端信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetImplInfoResponse {
     /// 协议端名称
     pub impl_name: String,
@@ -43,6 +47,7 @@ pub struct GetImplInfoResponse {
 
 /// 获取用户个人信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetUserProfileRequest {
     /// 用户的QQ号
     pub user_id: i64,
@@ -50,6 +55,7 @@ pub struct GetUserProfileRequest {
 
 /// 获取用户个人信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetUserProfileResponse {
     /// 昵称
     pub nickname: String,
@@ -75,6 +81,7 @@ pub struct GetUserProfileResponse {
 
 /// 获取好友列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendListRequest {
     /// 是否强制不使用缓存默认为 `false`（即允许使用缓存）
     #[serde(default)]
@@ -83,6 +90,7 @@ pub struct GetFriendListRequest {
 
 /// 获取好友列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendListResponse {
     /// 获取到的好友信息列表
     pub friends: Vec<Friend>,
@@ -90,6 +98,7 @@ pub struct GetFriendListResponse {
 
 /// 获取指定好友信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendInfoRequest {
     /// 要查询的好友的QQ号
     pub user_id: i64,
@@ -100,12 +109,14 @@ pub struct GetFriendInfoRequest {
 
 /// 获取指定好友信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetFriendInfoResponse {
     pub friend: Friend,
 }
 
 /// 获取群列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupListRequest {
     /// 是否强制不使用缓存默认为 `false`
     #[serde(default)]
@@ -114,6 +125,7 @@ pub struct GetGroupListRequest {
 
 /// 获取群列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupListResponse {
     /// 获取到的群信息列表
     pub groups: Vec<Group>,
@@ -121,6 +133,7 @@ pub struct GetGroupListResponse {
 
 /// 获取指定群信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupInfoRequest {
     /// 要查询的群组的群号
     pub group_id: i64,
@@ -131,12 +144,14 @@ pub struct GetGroupInfoRequest {
 
 /// 获取指定群信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupInfoResponse {
     pub group: Group,
 }
 
 /// 获取指定群成员列表的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupMemberListRequest {
     /// 要查询的群组的群号
     pub group_id: i64,
@@ -147,6 +162,7 @@ pub struct GetGroupMemberListRequest {
 
 /// 获取指定群成员列表的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupMemberListResponse {
     /// 获取到的群成员信息列表
     pub members: Vec<GroupMember>,
@@ -154,6 +170,7 @@ pub struct GetGroupMemberListResponse {
 
 /// 获取指定群成员信息的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupMemberInfoRequest {
     /// 成员所属群组的群号
     pub group_id: i64,
@@ -166,12 +183,14 @@ pub struct GetGroupMemberInfoRequest {
 
 /// 获取指定群成员信息的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetGroupMemberInfoResponse {
     pub member: GroupMember,
 }
 
 /// 获取 Cookies 的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetCookiesRequest {
     /// 需要获取 Cookies 的域名
     pub domain: String,
@@ -179,6 +198,7 @@ pub struct GetCookiesRequest {
 
 /// 获取指定群的 Cookies 的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetCookiesResponse {
     /// 域名对应的 Cookies 字符串
     pub cookies: String,
@@ -186,16 +206,57 @@ pub struct GetCookiesResponse {
 
 /// 获取 CSRF Token 的请求参数
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetCsrfTokenRequest {}
 
 /// 获取 CSRF Token的响应数据
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetCsrfTokenResponse {
     /// 获取到的 CSRF Token
     pub csrf_token: String,
 }
 
-impl MilkyClient {
+/// 获取最近会话列表的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GetRecentContactsRequest {
+    /// 获取的最大会话数量
+    pub limit: i32,
+}
+
+/// 获取最近会话列表的响应数据
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GetRecentContactsResponse {
+    /// 最近会话列表，按最后一条消息的时间从新到旧排列
+    pub contacts: Vec<RecentContact>,
+}
+
+/// 最近会话列表中的一个会话
+#[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RecentContact {
+    /// 会话的消息场景
+    pub message_scene: MessageScene,
+    /// 好友QQ号或群号
+    pub peer_id: i64,
+    /// 好友昵称或群名称
+    pub peer_name: String,
+    /// 最后一条消息的摘要
+    pub last_message_summary: String,
+    /// 最后一条消息的发送时间（Unix时间戳，秒）
+    pub last_message_time: i64,
+    /// 未读消息数量
+    pub unread_count: i32,
+}
+
+/// 清理协议端缓存的请求参数
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CleanCacheRequest {}
+
+impl ApiClient {
     /// 获取当前登录账号的基本信息
     ///
     /// # 返回
@@ -341,4 +402,35 @@ impl MilkyClient {
         let params = GetCsrfTokenRequest {}; // 此API通常无参数
         self.send_request("get_csrf_token", params).await
     }
+
+    /// 获取最近会话列表
+    ///
+    /// # 参数
+    /// * `limit`: 可选参数，获取的最大会话数量，默认为 `20`
+    ///
+    /// # 返回
+    /// 成功则返回包含最近会话的 [`GetRecentContactsResponse`]
+    pub async fn get_recent_contacts(
+        &self,
+        limit: Option<i32>,
+    ) -> Result<GetRecentContactsResponse> {
+        let limit = limit.unwrap_or(20); // 默认获取20个会话
+        let params = GetRecentContactsRequest { limit };
+        self.send_request("get_recent_contacts", params).await
+    }
+
+    /// 请求协议端清理其缓存的图片、语音等媒体文件与消息记录
+    ///
+    /// 长时间运行时协议端的缓存会持续增长，可以定期调用以释放磁盘空间。
+    /// 清理后，此前获取的资源临时链接可能失效，因此成功后会一并清除 SDK 缓存的临时链接。
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`
+    pub async fn clean_cache(&self) -> Result<()> {
+        let params = CleanCacheRequest {}; // 此API无参数
+        self.send_request::<_, ()>("clean_cache", params).await?;
+        #[cfg(feature = "client")]
+        self.temp_url_cache.clear();
+        Ok(())
+    }
 }
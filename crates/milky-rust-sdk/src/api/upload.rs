@@ -0,0 +1,135 @@
+//! 提供了从 [`AsyncRead`] 数据源上传文件的辅助功能
+//!
+//! Milky 协议的上传接口只接受文件 URI，不支持 multipart 上传。
+//! 本模块会先将数据以流的方式写入系统临时目录，再以 `file://` URI 调用上传接口，上传结束后删除临时文件，
+//! 从而避免将大文件整体读入内存并编码为 `base64://`。
+//! 这要求协议端与机器人运行在同一台机器上（或共享临时目录）。
+//!
+//! 协议目前没有提供分片上传或断点续传的接口，上传大文件时请优先使用本地路径（`file://`），
+//! 由协议端直接读取文件，避免通过网络传输文件内容。
+
+use crate::api::file::{UploadGroupFileResponse, UploadPrivateFileResponse};
+use crate::client::MilkyClient;
+use crate::error::Result;
+use crate::logger::{debug, warn};
+use crate::utils::mime::{detect_uri_mime, extension_for};
+
+use milky_types::common::FileUri;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncWriteExt};
+
+/// 临时文件，离开作用域时自动删除
+struct SpooledFile {
+    /// 临时文件路径
+    path: PathBuf,
+}
+
+impl SpooledFile {
+    /// 将数据源的全部内容写入新的临时文件
+    async fn spool<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("milky-upload-{}", uuid::Uuid::new_v4()));
+        let spooled = Self { path };
+        let mut file = tokio::fs::File::create(&spooled.path).await?;
+        let size = tokio::io::copy(reader, &mut file).await?;
+        file.flush().await?;
+        debug!("已将 {size} 字节写入临时文件 {}", spooled.path.display());
+        Ok(spooled)
+    }
+
+    /// 临时文件的 URI
+    fn uri(&self) -> FileUri {
+        FileUri::Path(self.path.clone())
+    }
+
+    /// 根据文件内容生成文件名，例如 `file.png`
+    async fn default_file_name(&self) -> String {
+        match detect_uri_mime(&self.uri()).await.and_then(extension_for) {
+            Some(ext) => format!("file.{ext}"),
+            None => "file".to_string(),
+        }
+    }
+}
+
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("删除临时文件 {} 失败: {e}", self.path.display());
+        }
+    }
+}
+
+impl MilkyClient {
+    /// 从数据源读取文件内容并上传给指定好友
+    ///
+    /// # 参数
+    /// * `user_id`: 接收文件的好友QQ号
+    /// * `reader`: 文件内容的数据源
+    /// * `file_name`: 文件名称，为 `None` 时根据文件内容推断，例如 `file.png`
+    ///
+    /// # 返回
+    /// 成功则返回包含文件ID的 [`UploadPrivateFileResponse`]
+    pub async fn upload_private_file_from_reader<R: AsyncRead + Unpin>(
+        &self,
+        user_id: i64,
+        mut reader: R,
+        file_name: Option<String>,
+    ) -> Result<UploadPrivateFileResponse> {
+        let spooled = SpooledFile::spool(&mut reader).await?;
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => spooled.default_file_name().await,
+        };
+        self.upload_private_file(user_id, spooled.uri(), Some(file_name))
+            .await
+    }
+
+    /// 从数据源读取文件内容并上传到指定群组
+    ///
+    /// # 参数
+    /// * `group_id`: 文件要上传到的目标群组的群号
+    /// * `parent_folder_id`: 目标文件夹 ID，为 `None` 时上传到根目录
+    /// * `reader`: 文件内容的数据源
+    /// * `file_name`: 文件名称，为 `None` 时根据文件内容推断，例如 `file.png`
+    ///
+    /// # 返回
+    /// 成功则返回包含文件ID的 [`UploadGroupFileResponse`]
+    pub async fn upload_group_file_from_reader<R: AsyncRead + Unpin>(
+        &self,
+        group_id: i64,
+        parent_folder_id: Option<String>,
+        mut reader: R,
+        file_name: Option<String>,
+    ) -> Result<UploadGroupFileResponse> {
+        let spooled = SpooledFile::spool(&mut reader).await?;
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => spooled.default_file_name().await,
+        };
+        self.upload_group_file(group_id, parent_folder_id, spooled.uri(), Some(file_name))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spooled_file_is_removed() {
+        let mut reader: &[u8] = b"%PDF-1.7 large file";
+        let spooled = SpooledFile::spool(&mut reader).await.unwrap();
+        let path = spooled.path.clone();
+        assert_eq!(std::fs::read(&path).unwrap(), b"%PDF-1.7 large file");
+        assert_eq!(spooled.default_file_name().await, "file.pdf");
+        assert!(
+            spooled
+                .uri()
+                .to_uri_string()
+                .unwrap()
+                .starts_with("file:///")
+        );
+
+        drop(spooled);
+        assert!(!path.exists());
+    }
+}
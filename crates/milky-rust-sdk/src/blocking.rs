@@ -0,0 +1,218 @@
+//! 同步（阻塞）版本的客户端
+//!
+//! [`MilkyClient`] 在内部管理一个 tokio 运行时，以同步方法的形式提供 API 调用，
+//! 并通过回调函数接收事件，适合命令行工具或嵌入在非异步宿主中的插件使用。
+//!
+//! 常用的 API 提供了同名的同步方法；其余 API 可以通过 [`MilkyClient::block_on`] 调用异步客户端上的对应方法。
+//!
+//! 不要在异步上下文（例如 `#[tokio::main]` 的函数）中创建或使用本客户端，否则会引发 panic。
+//!
+//! ```no_run
+//! use milky_rust_sdk::blocking::MilkyClient;
+//! use milky_rust_sdk::prelude::*;
+//! use milky_rust_sdk::{Communication, WebSocketConfig};
+//!
+//! # fn main() -> milky_rust_sdk::Result<()> {
+//! let comm = Communication::WebSocket(WebSocketConfig::new("ws://127.0.0.1:8080".to_string(), None));
+//! let client = MilkyClient::new(comm)?;
+//! let login = client.get_login_info()?;
+//! println!("已登录: {}", login.nickname);
+//!
+//! client.subscribe(|event| println!("收到事件: {event:?}"))?;
+//! let group = client.block_on(|c| async move { c.get_group_info(123456, false).await })?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::message::{
+    SendGroupMessageResponse, SendPrivateMessageResponse, SendTempMessageResponse,
+};
+use crate::api::system::{GetFriendListResponse, GetGroupListResponse, GetLoginInfoResponse};
+use crate::error::{MilkyError, Result};
+use crate::logger::warn;
+use crate::types::communication::Communication;
+
+use milky_types::Event;
+use milky_types::message::out_going::OutgoingSegment;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+/// 事件通道的容量
+const EVENT_CHANNEL_CAPACITY: usize = 128;
+
+/// 同步（阻塞）版本的客户端
+pub struct MilkyClient {
+    /// 内部的异步客户端
+    inner: Arc<crate::MilkyClient>,
+    /// 运行异步任务的运行时
+    runtime: Runtime,
+    /// 尚未被订阅的事件接收端
+    events: Mutex<Option<mpsc::Receiver<Event>>>,
+}
+
+impl MilkyClient {
+    /// 创建客户端，并在内部启动一个多线程的 tokio 运行时
+    ///
+    /// # 参数
+    /// * `comm`: 与服务端的通信方式
+    pub fn new(comm: Communication) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let (tx, rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let inner = {
+            let _guard = runtime.enter();
+            crate::MilkyClient::new(comm, tx)?
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
+            runtime,
+            events: Mutex::new(Some(rx)),
+        })
+    }
+
+    /// 获取内部的异步客户端
+    pub fn inner(&self) -> &Arc<crate::MilkyClient> {
+        &self.inner
+    }
+
+    /// 在内部运行时上执行一个使用异步客户端的任务，并阻塞等待其完成
+    ///
+    /// # 参数
+    /// * `f`: 接收异步客户端并返回 `Future` 的函数
+    pub fn block_on<F, Fut, T>(&self, f: F) -> T
+    where
+        F: FnOnce(Arc<crate::MilkyClient>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.runtime.block_on(f(Arc::clone(&self.inner)))
+    }
+
+    /// 连接事件流，并在后台线程中对每个收到的事件调用回调函数
+    ///
+    /// 每个客户端只能订阅一次。回调在独立的线程中按顺序执行，耗时的回调会使后续事件排队等待。
+    ///
+    /// # 参数
+    /// * `callback`: 处理事件的回调函数
+    ///
+    /// # 返回
+    /// 成功建立连接则返回 `Ok(())`；连接失败或已经订阅过时返回错误
+    pub fn subscribe<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(Event) + Send + 'static,
+    {
+        let mut guard = self.events.lock().unwrap();
+        if guard.is_none() {
+            return Err(MilkyError::Internal("事件已经被订阅".to_string()));
+        }
+        self.runtime.block_on(self.inner.connect_events())?;
+        let mut events = guard.take().unwrap();
+        drop(guard);
+        std::thread::Builder::new()
+            .name("milky-events".to_string())
+            .spawn(move || {
+                while let Some(event) = events.blocking_recv() {
+                    callback(event);
+                }
+                warn!("事件通道已关闭，事件回调线程退出");
+            })?;
+        Ok(())
+    }
+
+    /// 发送一个API请求到后端服务，参见 [`crate::MilkyClient::send_request`]
+    pub fn send_request<P: Serialize, R: DeserializeOwned>(
+        &self,
+        action: &str,
+        params: P,
+    ) -> Result<R> {
+        self.runtime
+            .block_on(self.inner.send_request(action, params))
+    }
+
+    /// 调用任意 API 并直接返回响应中的 `data`，参见 [`crate::MilkyClient::call_raw`]
+    pub fn call_raw(&self, action: &str, params: Value) -> Result<Value> {
+        self.runtime.block_on(self.inner.call_raw(action, params))
+    }
+
+    /// 获取登录信息，参见 [`crate::MilkyClient::get_login_info`]
+    pub fn get_login_info(&self) -> Result<GetLoginInfoResponse> {
+        self.runtime.block_on(self.inner.get_login_info())
+    }
+
+    /// 获取好友列表，参见 [`crate::MilkyClient::get_friend_list`]
+    pub fn get_friend_list(&self, no_cache: bool) -> Result<GetFriendListResponse> {
+        self.runtime.block_on(self.inner.get_friend_list(no_cache))
+    }
+
+    /// 获取群列表，参见 [`crate::MilkyClient::get_group_list`]
+    pub fn get_group_list(&self, no_cache: bool) -> Result<GetGroupListResponse> {
+        self.runtime.block_on(self.inner.get_group_list(no_cache))
+    }
+
+    /// 发送私聊消息，参见 [`crate::MilkyClient::send_private_message`]
+    pub fn send_private_message(
+        &self,
+        user_id: i64,
+        message: Vec<OutgoingSegment>,
+    ) -> Result<SendPrivateMessageResponse> {
+        self.runtime
+            .block_on(self.inner.send_private_message(user_id, message))
+    }
+
+    /// 发送群消息，参见 [`crate::MilkyClient::send_group_message`]
+    pub fn send_group_message(
+        &self,
+        group_id: i64,
+        message: Vec<OutgoingSegment>,
+    ) -> Result<SendGroupMessageResponse> {
+        self.runtime
+            .block_on(self.inner.send_group_message(group_id, message))
+    }
+
+    /// 发送群临时会话消息，参见 [`crate::MilkyClient::send_temp_message`]
+    pub fn send_temp_message(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        message: Vec<OutgoingSegment>,
+    ) -> Result<SendTempMessageResponse> {
+        self.runtime
+            .block_on(self.inner.send_temp_message(group_id, user_id, message))
+    }
+
+    /// 撤回私聊消息，参见 [`crate::MilkyClient::recall_private_message`]
+    pub fn recall_private_message(&self, user_id: i64, message_seq: i64) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.recall_private_message(user_id, message_seq))
+    }
+
+    /// 撤回群消息，参见 [`crate::MilkyClient::recall_group_message`]
+    pub fn recall_group_message(&self, group_id: i64, message_seq: i64) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.recall_group_message(group_id, message_seq))
+    }
+
+    /// 关闭与服务器的连接，参见 [`crate::MilkyClient::shutdown`]
+    pub fn shutdown(&self) {
+        self.runtime.block_on(self.inner.shutdown());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+
+    #[test]
+    fn test_blocking_client() {
+        let client = MilkyClient::new(test_util::ws_comm(OFFLINE)).unwrap();
+        assert!(client.get_login_info().is_err());
+        assert!(client.subscribe(|_| {}).is_err());
+        assert!(client.subscribe(|_| {}).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! 将 Milky 协议桥接为其他机器人协议
+//!
+//! 在同时使用多个机器人框架的场景中，可以让 vivian-rs 充当协议转换器：
+//! 接收 Milky 事件并以其他协议的格式转发，同时接受该协议的动作请求并转换为 Milky API 调用。
+//! 目前支持 [OneBot 12](https://12.onebot.dev/)，参见 [`OneBot12Bridge`]。
+
+pub mod onebot12;
+
+pub use onebot12::OneBot12Bridge;
@@ -0,0 +1,525 @@
+//! OneBot 12 协议桥接
+//!
+//! [`OneBot12Bridge`] 将收到的 Milky 事件转换为 OneBot 12 格式，通过正向 WebSocket 推送给连接的应用，
+//! 并接受应用通过 WebSocket 或 HTTP 发来的 OneBot 12 动作请求，转换为对应的 Milky API 调用。
+//!
+//! 目前支持的动作：`send_message`、`delete_message`、`get_self_info`、`get_friend_list`、
+//! `get_group_list`、`get_status`、`get_version` 与 `get_supported_actions`。
+//!
+//! 监听非回环地址（例如 `0.0.0.0`）时必须通过 [`OneBot12Bridge::with_access_token`] 设置访问令牌，
+//! 否则 [`serve_ws`](OneBot12Bridge::serve_ws) 与 [`serve_http`](OneBot12Bridge::serve_http) 会返回错误，
+//! 避免任何能访问该端口的人都能以机器人的身份发送消息。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! # use milky_rust_sdk::bridge::OneBot12Bridge;
+//! # use std::sync::Arc;
+//! # async fn run(client: Arc<MilkyClient>, mut rx: tokio::sync::mpsc::Receiver<milky_types::Event>) -> milky_rust_sdk::Result<()> {
+//! let bridge = Arc::new(OneBot12Bridge::new(client).with_access_token("secret"));
+//! bridge.serve_ws("0.0.0.0:6700").await?;
+//! bridge.serve_http("0.0.0.0:6701").await?;
+//! while let Some(event) = rx.recv().await {
+//!     bridge.publish(&event);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod convert;
+
+use crate::client::MilkyClient;
+use crate::client::webhook::constant_time_eq;
+use crate::error::{MilkyError, Result};
+use crate::logger::{debug, info, warn};
+use crate::runtime::{self, JoinHandle};
+use convert::{MessageId, PLATFORM, event_to_onebot, segments_from_onebot};
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures_util::{SinkExt, StreamExt};
+use milky_types::Event;
+use milky_types::common::MessageScene;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+/// 事件广播通道的容量，连接的应用处理过慢时会丢弃最旧的事件
+const EVENT_BUFFER: usize = 256;
+
+/// 支持的动作列表
+const SUPPORTED_ACTIONS: &[&str] = &[
+    "send_message",
+    "delete_message",
+    "get_self_info",
+    "get_friend_list",
+    "get_group_list",
+    "get_status",
+    "get_version",
+    "get_supported_actions",
+];
+
+/// OneBot 12 动作响应的返回码
+mod retcode {
+    /// 请求格式错误
+    pub const BAD_REQUEST: i64 = 10001;
+    /// 不支持的动作
+    pub const UNSUPPORTED_ACTION: i64 = 10002;
+    /// 参数错误
+    pub const BAD_PARAM: i64 = 10003;
+    /// 处理动作时出错
+    pub const INTERNAL_HANDLER_ERROR: i64 = 20002;
+}
+
+/// 动作处理失败的原因
+struct ActionError {
+    /// 返回码
+    retcode: i64,
+    /// 错误描述
+    message: String,
+}
+
+impl ActionError {
+    /// 参数错误
+    fn bad_param(message: impl Into<String>) -> Self {
+        Self {
+            retcode: retcode::BAD_PARAM,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<MilkyError> for ActionError {
+    fn from(e: MilkyError) -> Self {
+        Self {
+            retcode: retcode::INTERNAL_HANDLER_ERROR,
+            message: e.to_string(),
+        }
+    }
+}
+
+/// 将 Milky 协议桥接为 OneBot 12 协议
+pub struct OneBot12Bridge {
+    /// 用于调用 Milky API 的客户端
+    client: Arc<MilkyClient>,
+    /// 向已连接的应用广播事件的通道
+    events: broadcast::Sender<Arc<str>>,
+    /// 应用连接时需要提供的访问令牌
+    access_token: Option<String>,
+}
+
+impl OneBot12Bridge {
+    /// 创建协议桥接
+    ///
+    /// # 参数
+    /// * `client`: 用于调用 Milky API 的客户端
+    pub fn new(client: Arc<MilkyClient>) -> Self {
+        Self {
+            client,
+            events: broadcast::channel(EVENT_BUFFER).0,
+            access_token: None,
+        }
+    }
+
+    /// 设置访问令牌，应用需要通过 `Authorization: Bearer <令牌>` 请求头或 `access_token` 查询参数提供
+    pub fn with_access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// 将收到的事件转换为 OneBot 12 格式并推送给所有已连接的应用
+    ///
+    /// # 参数
+    /// * `event`: 收到的 Milky 事件
+    pub fn publish(&self, event: &Event) {
+        if let Some(value) = event_to_onebot(event) {
+            // 没有已连接的应用时发送会失败，直接忽略
+            let _ = self.events.send(value.to_string().into());
+        }
+    }
+
+    /// 启动正向 WebSocket 服务，向连接的应用推送事件并接受动作请求
+    ///
+    /// # 参数
+    /// * `addr`: 监听地址，例如 `0.0.0.0:6700`
+    ///
+    /// # 返回
+    /// 成功则返回后台服务任务的句柄；无法监听地址，或监听非回环地址但未设置访问令牌时返回错误
+    pub async fn serve_ws(self: &Arc<Self>, addr: &str) -> Result<JoinHandle<()>> {
+        let listener = self.bind(addr).await?;
+        info!("OneBot 12 WebSocket 服务正在监听: ws://{addr}");
+        let bridge = Arc::clone(self);
+        Ok(runtime::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        debug!("OneBot 12 应用 {peer} 正在连接");
+                        runtime::spawn(Arc::clone(&bridge).handle_connection(stream));
+                    }
+                    Err(e) => warn!("接受 OneBot 12 WebSocket 连接失败: {e}"),
+                }
+            }
+        }))
+    }
+
+    /// 启动 HTTP 服务，接受 `POST /` 的动作请求
+    ///
+    /// # 参数
+    /// * `addr`: 监听地址，例如 `0.0.0.0:6701`
+    ///
+    /// # 返回
+    /// 成功则返回后台服务任务的句柄；无法监听地址，或监听非回环地址但未设置访问令牌时返回错误
+    pub async fn serve_http(self: &Arc<Self>, addr: &str) -> Result<JoinHandle<()>> {
+        let listener = self.bind(addr).await?;
+        info!("OneBot 12 HTTP 服务正在监听: http://{addr}");
+        let app = self.http_router();
+        Ok(runtime::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!("OneBot 12 HTTP 服务遇到错误: {e}");
+            }
+        }))
+    }
+
+    /// 监听指定地址，监听非回环地址时要求已设置访问令牌
+    async fn bind(&self, addr: &str) -> Result<TcpListener> {
+        let listener = TcpListener::bind(addr).await?;
+        if self.access_token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+            return Err(MilkyError::Config(format!(
+                "OneBot 12 服务监听非回环地址 {addr} 时必须设置访问令牌"
+            )));
+        }
+        Ok(listener)
+    }
+
+    /// 创建处理动作请求的 HTTP 路由，可以合并到已有的 axum 应用中
+    ///
+    /// 路由本身不检查监听地址，未设置访问令牌时应只在回环地址上提供服务。
+    pub fn http_router(self: &Arc<Self>) -> Router {
+        let bridge = Arc::clone(self);
+        let handler = move |headers: HeaderMap, Json(request): Json<Value>| {
+            let bridge = Arc::clone(&bridge);
+            async move {
+                let token = headers
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("Bearer "));
+                if !bridge.authorized(token) {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(bridge.handle_action(request).await))
+            }
+        };
+        Router::new().route("/", post(handler))
+    }
+
+    /// 检查应用提供的访问令牌
+    fn authorized(&self, token: Option<&str>) -> bool {
+        match &self.access_token {
+            Some(expected) => {
+                token.is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+            }
+            None => true,
+        }
+    }
+
+    /// 处理一个 WebSocket 连接
+    async fn handle_connection(self: Arc<Self>, stream: TcpStream) {
+        // 回调的签名由 tungstenite 规定，无法缩小错误类型
+        #[allow(clippy::result_large_err)]
+        let check_token = |request: &Request, response: Response| {
+            let header = request
+                .headers()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "));
+            let query = request.uri().query().and_then(|query| {
+                query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("access_token="))
+            });
+            if self.authorized(header.or(query)) {
+                Ok(response)
+            } else {
+                let mut response = ErrorResponse::new(Some("invalid access token".to_string()));
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(response)
+            }
+        };
+        let ws = match tokio_tungstenite::accept_hdr_async(stream, check_token).await {
+            Ok(ws) => ws,
+            Err(e) => {
+                warn!("OneBot 12 WebSocket 握手失败: {e}");
+                return;
+            }
+        };
+        let (mut sink, mut source) = ws.split();
+        let mut events = self.events.subscribe();
+        let (reply_tx, mut replies) = mpsc::channel::<String>(EVENT_BUFFER);
+
+        let connect = json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "time": chrono::Utc::now().timestamp() as f64,
+            "type": "meta",
+            "detail_type": "connect",
+            "sub_type": "",
+            "version": version_info(),
+        });
+        if sink
+            .send(WsMessage::text(connect.to_string()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+
+        loop {
+            let outgoing = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => event.to_string(),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("OneBot 12 应用处理过慢，已丢弃 {n} 个事件");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(reply) = replies.recv() => reply,
+                message = source.next() => match message {
+                    Some(Ok(WsMessage::Text(text))) => {
+                        let bridge = Arc::clone(&self);
+                        let reply_tx = reply_tx.clone();
+                        let request = serde_json::from_str(&text).unwrap_or(Value::Null);
+                        runtime::spawn(async move {
+                            let response = bridge.handle_action(request).await;
+                            let _ = reply_tx.send(response.to_string()).await;
+                        });
+                        continue;
+                    }
+                    Some(Ok(WsMessage::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        warn!("OneBot 12 WebSocket 连接出错: {e}");
+                        break;
+                    }
+                },
+            };
+            if sink.send(WsMessage::text(outgoing)).await.is_err() {
+                break;
+            }
+        }
+        debug!("OneBot 12 WebSocket 连接已关闭");
+    }
+
+    /// 处理一个 OneBot 12 动作请求
+    ///
+    /// # 参数
+    /// * `request`: 动作请求，包含 `action`、`params` 与可选的 `echo` 字段
+    ///
+    /// # 返回
+    /// OneBot 12 格式的动作响应
+    pub async fn handle_action(&self, request: Value) -> Value {
+        let echo = request.get("echo").cloned();
+        let result = match request.get("action").and_then(Value::as_str) {
+            Some(action) => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                self.dispatch(action, &params).await
+            }
+            None => Err(ActionError {
+                retcode: retcode::BAD_REQUEST,
+                message: "请求缺少 action 字段".to_string(),
+            }),
+        };
+        let mut response = match result {
+            Ok(data) => json!({"status": "ok", "retcode": 0, "data": data, "message": ""}),
+            Err(e) => {
+                json!({"status": "failed", "retcode": e.retcode, "data": null, "message": e.message})
+            }
+        };
+        if let Some(echo) = echo {
+            response["echo"] = echo;
+        }
+        response
+    }
+
+    /// 将动作转换为对应的 Milky API 调用
+    async fn dispatch(
+        &self,
+        action: &str,
+        params: &Value,
+    ) -> std::result::Result<Value, ActionError> {
+        let data = match action {
+            "send_message" => {
+                let message =
+                    segments_from_onebot(&params["message"]).map_err(ActionError::bad_param)?;
+                let (scene, peer_id, resp) = match params["detail_type"].as_str() {
+                    Some("private") => {
+                        let user_id = id_param(params, "user_id")?;
+                        let resp = self.client.send_private_message(user_id, message).await?;
+                        (MessageScene::Friend, user_id, (resp.message_seq, resp.time))
+                    }
+                    Some("group") => {
+                        let group_id = id_param(params, "group_id")?;
+                        let resp = self.client.send_group_message(group_id, message).await?;
+                        (MessageScene::Group, group_id, (resp.message_seq, resp.time))
+                    }
+                    _ => {
+                        return Err(ActionError::bad_param(
+                            "detail_type 必须是 private 或 group",
+                        ));
+                    }
+                };
+                let message_id = MessageId {
+                    scene,
+                    peer_id,
+                    message_seq: resp.0,
+                };
+                json!({"message_id": message_id.to_string(), "time": resp.1 as f64})
+            }
+            "delete_message" => {
+                let raw = params["message_id"].as_str().unwrap_or_default();
+                let message_id = MessageId::parse(raw)
+                    .ok_or_else(|| ActionError::bad_param(format!("无法识别的消息ID: {raw}")))?;
+                match message_id.scene {
+                    MessageScene::Friend => {
+                        self.client
+                            .recall_private_message(message_id.peer_id, message_id.message_seq)
+                            .await?
+                    }
+                    MessageScene::Group => {
+                        self.client
+                            .recall_group_message(message_id.peer_id, message_id.message_seq)
+                            .await?
+                    }
+                    MessageScene::Temp => {
+                        return Err(ActionError::bad_param("不支持撤回临时会话消息"));
+                    }
+                }
+                Value::Null
+            }
+            "get_self_info" => {
+                let info = self.client.get_login_info().await?;
+                json!({
+                    "user_id": info.uin.to_string(),
+                    "user_name": info.nickname,
+                    "user_displayname": "",
+                })
+            }
+            "get_friend_list" => {
+                let friends = self.client.get_friend_list(false).await?.friends;
+                friends
+                    .into_iter()
+                    .map(|friend| {
+                        json!({
+                            "user_id": friend.user_id.to_string(),
+                            "user_name": friend.nickname,
+                            "user_displayname": "",
+                            "user_remark": friend.remark,
+                        })
+                    })
+                    .collect()
+            }
+            "get_group_list" => {
+                let groups = self.client.get_group_list(false).await?.groups;
+                groups
+                    .into_iter()
+                    .map(|group| {
+                        json!({
+                            "group_id": group.group_id.to_string(),
+                            "group_name": group.group_name,
+                        })
+                    })
+                    .collect()
+            }
+            "get_status" => {
+                let online = self.client.get_login_info().await.ok();
+                let bots: Vec<_> = online
+                    .iter()
+                    .map(|info| {
+                        json!({
+                            "self": {"platform": PLATFORM, "user_id": info.uin.to_string()},
+                            "online": true,
+                        })
+                    })
+                    .collect();
+                json!({"good": online.is_some(), "bots": bots})
+            }
+            "get_version" => version_info(),
+            "get_supported_actions" => json!(SUPPORTED_ACTIONS),
+            _ => {
+                return Err(ActionError {
+                    retcode: retcode::UNSUPPORTED_ACTION,
+                    message: format!("不支持的动作: {action}"),
+                });
+            }
+        };
+        Ok(data)
+    }
+}
+
+/// 读取以字符串或数字表示的QQ号参数
+fn id_param(params: &Value, name: &str) -> std::result::Result<i64, ActionError> {
+    let value = &params[name];
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| ActionError::bad_param(format!("缺少有效的参数 {name}")))
+}
+
+/// 实现的版本信息
+fn version_info() -> Value {
+    json!({
+        "impl": "vivian-rs",
+        "version": env!("CARGO_PKG_VERSION"),
+        "onebot_version": "12",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+
+    #[tokio::test]
+    async fn test_handle_action_errors() {
+        let (client, _rx) = test_util::client(OFFLINE);
+        let bridge = OneBot12Bridge::new(Arc::new(client));
+
+        let resp = bridge
+            .handle_action(json!({"action": "get_version", "echo": 7}))
+            .await;
+        assert_eq!(resp["status"], "ok");
+        assert_eq!(resp["data"]["onebot_version"], "12");
+        assert_eq!(resp["echo"], 7);
+
+        let resp = bridge.handle_action(json!({"action": "upload_file"})).await;
+        assert_eq!(resp["retcode"], retcode::UNSUPPORTED_ACTION);
+
+        let resp = bridge
+            .handle_action(json!({"action": "send_message", "params": {"detail_type": "channel", "message": []}}))
+            .await;
+        assert_eq!(resp["retcode"], retcode::BAD_PARAM);
+    }
+
+    #[tokio::test]
+    async fn test_require_token_on_public_addr() {
+        let (client, _rx) = test_util::client(OFFLINE);
+        let client = Arc::new(client);
+
+        let bridge = Arc::new(OneBot12Bridge::new(Arc::clone(&client)));
+        assert!(matches!(
+            bridge.serve_http("0.0.0.0:0").await,
+            Err(MilkyError::Config(_))
+        ));
+        bridge.serve_ws("127.0.0.1:0").await.unwrap().abort();
+        assert!(bridge.authorized(None));
+
+        let bridge = Arc::new(OneBot12Bridge::new(client).with_access_token("secret"));
+        bridge.serve_ws("0.0.0.0:0").await.unwrap().abort();
+        assert!(bridge.authorized(Some("secret")));
+        assert!(!bridge.authorized(Some("secreT")));
+        assert!(!bridge.authorized(Some("secret2")));
+        assert!(!bridge.authorized(None));
+    }
+}
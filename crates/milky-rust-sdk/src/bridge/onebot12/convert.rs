@@ -0,0 +1,404 @@
+//! Milky 与 OneBot 12 数据格式之间的转换
+//!
+//! OneBot 12 的消息ID为字符串，这里使用 `场景:会话ID:消息序列号` 的格式（例如 `group:123456:42`），
+//! 以便从消息ID还原出撤回、回复所需的信息。标准中没有对应的 QQ 特有消息段与事件，
+//! 以 `qq.` 为前缀的扩展类型输出。
+
+use crate::utils::get_plain_text_from_segments;
+
+use milky_types::common::{FileUri, MessageScene};
+use milky_types::message::in_coming::IncomingSegment;
+use milky_types::message::out_going::{
+    FaceData, ImageData, MentionAllData, MentionData, OutgoingSegment, RecordData, ReplyData,
+    TextData, VideoData,
+};
+use milky_types::{Event, EventKind, MessageEvent};
+use serde_json::{Map, Value, json};
+
+/// OneBot 12 中使用的平台名称
+pub const PLATFORM: &str = "qq";
+
+/// 由场景、会话ID与消息序列号组成的消息ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageId {
+    /// 消息场景
+    pub scene: MessageScene,
+    /// 好友QQ号或群号
+    pub peer_id: i64,
+    /// 消息序列号
+    pub message_seq: i64,
+}
+
+impl MessageId {
+    /// 解析 `场景:会话ID:消息序列号` 格式的消息ID
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, ':');
+        let scene = match parts.next()? {
+            "friend" => MessageScene::Friend,
+            "group" => MessageScene::Group,
+            "temp" => MessageScene::Temp,
+            _ => return None,
+        };
+        Some(Self {
+            scene,
+            peer_id: parts.next()?.parse().ok()?,
+            message_seq: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for MessageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scene = match self.scene {
+            MessageScene::Friend => "friend",
+            MessageScene::Group => "group",
+            MessageScene::Temp => "temp",
+        };
+        write!(f, "{scene}:{}:{}", self.peer_id, self.message_seq)
+    }
+}
+
+/// 构造 OneBot 12 事件的公共字段
+fn base_event(event: &Event, kind: &str, detail_type: &str) -> Map<String, Value> {
+    let value = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "time": event.time as f64,
+        "type": kind,
+        "detail_type": detail_type,
+        "sub_type": "",
+        "self": { "platform": PLATFORM, "user_id": event.self_id.to_string() },
+    });
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+/// 将 Milky 事件转换为 OneBot 12 事件
+///
+/// # 返回
+/// 转换后的事件 JSON；没有对应的 OneBot 12 事件时返回 `None`
+pub fn event_to_onebot(event: &Event) -> Option<Value> {
+    let mut map = match &event.kind {
+        EventKind::MessageReceive { message } => {
+            let base = message.base_message();
+            let message_id = MessageId {
+                scene: base.message_scene,
+                peer_id: base.peer_id,
+                message_seq: base.message_seq,
+            };
+            let (detail_type, group_id) = match message {
+                MessageEvent::Group(_) => ("group", Some(base.peer_id)),
+                MessageEvent::Friend(_) | MessageEvent::Temp(_) => ("private", None),
+            };
+            let mut map = base_event(event, "message", detail_type);
+            map.insert("message_id".into(), json!(message_id.to_string()));
+            map.insert(
+                "message".into(),
+                segments_to_onebot(&base.segments, base.message_scene, base.peer_id),
+            );
+            map.insert(
+                "alt_message".into(),
+                json!(get_plain_text_from_segments(&base.segments)),
+            );
+            map.insert("user_id".into(), json!(base.sender_id.to_string()));
+            if let Some(group_id) = group_id {
+                map.insert("group_id".into(), json!(group_id.to_string()));
+            }
+            map
+        }
+        EventKind::MessageRecall {
+            message_scene,
+            peer_id,
+            message_seq,
+            sender_id,
+            operator_id,
+            ..
+        } => {
+            let message_id = MessageId {
+                scene: *message_scene,
+                peer_id: *peer_id,
+                message_seq: *message_seq,
+            };
+            let detail_type = match message_scene {
+                MessageScene::Group => "group_message_delete",
+                _ => "private_message_delete",
+            };
+            let mut map = base_event(event, "notice", detail_type);
+            map.insert("message_id".into(), json!(message_id.to_string()));
+            map.insert("user_id".into(), json!(sender_id.to_string()));
+            if *message_scene == MessageScene::Group {
+                map.insert("group_id".into(), json!(peer_id.to_string()));
+                map.insert("operator_id".into(), json!(operator_id.to_string()));
+                let sub_type = if operator_id == sender_id {
+                    "recall"
+                } else {
+                    "delete"
+                };
+                map.insert("sub_type".into(), json!(sub_type));
+            }
+            map
+        }
+        EventKind::GroupMemberIncrease {
+            group_id,
+            user_id,
+            operator_id,
+            invitor_id,
+        } => {
+            let mut map = base_event(event, "notice", "group_member_increase");
+            let sub_type = if invitor_id.is_some() {
+                "invite"
+            } else {
+                "join"
+            };
+            map.insert("sub_type".into(), json!(sub_type));
+            map.insert("group_id".into(), json!(group_id.to_string()));
+            map.insert("user_id".into(), json!(user_id.to_string()));
+            let operator = invitor_id.or(*operator_id).unwrap_or(*user_id);
+            map.insert("operator_id".into(), json!(operator.to_string()));
+            map
+        }
+        EventKind::GroupMemberDecrease {
+            group_id,
+            user_id,
+            operator_id,
+        } => {
+            let mut map = base_event(event, "notice", "group_member_decrease");
+            let sub_type = match operator_id {
+                Some(op) if op != user_id => "kick",
+                _ => "leave",
+            };
+            map.insert("sub_type".into(), json!(sub_type));
+            map.insert("group_id".into(), json!(group_id.to_string()));
+            map.insert("user_id".into(), json!(user_id.to_string()));
+            let operator = operator_id.unwrap_or(*user_id);
+            map.insert("operator_id".into(), json!(operator.to_string()));
+            map
+        }
+        EventKind::BotOffline { reason } => {
+            let mut map = base_event(event, "meta", "status_update");
+            map.insert(
+                "status".into(),
+                json!({
+                    "good": false,
+                    "bots": [{
+                        "self": { "platform": PLATFORM, "user_id": event.self_id.to_string() },
+                        "online": false,
+                        "qq.reason": reason,
+                    }],
+                }),
+            );
+            map
+        }
+        _ => {
+            // 其余事件以 `qq.` 前缀的扩展事件输出，字段与 Milky 保持一致
+            let Ok(Value::Object(raw)) = serde_json::to_value(&event.kind) else {
+                return None;
+            };
+            let event_type = raw.get("event_type")?.as_str()?.to_string();
+            let mut map = base_event(event, "notice", &format!("qq.{event_type}"));
+            if let Some(Value::Object(data)) = raw.get("data") {
+                for (key, value) in data {
+                    map.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            map
+        }
+    };
+    map.retain(|_, v| !v.is_null());
+    Some(Value::Object(map))
+}
+
+/// 将 Milky 接收到的消息段转换为 OneBot 12 消息段
+///
+/// # 参数
+/// * `segments`: 消息段
+/// * `scene`: 消息所在的场景，用于生成回复消息段中的消息ID
+/// * `peer_id`: 消息所在的好友QQ号或群号
+pub fn segments_to_onebot(
+    segments: &[IncomingSegment],
+    scene: MessageScene,
+    peer_id: i64,
+) -> Value {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            IncomingSegment::Text { text } => json!({"type": "text", "data": {"text": text}}),
+            IncomingSegment::Mention { user_id } => {
+                json!({"type": "mention", "data": {"user_id": user_id.to_string()}})
+            }
+            IncomingSegment::MentionAll {} => json!({"type": "mention_all", "data": {}}),
+            IncomingSegment::Reply { message_seq } => {
+                let message_id = MessageId {
+                    scene,
+                    peer_id,
+                    message_seq: *message_seq,
+                };
+                json!({"type": "reply", "data": {"message_id": message_id.to_string()}})
+            }
+            IncomingSegment::Image {
+                resource_id,
+                temp_url,
+                ..
+            } => json!({"type": "image", "data": {"file_id": resource_id, "qq.url": temp_url}}),
+            IncomingSegment::Record {
+                resource_id,
+                temp_url,
+                ..
+            } => json!({"type": "voice", "data": {"file_id": resource_id, "qq.url": temp_url}}),
+            IncomingSegment::Video {
+                resource_id,
+                temp_url,
+                ..
+            } => json!({"type": "video", "data": {"file_id": resource_id, "qq.url": temp_url}}),
+            IncomingSegment::File { file_id, .. } => {
+                json!({"type": "file", "data": {"file_id": file_id}})
+            }
+            other => {
+                let mut raw = serde_json::to_value(other).unwrap_or(Value::Null);
+                if let Some(Value::String(kind)) = raw.get_mut("type") {
+                    *kind = format!("qq.{kind}");
+                }
+                raw
+            }
+        })
+        .collect()
+}
+
+/// 将 OneBot 12 消息段转换为待发送的 Milky 消息段
+///
+/// 图片、语音、视频消息段的 `file_id` 需要是 Milky 支持的文件 URI（本地路径、网络链接或 `base64://` 内容）。
+///
+/// # 返回
+/// 转换成功则返回消息段列表；遇到不支持或格式错误的消息段时返回错误描述
+pub fn segments_from_onebot(message: &Value) -> Result<Vec<OutgoingSegment>, String> {
+    let segments = match message {
+        Value::String(text) => {
+            return Ok(vec![OutgoingSegment::Text(TextData { text: text.clone() })]);
+        }
+        Value::Array(segments) => segments,
+        _ => return Err("message 必须是消息段数组".to_string()),
+    };
+
+    segments
+        .iter()
+        .map(|segment| {
+            let kind = segment["type"].as_str().unwrap_or_default();
+            let data = &segment["data"];
+            let str_field = |name: &str| {
+                data[name]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("{kind} 消息段缺少字段 {name}"))
+            };
+            let id_field = |name: &str| {
+                str_field(name)?
+                    .parse::<i64>()
+                    .map_err(|_| format!("{kind} 消息段的 {name} 不是有效的QQ号"))
+            };
+            let uri_field = || {
+                FileUri::parse(&str_field("file_id")?)
+                    .map_err(|e| format!("{kind} 消息段的 file_id 不是有效的文件 URI: {e}"))
+            };
+            let segment = match kind {
+                "text" => OutgoingSegment::Text(TextData {
+                    text: str_field("text")?,
+                }),
+                "mention" => OutgoingSegment::Mention(MentionData {
+                    user_id: id_field("user_id")?,
+                }),
+                "mention_all" => OutgoingSegment::MentionAll(MentionAllData),
+                "reply" => {
+                    let message_id = str_field("message_id")?;
+                    let message_id = MessageId::parse(&message_id)
+                        .ok_or_else(|| format!("无法识别的消息ID: {message_id}"))?;
+                    OutgoingSegment::Reply(ReplyData {
+                        message_seq: message_id.message_seq,
+                    })
+                }
+                "image" => OutgoingSegment::Image(ImageData {
+                    uri: uri_field()?,
+                    summary: None,
+                    sub_type: "normal".to_string(),
+                }),
+                "voice" | "audio" => OutgoingSegment::Record(RecordData { uri: uri_field()? }),
+                "video" => OutgoingSegment::Video(VideoData {
+                    uri: uri_field()?,
+                    thumb_uri: None,
+                }),
+                "qq.face" => OutgoingSegment::Face(FaceData {
+                    face_id: str_field("face_id")?,
+                }),
+                _ => return Err(format!("不支持的消息段类型: {kind}")),
+            };
+            Ok(segment)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use milky_types::message::in_coming::{GroupMessage, IncomingMessage};
+
+    #[test]
+    fn test_group_message_to_onebot() {
+        let event = Event {
+            time: 1700000000,
+            self_id: 10000,
+            kind: EventKind::MessageReceive {
+                message: MessageEvent::Group(GroupMessage {
+                    message: IncomingMessage {
+                        peer_id: 123456,
+                        message_seq: 42,
+                        sender_id: 10001,
+                        time: 1700000000,
+                        segments: vec![
+                            IncomingSegment::Reply { message_seq: 41 },
+                            IncomingSegment::Text {
+                                text: "你好".to_string(),
+                            },
+                        ],
+                        message_scene: MessageScene::Group,
+                    },
+                    ..Default::default()
+                }),
+            },
+        };
+
+        let value = event_to_onebot(&event).unwrap();
+        assert_eq!(value["type"], "message");
+        assert_eq!(value["detail_type"], "group");
+        assert_eq!(value["message_id"], "group:123456:42");
+        assert_eq!(value["group_id"], "123456");
+        assert_eq!(value["user_id"], "10001");
+        assert_eq!(value["alt_message"], "你好");
+        assert_eq!(value["self"]["user_id"], "10000");
+        assert_eq!(value["message"][0]["data"]["message_id"], "group:123456:41");
+    }
+
+    #[test]
+    fn test_segments_from_onebot() {
+        let message = json!([
+            {"type": "reply", "data": {"message_id": "friend:10001:7"}},
+            {"type": "mention", "data": {"user_id": "10002"}},
+            {"type": "image", "data": {"file_id": "https://example.com/a.png"}},
+        ]);
+        let segments = segments_from_onebot(&message).unwrap();
+        assert!(matches!(
+            segments[0],
+            OutgoingSegment::Reply(ReplyData { message_seq: 7 })
+        ));
+        assert!(matches!(
+            segments[1],
+            OutgoingSegment::Mention(MentionData { user_id: 10002 })
+        ));
+        assert!(matches!(segments[2], OutgoingSegment::Image(_)));
+
+        assert!(segments_from_onebot(&json!([{"type": "location", "data": {}}])).is_err());
+        assert_eq!(
+            MessageId::parse("temp:1:2").unwrap().to_string(),
+            "temp:1:2"
+        );
+    }
+}
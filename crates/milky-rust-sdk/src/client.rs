@@ -4,27 +4,137 @@
 //! 它管理连接状态、认证信息，并提供了一系列方法来调用具体的API端点
 //! 和处理从服务器推送的事件
 
+pub mod builder;
+pub mod cancel;
+#[cfg(feature = "websocket")]
+mod connection;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub mod decode;
+#[cfg(all(feature = "websocket", feature = "webhook"))]
+pub mod failover;
+#[cfg(feature = "websocket")]
+pub mod heartbeat;
+pub mod interceptor;
+pub mod options;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub mod overflow;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub mod pool;
+pub mod proxy;
+pub mod rate_limit;
+#[cfg(feature = "websocket")]
+pub mod reconnect;
+pub mod retry;
+#[cfg(feature = "websocket")]
+pub mod status;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub mod subscribe;
+pub mod task;
+pub mod tls;
+pub mod token;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "websocket")]
+pub mod ws_api;
+
+pub use builder::MilkyClientBuilder;
+pub use cancel::CancellationToken;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub use decode::DeserializeMode;
+#[cfg(all(feature = "websocket", feature = "webhook"))]
+pub use failover::WebhookFailover;
+#[cfg(feature = "websocket")]
+pub use heartbeat::Heartbeat;
+pub use interceptor::{InterceptFuture, Interceptor};
+pub use options::RequestOptions;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub use overflow::OverflowPolicy;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub use pool::ClientPool;
+pub use proxy::Proxy;
+pub use rate_limit::{RateLimit, RateLimiter};
+#[cfg(feature = "websocket")]
+pub use reconnect::ReconnectPolicy;
+pub use retry::RetryPolicy;
+#[cfg(feature = "websocket")]
+pub use status::ConnectionStatus;
+pub use task::EventTask;
+pub use tls::TlsConfig;
+pub use token::{AsyncTokenProvider, TokenFuture, TokenProvider};
+#[cfg(feature = "websocket")]
+pub use ws_api::ApiTransport;
+
+#[cfg(feature = "websocket")]
+use crate::client::connection::EventWsConnection;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::client::decode::EventDecoder;
+#[cfg(feature = "websocket")]
+use crate::client::heartbeat::HeartbeatState;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::client::task::LastError;
+#[cfg(feature = "websocket")]
+use crate::client::tls::Connector;
+#[cfg(feature = "websocket")]
+use crate::client::ws_api::WsApi;
 use crate::error::{MilkyError, Result};
+use crate::logger::body::BodyLogger;
+#[cfg(feature = "websocket")]
+use crate::logger::debug;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::logger::error;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::logger::redact::LogPayload;
+#[cfg(feature = "websocket")]
+use crate::logger::redact::redact_url;
+use crate::logger::redact::summarize;
+use crate::logger::{debug_fields, info, warn};
+use crate::media::transcode::Transcoder;
+use crate::runtime;
+use crate::stats::events::EventPipelineRecorder;
+use crate::stats::{ApiStatsRecorder, MetricsSink};
 use crate::types::common::ApiResponse;
 use crate::types::communication::Communication;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
 use crate::types::message::OriginalMessage;
+use crate::utils::cache::TtlCache;
+use crate::utils::json;
 
-use axum::routing::post;
-use axum::{Json, Router};
-use futures_util::{StreamExt, lock::Mutex};
-use log::{debug, error, info, warn};
+use bytes::Bytes;
+#[cfg(feature = "websocket")]
+use futures_util::StreamExt;
+use futures_util::lock::Mutex;
 use milky_types::Event;
 use reqwest::StatusCode;
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+#[cfg(feature = "websocket")]
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, oneshot};
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+#[cfg(feature = "websocket")]
 use tokio_tungstenite::{
     MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message as WsMessage,
 };
+#[cfg(all(feature = "tracing", feature = "websocket"))]
+use tracing::Instrument;
 use url::Url;
 
+/// 事件 WebSocket 连接的数据流
+#[cfg(feature = "websocket")]
+type EventWsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 资源临时下载链接的默认缓存时长
+const DEFAULT_TEMP_URL_TTL: Duration = Duration::from_secs(600);
+
+/// 最多缓存的资源临时下载链接数量
+const TEMP_URL_CACHE_CAPACITY: usize = 1024;
+
+/// 最多缓存的头像数量
+const AVATAR_CACHE_CAPACITY: usize = 256;
+
 /// 与后端服务交互的主要结构体
 pub struct MilkyClient {
     /// 用于发送HTTP API请求的 `reqwest` 客户端实例
@@ -34,19 +144,86 @@ pub struct MilkyClient {
     /// API请求的基础URL，例如 `http://127.0.0.1:8080/api/`
     api_base_url: Url,
     /// WebHook接收事件的URL
+    #[cfg(feature = "webhook")]
     event_wh_url: String,
+    /// WebHook 事件接收服务器实际监听的地址
+    #[cfg(feature = "webhook")]
+    webhook_addr: Arc<std::sync::Mutex<Option<std::net::SocketAddr>>>,
     /// 事件WebSocket连接的URL，例如 `ws://127.0.0.1:8080/event`
+    #[cfg(feature = "websocket")]
     event_ws_url: Option<Url>,
     /// 可选的访问令牌，用于API请求和WebSocket连接的认证
     access_token: Option<String>,
-    /// WebSocket流的可选共享引用
-    /// 使用 `Arc<Mutex<...>>` 来允许多个任务安全地访问和修改WebSocket流
-    /// `Option` 表示连接可能尚未建立或已关闭
-    ws_stream: Arc<Mutex<Option<WebSocketStream<MaybeTlsStream<TcpStream>>>>>,
-    // 用于发送关闭 WebSocket 的信号
-    ws_shutdown_signal_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    /// 运行时刷新访问令牌的提供者，设置后优先于 `access_token`
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    /// API 请求的超时时间，为 `None` 时不限制
+    request_timeout: Option<Duration>,
+    /// API 调用失败后的重试策略，为 `None` 时不重试
+    retry_policy: Option<RetryPolicy>,
+    /// API 调用的限速器，为 `None` 时不限速
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// API 请求的拦截器，按注册顺序排列
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    /// 出站代理，为 `None` 时直接连接
+    proxy: Option<Proxy>,
+    /// HTTP API 请求使用的 TLS 设置，为 `None` 时使用默认设置
+    api_tls: Option<TlsConfig>,
+    /// 事件 WebSocket 连接使用的 TLS 连接器，为 `None` 时使用默认设置
+    #[cfg(feature = "websocket")]
+    event_tls: Option<Connector>,
+    /// 决定客户端生命周期的取消令牌，被取消时关闭事件接收与后台任务
+    pub(crate) cancel_token: CancellationToken,
+    /// 当前事件接收的取消令牌，用于关闭 WebSocket 事件读取循环或 WebHook 服务器
+    #[cfg_attr(not(any(feature = "websocket", feature = "webhook")), allow(dead_code))]
+    shutdown_token: Arc<Mutex<Option<CancellationToken>>>,
+    /// 事件 WebSocket 连接断开后的重连策略，为 `None` 时不重连
+    #[cfg(feature = "websocket")]
+    reconnect_policy: Option<ReconnectPolicy>,
+    /// 事件 WebSocket 连接的心跳保活设置，为 `None` 时不发送心跳
+    #[cfg(feature = "websocket")]
+    heartbeat: Option<Heartbeat>,
+    /// 事件 WebSocket 无法恢复时切换为 WebHook 的设置，为 `None` 时不切换
+    #[cfg(all(feature = "websocket", feature = "webhook"))]
+    failover: Option<WebhookFailover>,
+    /// 通过事件 WebSocket 连接调用 API 的状态，为 `None` 时通过 HTTP 调用
+    #[cfg(feature = "websocket")]
+    ws_api: Option<Arc<WsApi>>,
+    /// 事件 WebSocket 连接状态的广播通道
+    #[cfg(feature = "websocket")]
+    status_sender: broadcast::Sender<ConnectionStatus>,
     /// 用于将从WebSocket接收到的事件发送到上层处理逻辑的mpsc通道发送端
+    #[cfg_attr(not(any(feature = "websocket", feature = "webhook")), allow(dead_code))]
     event_sender: mpsc::Sender<Event>,
+    /// 向所有订阅者广播事件的通道
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    event_broadcast: broadcast::Sender<Event>,
+    /// 资源临时下载链接的缓存，以资源ID为键
+    pub(crate) temp_url_cache: TtlCache<String, String>,
+    /// 资源临时下载链接的默认缓存时长
+    pub(crate) temp_url_ttl: Duration,
+    /// 头像图片的缓存，以头像链接为键
+    pub(crate) avatar_cache: TtlCache<String, Bytes>,
+    /// 替换 QQ 头像服务地址的基础地址，为 `None` 时使用公开的头像服务，测试中指向模拟服务
+    pub(crate) avatar_base_url: Option<String>,
+    /// 发送前的图片预处理设置
+    #[cfg(feature = "image")]
+    pub(crate) image_preprocessor: Option<crate::media::image::ImagePreprocessor>,
+    /// 语音消息段的音频转码器，为 `None` 时不进行转码
+    pub(crate) transcoder: Option<Arc<dyn Transcoder>>,
+    /// 按 API 操作统计的调用数据
+    pub(crate) api_stats: Arc<ApiStatsRecorder>,
+    /// 事件管线的积压情况
+    pub(crate) event_stats: Arc<EventPipelineRecorder>,
+    /// 按设置的方式解析事件的解析器
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub(crate) event_decoder: Arc<EventDecoder>,
+    /// 导出调用数据的指标导出器，为 `None` 时不导出
+    pub(crate) metrics_sink: Option<Arc<dyn MetricsSink>>,
+    /// 请求与响应内容的记录器，为 `None` 时不记录
+    pub(crate) body_logger: Option<BodyLogger>,
+    /// 向 API 请求注入链路上下文的传播器
+    #[cfg(feature = "otel")]
+    pub(crate) trace_propagator: Arc<dyn crate::otel::TracePropagator>,
 }
 
 impl MilkyClient {
@@ -79,29 +256,74 @@ impl MilkyClient {
                 api_base_url.set_path("api/");
 
                 // 构建事件WebSocket URL
-                let mut event_ws_url = ws_url.clone();
-                event_ws_url.set_path("event");
-                if let Some(token) = &config.access_token {
-                    // 如果有访问令牌，则添加到查询参数中
+                #[cfg(feature = "websocket")]
+                let event_ws_url = {
+                    let mut event_ws_url = ws_url.clone();
+                    event_ws_url.set_path("event");
+                    if let Some(token) = &config.access_token {
+                        // 如果有访问令牌，则添加到查询参数中
+                        event_ws_url
+                            .query_pairs_mut()
+                            .append_pair("access_token", token);
+                    }
                     event_ws_url
-                        .query_pairs_mut()
-                        .append_pair("access_token", token);
-                }
+                };
 
                 Ok(Self {
                     http_client: reqwest::Client::new(),
                     api_base_url,
                     comm_type: _comm,
+                    #[cfg(feature = "webhook")]
                     event_wh_url: String::new(),
+                    #[cfg(feature = "webhook")]
+                    webhook_addr: Arc::default(),
+                    #[cfg(feature = "websocket")]
                     event_ws_url: Some(event_ws_url),
                     access_token: config.access_token,
-                    ws_stream: Arc::new(Mutex::new(None)),
-                    ws_shutdown_signal_tx: Arc::new(Mutex::new(None)),
+                    token_provider: None,
+                    request_timeout: None,
+                    retry_policy: None,
+                    rate_limiter: None,
+                    interceptors: Vec::new(),
+                    proxy: None,
+                    api_tls: None,
+                    #[cfg(feature = "websocket")]
+                    event_tls: None,
+                    cancel_token: CancellationToken::new(),
+                    shutdown_token: Arc::new(Mutex::new(None)),
+                    #[cfg(feature = "websocket")]
+                    reconnect_policy: None,
+                    #[cfg(feature = "websocket")]
+                    heartbeat: None,
+                    #[cfg(all(feature = "websocket", feature = "webhook"))]
+                    failover: None,
+                    #[cfg(feature = "websocket")]
+                    ws_api: None,
+                    #[cfg(feature = "websocket")]
+                    status_sender: broadcast::channel(status::STATUS_CHANNEL_CAPACITY).0,
                     event_sender,
+                    #[cfg(any(feature = "websocket", feature = "webhook"))]
+                    event_broadcast: broadcast::channel(subscribe::DEFAULT_BROADCAST_CAPACITY).0,
+                    temp_url_cache: TtlCache::new(TEMP_URL_CACHE_CAPACITY),
+                    temp_url_ttl: DEFAULT_TEMP_URL_TTL,
+                    avatar_cache: TtlCache::new(AVATAR_CACHE_CAPACITY),
+                    avatar_base_url: None,
+                    #[cfg(feature = "image")]
+                    image_preprocessor: None,
+                    transcoder: None,
+                    api_stats: Arc::default(),
+                    event_stats: Arc::default(),
+                    #[cfg(any(feature = "websocket", feature = "webhook"))]
+                    event_decoder: Arc::default(),
+                    metrics_sink: None,
+                    body_logger: None,
+                    #[cfg(feature = "otel")]
+                    trace_propagator: Arc::new(crate::otel::W3cPropagator),
                 })
             }
             Communication::WebHook(config) => {
                 // 构建Event基础URL
+                #[cfg(feature = "webhook")]
                 let event_base_url = format!("{}:{}", config.host, config.port);
 
                 // 构建Event基础URL
@@ -112,12 +334,52 @@ impl MilkyClient {
                     http_client: reqwest::Client::new(),
                     comm_type: _comm,
                     api_base_url,
+                    #[cfg(feature = "webhook")]
                     event_wh_url: event_base_url,
+                    #[cfg(feature = "webhook")]
+                    webhook_addr: Arc::default(),
+                    #[cfg(feature = "websocket")]
                     event_ws_url: None,
                     access_token: config.access_token,
-                    ws_stream: Arc::new(Mutex::new(None)),
-                    ws_shutdown_signal_tx: Arc::new(Mutex::new(None)),
+                    token_provider: None,
+                    request_timeout: None,
+                    retry_policy: None,
+                    rate_limiter: None,
+                    interceptors: Vec::new(),
+                    proxy: None,
+                    api_tls: None,
+                    #[cfg(feature = "websocket")]
+                    event_tls: None,
+                    cancel_token: CancellationToken::new(),
+                    shutdown_token: Arc::new(Mutex::new(None)),
+                    #[cfg(feature = "websocket")]
+                    reconnect_policy: None,
+                    #[cfg(feature = "websocket")]
+                    heartbeat: None,
+                    #[cfg(all(feature = "websocket", feature = "webhook"))]
+                    failover: None,
+                    #[cfg(feature = "websocket")]
+                    ws_api: None,
+                    #[cfg(feature = "websocket")]
+                    status_sender: broadcast::channel(status::STATUS_CHANNEL_CAPACITY).0,
                     event_sender,
+                    #[cfg(any(feature = "websocket", feature = "webhook"))]
+                    event_broadcast: broadcast::channel(subscribe::DEFAULT_BROADCAST_CAPACITY).0,
+                    temp_url_cache: TtlCache::new(TEMP_URL_CACHE_CAPACITY),
+                    temp_url_ttl: DEFAULT_TEMP_URL_TTL,
+                    avatar_cache: TtlCache::new(AVATAR_CACHE_CAPACITY),
+                    avatar_base_url: None,
+                    #[cfg(feature = "image")]
+                    image_preprocessor: None,
+                    transcoder: None,
+                    api_stats: Arc::default(),
+                    event_stats: Arc::default(),
+                    #[cfg(any(feature = "websocket", feature = "webhook"))]
+                    event_decoder: Arc::default(),
+                    metrics_sink: None,
+                    body_logger: None,
+                    #[cfg(feature = "otel")]
+                    trace_propagator: Arc::new(crate::otel::W3cPropagator),
                 })
             }
         }
@@ -126,9 +388,14 @@ impl MilkyClient {
     /// 尝试接收服务端发送的事件
     ///
     /// # 返回
-    /// 成功建立连接并启动事件读取循环则返回 `Ok(())`，否则返回错误
-    pub async fn connect_events(&self) -> Result<()> {
+    /// 成功建立连接并启动事件读取循环则返回后台任务的 [`EventTask`]，否则返回错误
+    pub async fn connect_events(&self) -> Result<EventTask> {
         match self.comm_type {
+            #[cfg(not(feature = "websocket"))]
+            Communication::WebSocket(_) => Err(MilkyError::Config(
+                "未启用 `websocket` 特性，无法通过 WebSocket 接收事件".to_string(),
+            )),
+            #[cfg(feature = "websocket")]
             Communication::WebSocket(_) => {
                 let event_ws_url = self
                     .event_ws_url
@@ -138,126 +405,269 @@ impl MilkyClient {
                         MilkyError::Internal("WebSocket URL未配置".to_string())
                     })?
                     .to_string();
-                info!("正在连接 WebSocket 以接收事件: {event_ws_url}");
-                // 异步连接WebSocket
-                let (ws_stream_internal, response) = connect_async(event_ws_url.clone())
-                    .await
-                    .map_err(|e| MilkyError::WebSocket(Box::new(e)))?;
-                info!("事件 WebSocket 握手成功完成！");
-                debug!("响应的 HTTP 代码: {}", response.status());
+                info!(
+                    "正在连接 WebSocket 以接收事件: {}",
+                    redact_url(&event_ws_url)
+                );
+                let ws_stream_internal = Self::connect_event_ws(
+                    &event_ws_url,
+                    self.proxy.as_ref(),
+                    self.event_tls.clone(),
+                    self.token_provider.as_deref(),
+                )
+                .await?;
+                let mut connection = EventWsConnection::new(ws_stream_internal);
 
-                *self.ws_stream.lock().await = Some(ws_stream_internal);
+                let shutdown = self.start_session().await;
 
-                let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
-                *self.ws_shutdown_signal_tx.lock().await = Some(shutdown_tx);
-
-                let ws_stream_clone = Arc::clone(&self.ws_stream);
                 let event_sender_clone = self.event_sender.clone();
-                let ws_shutdown_signal_tx_clone_for_loop = Arc::clone(&self.ws_shutdown_signal_tx);
+                let event_broadcast = self.event_broadcast.clone();
+                let event_stats = Arc::clone(&self.event_stats);
+                let event_decoder = Arc::clone(&self.event_decoder);
+                let shutdown_token_for_loop = Arc::clone(&self.shutdown_token);
+                let last_error = LastError::default();
+                let last_error_for_loop = Arc::clone(&last_error);
+                let shutdown_for_loop = shutdown.clone();
+                let reconnect_policy = self.reconnect_policy.clone();
+                let heartbeat = self.heartbeat;
+                let reconnect_url = event_ws_url.clone();
+                let proxy = self.proxy.clone();
+                let event_tls = self.event_tls.clone();
+                let token_provider = self.token_provider.clone();
+                let ws_api = self.ws_api.clone();
+                let status_sender = self.status_sender.clone();
+                #[cfg(feature = "webhook")]
+                let failover = self.prepare_failover()?;
+                // 在启动读取循环前接管 API 请求，避免连接建立后立即发起的调用失败
+                if let Some(api) = &ws_api {
+                    api.attach(connection.writer.clone());
+                }
 
-                tokio::spawn(async move {
+                let read_loop = async move {
                     info!("WebSocket 事件读取循环已启动");
-                    loop {
-                        tokio::select! {
-                            biased;
+                    'connection: loop {
+                        let _ = status_sender.send(ConnectionStatus::Connected);
+                        let mut heartbeat_state = heartbeat.map(HeartbeatState::new);
+                        let reason = loop {
+                            let heartbeat_wait = heartbeat_state
+                                .as_ref()
+                                .map_or(Duration::MAX, |state| state.wait(Instant::now()));
+                            tokio::select! {
+                                biased;
 
-                            _ = &mut shutdown_rx => {
-                                info!("WebSocket 事件读取循环收到关闭信号");
-                                if let Some(mut stream_to_close) = ws_stream_clone.lock().await.take() {
+                                _ = shutdown.cancelled() => {
+                                    info!("WebSocket 事件读取循环收到关闭信号");
                                     info!("正在发送 WebSocket Close 帧...");
-                                    if let Err(e) = stream_to_close.close(None).await {
-                                        error!("发送 WebSocket Close 帧时出错: {e:?}");
-                                    } else {
+                                    if connection.close().await {
                                         info!("WebSocket Close 帧已发送，连接已关闭");
                                     }
+                                    let _ = status_sender.send(ConnectionStatus::Disconnected {
+                                        reason: "客户端主动关闭连接".to_string(),
+                                    });
+                                    break 'connection;
                                 }
-                                break;
-                            }
 
-                            message_result = async {
-                                let mut guard = ws_stream_clone.lock().await;
-                                if let Some(stream) = guard.as_mut() {
-                                    stream.next().await
-                                } else {
-                                    None
+                                message_result = connection.reader.next() => {
+                                    match message_result {
+                                        Some(Ok(message)) => {
+                                            if let Some(state) = heartbeat_state.as_mut() {
+                                                state.on_message();
+                                            }
+                                            // 通过 WebSocket 调用 API 时，先将响应交给等待中的调用
+                                            if let WsMessage::Text(text) = &message
+                                                && ws_api.as_ref().is_some_and(|api| api.dispatch(text))
+                                            {
+                                                continue;
+                                            }
+                                            if let Err(e) = Self::handle_event_message(
+                                                OriginalMessage::Ws(message),
+                                                &event_sender_clone,
+                                                &event_broadcast,
+                                                &event_stats,
+                                                &event_decoder,
+                                            )
+                                            .await
+                                            {
+                                                warn!("处理WebSocket事件消息时出错: {e:?}");
+                                            }
+                                        }
+                                        Some(Err(e)) => {
+                                            error!("接收WebSocket事件消息时出错: {e:?}");
+                                            break format!("接收消息时出错: {e}"); // 退出循环
+                                        }
+                                        None => { // 服务器关闭了连接
+                                            info!("服务器关闭了事件 WebSocket 连接");
+                                            break "服务端关闭了连接".to_string(); // 退出循环
+                                        }
+                                    }
                                 }
-                            } => {
-                                match message_result {
-                                    Some(Ok(message)) => {
-                                        if let Err(e) = Self::handle_event_message(
-                                            OriginalMessage::Ws(message),
-                                            event_sender_clone.clone(),
-                                        )
-                                        .await
-                                        {
-                                            warn!("处理WebSocket事件消息时出错: {e:?}");
+
+                                write_result = &mut connection.write_failed => {
+                                    break match write_result {
+                                        Ok(e) => format!("写入消息时出错: {e}"),
+                                        Err(_) => "写入任务已结束".to_string(),
+                                    };
+                                }
+
+                                _ = runtime::sleep(heartbeat_wait), if heartbeat_state.is_some() => {
+                                    let Some(state) = heartbeat_state.as_mut() else {
+                                        continue;
+                                    };
+                                    let now = Instant::now();
+                                    if state.is_stale(now) {
+                                        warn!(
+                                            "{:?} 内未收到服务端的响应，判定事件 WebSocket 连接已失效",
+                                            state.timeout()
+                                        );
+                                        break "心跳超时".to_string();
+                                    }
+                                    // 写入失败时由写入任务通过 `write_failed` 通知
+                                    let _ = connection.writer.send(WsMessage::Ping(Bytes::new()));
+                                    debug!("已发送 WebSocket 心跳");
+                                    state.on_ping_sent(now);
+                                }
+                            }
+                        };
+                        *last_error_for_loop.lock().unwrap() = Some(reason.clone());
+                        let _ = status_sender.send(ConnectionStatus::Disconnected { reason });
+
+                        // 连接已断开，等待中的 API 调用无法再收到响应
+                        if let Some(api) = &ws_api {
+                            api.detach();
+                        }
+                        // 连接已断开，按重连策略重新建立连接
+                        // 切换为 WebHook 前允许的重连次数，切换失败后只按重连策略继续重连
+                        #[cfg(feature = "webhook")]
+                        let mut failover_after = failover.as_ref().map(|f| f.after_attempts);
+                        #[cfg(not(feature = "webhook"))]
+                        let failover_after: Option<u32> = None;
+                        let mut failures = 0;
+                        // 切换失败时回到重连，未启用 `webhook` 特性时只执行一次
+                        #[cfg_attr(not(feature = "webhook"), allow(clippy::never_loop))]
+                        loop {
+                            if let Some(policy) = &reconnect_policy {
+                                while policy.should_retry(failures)
+                                    && failover_after.is_none_or(|after| failures < after)
+                                {
+                                    let delay = policy.backoff(failures + 1);
+                                    info!(
+                                        "将在 {delay:?} 后进行第 {} 次 WebSocket 重连",
+                                        failures + 1
+                                    );
+                                    let _ = status_sender.send(ConnectionStatus::Reconnecting {
+                                        attempt: failures + 1,
+                                    });
+                                    tokio::select! {
+                                        biased;
+
+                                        _ = shutdown.cancelled() => {
+                                            info!("WebSocket 重连等待期间收到关闭信号");
+                                            break 'connection;
                                         }
+                                        _ = runtime::sleep(delay) => {}
                                     }
-                                    Some(Err(e)) => {
-                                        error!("接收WebSocket事件消息时出错: {e:?}");
-                                        ws_stream_clone.lock().await.take(); // 移除错误的流
-                                        break; // 退出循环
+                                    match Self::connect_event_ws(
+                                        &reconnect_url,
+                                        proxy.as_ref(),
+                                        event_tls.clone(),
+                                        token_provider.as_deref(),
+                                    )
+                                    .await
+                                    {
+                                        Ok(stream) => {
+                                            connection = EventWsConnection::new(stream);
+                                            info!("事件 WebSocket 重连成功");
+                                            if let Some(api) = &ws_api {
+                                                api.attach(connection.writer.clone());
+                                            }
+                                            continue 'connection;
+                                        }
+                                        Err(e) => {
+                                            failures += 1;
+                                            warn!("第 {failures} 次 WebSocket 重连失败: {e}");
+                                            *last_error_for_loop.lock().unwrap() =
+                                                Some(e.to_string());
+                                        }
                                     }
-                                    None => { // 服务器关闭连接或流在读取前变为None
-                                        info!("服务器关闭了事件 WebSocket 连接或流已不存在");
-                                        ws_stream_clone.lock().await.take(); // 确保流被移除
-                                        break; // 退出循环
+                                }
+                            }
+                            #[cfg(feature = "webhook")]
+                            if failover_after.take().is_some()
+                                && let Some(failover) = &failover
+                            {
+                                warn!(
+                                    "事件 WebSocket 连续重连 {failures} 次均失败，切换为通过 WebHook 接收事件"
+                                );
+                                match failover.run(&shutdown, &status_sender).await {
+                                    Ok(()) => break 'connection,
+                                    Err(e) => {
+                                        warn!(
+                                            "切换为 WebHook 接收事件失败，继续重连事件 WebSocket"
+                                        );
+                                        *last_error_for_loop.lock().unwrap() = Some(e.to_string());
+                                        continue;
                                     }
                                 }
                             }
+                            break;
                         }
+                        if reconnect_policy.is_some() {
+                            error!("WebSocket 连续重连 {failures} 次均失败，放弃重连");
+                        }
+                        break;
+                    }
+                    if let Some(api) = &ws_api {
+                        api.detach();
                     }
                     info!("WebSocket 事件读取循环已结束");
-                    ws_shutdown_signal_tx_clone_for_loop.lock().await.take();
-                });
+                    shutdown_token_for_loop.lock().await.take();
+                };
+                #[cfg(feature = "tracing")]
+                let read_loop = read_loop
+                    .instrument(tracing::info_span!("ws_events", url = %redact_url(&event_ws_url)));
+                let handle = runtime::spawn(read_loop);
+                Ok(EventTask::new(handle, shutdown_for_loop, last_error))
             }
-            Communication::WebHook(_) => {
+            #[cfg(not(feature = "webhook"))]
+            Communication::WebHook(_) => Err(MilkyError::Config(
+                "未启用 `webhook` 特性，无法通过 WebHook 接收事件".to_string(),
+            )),
+            #[cfg(feature = "webhook")]
+            Communication::WebHook(ref config) => {
                 info!("正在为 WebHook 配置事件接收路由...");
-                let event_sender_for_webhook = self.event_sender.clone();
+                let tls_acceptor = webhook::tls_acceptor(config)?;
+                let app = self.build_webhook_router(config)?;
                 let webhook_listen_address = self.event_wh_url.clone();
+                let shutdown = self.start_session().await;
+                let shutdown_token_for_server = Arc::clone(&self.shutdown_token);
+                let last_error = LastError::default();
+                let last_error_for_server = Arc::clone(&last_error);
+                let shutdown_for_server = shutdown.clone();
+                let webhook_addr = Arc::clone(&self.webhook_addr);
 
-                let axum_webhook_handler = move |Json(payload): Json<Value>| {
-                    let sender_clone_for_call = event_sender_for_webhook.clone();
-                    async move {
-                        debug!("WebHook 接收到 payload: {payload:?}");
-                        if let Err(e) = Self::handle_event_message(
-                            OriginalMessage::WebHook(payload),
-                            sender_clone_for_call,
-                        )
-                        .await
-                        {
-                            warn!("处理 WebHook 事件消息时出错: {e:?}");
-                            // 返回一个错误响应给调用方
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                format!("Failed to process webhook: {e:?}"),
-                            )
-                        } else {
-                            // 返回成功响应
-                            (StatusCode::OK, "Webhook received successfully".to_string())
-                        }
+                info!("尝试在 {webhook_listen_address} 上启动 WebHook 事件接收服务器",);
+                let listener = match tokio::net::TcpListener::bind(&webhook_listen_address).await {
+                    Ok(l) => l,
+                    Err(e) => {
+                        error!("无法将 WebHook 监听器绑定到 {webhook_listen_address}: {e}");
+                        self.shutdown_token.lock().await.take();
+                        return Err(e.into());
                     }
                 };
+                let local_addr = listener.local_addr()?;
+                *self.webhook_addr.lock().unwrap() = Some(local_addr);
+                let scheme = if tls_acceptor.is_some() {
+                    "https"
+                } else {
+                    "http"
+                };
+                info!(
+                    "WebHook 事件接收服务器正在监听: {scheme}://{local_addr}{}",
+                    config.path
+                );
 
-                let app = Router::new().route("/webhook", post(axum_webhook_handler));
-
-                tokio::spawn(async move {
-                    info!("尝试在 {webhook_listen_address} 上启动 WebHook 事件接收服务器",);
-
-                    let listener =
-                        match tokio::net::TcpListener::bind(&webhook_listen_address).await {
-                            Ok(l) => l,
-                            Err(e) => {
-                                error!("无法将 WebHook 监听器绑定到 {webhook_listen_address}: {e}");
-                                return;
-                            }
-                        };
-                    let actual_listen_addr = listener
-                        .local_addr()
-                        .map_or_else(|_| webhook_listen_address.clone(), |addr| addr.to_string());
-                    info!("WebHook 事件接收服务器正在监听: http://{actual_listen_addr}");
-
-                    let shutdown_signal = async {
+                let handle = runtime::spawn(async move {
+                    let shutdown_signal = async move {
                         let ctrl_c = async {
                             tokio::signal::ctrl_c()
                                 .await
@@ -278,41 +688,101 @@ impl MilkyClient {
                         let terminate = std::future::pending::<()>();
 
                         tokio::select! {
+                            _ = shutdown.cancelled() => info!("收到关闭信号，开始关闭 WebHook 服务器..."),
                             _ = ctrl_c => info!("Ctrl+C信号接收，开始关闭 WebHook 服务器..."),
                             _ = terminate => info!("SIGTERM信号接收，开始关闭 WebHook 服务器..."),
                         }
                         info!("WebHook 服务器关闭信号已触发");
                     };
 
-                    if let Err(e) = axum::serve(listener, app.into_make_service())
-                        .with_graceful_shutdown(shutdown_signal)
-                        .await
-                    {
+                    let result = webhook::serve(listener, app, tls_acceptor, shutdown_signal).await;
+                    if let Err(e) = result {
                         error!("WebHook 事件接收服务器遇到错误: {e:?}");
+                        *last_error_for_server.lock().unwrap() = Some(e.to_string());
                     }
                     info!("WebHook 事件接收服务器已关闭");
+                    shutdown_token_for_server.lock().await.take();
+                    webhook_addr.lock().unwrap().take();
                 });
                 info!("WebHook 事件接收服务器已安排在后台运行");
+                Ok(EventTask::new(handle, shutdown_for_server, last_error))
             }
+        }
+    }
+
+    /// 连接事件 WebSocket 并完成握手
+    ///
+    /// # 参数
+    /// * `event_ws_url`: 事件 WebSocket 连接的URL
+    /// * `proxy`: 可选的出站代理
+    /// * `tls`: 可选的 TLS 连接器
+    /// * `token_provider`: 可选的访问令牌提供者，设置时以其返回的令牌替换 URL 中的令牌
+    #[cfg(feature = "websocket")]
+    async fn connect_event_ws(
+        event_ws_url: &str,
+        proxy: Option<&Proxy>,
+        tls: Option<Connector>,
+        token_provider: Option<&dyn TokenProvider>,
+    ) -> Result<EventWsStream> {
+        let event_ws_url = match token_provider {
+            Some(provider) => {
+                token::replace_access_token(event_ws_url, provider.token().await?.as_deref())?
+            }
+            None => event_ws_url.to_string(),
         };
+        let event_ws_url = event_ws_url.as_str();
+        let (stream, response) = if proxy.is_none() && tls.is_none() {
+            connect_async(event_ws_url).await
+        } else {
+            let url = Url::parse(event_ws_url)?;
+            let host = url
+                .host_str()
+                .ok_or(MilkyError::UrlParse(url::ParseError::EmptyHost))?;
+            let port = url
+                .port_or_known_default()
+                .ok_or(MilkyError::UrlParse(url::ParseError::InvalidPort))?;
+            let tcp = match proxy {
+                Some(proxy) => proxy.connect(host, port).await?,
+                None => TcpStream::connect((host, port)).await?,
+            };
+            #[cfg(any(feature = "native-tls", feature = "rustls"))]
+            let handshake =
+                tokio_tungstenite::client_async_tls_with_config(event_ws_url, tcp, None, tls).await;
+            // 未启用 TLS 特性时只能建立明文连接
+            #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+            let handshake =
+                tokio_tungstenite::client_async(event_ws_url, MaybeTlsStream::Plain(tcp)).await;
+            handshake
+        }
+        .map_err(|e| MilkyError::WebSocket(Box::new(e)))?;
+        info!("事件 WebSocket 握手成功完成！");
+        debug!("响应的 HTTP 代码: {}", response.status());
+        Ok(stream)
+    }
 
-        Ok(())
+    /// 按当前的代理与 TLS 设置创建 HTTP 客户端
+    fn build_http_client(&self) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        if let Some(tls) = &self.api_tls {
+            builder = tls.apply(builder)?;
+        }
+        Ok(builder.build()?)
     }
 
     /// 关闭与服务器的连接
     ///
-    /// 目前主要用于主动关闭 WebSocket 事件流连接
-    /// 它会向事件读取循环发送一个关闭信号
+    /// WebSocket 模式下关闭事件流连接，WebHook 模式下停止事件接收服务器
+    /// 它会取消当前事件接收的令牌，客户端自身的令牌不受影响，之后仍可重新接收事件
     pub async fn shutdown(&self) {
         info!("正在请求关闭 MilkyClient...");
-        if let Some(tx) = self.ws_shutdown_signal_tx.lock().await.take() {
-            if tx.send(()).is_ok() {
-                info!("已成功发送关闭信号到 WebSocket 事件读取循环");
-            } else {
-                info!("无法发送关闭信号，WebSocket 事件读取循环可能已经关闭");
-            }
+        if let Some(token) = self.shutdown_token.lock().await.take() {
+            token.cancel();
+            info!("已成功发送关闭信号到事件接收任务");
         } else {
-            info!("没有活动的 WebSocket 关闭信号发送器，可能连接从未完全建立或已被关闭");
+            info!("没有活动的关闭信号发送器，可能事件接收从未启动或已被关闭");
         }
     }
 
@@ -321,25 +791,33 @@ impl MilkyClient {
     /// # 参数
     /// * `msg`: 接收到的原始 [`OriginalMessage`]
     /// * `event_sender`: 用于发送解析后事件的mpsc通道发送端
+    /// * `event_broadcast`: 向所有订阅者广播事件的通道发送端
     ///
     /// # 返回
     /// 成功处理则返回 `Ok(())`，否则返回错误（主要是在发送事件到通道失败时）
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
     async fn handle_event_message(
         msg: OriginalMessage,
-        event_sender: mpsc::Sender<Event>,
+        event_sender: &mpsc::Sender<Event>,
+        event_broadcast: &broadcast::Sender<Event>,
+        event_stats: &EventPipelineRecorder,
+        event_decoder: &EventDecoder,
     ) -> Result<()> {
         match msg {
+            #[cfg(feature = "websocket")]
             OriginalMessage::Ws(ws_msg) => match ws_msg {
                 WsMessage::Text(text) => {
-                    debug!("接收到事件文本: {text}",);
-                    match serde_json::from_str::<Event>(&text) {
+                    debug!("接收到事件文本: {}", LogPayload(&text));
+                    match event_decoder.decode(&text) {
                         Ok(event) => {
-                            if event_sender.send(event).await.is_err() {
-                                error!("事件接收端已关闭，无法发送事件");
-                            }
+                            Self::dispatch_event(event_sender, event_broadcast, event_stats, event)
+                                .await;
                         }
                         Err(e) => {
-                            warn!("无法将消息解析为已知的 Event 类型: {e}原始文本: {text}");
+                            warn!(
+                                "无法将消息解析为已知的 Event 类型: {e}原始文本: {}",
+                                LogPayload(&text)
+                            );
                         }
                     }
                 }
@@ -359,16 +837,19 @@ impl MilkyClient {
                     debug!("在事件流上接收到原始 Frame (未处理)");
                 }
             },
+            #[cfg(feature = "webhook")]
             OriginalMessage::WebHook(wh_msg) => {
-                let msg = wh_msg.clone();
-                match serde_json::from_value::<Event>(wh_msg) {
+                // 从引用解析，解析失败时仍可输出原始内容，无需事先复制整个事件
+                match event_decoder.decode(&wh_msg) {
                     Ok(event) => {
-                        if event_sender.send(event).await.is_err() {
-                            error!("事件接收端已关闭，无法发送事件");
-                        }
+                        Self::dispatch_event(event_sender, event_broadcast, event_stats, event)
+                            .await;
                     }
                     Err(e) => {
-                        warn!("无法将消息解析为已知的 Event 类型: {e}原始文本: {msg:?}");
+                        warn!(
+                            "无法将消息解析为已知的 Event 类型: {e}原始文本: {}",
+                            LogPayload(&wh_msg)
+                        );
                     }
                 }
             }
@@ -376,6 +857,32 @@ impl MilkyClient {
         Ok(())
     }
 
+    /// 设置资源临时下载链接的缓存时长，传入 `Duration::ZERO` 可关闭缓存
+    ///
+    /// 链接本身带有过期时间参数时，以两者中较短的为准。默认缓存 10 分钟。
+    pub fn with_temp_url_ttl(mut self, ttl: Duration) -> Self {
+        self.temp_url_ttl = ttl;
+        self
+    }
+
+    /// 使用自定义的 HTTP 客户端发送 API 请求与下载资源
+    ///
+    /// 可以借此与应用的其他部分共享连接池、默认请求头、代理与超时等设置。
+    /// 之后再调用 [`with_proxy`](Self::with_proxy) 或 [`with_api_tls`](Self::with_api_tls)
+    /// 会重新创建 HTTP 客户端并替换该客户端。
+    ///
+    /// # 参数
+    /// * `client`: 预先配置好的 `reqwest` 客户端
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = client;
+        self
+    }
+
+    /// 获取底层的 HTTP 客户端，供下载等需要直接发起 HTTP 请求的功能复用连接池
+    pub(crate) fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
     /// 发送一个API请求到后端服务
     ///
     /// # 参数
@@ -390,34 +897,139 @@ impl MilkyClient {
         action: &str,
         params: P,
     ) -> Result<R> {
+        self.send_request_with_opts(action, params, &RequestOptions::default())
+            .await
+    }
+
+    /// 以指定的选项发送一个API请求到后端服务，参见 [`MilkyClient::send_request`]
+    ///
+    /// # 参数
+    /// * `action`: API操作的名称，例如 "send_private_msg"
+    /// * `params`: 要发送的请求参数
+    /// * `options`: 本次调用的选项，例如超时时间
+    pub async fn send_request_with_opts<P: Serialize, R: DeserializeOwned>(
+        &self,
+        action: &str,
+        params: P,
+        options: &RequestOptions,
+    ) -> Result<R> {
+        let mut retries = 0;
+        loop {
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire(action).await;
+            }
+            let request = self.execute_request(action, &params, options);
+            #[cfg(feature = "otel")]
+            let request = crate::otel::instrument_request(action, request);
+            #[cfg(all(feature = "tracing", not(feature = "otel")))]
+            let request = tracing::Instrument::instrument(
+                request,
+                tracing::info_span!(
+                    "send_request",
+                    action,
+                    peer_id = tracing::field::Empty,
+                    message_seq = tracing::field::Empty,
+                ),
+            );
+            let started = Instant::now();
+            let result = request.await;
+            let latency = started.elapsed();
+            self.api_stats.record(action, latency, result.is_ok());
+            if let Some(sink) = &self.metrics_sink {
+                sink.api_call(action, latency, result.as_ref().err());
+            }
+
+            let policy = self.retry_policy.as_ref().filter(|_| !options.no_retry);
+            match (result, policy) {
+                (Err(e), Some(policy)) if policy.should_retry(action, &e, retries) => {
+                    retries += 1;
+                    let delay = policy.backoff(retries);
+                    warn!("API {action} 调用失败，将在 {delay:?} 后进行第 {retries} 次重试: {e}");
+                    runtime::sleep(delay).await;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// 调用任意 API 并直接返回响应中的 `data`
+    ///
+    /// 用于调用 SDK 尚未封装的协议端接口。请求同样会经过拦截器、限流与重试策略。
+    ///
+    /// # 参数
+    /// * `action`: API操作的名称，例如 "get_login_info"
+    /// * `params`: 请求参数
+    ///
+    /// # 返回
+    /// 响应中的 `data`，响应不包含 `data` 时返回 `Value::Null`
+    pub async fn call_raw(&self, action: &str, params: Value) -> Result<Value> {
+        self.send_request(action, params).await
+    }
+
+    /// 实际执行 API 请求，参见 [`MilkyClient::send_request`]
+    async fn execute_request<P: Serialize, R: DeserializeOwned>(
+        &self,
+        action: &str,
+        params: &P,
+        options: &RequestOptions,
+    ) -> Result<R> {
+        let mut params = serde_json::to_value(params)?;
+        self.intercept_request(action, &mut params).await?;
+        #[cfg(feature = "tracing")]
+        record_request_fields(&params);
+        let body_logger = self
+            .body_logger
+            .as_ref()
+            .filter(|logger| logger.should_log(action));
+        if let Some(logger) = body_logger {
+            logger.log_request(action, &params);
+        }
+
+        #[cfg(feature = "websocket")]
+        if let Some(ws_api) = &self.ws_api {
+            let timeout = options.timeout.or(self.request_timeout);
+            let mut api_resp = ws_api.call(action, &params, timeout).await?;
+            self.intercept_response(action, &mut api_resp).await?;
+            return Self::parse_api_response(action, &params, api_resp, body_logger);
+        }
+
         // 构建完整的API URL
         let full_api_url = self.api_base_url.join(action)?;
-        debug!("正在发送 API 请求至: {full_api_url}",);
+        debug_fields!(
+            action = action,
+            peer_id = request_peer_id(&params);
+            "正在发送 API 请求至: {full_api_url}"
+        );
 
         // 构建HTTP POST请求
         let mut request_builder = self.http_client.post(full_api_url);
-        if let Some(token) = &self.access_token {
+        if let Some(token) = self.current_token().await? {
             // 如果有访问令牌，则添加Bearer Token认证头
             request_builder = request_builder.bearer_auth(token);
         }
         request_builder = request_builder.header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(timeout) = options.timeout.or(self.request_timeout) {
+            request_builder = request_builder.timeout(timeout);
+        }
+        #[cfg(feature = "otel")]
+        {
+            let mut headers = reqwest::header::HeaderMap::new();
+            self.trace_propagator.inject(&mut headers);
+            request_builder = request_builder.headers(headers);
+        }
 
-        let http_response = request_builder.json(&params).send().await?;
+        let http_response = request_builder
+            .json(&params)
+            .send()
+            .await
+            .map_err(map_reqwest_error)?;
 
         let status = http_response.status();
         if status == StatusCode::OK {
-            let api_resp = http_response.json::<ApiResponse<Value>>().await?;
-            if api_resp.status == "ok" && api_resp.retcode == 0 {
-                let data = api_resp.data.unwrap_or(Value::Null);
-                serde_json::from_value(data).map_err(MilkyError::Json)
-            } else {
-                Err(MilkyError::ApiError {
-                    message: api_resp
-                        .message
-                        .unwrap_or_else(|| "未知的 API 错误".to_string()),
-                    retcode: Some(api_resp.retcode),
-                })
-            }
+            let body = http_response.bytes().await.map_err(map_reqwest_error)?;
+            let mut api_resp = json::from_vec::<ApiResponse<Value>>(body.into())?;
+            self.intercept_response(action, &mut api_resp).await?;
+            Self::parse_api_response(action, &params, api_resp, body_logger)
         } else {
             let error_message = http_response
                 .text()
@@ -429,4 +1041,150 @@ impl MilkyClient {
             })
         }
     }
+
+    /// 将 API 响应转换为调用结果
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `params`: 请求参数，响应数据无法解析时用于错误信息
+    /// * `api_resp`: 服务端返回的响应
+    /// * `body_logger`: 需要记录响应内容时的记录器
+    fn parse_api_response<R: DeserializeOwned>(
+        action: &str,
+        params: &Value,
+        api_resp: ApiResponse<Value>,
+        body_logger: Option<&BodyLogger>,
+    ) -> Result<R> {
+        if let Some(logger) = body_logger {
+            logger.log_response(action, &serde_json::to_value(&api_resp)?);
+        }
+        if api_resp.status == "ok" && api_resp.retcode == 0 {
+            let data = api_resp.data.unwrap_or(Value::Null);
+            serde_json::from_value(data).map_err(|source| MilkyError::Decode {
+                action: action.to_string(),
+                request: summarize(params),
+                source,
+            })
+        } else {
+            Err(MilkyError::api_error(
+                action,
+                api_resp
+                    .message
+                    .unwrap_or_else(|| "未知的 API 错误".to_string()),
+                Some(api_resp.retcode),
+            ))
+        }
+    }
+}
+
+/// 将 HTTP 请求的错误转换为 [`MilkyError`]，请求超时转换为 [`MilkyError::Timeout`]
+fn map_reqwest_error(e: reqwest::Error) -> MilkyError {
+    if e.is_timeout() {
+        MilkyError::Timeout
+    } else {
+        MilkyError::Reqwest(e)
+    }
+}
+
+/// 获取请求参数中的会话对象，依次查找 `peer_id`、`group_id` 与 `user_id`
+fn request_peer_id(params: &Value) -> Option<i64> {
+    ["peer_id", "group_id", "user_id"]
+        .iter()
+        .find_map(|key| params.get(key)?.as_i64())
+}
+
+/// 在当前的 `send_request` span 中记录请求的会话与消息序列号
+#[cfg(feature = "tracing")]
+fn record_request_fields(params: &Value) {
+    let span = tracing::Span::current();
+    if let Some(peer_id) = request_peer_id(params) {
+        span.record("peer_id", peer_id);
+    }
+    if let Some(message_seq) = params.get("message_seq").and_then(Value::as_i64) {
+        span.record("message_seq", message_seq);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use axum::Router;
+    use axum::routing::post;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_call_raw() {
+        let app = Router::new()
+            .route(
+                "/api/echo",
+                post(|axum::Json(params): axum::Json<Value>| async move {
+                    axum::Json(json!({"status": "ok", "retcode": 0, "data": {"params": params}}))
+                }),
+            )
+            .route(
+                "/api/denied",
+                post(|| async {
+                    axum::Json(json!({"status": "failed", "retcode": 403, "message": "权限不足"}))
+                }),
+            );
+        let (client, _rx) = test_util::client(test_util::serve(app).await);
+
+        // 参数原样发送，响应中的 `data` 不经转换直接返回
+        let params = json!({"user_id": 10000, "nested": {"list": [1, "二", null]}});
+        let data = client.call_raw("echo", params.clone()).await.unwrap();
+        assert_eq!(data, json!({"params": params}));
+
+        let err = client.call_raw("denied", json!({})).await.unwrap_err();
+        assert!(matches!(
+            err,
+            MilkyError::ApiError { action, retcode: Some(403), message, .. }
+                if action == "denied" && message == "权限不足"
+        ));
+    }
+
+    #[cfg(feature = "webhook")]
+    #[tokio::test]
+    async fn test_shutdown_stops_webhook_server() {
+        let (client, _rx) =
+            test_util::client_for(Communication::WebHook(test_util::webhook_config()));
+        client.connect_events().await.unwrap();
+        let addr = client.webhook_local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+        assert!(tokio::net::TcpStream::connect(addr).await.is_ok());
+
+        client.shutdown().await;
+        let mut stopped = false;
+        for _ in 0..50 {
+            if client.webhook_local_addr().is_none() {
+                stopped = true;
+                break;
+            }
+            runtime::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(stopped);
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_request_span_fields() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = test_util::SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let (client, _rx) = test_util::client(test_util::OFFLINE);
+        let result = client
+            .send_request::<_, Value>(
+                "recall_group_message",
+                serde_json::json!({"group_id": 123, "message_seq": 45}),
+            )
+            .await;
+        assert!(result.is_err());
+
+        let fields = recorder.fields("send_request");
+        assert_eq!(fields["peer_id"], "123");
+        assert_eq!(fields["message_seq"], "45");
+    }
 }
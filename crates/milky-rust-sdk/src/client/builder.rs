@@ -0,0 +1,427 @@
+//! 以链式调用的方式创建 [`MilkyClient`]
+//!
+//! ```no_run
+//! use milky_rust_sdk::{Communication, MilkyClient, WebSocketConfig};
+//! use std::time::Duration;
+//!
+//! # async fn run() -> milky_rust_sdk::Result<()> {
+//! let comm = Communication::WebSocket(WebSocketConfig::new("ws://127.0.0.1:3000".to_string(), None));
+//! let (client, mut events) = MilkyClient::builder(comm)
+//!     .access_token("secret")
+//!     .request_timeout(Duration::from_secs(10))
+//!     .channel_capacity(256)
+//!     .build()?;
+//! client.connect_events().await?;
+//! while let Some(event) = events.recv().await {
+//!     println!("收到事件: {event:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(all(feature = "websocket", feature = "webhook"))]
+use crate::client::WebhookFailover;
+#[cfg(feature = "websocket")]
+use crate::client::{ApiTransport, Heartbeat, ReconnectPolicy};
+use crate::client::{
+    CancellationToken, Interceptor, MilkyClient, Proxy, RateLimiter, RetryPolicy, TlsConfig,
+    TokenProvider,
+};
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::client::{DeserializeMode, OverflowPolicy};
+use crate::error::Result;
+use crate::logger::body::{BodyLogConfig, BodyLogger};
+use crate::stats::MetricsSink;
+use crate::types::communication::Communication;
+
+use milky_types::Event;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 默认的事件通道容量
+const DEFAULT_CHANNEL_CAPACITY: usize = 128;
+
+/// [`MilkyClient`] 的构建器
+pub struct MilkyClientBuilder {
+    /// 与服务端的通信方式
+    comm: Communication,
+    /// 覆盖通信方式中设置的访问令牌
+    access_token: Option<String>,
+    /// 运行时刷新访问令牌的提供者
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    /// API 请求的超时时间
+    request_timeout: Option<Duration>,
+    /// 自定义的 HTTP 客户端
+    http_client: Option<reqwest::Client>,
+    /// 出站代理
+    proxy: Option<Proxy>,
+    /// HTTP API 请求使用的 TLS 设置
+    api_tls: Option<TlsConfig>,
+    /// 事件 WebSocket 连接使用的 TLS 设置
+    #[cfg(feature = "websocket")]
+    event_tls: Option<TlsConfig>,
+    /// 事件通道的容量
+    channel_capacity: usize,
+    /// 每个订阅者缓存的事件数量
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    broadcast_capacity: Option<usize>,
+    /// 事件通道写满时的处理方式
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    overflow_policy: OverflowPolicy,
+    /// 事件的解析方式
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    deserialize_mode: DeserializeMode,
+    /// 是否记录事件中未定义的字段
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    collect_unknown_fields: bool,
+    /// 请求与响应内容的记录设置
+    body_logging: Option<BodyLogConfig>,
+    /// API 调用失败后的重试策略
+    retry_policy: Option<RetryPolicy>,
+    /// API 调用的限速器
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// API 请求的拦截器
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    /// 导出调用与事件数据的指标导出器
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    /// 决定客户端生命周期的取消令牌
+    cancel_token: Option<CancellationToken>,
+    /// 事件 WebSocket 连接的重连策略
+    #[cfg(feature = "websocket")]
+    reconnect_policy: Option<ReconnectPolicy>,
+    /// 事件 WebSocket 连接的心跳保活设置
+    #[cfg(feature = "websocket")]
+    heartbeat: Option<Heartbeat>,
+    /// 事件 WebSocket 无法恢复时切换为 WebHook 的设置
+    #[cfg(all(feature = "websocket", feature = "webhook"))]
+    failover: Option<WebhookFailover>,
+    /// API 调用使用的传输方式
+    #[cfg(feature = "websocket")]
+    api_transport: ApiTransport,
+}
+
+impl MilkyClientBuilder {
+    /// 创建构建器
+    ///
+    /// # 参数
+    /// * `comm`: 与服务端的通信方式
+    pub fn new(comm: Communication) -> Self {
+        Self {
+            comm,
+            access_token: None,
+            token_provider: None,
+            request_timeout: None,
+            http_client: None,
+            proxy: None,
+            api_tls: None,
+            #[cfg(feature = "websocket")]
+            event_tls: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            #[cfg(any(feature = "websocket", feature = "webhook"))]
+            broadcast_capacity: None,
+            #[cfg(any(feature = "websocket", feature = "webhook"))]
+            overflow_policy: OverflowPolicy::Block,
+            #[cfg(any(feature = "websocket", feature = "webhook"))]
+            deserialize_mode: DeserializeMode::Tolerant,
+            #[cfg(any(feature = "websocket", feature = "webhook"))]
+            collect_unknown_fields: false,
+            body_logging: None,
+            retry_policy: None,
+            rate_limiter: None,
+            interceptors: Vec::new(),
+            metrics_sink: None,
+            cancel_token: None,
+            #[cfg(feature = "websocket")]
+            reconnect_policy: None,
+            #[cfg(feature = "websocket")]
+            heartbeat: None,
+            #[cfg(all(feature = "websocket", feature = "webhook"))]
+            failover: None,
+            #[cfg(feature = "websocket")]
+            api_transport: ApiTransport::Http,
+        }
+    }
+
+    /// 设置访问令牌，覆盖通信方式中的设置
+    pub fn access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// 设置访问令牌的提供者，参见 [`MilkyClient::with_token_provider`]
+    pub fn token_provider(mut self, provider: impl TokenProvider + 'static) -> Self {
+        self.token_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// 设置 API 请求的超时时间，默认不限制
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// 通过代理访问服务端，参见 [`MilkyClient::with_proxy`]
+    ///
+    /// 同时设置了自定义 HTTP 客户端时，HTTP 请求使用该客户端自身的代理设置，本设置只作用于事件 WebSocket 连接。
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// 设置 HTTP API 请求使用的 TLS，参见 [`MilkyClient::with_api_tls`]
+    ///
+    /// 同时设置了自定义 HTTP 客户端时，HTTP 请求使用该客户端自身的 TLS 设置。
+    pub fn api_tls(mut self, config: TlsConfig) -> Self {
+        self.api_tls = Some(config);
+        self
+    }
+
+    /// 设置事件 WebSocket 连接使用的 TLS，参见 [`MilkyClient::with_event_tls`]
+    #[cfg(feature = "websocket")]
+    pub fn event_tls(mut self, config: TlsConfig) -> Self {
+        self.event_tls = Some(config);
+        self
+    }
+
+    /// 使用自定义的 HTTP 客户端发送 API 请求与下载资源，参见 [`MilkyClient::with_http_client`]
+    ///
+    /// 该客户端优先于 [`proxy`](Self::proxy) 与 [`api_tls`](Self::api_tls) 对 HTTP 请求的设置。
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// 设置事件通道的容量，默认为 128
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// 设置每个订阅者缓存的事件数量，参见 [`MilkyClient::with_broadcast_capacity`]
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub fn broadcast_capacity(mut self, capacity: usize) -> Self {
+        self.broadcast_capacity = Some(capacity);
+        self
+    }
+
+    /// 设置事件通道写满时的处理方式，参见 [`MilkyClient::with_overflow_policy`]
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// 设置事件的解析方式，参见 [`MilkyClient::with_deserialize_mode`]
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub fn deserialize_mode(mut self, mode: DeserializeMode) -> Self {
+        self.deserialize_mode = mode;
+        self
+    }
+
+    /// 设置是否记录事件中未定义的字段，参见 [`MilkyClient::with_unknown_field_collection`]
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub fn collect_unknown_fields(mut self, enabled: bool) -> Self {
+        self.collect_unknown_fields = enabled;
+        self
+    }
+
+    /// 启用 API 请求与响应内容的记录，参见 [`MilkyClient::with_body_logging`]
+    pub fn body_logging(mut self, config: BodyLogConfig) -> Self {
+        self.body_logging = Some(config);
+        self
+    }
+
+    /// 设置 API 调用失败后的自动重试策略，参见 [`MilkyClient::with_retry_policy`]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// 设置 API 调用的全局与按操作的限速，参见 [`MilkyClient::with_rate_limiter`]
+    pub fn rate_limiter(mut self, limiter: impl Into<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = Some(limiter.into());
+        self
+    }
+
+    /// 注册 API 请求的拦截器，参见 [`MilkyClient::with_interceptor`]
+    pub fn interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// 设置导出调用与事件数据的指标导出器，参见 [`MilkyClient::with_metrics_sink`]
+    pub fn metrics_sink(mut self, sink: impl MetricsSink + 'static) -> Self {
+        self.metrics_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// 设置决定客户端生命周期的取消令牌，参见 [`MilkyClient::with_cancellation_token`]
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// 设置事件 WebSocket 连接断开后的重连策略，参见 [`MilkyClient::with_reconnect_policy`]
+    #[cfg(feature = "websocket")]
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// 为事件 WebSocket 连接启用心跳保活，参见 [`MilkyClient::with_heartbeat`]
+    #[cfg(feature = "websocket")]
+    pub fn heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// 设置事件 WebSocket 无法恢复时切换为 WebHook 接收事件，参见 [`MilkyClient::with_webhook_failover`]
+    #[cfg(all(feature = "websocket", feature = "webhook"))]
+    pub fn webhook_failover(mut self, failover: WebhookFailover) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// 设置 API 调用使用的传输方式，参见 [`MilkyClient::with_api_transport`]
+    #[cfg(feature = "websocket")]
+    pub fn api_transport(mut self, transport: ApiTransport) -> Self {
+        self.api_transport = transport;
+        self
+    }
+
+    /// 创建客户端与对应的事件通道
+    ///
+    /// # 返回
+    /// 成功则返回客户端与接收事件的通道接收端；URL 解析失败、协议不受支持或 TLS 设置无效时返回错误
+    pub fn build(self) -> Result<(MilkyClient, mpsc::Receiver<Event>)> {
+        let mut comm = self.comm;
+        if let Some(token) = self.access_token {
+            match &mut comm {
+                Communication::WebSocket(config) => config.access_token = Some(token),
+                Communication::WebHook(config) => config.access_token = Some(token),
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(self.channel_capacity);
+        let mut client = MilkyClient::new(comm, tx)?;
+        if let Some(proxy) = self.proxy {
+            client = client.with_proxy(proxy)?;
+        }
+        if let Some(config) = self.api_tls {
+            client = client.with_api_tls(config)?;
+        }
+        #[cfg(feature = "websocket")]
+        if let Some(config) = self.event_tls {
+            client = client.with_event_tls(config)?;
+        }
+        if let Some(http_client) = self.http_client {
+            client = client.with_http_client(http_client);
+        }
+        #[cfg(any(feature = "websocket", feature = "webhook"))]
+        if let Some(capacity) = self.broadcast_capacity {
+            client = client.with_broadcast_capacity(capacity);
+        }
+        #[cfg(any(feature = "websocket", feature = "webhook"))]
+        {
+            client = client
+                .with_overflow_policy(self.overflow_policy)
+                .with_deserialize_mode(self.deserialize_mode)
+                .with_unknown_field_collection(self.collect_unknown_fields);
+        }
+        client.token_provider = self.token_provider;
+        client.request_timeout = self.request_timeout;
+        client.body_logger = self.body_logging.map(BodyLogger::new);
+        client.retry_policy = self.retry_policy;
+        client.rate_limiter = self.rate_limiter;
+        client.interceptors = self.interceptors;
+        if let Some(token) = self.cancel_token {
+            client.cancel_token = token;
+        }
+        if let Some(sink) = self.metrics_sink {
+            client.event_stats.set_sink(Arc::clone(&sink));
+            client.metrics_sink = Some(sink);
+        }
+        #[cfg(feature = "websocket")]
+        {
+            client.reconnect_policy = self.reconnect_policy;
+            client.heartbeat = self.heartbeat;
+            client = client.with_api_transport(self.api_transport);
+        }
+        #[cfg(all(feature = "websocket", feature = "webhook"))]
+        {
+            client.failover = self.failover;
+        }
+        Ok((client, rx))
+    }
+}
+
+impl MilkyClient {
+    /// 创建一个 [`MilkyClientBuilder`]
+    ///
+    /// # 参数
+    /// * `comm`: 与服务端的通信方式
+    pub fn builder(comm: Communication) -> MilkyClientBuilder {
+        MilkyClientBuilder::new(comm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WebHookConfig;
+    use crate::test_util;
+    use axum::Router;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use reqwest::header::{HeaderValue, USER_AGENT};
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_build() {
+        let comm = Communication::WebHook(WebHookConfig::new(
+            None,
+            8080,
+            "http://127.0.0.1:3000".to_string(),
+            None,
+        ));
+        let (client, _rx) = MilkyClient::builder(comm)
+            .access_token("secret")
+            .request_timeout(Duration::from_secs(5))
+            .channel_capacity(16)
+            .build()
+            .unwrap();
+        assert_eq!(client.access_token.as_deref(), Some("secret"));
+        assert_eq!(client.request_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(client.event_sender.max_capacity(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client() {
+        let app = Router::new().route(
+            "/api/get_login_info",
+            post(|headers: HeaderMap| async move {
+                let user_agent = headers[USER_AGENT].to_str().unwrap().to_string();
+                axum::Json(json!({"status": "ok", "retcode": 0, "data": user_agent}))
+            }),
+        );
+        let addr = test_util::serve(app).await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static("my-bot/1.0"));
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let (client, _rx) = MilkyClient::builder(test_util::ws_comm(addr))
+            .http_client(http_client)
+            .build()
+            .unwrap();
+
+        let user_agent: Value = client
+            .send_request("get_login_info", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(user_agent, "my-bot/1.0");
+    }
+}
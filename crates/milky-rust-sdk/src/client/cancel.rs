@@ -0,0 +1,88 @@
+//! 以 [`CancellationToken`] 控制客户端的生命周期
+//!
+//! 每个客户端都持有一个取消令牌，事件读取循环、WebHook 服务器以及
+//! [`spawn_api_stats_logger`](MilkyClient::spawn_api_stats_logger) 等后台任务都会在它被取消时结束。
+//! 通过 [`MilkyClient::with_cancellation_token`] 传入应用关闭树中的令牌（或它的子令牌）后，
+//! 取消应用的令牌即可一并关闭客户端，无需逐个调用 [`MilkyClient::shutdown`]。
+//!
+//! [`MilkyClient::shutdown`] 只会结束当前的事件接收，之后仍可再次调用
+//! [`connect_events`](MilkyClient::connect_events)；客户端的令牌被取消后则不能再接收事件。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::client::CancellationToken;
+//!
+//! # async fn run(client: MilkyClient) -> milky_rust_sdk::Result<()> {
+//! let shutdown = CancellationToken::new();
+//! let client = client.with_cancellation_token(shutdown.child_token());
+//! client.connect_events().await?;
+//!
+//! tokio::signal::ctrl_c().await?;
+//! shutdown.cancel();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+
+pub use tokio_util::sync::CancellationToken;
+
+impl MilkyClient {
+    /// 设置决定客户端生命周期的取消令牌
+    ///
+    /// 需要在调用 [`connect_events`](Self::connect_events) 之前设置。
+    ///
+    /// # 参数
+    /// * `token`: 取消令牌，被取消时关闭事件接收与后台任务
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = token;
+        self
+    }
+
+    /// 获取客户端的取消令牌
+    ///
+    /// 取消该令牌与取消通过 [`with_cancellation_token`](Self::with_cancellation_token) 设置的令牌效果相同。
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel_token
+    }
+
+    /// 为一次事件接收创建令牌，并替换之前的令牌
+    ///
+    /// # 返回
+    /// 客户端令牌的子令牌，[`shutdown`](Self::shutdown) 只会取消该令牌
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub(crate) async fn start_session(&self) -> CancellationToken {
+        let token = self.cancel_token.child_token();
+        if let Some(previous) = self.shutdown_token.lock().await.replace(token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+}
+
+#[cfg(all(test, feature = "webhook"))]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use crate::types::communication::Communication;
+
+    #[tokio::test]
+    async fn test_parent_token_stops_webhook_server() {
+        let (client, _rx) =
+            test_util::client_for(Communication::WebHook(test_util::webhook_config()));
+        let parent = CancellationToken::new();
+        let client = client.with_cancellation_token(parent.child_token());
+        client.connect_events().await.unwrap();
+        let addr = client.webhook_local_addr().unwrap();
+
+        parent.cancel();
+        for _ in 0..50 {
+            if client.webhook_local_addr().is_none() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(client.webhook_local_addr().is_none());
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! 读写分离的事件 WebSocket 连接
+//!
+//! 读取端由事件读取循环直接持有，读取消息时无需加锁；写入端由独立的写入任务持有，
+//! 心跳 Ping、API 请求与 Close 帧都通过 [`EventWsConnection::writer`] 交给写入任务发送，
+//! 发送过程不会阻塞读取，多个发送方也无需相互等待。
+
+use crate::client::EventWsStream;
+use crate::logger::{error, warn};
+use crate::runtime::{self, JoinHandle};
+
+use futures_util::stream::SplitStream;
+use futures_util::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+
+/// 关闭连接时等待写入任务发送完剩余消息与 Close 帧的最长时间
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 已建立的事件 WebSocket 连接
+pub(crate) struct EventWsConnection {
+    /// 连接的读取端
+    pub(crate) reader: SplitStream<EventWsStream>,
+    /// 向写入任务提交待发送消息的通道
+    pub(crate) writer: mpsc::UnboundedSender<WsMessage>,
+    /// 写入失败时收到对应的错误
+    pub(crate) write_failed: oneshot::Receiver<WsError>,
+    /// 写入任务
+    writer_task: JoinHandle<()>,
+}
+
+impl EventWsConnection {
+    /// 拆分连接的读写两端，并启动写入任务
+    ///
+    /// 写入任务在发送 Close 帧、写入失败或所有写入通道的发送端都被丢弃后结束。
+    ///
+    /// # 参数
+    /// * `stream`: 已完成握手的连接
+    pub(crate) fn new(stream: EventWsStream) -> Self {
+        let (mut sink, reader) = stream.split();
+        let (writer, mut outgoing) = mpsc::unbounded_channel::<WsMessage>();
+        let (failed_tx, write_failed) = oneshot::channel();
+        let writer_task = runtime::spawn(async move {
+            while let Some(message) = outgoing.recv().await {
+                let is_close = matches!(message, WsMessage::Close(_));
+                if let Err(e) = sink.send(message).await {
+                    error!("向事件 WebSocket 写入消息时出错: {e:?}");
+                    let _ = failed_tx.send(e);
+                    return;
+                }
+                if is_close {
+                    return;
+                }
+            }
+        });
+        Self {
+            reader,
+            writer,
+            write_failed,
+            writer_task,
+        }
+    }
+
+    /// 发送 Close 帧并等待写入任务结束
+    ///
+    /// 写入任务在 5 秒内没有结束时（例如对端不再读取导致写入阻塞）会被中止。
+    ///
+    /// # 返回
+    /// Close 帧发送成功则返回 `true`
+    pub(crate) async fn close(&mut self) -> bool {
+        self.close_within(CLOSE_TIMEOUT).await
+    }
+
+    /// 发送 Close 帧，并最多等待写入任务 `timeout` 的时长
+    async fn close_within(&mut self, timeout: Duration) -> bool {
+        let _ = self.writer.send(WsMessage::Close(None));
+        if runtime::timeout(timeout, &mut self.writer_task)
+            .await
+            .is_err()
+        {
+            warn!("等待 {timeout:?} 后 Close 帧仍未发送，中止写入任务");
+            self.writer_task.abort();
+            return false;
+        }
+        self.write_failed.try_recv().is_err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::WsServer;
+    use tokio_tungstenite::connect_async;
+
+    #[tokio::test]
+    async fn test_write_while_reading() {
+        let server = WsServer::bind().await;
+        let addr = server.addr();
+        tokio::spawn(async move {
+            let mut socket = server.accept().await;
+            while let Some(Ok(message)) = socket.next().await {
+                if message.is_text() {
+                    socket.send(message).await.unwrap();
+                }
+            }
+        });
+
+        let (stream, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+        let mut connection = EventWsConnection::new(stream);
+        let writer = connection.writer.clone();
+        // 读取端等待期间通过写入端发送的消息会被服务端原样返回
+        let read = tokio::spawn(async move {
+            let message = connection.reader.next().await;
+            (connection, message)
+        });
+        writer.send(WsMessage::text("ping")).unwrap();
+        let (mut connection, message) = read.await.unwrap();
+        assert_eq!(message.unwrap().unwrap(), WsMessage::text("ping"));
+        assert!(connection.close().await);
+    }
+
+    #[tokio::test]
+    async fn test_close_with_stuck_writer() {
+        let server = WsServer::bind().await;
+        let addr = server.addr();
+        let (accepted_tx, accepted_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            // 完成握手后不再读取，写满缓冲区后写入会一直阻塞
+            let _ = accepted_tx.send(server.accept().await);
+        });
+
+        let (stream, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+        let _socket = accepted_rx.await.unwrap();
+        let mut connection = EventWsConnection::new(stream);
+        for _ in 0..64 {
+            let _ = connection
+                .writer
+                .send(WsMessage::binary(vec![0u8; 1024 * 1024]));
+        }
+        assert!(!connection.close_within(Duration::from_millis(200)).await);
+        runtime::sleep(Duration::from_millis(10)).await;
+        assert!(connection.writer_task.is_finished());
+    }
+}
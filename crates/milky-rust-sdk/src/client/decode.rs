@@ -0,0 +1,298 @@
+//! 事件的解析方式
+//!
+//! 默认的 [`DeserializeMode::Tolerant`] 直接解析事件，忽略 SDK 未定义的字段。
+//! 通过 [`MilkyClient::with_unknown_field_collection`] 开启记录后，每个新出现的字段输出一次警告，
+//! 所有记录可以通过 [`MilkyClient::unknown_fields`] 查看，便于发现协议的更新。记录需要额外比对事件的原始内容，
+//! 会增加解析的开销，因此默认关闭。
+//! 进行协议一致性测试时可以改用 [`DeserializeMode::Strict`]，此时包含未定义的字段、事件类型或消息段类型的事件会被丢弃。
+//!
+//! ```no_run
+//! use milky_rust_sdk::client::DeserializeMode;
+//! use milky_rust_sdk::{Communication, MilkyClient, WebSocketConfig};
+//!
+//! # fn run() -> milky_rust_sdk::Result<()> {
+//! let comm = Communication::WebSocket(WebSocketConfig::new("ws://127.0.0.1:3000".to_string(), None));
+//! let (client, events) = MilkyClient::builder(comm)
+//!     .deserialize_mode(DeserializeMode::Strict)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+use crate::logger::warn;
+use crate::utils::json;
+
+use milky_types::message::in_coming::IncomingSegment;
+use milky_types::{Event, EventKind};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+/// 事件的解析方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeserializeMode {
+    /// 忽略并记录未定义的字段
+    #[default]
+    Tolerant,
+    /// 丢弃包含未定义的字段、事件类型或消息段类型的事件
+    Strict,
+}
+
+/// 每种事件类型最多记录的未定义字段数量
+const MAX_UNKNOWN_FIELDS_PER_TYPE: usize = 64;
+
+/// 最多记录未定义字段的事件类型数量
+const MAX_UNKNOWN_FIELD_TYPES: usize = 64;
+
+/// 按设置的方式解析事件，并记录未定义的字段
+#[derive(Default)]
+pub(crate) struct EventDecoder {
+    /// 解析方式
+    mode: DeserializeMode,
+    /// 是否记录未定义的字段
+    collect_unknown: bool,
+    /// 以事件类型为键，记录出现过的未定义字段的路径
+    unknown_fields: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl EventDecoder {
+    /// 创建解析器
+    ///
+    /// # 参数
+    /// * `mode`: 解析方式
+    /// * `collect_unknown`: 宽松模式下是否记录未定义的字段
+    pub(crate) fn new(mode: DeserializeMode, collect_unknown: bool) -> Self {
+        Self {
+            mode,
+            collect_unknown,
+            unknown_fields: Mutex::default(),
+        }
+    }
+
+    /// 解析事件
+    ///
+    /// 宽松模式且未开启记录时直接解析文本，不构造中间的 [`Value`]。
+    ///
+    /// # 参数
+    /// * `text`: 事件的原始文本
+    ///
+    /// # 返回
+    /// 解析失败或严格模式下事件不符合协议定义时返回原因
+    pub(crate) fn decode(&self, text: &str) -> Result<Event, String> {
+        if self.mode == DeserializeMode::Tolerant && !self.collect_unknown {
+            return json::from_str(text).map_err(|e| e.to_string());
+        }
+        let raw = json::from_str::<Value>(text).map_err(|e| e.to_string())?;
+        let event = Event::deserialize(&raw).map_err(|e| e.to_string())?;
+        let parsed = serde_json::to_value(&event).map_err(|e| e.to_string())?;
+        let mut unknown = Vec::new();
+        collect_unknown_fields(&raw, &parsed, &mut String::new(), &mut unknown);
+
+        match self.mode {
+            DeserializeMode::Strict => {
+                if !unknown.is_empty() {
+                    return Err(format!("事件包含未定义的字段: {}", unknown.join(", ")));
+                }
+                if let Some(kind) = unknown_type(&event) {
+                    return Err(format!("事件包含未定义的类型: {kind}"));
+                }
+            }
+            DeserializeMode::Tolerant if !unknown.is_empty() => {
+                let mut fields = self.unknown_fields.lock().unwrap();
+                let event_type = event.kind.event_type();
+                if !fields.contains_key(event_type) && fields.len() >= MAX_UNKNOWN_FIELD_TYPES {
+                    return Ok(event);
+                }
+                let seen = fields.entry(event_type.to_string()).or_default();
+                for path in unknown {
+                    if seen.len() >= MAX_UNKNOWN_FIELDS_PER_TYPE {
+                        break;
+                    }
+                    if !seen.contains(&path) {
+                        warn!("{} 事件包含未定义的字段: {path}", event.kind.event_type());
+                        seen.insert(path);
+                    }
+                }
+            }
+            DeserializeMode::Tolerant => {}
+        }
+        Ok(event)
+    }
+
+    /// 获取记录的未定义字段
+    fn unknown_fields(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.unknown_fields.lock().unwrap().clone()
+    }
+}
+
+/// 找出原始内容中存在、解析后却丢失的字段
+///
+/// 字段路径以 `.` 分隔，数组元素记为 `[]`，例如 `data.segments[].data.extra`。值为 `null` 的字段视为不存在。
+fn collect_unknown_fields(raw: &Value, parsed: &Value, path: &mut String, out: &mut Vec<String>) {
+    match (raw, parsed) {
+        (Value::Object(raw), Value::Object(parsed)) => {
+            for (key, value) in raw {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                match parsed.get(key) {
+                    Some(parsed) => collect_unknown_fields(value, parsed, path, out),
+                    None if !value.is_null() && !out.contains(path) => out.push(path.clone()),
+                    None => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(raw), Value::Array(parsed)) => {
+            let len = path.len();
+            path.push_str("[]");
+            for (raw, parsed) in raw.iter().zip(parsed) {
+                collect_unknown_fields(raw, parsed, path, out);
+            }
+            path.truncate(len);
+        }
+        _ => {}
+    }
+}
+
+/// 找出事件中未定义的事件类型或消息段类型
+fn unknown_type(event: &Event) -> Option<&str> {
+    match &event.kind {
+        EventKind::Unknown { event_type, .. } => Some(event_type),
+        EventKind::MessageReceive { message } => {
+            message
+                .base_message()
+                .segments
+                .iter()
+                .find_map(|segment| match segment {
+                    IncomingSegment::Unknown { r#type, .. } => Some(r#type.as_str()),
+                    _ => None,
+                })
+        }
+        _ => None,
+    }
+}
+
+impl MilkyClient {
+    /// 设置事件的解析方式，默认为 [`DeserializeMode::Tolerant`]
+    ///
+    /// 需要在调用 [`connect_events`](Self::connect_events) 之前设置。
+    ///
+    /// # 参数
+    /// * `mode`: 解析方式
+    pub fn with_deserialize_mode(mut self, mode: DeserializeMode) -> Self {
+        let collect_unknown = self.event_decoder.collect_unknown;
+        self.event_decoder = Arc::new(EventDecoder::new(mode, collect_unknown));
+        self
+    }
+
+    /// 设置宽松模式下是否记录事件中未定义的字段，默认不记录
+    ///
+    /// 开启后每个事件都需要额外比对原始内容，只建议在排查协议差异时使用。
+    /// 需要在调用 [`connect_events`](Self::connect_events) 之前设置。
+    ///
+    /// # 参数
+    /// * `enabled`: 是否记录
+    pub fn with_unknown_field_collection(mut self, enabled: bool) -> Self {
+        let mode = self.event_decoder.mode;
+        self.event_decoder = Arc::new(EventDecoder::new(mode, enabled));
+        self
+    }
+
+    /// 获取事件中出现过的未定义字段，需要先通过 [`with_unknown_field_collection`](Self::with_unknown_field_collection) 开启记录
+    ///
+    /// 每种事件类型最多记录 64 个字段，避免异常的服务端不断发送随机字段时占用过多内存。
+    ///
+    /// # 返回
+    /// 以事件类型为键、字段路径为值的映射，例如 `message_receive` 对应 `data.segments[].data.extra`
+    pub fn unknown_fields(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.event_decoder.unknown_fields()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_modes() {
+        let raw = json!({
+            "time": 1,
+            "self_id": 10000,
+            "event_type": "bot_offline",
+            "data": {"reason": "test", "code": 3, "detail": null},
+            "extra": {"a": 1},
+        })
+        .to_string();
+
+        let direct = EventDecoder::default();
+        assert!(direct.decode(&raw).is_ok());
+        assert!(direct.unknown_fields().is_empty());
+
+        let tolerant = EventDecoder::new(DeserializeMode::Tolerant, true);
+        assert!(tolerant.decode(&raw).is_ok());
+        assert_eq!(
+            tolerant.unknown_fields()["bot_offline"],
+            BTreeSet::from(["data.code".to_string(), "extra".to_string()])
+        );
+
+        let strict = EventDecoder::new(DeserializeMode::Strict, false);
+        assert!(strict.decode(&raw).unwrap_err().contains("data.code"));
+        let unknown_kind =
+            json!({"time": 1, "self_id": 10000, "event_type": "todo", "data": {}}).to_string();
+        assert!(strict.decode(&unknown_kind).is_err());
+        assert!(tolerant.decode(&unknown_kind).is_ok());
+
+        let message = json!({
+            "time": 1,
+            "self_id": 10000,
+            "event_type": "message_receive",
+            "data": {
+                "peer_id": 123456,
+                "message_seq": 1,
+                "sender_id": 20000,
+                "time": 1,
+                "segments": [{"type": "text", "data": {"text": "你好"}}],
+                "message_scene": "friend",
+                "friend": {
+                    "user_id": 20000,
+                    "nickname": "测试",
+                    "sex": "female",
+                    "qid": "",
+                    "remark": "",
+                    "category": {"category_id": 0, "category_name": "我的好友"}
+                }
+            }
+        })
+        .to_string();
+        assert!(
+            strict.decode(&message).is_ok(),
+            "{:?}",
+            strict.decode(&message)
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_are_capped() {
+        let decoder = EventDecoder::new(DeserializeMode::Tolerant, true);
+        for i in 0..MAX_UNKNOWN_FIELDS_PER_TYPE * 2 {
+            let raw = json!({
+                "time": 1,
+                "self_id": 10000,
+                "event_type": "bot_offline",
+                "data": {"reason": "test"},
+                format!("random_{i}"): 1,
+            });
+            assert!(decoder.decode(&raw.to_string()).is_ok());
+        }
+        assert_eq!(
+            decoder.unknown_fields()["bot_offline"].len(),
+            MAX_UNKNOWN_FIELDS_PER_TYPE
+        );
+    }
+}
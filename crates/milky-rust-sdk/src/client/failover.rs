@@ -0,0 +1,238 @@
+//! 事件 WebSocket 不可用时切换为 WebHook 接收事件
+//!
+//! 通过 [`MilkyClient::with_webhook_failover`] 设置 [`WebhookFailover`] 后，事件 WebSocket 连接断开且
+//! 连续重连失败达到设置的次数（或重连策略已放弃重连）时，事件读取循环会在备用的 WebHook 配置上启动事件接收服务器，
+//! 之后的事件改由服务端推送，并通过 [`ConnectionStatus::FailedOver`] 通知应用。
+//! 备用地址无法监听时会发送 [`ConnectionStatus::FailoverFailed`]，并继续按重连策略重连事件 WebSocket。
+//! API 请求仍发往原有的地址，[`MilkyClient::shutdown`] 会同时关闭切换后启动的服务器。
+//!
+//! WebHook 方式只被动等待服务端的请求，无法判断服务端是否已停止推送，因此只支持从 WebSocket 切换为 WebHook。
+//! 服务端需要事先配置为在 WebSocket 连接不可用时向备用地址推送事件。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::client::{ReconnectPolicy, WebhookFailover};
+//! use milky_rust_sdk::types::communication::WebHookConfig;
+//!
+//! # fn build(client: MilkyClient) -> MilkyClient {
+//! let fallback = WebHookConfig::new(None, 8081, "http://127.0.0.1:3000".to_string(), None);
+//! client
+//!     .with_reconnect_policy(ReconnectPolicy::new())
+//!     .with_webhook_failover(WebhookFailover::new(fallback, 5))
+//! # }
+//! ```
+
+use crate::client::webhook::{self, WebhookTlsAcceptor};
+use crate::client::{ConnectionStatus, MilkyClient};
+use crate::error::Result;
+use crate::logger::{error, info};
+use crate::types::communication::WebHookConfig;
+
+use axum::Router;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// 切换为 WebHook 接收事件的设置
+#[derive(Debug, Clone)]
+pub struct WebhookFailover {
+    /// 备用的 WebHook 配置
+    pub(crate) config: WebHookConfig,
+    /// 连续重连失败多少次后切换
+    pub(crate) after_attempts: u32,
+}
+
+impl WebhookFailover {
+    /// 创建切换设置
+    ///
+    /// # 参数
+    /// * `config`: 备用的 WebHook 配置，其中的 `http_endpoint` 不会被使用
+    /// * `after_attempts`: 连续重连失败多少次后切换，为 `0` 时连接断开后立即切换
+    pub fn new(config: WebHookConfig, after_attempts: u32) -> Self {
+        Self {
+            config,
+            after_attempts,
+        }
+    }
+}
+
+/// 启动事件读取循环前准备好的备用事件接收服务器
+pub(crate) struct PreparedFailover {
+    /// 连续重连失败多少次后切换
+    pub(crate) after_attempts: u32,
+    /// 监听的地址
+    address: String,
+    /// 接收事件的路由
+    router: Router,
+    /// 设置时以 HTTPS 接收事件
+    tls_acceptor: Option<WebhookTlsAcceptor>,
+    /// 实际监听的地址
+    webhook_addr: Arc<std::sync::Mutex<Option<SocketAddr>>>,
+}
+
+impl PreparedFailover {
+    /// 启动备用的事件接收服务器，并运行到收到关闭信号为止
+    ///
+    /// # 参数
+    /// * `shutdown`: 当前事件接收的取消令牌
+    /// * `status_sender`: 连接状态的广播通道
+    ///
+    /// # 返回
+    /// 服务器正常关闭时返回 `Ok(())`；无法监听备用地址时返回错误，此时会通过
+    /// [`ConnectionStatus::FailoverFailed`] 通知应用，由调用方继续重连事件 WebSocket
+    pub(crate) async fn run(
+        &self,
+        shutdown: &CancellationToken,
+        status_sender: &broadcast::Sender<ConnectionStatus>,
+    ) -> Result<()> {
+        let listener = match tokio::net::TcpListener::bind(&self.address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("无法将备用 WebHook 监听器绑定到 {}: {e}", self.address);
+                let _ = status_sender.send(ConnectionStatus::FailoverFailed {
+                    reason: format!("无法监听备用地址 {}: {e}", self.address),
+                });
+                return Err(e.into());
+            }
+        };
+        if let Ok(local_addr) = listener.local_addr() {
+            info!("已切换为通过 WebHook 接收事件，正在监听: {local_addr}");
+            *self.webhook_addr.lock().unwrap() = Some(local_addr);
+        }
+        let _ = status_sender.send(ConnectionStatus::FailedOver);
+
+        let shutdown = shutdown.clone();
+        let shutdown = async move {
+            shutdown.cancelled().await;
+            info!("收到关闭信号，开始关闭备用 WebHook 服务器...");
+        };
+        let router = self.router.clone();
+        // 未启用 `webhook-tls` 特性时接收器的类型实现了 `Copy`
+        #[cfg_attr(not(feature = "webhook-tls"), allow(clippy::clone_on_copy))]
+        let tls_acceptor = self.tls_acceptor.clone();
+        if let Err(e) = webhook::serve(listener, router, tls_acceptor, shutdown).await {
+            error!("备用 WebHook 事件接收服务器遇到错误: {e:?}");
+        }
+        info!("备用 WebHook 事件接收服务器已关闭");
+        self.webhook_addr.lock().unwrap().take();
+        Ok(())
+    }
+}
+
+impl MilkyClient {
+    /// 设置事件 WebSocket 无法恢复时切换为 WebHook 接收事件
+    ///
+    /// # 参数
+    /// * `failover`: 切换设置
+    pub fn with_webhook_failover(mut self, failover: WebhookFailover) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// 按切换设置准备备用的事件接收服务器
+    ///
+    /// # 返回
+    /// 未设置切换时返回 `Ok(None)`；备用配置中的路由路径或证书无效时返回错误
+    pub(crate) fn prepare_failover(&self) -> Result<Option<PreparedFailover>> {
+        let Some(failover) = &self.failover else {
+            return Ok(None);
+        };
+        let config = &failover.config;
+        Ok(Some(PreparedFailover {
+            after_attempts: failover.after_attempts,
+            address: format!("{}:{}", config.host, config.port),
+            router: self.build_webhook_router(config)?,
+            tls_acceptor: webhook::tls_acceptor(config)?,
+            webhook_addr: Arc::clone(&self.webhook_addr),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ReconnectPolicy;
+    use crate::test_util::{self, WsServer};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_failover_to_webhook() {
+        let server = WsServer::bind().await;
+        let (client, mut rx) = test_util::client(server.addr());
+        tokio::spawn(async move {
+            // 接受第一个连接后立即关闭，之后不再接受连接
+            drop(server.accept().await);
+        });
+
+        let client =
+            client.with_webhook_failover(WebhookFailover::new(test_util::webhook_config(), 0));
+        let mut status = client.connection_status();
+        client.connect_events().await.unwrap();
+        while status.recv().await.unwrap() != ConnectionStatus::FailedOver {}
+
+        let url = format!("http://{}/webhook", client.webhook_local_addr().unwrap());
+        let response = reqwest::Client::new()
+            .post(url)
+            .json(&json!({
+                "time": 5,
+                "self_id": 10000,
+                "event_type": "bot_offline",
+                "data": {"reason": "test"},
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(rx.recv().await.unwrap().time, 5);
+
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_failover_bind_failure() {
+        let server = WsServer::bind().await;
+        let (client, _rx) = test_util::client(server.addr());
+        tokio::spawn(async move {
+            // 第一个连接建立后立即关闭，重连后的连接保持打开
+            drop(server.accept().await);
+            let _socket = server.accept().await;
+            std::future::pending::<()>().await;
+        });
+        // 占用备用地址，使切换失败
+        let occupied = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let occupied_port = occupied.local_addr().unwrap().port();
+
+        let mut fallback = test_util::webhook_config();
+        fallback.host = "127.0.0.1".to_string();
+        fallback.port = i32::from(occupied_port);
+        let client = client
+            .with_reconnect_policy(
+                ReconnectPolicy::new()
+                    .initial_backoff(Duration::from_millis(10))
+                    .jitter(0.0),
+            )
+            .with_webhook_failover(WebhookFailover::new(fallback, 0));
+        let mut status = client.connection_status();
+        client.connect_events().await.unwrap();
+
+        assert_eq!(status.recv().await.unwrap(), ConnectionStatus::Connected);
+        assert!(matches!(
+            status.recv().await.unwrap(),
+            ConnectionStatus::Disconnected { .. }
+        ));
+        assert!(matches!(
+            status.recv().await.unwrap(),
+            ConnectionStatus::FailoverFailed { .. }
+        ));
+        assert_eq!(
+            status.recv().await.unwrap(),
+            ConnectionStatus::Reconnecting { attempt: 1 }
+        );
+        assert_eq!(status.recv().await.unwrap(), ConnectionStatus::Connected);
+        assert!(client.webhook_local_addr().is_none());
+
+        client.shutdown().await;
+    }
+}
@@ -0,0 +1,127 @@
+//! 事件 WebSocket 连接的心跳保活
+//!
+//! 经过 NAT 或代理的长连接可能在没有任何提示的情况下失效，此时事件读取循环会一直等待而收不到事件。
+//! 通过 [`MilkyClient::with_heartbeat`] 启用心跳后，读取循环会定期发送 Ping 帧，
+//! 若在超时时间内没有收到服务端的任何数据，则判定连接已失效并断开；设置了 [`ReconnectPolicy`] 时会随后重连。
+//!
+//! [`ReconnectPolicy`]: crate::client::ReconnectPolicy
+
+use crate::client::MilkyClient;
+
+use std::time::{Duration, Instant};
+
+/// 心跳保活的设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// 发送 Ping 帧的间隔
+    interval: Duration,
+    /// 发送 Ping 帧后等待服务端数据的超时时间
+    timeout: Duration,
+}
+
+impl Heartbeat {
+    /// 创建心跳设置
+    ///
+    /// # 参数
+    /// * `interval`: 发送 Ping 帧的间隔
+    /// * `timeout`: 发送 Ping 帧后等待服务端数据的超时时间
+    pub fn new(interval: Duration, timeout: Duration) -> Self {
+        Self { interval, timeout }
+    }
+}
+
+impl Default for Heartbeat {
+    /// 每 30 秒发送一次 Ping 帧，10 秒内未收到数据视为连接失效
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), Duration::from_secs(10))
+    }
+}
+
+/// 单个连接的心跳状态
+pub(crate) struct HeartbeatState {
+    /// 心跳设置
+    config: Heartbeat,
+    /// 下一次发送 Ping 帧的时间
+    next_ping: Instant,
+    /// 尚未得到响应的 Ping 帧的发送时间
+    awaiting_since: Option<Instant>,
+}
+
+impl HeartbeatState {
+    /// 为新建立的连接创建心跳状态
+    pub(crate) fn new(config: Heartbeat) -> Self {
+        Self {
+            config,
+            next_ping: Instant::now() + config.interval,
+            awaiting_since: None,
+        }
+    }
+
+    /// 距离下一次需要处理心跳（发送 Ping 帧或判定超时）的时长
+    pub(crate) fn wait(&self, now: Instant) -> Duration {
+        let deadline = match self.awaiting_since {
+            Some(sent) => sent + self.config.timeout,
+            None => self.next_ping,
+        };
+        deadline.saturating_duration_since(now)
+    }
+
+    /// 收到服务端的任意数据，说明连接仍然可用
+    pub(crate) fn on_message(&mut self) {
+        self.awaiting_since = None;
+    }
+
+    /// 已发送的 Ping 帧是否超时未得到响应
+    pub(crate) fn is_stale(&self, now: Instant) -> bool {
+        self.awaiting_since
+            .is_some_and(|sent| now.duration_since(sent) >= self.config.timeout)
+    }
+
+    /// 记录一次 Ping 帧的发送
+    pub(crate) fn on_ping_sent(&mut self, now: Instant) {
+        self.awaiting_since = Some(now);
+        self.next_ping = now + self.config.interval;
+    }
+
+    /// 心跳超时时间
+    pub(crate) fn timeout(&self) -> Duration {
+        self.config.timeout
+    }
+}
+
+impl MilkyClient {
+    /// 为事件 WebSocket 连接启用心跳保活
+    ///
+    /// # 参数
+    /// * `heartbeat`: 心跳设置
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_state() {
+        let config = Heartbeat::new(Duration::from_secs(30), Duration::from_secs(10));
+        let mut state = HeartbeatState::new(config);
+        let start = Instant::now();
+        assert!(state.wait(start) <= Duration::from_secs(30));
+        assert!(!state.is_stale(start));
+
+        state.on_ping_sent(start);
+        assert_eq!(state.wait(start), Duration::from_secs(10));
+        assert!(!state.is_stale(start + Duration::from_secs(5)));
+        assert!(state.is_stale(start + Duration::from_secs(10)));
+
+        state.on_message();
+        assert!(!state.is_stale(start + Duration::from_secs(10)));
+        assert_eq!(
+            state.wait(start + Duration::from_secs(10)),
+            Duration::from_secs(20)
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! API 请求的拦截器
+//!
+//! 通过 [`MilkyClient::with_interceptor`] 注册的 [`Interceptor`] 会在每次 API 请求发送前与收到响应后被调用，
+//! 可以读取或修改操作名称对应的请求参数与响应内容，用于实现日志、指标统计、签名或参数改写等功能。
+//!
+//! 多个拦截器按注册顺序调用 [`before_send`](Interceptor::before_send)，
+//! 按相反的顺序调用 [`after_receive`](Interceptor::after_receive)。任一钩子返回错误时本次请求以该错误结束，
+//! 启用重试时是否重试由重试策略决定。拒绝请求时返回 [`MilkyError::Rejected`]，它不会被重试。
+//!
+//! 钩子返回 [`InterceptFuture`]，可以在其中等待异步操作，例如向签名服务请求签名：
+//!
+//! ```
+//! use milky_rust_sdk::client::{InterceptFuture, Interceptor};
+//! use milky_rust_sdk::MilkyError;
+//! use serde_json::Value;
+//!
+//! struct Signer;
+//!
+//! impl Interceptor for Signer {
+//!     fn before_send<'a>(&'a self, action: &'a str, params: &'a mut Value) -> InterceptFuture<'a> {
+//!         Box::pin(async move {
+//!             let Some(params) = params.as_object_mut() else {
+//!                 return Err(MilkyError::Rejected {
+//!                     action: action.to_string(),
+//!                     reason: "请求参数不是对象".to_string(),
+//!                 });
+//!             };
+//!             params.insert("sign".to_string(), Value::from(format!("signed-{action}")));
+//!             Ok(())
+//!         })
+//!     }
+//! }
+//! ```
+
+use crate::client::MilkyClient;
+use crate::error::Result;
+use crate::types::common::ApiResponse;
+
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// [`Interceptor`] 的钩子返回的 Future
+pub type InterceptFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// API 请求的拦截器
+pub trait Interceptor: Send + Sync {
+    /// 请求发送前调用
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `params`: 即将发送的请求参数，可以直接修改
+    ///
+    /// # 返回
+    /// 返回错误时请求不会发送，拒绝请求时应返回 [`MilkyError::Rejected`](crate::MilkyError::Rejected)
+    fn before_send<'a>(&'a self, action: &'a str, params: &'a mut Value) -> InterceptFuture<'a> {
+        let _ = (action, params);
+        Box::pin(async { Ok(()) })
+    }
+
+    /// 收到服务端的响应后、解析响应数据前调用
+    ///
+    /// HTTP 状态码不为 200 或请求未能完成时不会调用。
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `response`: 服务端返回的响应，可以直接修改
+    ///
+    /// # 返回
+    /// 返回错误时本次请求以该错误结束
+    fn after_receive<'a>(
+        &'a self,
+        action: &'a str,
+        response: &'a mut ApiResponse<Value>,
+    ) -> InterceptFuture<'a> {
+        let _ = (action, response);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl MilkyClient {
+    /// 注册 API 请求的拦截器，可以多次调用以注册多个拦截器
+    ///
+    /// # 参数
+    /// * `interceptor`: 拦截器
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// 依次调用各拦截器的 [`Interceptor::before_send`]
+    pub(crate) async fn intercept_request(&self, action: &str, params: &mut Value) -> Result<()> {
+        for interceptor in &self.interceptors {
+            interceptor.before_send(action, params).await?;
+        }
+        Ok(())
+    }
+
+    /// 以相反的顺序调用各拦截器的 [`Interceptor::after_receive`]
+    pub(crate) async fn intercept_response(
+        &self,
+        action: &str,
+        response: &mut ApiResponse<Value>,
+    ) -> Result<()> {
+        for interceptor in self.interceptors.iter().rev() {
+            interceptor.after_receive(action, response).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MilkyError;
+    use crate::test_util;
+    use axum::Router;
+    use axum::routing::post;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// 记录调用顺序并改写参数与响应的拦截器
+    struct Tagger {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Interceptor for Tagger {
+        fn before_send<'a>(
+            &'a self,
+            action: &'a str,
+            params: &'a mut Value,
+        ) -> InterceptFuture<'a> {
+            Box::pin(async move {
+                // 钩子中可以等待异步操作
+                crate::runtime::sleep(Duration::from_millis(1)).await;
+                self.calls
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} before {action}", self.name));
+                params[self.name] = json!(true);
+                Ok(())
+            })
+        }
+
+        fn after_receive<'a>(
+            &'a self,
+            action: &'a str,
+            response: &'a mut ApiResponse<Value>,
+        ) -> InterceptFuture<'a> {
+            Box::pin(async move {
+                self.calls
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} after {action}", self.name));
+                if response.retcode != 0 {
+                    return Err(MilkyError::Rejected {
+                        action: action.to_string(),
+                        reason: format!("{} 拒绝了响应", self.name),
+                    });
+                }
+                response.data.as_mut().unwrap()["seen_by"] = json!(self.name);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_chain() {
+        let app = Router::new()
+            .route(
+                "/api/echo",
+                post(|axum::Json(params): axum::Json<Value>| async move {
+                    axum::Json(json!({"status": "ok", "retcode": 0, "data": params}))
+                }),
+            )
+            .route(
+                "/api/fail",
+                post(|| async { axum::Json(json!({"status": "failed", "retcode": 1})) }),
+            );
+        let (client, _rx) = test_util::client(test_util::serve(app).await);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = client
+            .with_interceptor(Tagger {
+                name: "outer",
+                calls: Arc::clone(&calls),
+            })
+            .with_interceptor(Tagger {
+                name: "inner",
+                calls: Arc::clone(&calls),
+            });
+
+        let data: Value = client.send_request("echo", json!({})).await.unwrap();
+        assert_eq!(
+            data,
+            json!({"outer": true, "inner": true, "seen_by": "outer"})
+        );
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "outer before echo",
+                "inner before echo",
+                "inner after echo",
+                "outer after echo"
+            ]
+        );
+
+        let result = client.send_request::<_, Value>("fail", json!({})).await;
+        match result {
+            Err(error @ MilkyError::Rejected { .. }) => {
+                assert_eq!(error.action(), Some("fail"));
+                assert!(error.is_permanent());
+                assert!(!error.is_retryable());
+                assert!(error.to_string().ends_with("inner 拒绝了响应"));
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! 单次 API 调用的选项
+//!
+//! 客户端级别的默认超时时间通过 [`MilkyClient::with_request_timeout`] 或
+//! [`MilkyClientBuilder::request_timeout`](crate::MilkyClientBuilder::request_timeout) 设置；
+//! 需要为某次调用单独设置时，使用 [`MilkyClient::send_request_with_opts`] 并传入 [`RequestOptions`]。
+//! 请求超时时返回 [`MilkyError::Timeout`](crate::MilkyError::Timeout)。
+
+use crate::client::MilkyClient;
+
+use std::time::Duration;
+
+/// 单次 API 调用的选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// 本次调用的超时时间，为 `None` 时使用客户端的默认设置
+    pub(crate) timeout: Option<Duration>,
+    /// 是否关闭本次调用的自动重试
+    pub(crate) no_retry: bool,
+}
+
+impl RequestOptions {
+    /// 创建不覆盖任何客户端设置的选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置本次调用的超时时间，覆盖客户端的默认超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 关闭本次调用的自动重试，参见 [`RetryPolicy`](crate::client::RetryPolicy)
+    pub fn no_retry(mut self) -> Self {
+        self.no_retry = true;
+        self
+    }
+}
+
+impl MilkyClient {
+    /// 设置 API 请求的默认超时时间，默认不限制
+    ///
+    /// # 参数
+    /// * `timeout`: 超时时间
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MilkyError;
+    use crate::test_util;
+    use axum::Router;
+    use axum::routing::post;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let app = Router::new().route(
+            "/api/get_login_info",
+            post(|| async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                axum::Json(json!({"status": "ok", "retcode": 0, "data": null}))
+            }),
+        );
+        let (client, _rx) = test_util::client(test_util::serve(app).await);
+        let client = client.with_request_timeout(Duration::from_millis(50));
+
+        let result = client
+            .send_request::<_, Value>("get_login_info", json!({}))
+            .await;
+        assert!(matches!(result, Err(MilkyError::Timeout)));
+
+        let options = RequestOptions::new().timeout(Duration::from_secs(5));
+        let result = client
+            .send_request_with_opts::<_, Value>("get_login_info", json!({}), &options)
+            .await;
+        assert!(result.is_ok());
+    }
+}
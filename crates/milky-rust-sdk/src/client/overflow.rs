@@ -0,0 +1,168 @@
+//! 事件通道写满时的处理方式
+//!
+//! 默认情况下事件通道写满后，事件读取循环会等待应用取出事件，期间不会读取新的 WebSocket 消息，
+//! WebHook 请求也会一直等待响应。事件处理可能跟不上接收速度时，可以通过
+//! [`MilkyClient::with_overflow_policy`] 改为丢弃事件，使事件接收不被阻塞。
+//! 被丢弃的事件会计入 [`EventGauges::dropped_events`](crate::stats::EventGauges::dropped_events)，
+//! 并通过 [`MetricsSink::event_dropped`](crate::stats::MetricsSink::event_dropped) 导出。
+//!
+//! ```no_run
+//! use milky_rust_sdk::client::OverflowPolicy;
+//! use milky_rust_sdk::{Communication, MilkyClient, WebSocketConfig};
+//!
+//! # fn run() -> milky_rust_sdk::Result<()> {
+//! let comm = Communication::WebSocket(WebSocketConfig::new("ws://127.0.0.1:3000".to_string(), None));
+//! let (client, events) = MilkyClient::builder(comm)
+//!     .channel_capacity(1024)
+//!     .overflow_policy(OverflowPolicy::DropOldest)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+use crate::runtime;
+
+use milky_types::Event;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// 事件通道写满时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 等待应用取出事件，期间暂停接收事件
+    #[default]
+    Block,
+    /// 丢弃尚未写入通道的最早的事件，保留最新的事件
+    ///
+    /// 通道写满后新的事件会先进入一个与通道容量相同的等待队列，通道有空位时按顺序写入；
+    /// 等待队列也写满时丢弃其中最早的事件。
+    DropOldest,
+    /// 丢弃当前接收到的事件
+    DropNewest,
+}
+
+/// 通道写满后等待写入的事件
+#[derive(Default)]
+pub(crate) struct Backlog {
+    /// 按接收顺序排列的事件
+    events: VecDeque<Event>,
+    /// 是否有任务正在将事件写入通道
+    draining: bool,
+}
+
+impl Backlog {
+    /// 将事件写入通道，通道已满时放入等待队列
+    ///
+    /// # 参数
+    /// * `backlog`: 等待队列
+    /// * `sender`: 事件通道的发送端
+    /// * `event`: 接收到的事件
+    ///
+    /// # 返回
+    /// 等待队列已满时返回被丢弃的最早的事件
+    pub(crate) fn push(
+        backlog: &Arc<Mutex<Self>>,
+        sender: &mpsc::Sender<Event>,
+        event: Event,
+    ) -> Option<Event> {
+        let mut state = backlog.lock().unwrap();
+        // 等待队列不为空时需要排在其后写入，以免打乱事件顺序
+        let event = if state.events.is_empty() {
+            match sender.try_send(event) {
+                Ok(()) => return None,
+                Err(mpsc::error::TrySendError::Full(event)) => event,
+                Err(mpsc::error::TrySendError::Closed(_)) => return None,
+            }
+        } else {
+            event
+        };
+        let dropped = if state.events.len() >= sender.max_capacity() {
+            state.events.pop_front()
+        } else {
+            None
+        };
+        state.events.push_back(event);
+        if !state.draining {
+            state.draining = true;
+            runtime::spawn(Self::drain(Arc::clone(backlog), sender.clone()));
+        }
+        dropped
+    }
+
+    /// 在通道有空位时依次写入等待队列中的事件，直到队列为空
+    async fn drain(backlog: Arc<Mutex<Self>>, sender: mpsc::Sender<Event>) {
+        loop {
+            let permit = sender.reserve().await;
+            let mut state = backlog.lock().unwrap();
+            let Ok(permit) = permit else {
+                state.events.clear();
+                state.draining = false;
+                return;
+            };
+            match state.events.pop_front() {
+                Some(event) => permit.send(event),
+                None => {
+                    state.draining = false;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl MilkyClient {
+    /// 设置事件通道写满时的处理方式，默认为 [`OverflowPolicy::Block`]
+    ///
+    /// # 参数
+    /// * `policy`: 处理方式
+    pub fn with_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        self.event_stats.set_overflow_policy(policy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::events::EventPipelineRecorder;
+    use milky_types::EventKind;
+
+    fn event(time: i64) -> Event {
+        Event {
+            time,
+            self_id: 10000,
+            kind: EventKind::BotOffline {
+                reason: String::new(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let recorder = EventPipelineRecorder::default();
+        recorder.set_overflow_policy(OverflowPolicy::DropNewest);
+        let (tx, mut rx) = mpsc::channel(2);
+        for time in 0..4 {
+            assert!(recorder.forward(&tx, event(time)).await);
+        }
+        assert_eq!(rx.recv().await.unwrap().time, 0);
+        assert_eq!(rx.recv().await.unwrap().time, 1);
+        assert_eq!(recorder.snapshot().dropped_events, 2);
+
+        let recorder = EventPipelineRecorder::default();
+        recorder.set_overflow_policy(OverflowPolicy::DropOldest);
+        let (tx, mut rx) = mpsc::channel(2);
+        for time in 0..6 {
+            assert!(recorder.forward(&tx, event(time)).await);
+        }
+        // 通道中的 0、1 已写入，等待队列只保留最新的 4、5
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(rx.recv().await.unwrap().time);
+        }
+        assert_eq!(received, [0, 1, 4, 5]);
+        assert_eq!(recorder.snapshot().dropped_events, 2);
+    }
+}
@@ -0,0 +1,201 @@
+//! 同时管理多个机器人账号的客户端
+//!
+//! [`ClientPool`] 以账号 QQ 号为键保存多个 [`MilkyClient`]，每个客户端各自连接一个协议端。
+//! [`ClientPool::events`] 将所有账号的事件合并为一个标记了账号的事件流，
+//! [`ClientPool::client`] 按账号取出对应的客户端调用 API，[`ClientPool::route`] 则直接取出接收到某个事件的客户端，
+//! 便于在回复消息时使用同一个账号。
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use milky_rust_sdk::client::ClientPool;
+//! use milky_rust_sdk::{Communication, MilkyClient, WebSocketConfig};
+//!
+//! # async fn run() -> milky_rust_sdk::Result<()> {
+//! let mut pool = ClientPool::new();
+//! for (self_id, url) in [(10001, "ws://127.0.0.1:3001"), (10002, "ws://127.0.0.1:3002")] {
+//!     let comm = Communication::WebSocket(WebSocketConfig::new(url.to_string(), None));
+//!     let (client, _events) = MilkyClient::builder(comm).build()?;
+//!     pool.insert(self_id, client);
+//! }
+//!
+//! let mut events = pool.events();
+//! pool.connect_events().await?;
+//! while let Some((self_id, event)) = events.next().await {
+//!     let client = pool.client(self_id)?;
+//!     println!("账号 {self_id} 收到事件: {event:?}，协议端 {:?}", client.get_impl_info().await?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+
+use futures_util::{Stream, StreamExt};
+use milky_types::Event;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 多个机器人账号的客户端集合
+#[derive(Default, Clone)]
+pub struct ClientPool {
+    /// 以账号 QQ 号为键的客户端
+    clients: BTreeMap<i64, Arc<MilkyClient>>,
+}
+
+impl ClientPool {
+    /// 创建空的客户端集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入账号对应的客户端
+    ///
+    /// # 参数
+    /// * `self_id`: 账号 QQ 号
+    /// * `client`: 连接该账号协议端的客户端
+    ///
+    /// # 返回
+    /// 账号已存在时返回被替换的客户端
+    pub fn insert(
+        &mut self,
+        self_id: i64,
+        client: impl Into<Arc<MilkyClient>>,
+    ) -> Option<Arc<MilkyClient>> {
+        self.clients.insert(self_id, client.into())
+    }
+
+    /// 通过 [`get_login_info`](MilkyClient::get_login_info) 查询账号后加入客户端
+    ///
+    /// # 返回
+    /// 成功则返回账号 QQ 号，查询失败时返回错误
+    pub async fn insert_by_login(&mut self, client: impl Into<Arc<MilkyClient>>) -> Result<i64> {
+        let client = client.into();
+        let self_id = client.get_login_info().await?.uin;
+        self.clients.insert(self_id, client);
+        Ok(self_id)
+    }
+
+    /// 移除账号对应的客户端
+    ///
+    /// 不会关闭客户端的事件接收，需要时请先调用 [`MilkyClient::shutdown`]。
+    pub fn remove(&mut self, self_id: i64) -> Option<Arc<MilkyClient>> {
+        self.clients.remove(&self_id)
+    }
+
+    /// 获取账号对应的客户端
+    pub fn get(&self, self_id: i64) -> Option<&Arc<MilkyClient>> {
+        self.clients.get(&self_id)
+    }
+
+    /// 获取账号对应的客户端，用于以该账号调用 API
+    ///
+    /// # 返回
+    /// 成功则返回客户端；集合中没有该账号时返回 [`MilkyError::AccountNotFound`]
+    pub fn client(&self, self_id: i64) -> Result<&Arc<MilkyClient>> {
+        self.get(self_id)
+            .ok_or(MilkyError::AccountNotFound(self_id))
+    }
+
+    /// 获取接收到事件的账号对应的客户端
+    ///
+    /// # 参数
+    /// * `event`: 接收到的事件
+    pub fn route(&self, event: &Event) -> Result<&Arc<MilkyClient>> {
+        self.client(event.self_id)
+    }
+
+    /// 集合中所有账号的 QQ 号
+    pub fn self_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.clients.keys().copied()
+    }
+
+    /// 集合中的账号数量
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// 集合是否为空
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// 依次启动所有客户端的事件接收
+    ///
+    /// # 返回
+    /// 全部启动成功则返回 `Ok(())`，否则返回第一个失败的错误，此前已启动的客户端不会被关闭
+    pub async fn connect_events(&self) -> Result<()> {
+        for client in self.clients.values() {
+            client.connect_events().await?;
+        }
+        Ok(())
+    }
+
+    /// 关闭所有客户端的事件接收
+    pub async fn shutdown(&self) {
+        for client in self.clients.values() {
+            client.shutdown().await;
+        }
+    }
+
+    /// 订阅所有账号之后接收到的事件，合并为一个带有账号 QQ 号的事件流
+    ///
+    /// 只包含订阅时已加入集合的客户端，所有客户端都被销毁后流随之结束。
+    pub fn events(&self) -> impl Stream<Item = (i64, Event)> + Send + 'static {
+        futures_util::stream::select_all(self.clients.iter().map(|(&self_id, client)| {
+            client
+                .event_stream()
+                .map(move |event| (self_id, event))
+                .boxed()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::events::EventPipelineRecorder;
+    use crate::test_util::{self, OFFLINE};
+    use milky_types::EventKind;
+
+    #[tokio::test]
+    async fn test_tagged_events_and_routing() {
+        let mut pool = ClientPool::new();
+        let mut senders = Vec::new();
+        for self_id in [10001, 10002] {
+            let (client, tx, _rx) = test_util::client_with_sender(OFFLINE);
+            let client = Arc::new(client);
+            senders.push((self_id, tx, Arc::clone(&client)));
+            pool.insert(self_id, client);
+        }
+        let events = pool.events();
+
+        let stats = EventPipelineRecorder::default();
+        for (self_id, tx, client) in &senders {
+            let event = Event {
+                time: 1,
+                self_id: *self_id,
+                kind: EventKind::BotOffline {
+                    reason: String::new(),
+                },
+            };
+            MilkyClient::dispatch_event(tx, &client.event_broadcast, &stats, event).await;
+        }
+        drop(senders);
+        let routed: Vec<_> = events
+            .take(2)
+            .map(|(self_id, event)| {
+                assert_eq!(self_id, event.self_id);
+                Arc::ptr_eq(pool.route(&event).unwrap(), pool.get(self_id).unwrap())
+            })
+            .collect()
+            .await;
+        assert_eq!(routed, [true, true]);
+
+        assert_eq!(pool.self_ids().collect::<Vec<_>>(), [10001, 10002]);
+        assert!(matches!(
+            pool.client(10003),
+            Err(MilkyError::AccountNotFound(10003))
+        ));
+    }
+}
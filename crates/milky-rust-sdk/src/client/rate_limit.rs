@@ -0,0 +1,251 @@
+//! 客户端侧的 API 调用限速
+//!
+//! 短时间内大量调用 `send_group_message`、`send_private_message` 等接口容易触发平台风控。
+//! 通过 [`MilkyClient::with_rate_limiter`] 或
+//! [`MilkyClientBuilder::rate_limiter`](crate::MilkyClientBuilder::rate_limiter) 设置 [`RateLimiter`] 后，
+//! 每次 API 调用（包括自动重试）发送前都会按令牌桶算法等待，直到全局与该操作的限额都有剩余。
+//!
+//! ```
+//! use milky_rust_sdk::client::{RateLimit, RateLimiter};
+//! use std::time::Duration;
+//!
+//! let limiter = RateLimiter::new()
+//!     .global(RateLimit::per_second(20))
+//!     .action("send_group_message", RateLimit::new(5, Duration::from_secs(3)).burst(2));
+//! ```
+
+use crate::client::MilkyClient;
+use crate::runtime;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 单个令牌桶的限额
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// 每秒补充的令牌数
+    rate: f64,
+    /// 令牌桶的容量，即允许的突发调用次数
+    burst: f64,
+}
+
+impl RateLimit {
+    /// 在 `period` 时长内最多允许 `permits` 次调用，默认允许一次性用完全部 `permits` 次
+    ///
+    /// # 参数
+    /// * `permits`: 时长内允许的调用次数
+    /// * `period`: 统计的时长
+    pub fn new(permits: u32, period: Duration) -> Self {
+        let permits = f64::from(permits.max(1));
+        Self {
+            rate: permits / period.as_secs_f64().max(f64::EPSILON),
+            burst: permits,
+        }
+    }
+
+    /// 每秒最多允许 `permits` 次调用
+    pub fn per_second(permits: u32) -> Self {
+        Self::new(permits, Duration::from_secs(1))
+    }
+
+    /// 设置允许的突发调用次数，即令牌桶的容量
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = f64::from(burst.max(1));
+        self
+    }
+}
+
+/// 令牌桶的当前状态
+struct TokenBucket {
+    /// 限额
+    limit: RateLimit,
+    /// 剩余的令牌数
+    tokens: f64,
+    /// 上一次补充令牌的时间
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// 创建一个装满令牌的令牌桶
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// 尝试取出一个令牌
+    ///
+    /// # 返回
+    /// 取出成功返回 `None`，否则返回还需等待的时长
+    fn try_acquire(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate).min(self.limit.burst);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.limit.rate,
+            ))
+        }
+    }
+}
+
+/// 全局与按操作的 API 调用限速器
+///
+/// 创建后仍可以通过 [`set_global`](Self::set_global) 与 [`set_action`](Self::set_action) 调整限额，
+/// 例如由 [`SharedConfig`](crate::config::SharedConfig) 在重新加载配置时更新。
+#[derive(Default)]
+pub struct RateLimiter {
+    /// 所有调用共享的令牌桶
+    global: Mutex<Option<TokenBucket>>,
+    /// 各操作独立的令牌桶
+    actions: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// 创建一个不做任何限制的限速器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置所有 API 调用共享的限额
+    pub fn global(self, limit: RateLimit) -> Self {
+        self.set_global(Some(limit));
+        self
+    }
+
+    /// 设置指定 API 操作的限额，例如 `send_group_message`
+    pub fn action(self, action: impl Into<String>, limit: RateLimit) -> Self {
+        self.set_action(action, Some(limit));
+        self
+    }
+
+    /// 修改所有 API 调用共享的限额，限额不变时保留当前的令牌数
+    ///
+    /// # 参数
+    /// * `limit`: 新的限额，为 `None` 时取消全局限速
+    pub fn set_global(&self, limit: Option<RateLimit>) {
+        update_bucket(&mut self.global.lock().unwrap(), limit);
+    }
+
+    /// 修改指定 API 操作的限额，限额不变时保留当前的令牌数
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `limit`: 新的限额，为 `None` 时取消该操作的限速
+    pub fn set_action(&self, action: impl Into<String>, limit: Option<RateLimit>) {
+        let mut actions = self.actions.lock().unwrap();
+        let action = action.into();
+        let mut bucket = actions.remove(&action);
+        update_bucket(&mut bucket, limit);
+        if let Some(bucket) = bucket {
+            actions.insert(action, bucket);
+        }
+    }
+
+    /// 等待直到可以发送指定操作的调用
+    ///
+    /// 先等待该操作的限额，再等待全局限额，避免被某个操作阻塞时占用全局令牌。
+    pub(crate) async fn acquire(&self, action: &str) {
+        loop {
+            let wait = self
+                .actions
+                .lock()
+                .unwrap()
+                .get_mut(action)
+                .and_then(|bucket| bucket.try_acquire(Instant::now()));
+            match wait {
+                Some(wait) => runtime::sleep(wait).await,
+                None => break,
+            }
+        }
+        loop {
+            let wait = self
+                .global
+                .lock()
+                .unwrap()
+                .as_mut()
+                .and_then(|bucket| bucket.try_acquire(Instant::now()));
+            match wait {
+                Some(wait) => runtime::sleep(wait).await,
+                None => break,
+            }
+        }
+    }
+}
+
+/// 以新的限额替换令牌桶，限额未变化时保留原有的令牌桶
+fn update_bucket(bucket: &mut Option<TokenBucket>, limit: Option<RateLimit>) {
+    match limit {
+        Some(limit) if bucket.as_ref().is_some_and(|b| b.limit == limit) => {}
+        Some(limit) => *bucket = Some(TokenBucket::new(limit)),
+        None => *bucket = None,
+    }
+}
+
+impl MilkyClient {
+    /// 设置 API 调用的限速器
+    ///
+    /// # 参数
+    /// * `limiter`: 限速器，传入 `Arc<RateLimiter>` 时可以在客户端创建后继续调整限额
+    pub fn with_rate_limiter(mut self, limiter: impl Into<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = Some(limiter.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(RateLimit::new(2, Duration::from_secs(1)).burst(2));
+        let start = bucket.refilled_at;
+        assert_eq!(bucket.try_acquire(start), None);
+        assert_eq!(bucket.try_acquire(start), None);
+        assert_eq!(bucket.try_acquire(start), Some(Duration::from_millis(500)));
+        assert_eq!(bucket.try_acquire(start + Duration::from_millis(500)), None);
+        assert!(
+            bucket
+                .try_acquire(start + Duration::from_millis(600))
+                .is_some()
+        );
+        assert_eq!(bucket.try_acquire(start + Duration::from_secs(10)), None);
+        assert_eq!(bucket.try_acquire(start + Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn test_update_limits() {
+        let limit = RateLimit::per_second(1);
+        let limiter = RateLimiter::new().action("send_group_message", limit);
+        let now = Instant::now();
+        let try_acquire = |now| {
+            limiter
+                .actions
+                .lock()
+                .unwrap()
+                .get_mut("send_group_message")
+                .and_then(|bucket| bucket.try_acquire(now))
+        };
+        assert_eq!(try_acquire(now), None);
+        assert!(try_acquire(now).is_some());
+
+        // 限额不变时保留令牌桶的状态
+        limiter.set_action("send_group_message", Some(limit));
+        assert!(try_acquire(now).is_some());
+
+        limiter.set_action("send_group_message", Some(RateLimit::per_second(5)));
+        assert_eq!(try_acquire(now), None);
+        limiter.set_action("send_group_message", None);
+        assert_eq!(try_acquire(now), None);
+        assert!(limiter.actions.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! WebSocket 事件连接断开后的自动重连策略
+//!
+//! 默认情况下，事件 WebSocket 连接被服务端关闭或出错后，事件读取循环会直接结束。
+//! 通过 [`MilkyClient::with_reconnect_policy`] 设置重连策略后，读取循环会按指数退避重新建立连接，
+//! 并继续向事件通道投递事件。
+
+use crate::client::MilkyClient;
+use crate::client::retry::backoff_delay;
+
+use std::time::Duration;
+
+/// WebSocket 事件连接的重连策略
+///
+/// 第 `n` 次重连前等待 `initial_backoff * 2^(n-1)`，不超过 `max_backoff`，
+/// 并在此基础上加入随机抖动，避免大量客户端在服务端重启后同时重连。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// 连续重连失败的次数上限，为 `None` 时不限次数
+    max_attempts: Option<u32>,
+    /// 第一次重连前的等待时间
+    initial_backoff: Duration,
+    /// 重连等待时间的上限
+    max_backoff: Duration,
+    /// 随机抖动的比例，取值 0 到 1
+    jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 创建一个不限重连次数的策略，从 1 秒开始退避，最长等待 60 秒，抖动比例为 20%
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置连续重连失败的次数上限，超过后放弃重连并结束事件读取循环
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// 设置第一次重连前的等待时间
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// 设置重连等待时间的上限
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// 设置随机抖动的比例，例如 `0.2` 表示实际等待时间在计算值的 80% 到 120% 之间
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 判断已经连续失败 `failures` 次后是否还应继续重连
+    pub(crate) fn should_retry(&self, failures: u32) -> bool {
+        self.max_attempts.is_none_or(|max| failures < max)
+    }
+
+    /// 计算第 `attempt` 次重连（从 1 开始）前的等待时间
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        backoff_delay(self.initial_backoff, self.max_backoff, self.jitter, attempt)
+    }
+}
+
+impl MilkyClient {
+    /// 设置事件 WebSocket 连接断开后的自动重连策略
+    ///
+    /// # 参数
+    /// * `policy`: 重连策略
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = ReconnectPolicy::new()
+            .max_attempts(3)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500))
+            .jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(100), Duration::from_millis(500));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+
+        let policy = policy.jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(300));
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! API 调用失败后的自动重试策略
+//!
+//! 通过 [`MilkyClient::with_retry_policy`] 设置重试策略后，[`MilkyClient::send_request`] 会在临时性失败
+//! （即 [`MilkyError::is_retryable`] 为真的错误，例如连接失败、请求超时、服务端 5xx 与频率限制，以及指定的返回码）时按指数退避自动重试。
+//!
+//! 为避免重复发送消息，默认只重试 `get_` 开头的查询类操作；其他操作需要通过 [`RetryPolicy::action`] 显式加入，
+//! 也可以通过 [`RetryPolicy::skip_action`] 排除某个查询操作，或通过 [`RequestOptions::no_retry`] 关闭单次调用的重试。
+//!
+//! [`RequestOptions::no_retry`]: crate::client::RequestOptions::no_retry
+
+use crate::client::MilkyClient;
+use crate::error::MilkyError;
+
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// API 调用的重试策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最多重试的次数
+    max_retries: u32,
+    /// 第一次重试前的等待时间
+    initial_backoff: Duration,
+    /// 重试等待时间的上限
+    max_backoff: Duration,
+    /// 随机抖动的比例，取值 0 到 1
+    jitter: f64,
+    /// 额外视为临时性失败的返回码
+    retcodes: HashSet<i64>,
+    /// 额外允许重试的操作
+    actions: HashSet<String>,
+    /// 不重试的操作
+    skipped_actions: HashSet<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.2,
+            retcodes: HashSet::new(),
+            actions: HashSet::new(),
+            skipped_actions: HashSet::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 创建一个最多重试 3 次、从 200 毫秒开始退避、最长等待 5 秒的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置最多重试的次数
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// 设置第一次重试前的等待时间
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// 设置重试等待时间的上限
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// 设置随机抖动的比例，例如 `0.2` 表示实际等待时间在计算值的 80% 到 120% 之间
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 将服务端返回的指定返回码视为临时性失败
+    pub fn retcode(mut self, retcode: i64) -> Self {
+        self.retcodes.insert(retcode);
+        self
+    }
+
+    /// 允许重试指定的操作，例如确认可以安全重复调用的 `set_group_name`
+    pub fn action(mut self, action: impl Into<String>) -> Self {
+        self.actions.insert(action.into());
+        self
+    }
+
+    /// 不重试指定的操作
+    pub fn skip_action(mut self, action: impl Into<String>) -> Self {
+        self.skipped_actions.insert(action.into());
+        self
+    }
+
+    /// 判断已经重试 `retries` 次后，是否应该再次重试失败的调用
+    pub(crate) fn should_retry(&self, action: &str, error: &MilkyError, retries: u32) -> bool {
+        if retries >= self.max_retries || self.skipped_actions.contains(action) {
+            return false;
+        }
+        if !action.starts_with("get_") && !self.actions.contains(action) {
+            return false;
+        }
+        match error {
+            MilkyError::ApiError {
+                retcode: Some(retcode),
+                ..
+            } if self.retcodes.contains(retcode) => true,
+            _ => error.is_retryable(),
+        }
+    }
+
+    /// 计算第 `attempt` 次重试（从 1 开始）前的等待时间
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        backoff_delay(self.initial_backoff, self.max_backoff, self.jitter, attempt)
+    }
+}
+
+/// 计算第 `attempt` 次（从 1 开始）尝试前的指数退避等待时间
+///
+/// 等待时间为 `initial * 2^(attempt-1)`，不超过 `max`，并按 `jitter` 比例随机浮动。
+pub(crate) fn backoff_delay(
+    initial: Duration,
+    max: Duration,
+    jitter: f64,
+    attempt: u32,
+) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(31);
+    let base = initial.saturating_mul(1 << exponent).min(max);
+    if jitter == 0.0 {
+        return base;
+    }
+    let factor = 1.0 - jitter + 2.0 * jitter * random_unit();
+    base.mul_f64(factor)
+}
+
+/// 生成一个 `[0, 1)` 区间内的随机数
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+impl MilkyClient {
+    /// 设置 API 调用失败后的自动重试策略
+    ///
+    /// # 参数
+    /// * `policy`: 重试策略
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::new()
+            .max_retries(2)
+            .retcode(-500)
+            .action("set_group_name")
+            .skip_action("get_history_messages");
+
+        assert!(policy.should_retry("get_group_info", &MilkyError::Timeout, 0));
+        assert!(!policy.should_retry("get_group_info", &MilkyError::Timeout, 2));
+        assert!(policy.should_retry("set_group_name", &MilkyError::Timeout, 0));
+        assert!(!policy.should_retry("send_group_message", &MilkyError::Timeout, 0));
+        assert!(!policy.should_retry("get_history_messages", &MilkyError::Timeout, 0));
+        assert!(!policy.should_retry("get_group_info", &MilkyError::Cancelled, 0));
+
+        let api_error = |retcode| MilkyError::api_error("get_group_info", "", Some(retcode));
+        assert!(policy.should_retry("get_group_info", &api_error(-500), 0));
+        assert!(!policy.should_retry("get_group_info", &api_error(-404), 0));
+
+        let policy = policy
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(5), Duration::from_millis(300));
+    }
+}
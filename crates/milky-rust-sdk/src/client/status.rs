@@ -0,0 +1,98 @@
+//! 事件 WebSocket 连接的状态通知
+//!
+//! 事件 WebSocket 连接建立、断开或开始重连时，读取循环会向 [`MilkyClient::connection_status`]
+//! 返回的接收端发送一条 [`ConnectionStatus`]，应用可以据此暂停发送消息、提示用户或记录指标，
+//! 而不必只从日志中得知连接已断开。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::client::ConnectionStatus;
+//!
+//! # async fn run(client: MilkyClient) {
+//! let mut status = client.connection_status();
+//! client.connect_events().await.unwrap();
+//! while let Ok(status) = status.recv().await {
+//!     if let ConnectionStatus::Disconnected { reason } = status {
+//!         eprintln!("事件连接已断开: {reason}");
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+
+use tokio::sync::broadcast;
+
+/// 每个接收端缓存的状态通知数量
+pub(crate) const STATUS_CHANNEL_CAPACITY: usize = 16;
+
+/// 事件 WebSocket 连接的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// 连接已建立（包括重连成功）
+    Connected,
+    /// 连接已断开
+    Disconnected {
+        /// 断开的原因
+        reason: String,
+    },
+    /// 即将进行重连
+    Reconnecting {
+        /// 本次是连续第几次重连，从 1 开始
+        attempt: u32,
+    },
+    /// 已放弃重连，改为通过 WebHook 接收事件，参见 [`WebhookFailover`](crate::client::WebhookFailover)
+    FailedOver,
+    /// 切换为 WebHook 接收事件失败，将继续按重连策略重连事件 WebSocket
+    FailoverFailed {
+        /// 失败的原因
+        reason: String,
+    },
+}
+
+impl MilkyClient {
+    /// 订阅之后的事件 WebSocket 连接状态变化
+    ///
+    /// # 返回
+    /// 独立的状态接收端，每个接收端都会收到完整的状态通知
+    pub fn connection_status(&self) -> broadcast::Receiver<ConnectionStatus> {
+        self.status_sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ReconnectPolicy;
+    use crate::test_util::{self, WsServer};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_connection_status() {
+        let server = WsServer::bind().await;
+        let (client, _rx) = test_util::client(server.addr());
+        tokio::spawn(async move {
+            // 接受第一个连接后立即关闭，之后不再接受连接
+            drop(server.accept().await);
+        });
+
+        let client = client.with_reconnect_policy(
+            ReconnectPolicy::new()
+                .max_attempts(1)
+                .initial_backoff(Duration::from_millis(10))
+                .jitter(0.0),
+        );
+        let mut status = client.connection_status();
+        client.connect_events().await.unwrap();
+
+        assert_eq!(status.recv().await.unwrap(), ConnectionStatus::Connected);
+        assert!(matches!(
+            status.recv().await.unwrap(),
+            ConnectionStatus::Disconnected { .. }
+        ));
+        assert_eq!(
+            status.recv().await.unwrap(),
+            ConnectionStatus::Reconnecting { attempt: 1 }
+        );
+    }
+}
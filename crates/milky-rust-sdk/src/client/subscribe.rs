@@ -0,0 +1,290 @@
+//! 向多个订阅者广播事件
+//!
+//! 创建客户端时传入的事件通道只能有一个接收端。需要让多个相互独立的任务（例如日志记录、命令处理与指标统计）
+//! 都收到完整的事件流时，可以分别调用 [`MilkyClient::subscribe`] 获取各自的接收端。
+//!
+//! 每个订阅者最多缓存 [`with_broadcast_capacity`](MilkyClient::with_broadcast_capacity) 个尚未取出的事件，
+//! 处理过慢的订阅者会丢弃最旧的事件并收到 [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged)，
+//! 不会影响其他订阅者。事件仍会同时写入原有的事件通道，只使用订阅时应及时取出或直接丢弃该通道的接收端，
+//! 以免通道写满后阻塞事件接收。
+//!
+//! [`MilkyClient::event_stream`] 以 [`Stream`] 的形式提供订阅，便于使用 `filter`、`take_while` 等组合子：
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! use futures_util::StreamExt;
+//! use milky_types::EventKind;
+//!
+//! # async fn run(client: MilkyClient) {
+//! let mut messages = client
+//!     .event_stream()
+//!     .filter(|event| std::future::ready(matches!(event.kind, EventKind::MessageReceive { .. })))
+//!     .boxed();
+//! while let Some(event) = messages.next().await {
+//!     println!("收到消息: {event:?}");
+//! }
+//! # }
+//! ```
+//!
+//! 只需要等待某一个事件（例如用户对提问的回复）时，可以使用 [`MilkyClient::wait_for`]。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+use crate::logger::{error, warn};
+use crate::runtime;
+use crate::stats::events::EventPipelineRecorder;
+
+use futures_util::Stream;
+use milky_types::Event;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc};
+
+/// 默认每个订阅者缓存的事件数量
+pub(crate) const DEFAULT_BROADCAST_CAPACITY: usize = 128;
+
+impl MilkyClient {
+    /// 订阅之后接收到的所有事件
+    ///
+    /// # 返回
+    /// 独立的事件接收端，每个接收端都会收到完整的事件流
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.event_broadcast.subscribe()
+    }
+
+    /// 以 [`Stream`] 的形式订阅之后接收到的所有事件
+    ///
+    /// 处理过慢而被丢弃的事件会被跳过并输出警告，客户端被销毁后流随之结束。
+    pub fn event_stream(&self) -> impl Stream<Item = Event> + Send + 'static {
+        futures_util::stream::unfold(self.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(n)) => warn!("事件流处理过慢，已丢弃 {n} 个事件"),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// 等待下一个满足条件的事件
+    ///
+    /// 只会收到调用之后接收到的事件。
+    ///
+    /// # 参数
+    /// * `timeout`: 最长等待时间
+    /// * `predicate`: 判断事件是否为所等待的事件
+    ///
+    /// # 返回
+    /// 满足条件的事件；超时返回 [`MilkyError::Timeout`]，客户端被销毁时返回 [`MilkyError::Disconnected`]
+    pub async fn wait_for<F>(&self, timeout: Duration, predicate: F) -> Result<Event>
+    where
+        F: FnMut(&Event) -> bool,
+    {
+        recv_matching(&mut self.subscribe(), timeout, predicate).await
+    }
+
+    /// 设置每个订阅者缓存的事件数量，默认为 128
+    ///
+    /// 会替换内部的广播通道，需要在调用 [`subscribe`](Self::subscribe) 之前设置。
+    pub fn with_broadcast_capacity(mut self, capacity: usize) -> Self {
+        self.event_broadcast = broadcast::channel(capacity.max(1)).0;
+        self
+    }
+
+    /// 将事件广播给所有订阅者并写入事件通道
+    ///
+    /// # 参数
+    /// * `event_sender`: 事件通道的发送端
+    /// * `event_broadcast`: 广播通道的发送端
+    /// * `event_stats`: 事件管线的统计器
+    /// * `event`: 接收到的事件
+    pub(crate) async fn dispatch_event(
+        event_sender: &mpsc::Sender<Event>,
+        event_broadcast: &broadcast::Sender<Event>,
+        event_stats: &EventPipelineRecorder,
+        event: Event,
+    ) {
+        #[cfg(feature = "tracing")]
+        let span = event_span(&event);
+        let dispatch = async move {
+            let subscribed =
+                event_broadcast.receiver_count() > 0 && event_broadcast.send(event.clone()).is_ok();
+            if !event_stats.forward(event_sender, event).await && !subscribed {
+                error!("事件接收端已关闭，无法发送事件");
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let dispatch = tracing::Instrument::instrument(dispatch, span);
+        dispatch.await
+    }
+}
+
+/// 从订阅中取出下一个满足条件的事件，参见 [`MilkyClient::wait_for`]
+pub(crate) async fn recv_matching<F>(
+    receiver: &mut broadcast::Receiver<Event>,
+    timeout: Duration,
+    mut predicate: F,
+) -> Result<Event>
+where
+    F: FnMut(&Event) -> bool,
+{
+    let wait = async {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if predicate(&event) => return Ok(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(n)) => warn!("等待事件时处理过慢，已丢弃 {n} 个事件"),
+                Err(RecvError::Closed) => {
+                    return Err(MilkyError::Disconnected("事件广播通道已关闭".to_string()));
+                }
+            }
+        }
+    };
+    runtime::timeout(timeout, wait)
+        .await
+        .map_err(|_| MilkyError::Timeout)?
+}
+
+/// 创建分发事件的 span，记录事件类型以及消息所在的会话与序列号
+#[cfg(feature = "tracing")]
+fn event_span(event: &Event) -> tracing::Span {
+    use milky_types::EventKind;
+
+    let (peer_id, message_seq) = match &event.kind {
+        EventKind::MessageReceive { message } => {
+            let message = message.base_message();
+            (Some(message.peer_id), Some(message.message_seq))
+        }
+        EventKind::MessageRecall {
+            peer_id,
+            message_seq,
+            ..
+        } => (Some(*peer_id), Some(*message_seq)),
+        _ => (None, None),
+    };
+    tracing::info_span!(
+        "event",
+        event_type = event.kind.event_type(),
+        self_id = event.self_id,
+        peer_id,
+        message_seq,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+    use milky_types::EventKind;
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let (client, tx, rx) = test_util::client_with_sender(OFFLINE);
+        let client = client.with_broadcast_capacity(1);
+        let mut logger = client.subscribe();
+        let mut handler = client.subscribe();
+        drop(rx);
+
+        let event = |time| Event {
+            time,
+            self_id: 10000,
+            kind: EventKind::BotOffline {
+                reason: String::new(),
+            },
+        };
+        let stats = EventPipelineRecorder::default();
+        MilkyClient::dispatch_event(&tx, &client.event_broadcast, &stats, event(1)).await;
+        assert_eq!(handler.recv().await.unwrap().time, 1);
+
+        MilkyClient::dispatch_event(&tx, &client.event_broadcast, &stats, event(2)).await;
+        assert_eq!(handler.recv().await.unwrap().time, 2);
+        assert!(matches!(logger.recv().await, Err(RecvError::Lagged(1))));
+        assert_eq!(logger.recv().await.unwrap().time, 2);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        use futures_util::StreamExt;
+
+        let (client, tx, _rx) = test_util::client_with_sender(OFFLINE);
+        let stream = client
+            .event_stream()
+            .filter(|event| std::future::ready(event.time % 2 == 0));
+
+        let stats = EventPipelineRecorder::default();
+        for time in 1..=4 {
+            let event = Event {
+                time,
+                self_id: 10000,
+                kind: EventKind::BotOffline {
+                    reason: String::new(),
+                },
+            };
+            MilkyClient::dispatch_event(&tx, &client.event_broadcast, &stats, event).await;
+        }
+        drop(client);
+        let times: Vec<_> = stream.map(|event| event.time).collect().await;
+        assert_eq!(times, [2, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for() {
+        let (client, tx, _rx) = test_util::client_with_sender(OFFLINE);
+        let stats = EventPipelineRecorder::default();
+        let dispatch = async {
+            for time in 1..=3 {
+                let event = Event {
+                    time,
+                    self_id: 10000,
+                    kind: EventKind::BotOffline {
+                        reason: String::new(),
+                    },
+                };
+                MilkyClient::dispatch_event(&tx, &client.event_broadcast, &stats, event).await;
+            }
+        };
+        // `join!` 先轮询等待的一方，保证分发事件前已经订阅
+        let (event, ()) = tokio::join!(
+            client.wait_for(Duration::from_secs(5), |event| event.time == 2),
+            dispatch
+        );
+        assert_eq!(event.unwrap().time, 2);
+
+        assert!(matches!(
+            client.wait_for(Duration::from_millis(10), |_| true).await,
+            Err(MilkyError::Timeout)
+        ));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_event_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = test_util::SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let (client, tx, _rx) = test_util::client_with_sender(OFFLINE);
+        let event = Event {
+            time: 1,
+            self_id: 10000,
+            kind: EventKind::MessageRecall {
+                message_scene: milky_types::common::MessageScene::Group,
+                peer_id: 123,
+                message_seq: 45,
+                sender_id: 20000,
+                operator_id: 20000,
+                display_suffix: String::new(),
+            },
+        };
+        let stats = EventPipelineRecorder::default();
+        MilkyClient::dispatch_event(&tx, &client.event_broadcast, &stats, event).await;
+
+        let fields = recorder.fields("event");
+        assert_eq!(fields["event_type"], "message_recall");
+        assert_eq!(fields["self_id"], "10000");
+        assert_eq!(fields["peer_id"], "123");
+        assert_eq!(fields["message_seq"], "45");
+    }
+}
@@ -0,0 +1,139 @@
+//! 后台事件接收任务的句柄
+//!
+//! [`MilkyClient::connect_events`] 在后台启动事件读取循环或 WebHook 服务器，并返回 [`EventTask`]。
+//! 通过它可以等待事件接收结束并得知结束的原因、查看最近一次连接断开或服务器出错的原因，
+//! 或者直接中止任务。丢弃句柄不会影响后台任务，此时仍可通过 [`MilkyClient::shutdown`] 关闭事件接收。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! # async fn run(client: MilkyClient) {
+//! let task = client.connect_events().await.expect("无法连接事件流");
+//! if let Err(e) = task.await {
+//!     eprintln!("事件接收意外结束: {e}");
+//! }
+//! # }
+//! ```
+
+use crate::client::CancellationToken;
+use crate::error::{MilkyError, Result};
+use crate::runtime::JoinHandle;
+
+use futures_util::future::BoxFuture;
+use std::future::IntoFuture;
+use std::sync::{Arc, Mutex};
+
+/// 最近一次事件接收出错的原因，由后台任务写入
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub(crate) type LastError = Arc<Mutex<Option<String>>>;
+
+/// 后台事件接收任务的句柄
+#[derive(Debug)]
+pub struct EventTask {
+    /// 后台任务
+    handle: JoinHandle<()>,
+    /// 本次事件接收的取消令牌
+    token: CancellationToken,
+    /// 最近一次出错的原因
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl EventTask {
+    /// 创建句柄
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub(crate) fn new(
+        handle: JoinHandle<()>,
+        token: CancellationToken,
+        last_error: LastError,
+    ) -> Self {
+        Self {
+            handle,
+            token,
+            last_error,
+        }
+    }
+
+    /// 后台任务是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// 最近一次连接断开、重连失败或服务器出错的原因
+    ///
+    /// 连接断开后成功重连时仍保留断开的原因，便于排查连接不稳定的问题。
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    /// 关闭本次事件接收，效果与 [`MilkyClient::shutdown`](crate::MilkyClient::shutdown) 相同
+    ///
+    /// WebSocket 连接会发送 Close 帧后关闭，WebHook 服务器会等待进行中的请求完成后关闭。
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// 立即中止后台任务，不会发送 Close 帧或等待进行中的请求
+    pub fn abort(&self) {
+        self.token.cancel();
+        self.handle.abort();
+    }
+
+    /// 等待后台任务结束
+    ///
+    /// # 返回
+    /// 通过取消令牌或 [`shutdown`](crate::MilkyClient::shutdown) 关闭、或 WebHook 服务器随进程信号正常退出时返回 `Ok(())`；
+    /// 被 [`abort`](Self::abort) 中止时返回 [`MilkyError::Cancelled`]；
+    /// 连接断开且不再重连、或服务器出错时返回 [`MilkyError::Disconnected`]
+    pub async fn join(self) -> Result<()> {
+        match self.handle.await {
+            Ok(()) => {}
+            Err(e) if e.is_cancelled() => return Err(MilkyError::Cancelled),
+            Err(e) => return Err(MilkyError::Internal(format!("事件接收任务异常退出: {e}"))),
+        }
+        if self.token.is_cancelled() {
+            return Ok(());
+        }
+        match self.last_error.lock().unwrap().take() {
+            Some(reason) => Err(MilkyError::Disconnected(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl IntoFuture for EventTask {
+    type Output = Result<()>;
+    type IntoFuture = BoxFuture<'static, Result<()>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.join())
+    }
+}
+
+#[cfg(all(test, feature = "websocket"))]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, WsServer};
+
+    #[tokio::test]
+    async fn test_task_reports_disconnect() {
+        let server = WsServer::bind().await;
+        let (client, _rx) = test_util::client(server.addr());
+        tokio::spawn(async move {
+            drop(server.accept().await);
+            // 第二个连接保持打开，直到被客户端关闭
+            let mut socket = server.accept().await;
+            while let Some(Ok(_)) = futures_util::StreamExt::next(&mut socket).await {}
+        });
+
+        let task = client.connect_events().await.unwrap();
+        let result = task.await;
+        assert!(
+            matches!(result, Err(MilkyError::Disconnected(_))),
+            "{result:?}"
+        );
+
+        let task = client.connect_events().await.unwrap();
+        assert!(!task.is_finished());
+        task.cancel();
+        assert!(task.await.is_ok());
+    }
+}
@@ -0,0 +1,305 @@
+//! 自定义 TLS 设置
+//!
+//! 连接使用自签名证书或私有 CA 签发证书的 `https://`、`wss://` 服务端时，可以通过 [`TlsConfig`]
+//! 添加额外信任的根证书，开发环境中也可以完全跳过证书校验。
+//! HTTP API 请求与事件 WebSocket 连接分别通过 [`MilkyClient::with_api_tls`] 与
+//! [`MilkyClient::with_event_tls`] 设置，二者互不影响。
+//!
+//! 需要启用 `native-tls` 或 `rustls` 特性，二者同时启用时使用 `native-tls`。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+
+#[cfg(all(feature = "websocket", feature = "rustls", not(feature = "native-tls")))]
+use std::sync::Arc;
+
+/// 事件 WebSocket 连接使用的 TLS 连接器，未启用 TLS 特性时无法创建
+#[cfg(all(feature = "websocket", any(feature = "native-tls", feature = "rustls")))]
+pub(crate) type Connector = tokio_tungstenite::Connector;
+/// 事件 WebSocket 连接使用的 TLS 连接器，未启用 TLS 特性时无法创建
+#[cfg(all(
+    feature = "websocket",
+    not(any(feature = "native-tls", feature = "rustls"))
+))]
+#[derive(Clone)]
+pub(crate) enum Connector {}
+
+/// PEM 格式证书的结束标记
+#[cfg(all(feature = "websocket", feature = "native-tls"))]
+const PEM_END_MARKER: &[u8] = b"-----END CERTIFICATE-----";
+
+/// TLS 连接的设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// 额外信任的 PEM 格式根证书，每一项可以包含多张证书
+    root_certificates: Vec<Vec<u8>>,
+    /// 是否跳过服务端证书的校验
+    accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    /// 创建一个使用系统（或内置）根证书的设置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 额外信任 PEM 格式的根证书，例如私有 CA 的证书或服务端的自签名证书
+    ///
+    /// # 参数
+    /// * `pem`: PEM 格式的证书内容，可以包含多张证书
+    pub fn add_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// 是否跳过服务端证书的校验
+    ///
+    /// 跳过校验后连接将无法抵御中间人攻击，只应在开发环境中使用。
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// 将设置应用到 HTTP 客户端的构建器
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub(crate) fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder> {
+        for pem in &self.root_certificates {
+            let certs = reqwest::Certificate::from_pem_bundle(pem).map_err(tls_error)?;
+            if certs.is_empty() {
+                return Err(tls_error("未找到 PEM 格式的证书"));
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        Ok(builder.danger_accept_invalid_certs(self.accept_invalid_certs))
+    }
+
+    /// 将设置应用到 HTTP 客户端的构建器
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    pub(crate) fn apply(&self, _builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        Err(tls_disabled())
+    }
+
+    /// 创建事件 WebSocket 连接使用的 TLS 连接器
+    #[cfg(all(feature = "websocket", feature = "native-tls"))]
+    pub(crate) fn connector(&self) -> Result<Connector> {
+        let mut builder = native_tls::TlsConnector::builder();
+        for pem in &self.root_certificates {
+            // native-tls 每次只解析一张证书，需要先按结束标记拆分证书链
+            let mut rest = pem.as_slice();
+            while let Some(end) = rest
+                .windows(PEM_END_MARKER.len())
+                .position(|window| window == PEM_END_MARKER)
+            {
+                let (cert, tail) = rest.split_at(end + PEM_END_MARKER.len());
+                builder.add_root_certificate(
+                    native_tls::Certificate::from_pem(cert).map_err(tls_error)?,
+                );
+                rest = tail;
+            }
+            if rest == pem.as_slice() {
+                return Err(tls_error("未找到 PEM 格式的证书"));
+            }
+        }
+        builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        Ok(Connector::NativeTls(builder.build().map_err(tls_error)?))
+    }
+
+    /// 创建事件 WebSocket 连接使用的 TLS 连接器
+    #[cfg(all(feature = "websocket", feature = "rustls", not(feature = "native-tls")))]
+    pub(crate) fn connector(&self) -> Result<Connector> {
+        use rustls::pki_types::CertificateDer;
+        use rustls::pki_types::pem::PemObject;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        for pem in &self.root_certificates {
+            let certs = CertificateDer::pem_slice_iter(pem)
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(tls_error)?;
+            if certs.is_empty() {
+                return Err(tls_error("未找到 PEM 格式的证书"));
+            }
+            for cert in certs {
+                roots.add(cert).map_err(tls_error)?;
+            }
+        }
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut config = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        if self.accept_invalid_certs {
+            config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(danger::NoVerification(provider)));
+        }
+        Ok(Connector::Rustls(Arc::new(config)))
+    }
+
+    /// 创建事件 WebSocket 连接使用的 TLS 连接器
+    #[cfg(all(
+        feature = "websocket",
+        not(any(feature = "native-tls", feature = "rustls"))
+    ))]
+    pub(crate) fn connector(&self) -> Result<Connector> {
+        Err(tls_disabled())
+    }
+}
+
+/// 跳过服务端证书校验的 rustls 校验器
+#[cfg(all(feature = "websocket", feature = "rustls", not(feature = "native-tls")))]
+mod danger {
+    use rustls::DigitallySignedStruct;
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use std::sync::Arc;
+
+    /// 接受任意服务端证书，但仍校验握手签名
+    #[derive(Debug)]
+    pub(super) struct NoVerification(pub(super) Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for NoVerification {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
+
+/// 创建 TLS 设置无效的错误
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn tls_error(e: impl std::fmt::Display) -> MilkyError {
+    MilkyError::Config(format!("TLS 配置错误: {e}"))
+}
+
+/// 创建未启用 TLS 特性的错误
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+fn tls_disabled() -> MilkyError {
+    MilkyError::Config("未启用 `native-tls` 或 `rustls` 特性，无法设置 TLS".to_string())
+}
+
+impl MilkyClient {
+    /// 设置 HTTP API 请求与资源下载使用的 TLS
+    ///
+    /// 会重新创建内部的 HTTP 客户端，之前通过构建器传入的自定义 HTTP 客户端将被替换。
+    ///
+    /// # 参数
+    /// * `config`: TLS 设置
+    ///
+    /// # 返回
+    /// 证书无法解析或未启用 TLS 特性时返回错误
+    pub fn with_api_tls(mut self, config: TlsConfig) -> Result<Self> {
+        self.api_tls = Some(config);
+        self.http_client = self.build_http_client()?;
+        Ok(self)
+    }
+
+    /// 设置事件 WebSocket 连接使用的 TLS
+    ///
+    /// # 参数
+    /// * `config`: TLS 设置
+    ///
+    /// # 返回
+    /// 证书无法解析或未启用 TLS 特性时返回错误
+    #[cfg(feature = "websocket")]
+    pub fn with_event_tls(mut self, config: TlsConfig) -> Result<Self> {
+        self.event_tls = Some(config.connector()?);
+        Ok(self)
+    }
+}
+
+#[cfg(all(test, any(feature = "native-tls", feature = "rustls")))]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+
+    /// 自签名的测试证书
+    const SELF_SIGNED_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBgDCCASWgAwIBAgIUKuJRH1cRhGBofBiLpK/AwLpTKeswCgYIKoZIzj0EAwIw
+FDESMBAGA1UEAwwJbG9jYWxob3N0MCAXDTI2MTAxNDE3MDY1M1oYDzIxMjYwOTIw
+MTcwNjUzWjAUMRIwEAYDVQQDDAlsb2NhbGhvc3QwWTATBgcqhkjOPQIBBggqhkjO
+PQMBBwNCAARzw/KcI+p3pstjtoODsLKt63hGwfgJrHSckPAQpZvRlVThMomFxWj3
+1dpqgNTJVU1AYZotVAppdrM5ySbxluiqo1MwUTAdBgNVHQ4EFgQUEs3RJLgDxdcj
+UKQXUrGvH/aBMqowHwYDVR0jBBgwFoAUEs3RJLgDxdcjUKQXUrGvH/aBMqowDwYD
+VR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBGAiEA+RFvL8cXUr3S2m2zzHcV
+nJD7LpM4YOBrJQ3NrE/JFhYCIQCF/EmfciE5M0dWMSGd+d7dAPAyYfGqgVMt/z6/
+vuyC6w==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_tls_config() {
+        let client = || test_util::client(OFFLINE).0;
+
+        let config = TlsConfig::new()
+            .add_root_certificate_pem(SELF_SIGNED_CERT)
+            .danger_accept_invalid_certs(true);
+        let client_with_tls = client().with_api_tls(config.clone()).unwrap();
+        assert_eq!(client_with_tls.api_tls, Some(config.clone()));
+        #[cfg(feature = "websocket")]
+        assert!(
+            client_with_tls
+                .with_event_tls(config)
+                .unwrap()
+                .event_tls
+                .is_some()
+        );
+
+        let invalid = TlsConfig::new().add_root_certificate_pem("not a certificate");
+        assert!(matches!(
+            client().with_api_tls(invalid.clone()),
+            Err(MilkyError::Config(_))
+        ));
+        #[cfg(feature = "websocket")]
+        assert!(matches!(
+            client().with_event_tls(invalid),
+            Err(MilkyError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+//! 运行时刷新的访问令牌
+//!
+//! 默认情况下，客户端始终使用创建时通信方式中设置的访问令牌。部署环境会定期轮换凭据时，
+//! 可以通过 [`MilkyClient::with_token_provider`] 设置 [`TokenProvider`]，此后每次 API 请求的 Bearer 认证头
+//! 以及每次建立（或重新建立）事件 WebSocket 连接时的 `access_token` 查询参数都会向它获取最新的令牌。
+//!
+//! 同步闭包可以直接作为提供者使用，需要异步获取令牌时使用 [`AsyncTokenProvider`]：
+//!
+//! ```
+//! use milky_rust_sdk::client::AsyncTokenProvider;
+//!
+//! let sync_provider = || std::env::var("MILKY_TOKEN").ok();
+//! let async_provider = AsyncTokenProvider::new(|| async {
+//!     // 例如从配置中心读取
+//!     Ok(Some("secret".to_string()))
+//! });
+//! ```
+
+use crate::client::MilkyClient;
+use crate::error::Result;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(feature = "websocket")]
+use url::Url;
+
+/// [`TokenProvider::token`] 返回的 Future
+pub type TokenFuture<'a> = Pin<Box<dyn Future<Output = Result<Option<String>>> + Send + 'a>>;
+
+/// 访问令牌的提供者
+pub trait TokenProvider: Send + Sync {
+    /// 获取当前的访问令牌
+    ///
+    /// # 返回
+    /// 成功则返回令牌，返回 `Ok(None)` 表示不进行认证；返回错误时本次请求或连接失败
+    fn token(&self) -> TokenFuture<'_>;
+}
+
+impl<F> TokenProvider for F
+where
+    F: Fn() -> Option<String> + Send + Sync,
+{
+    fn token(&self) -> TokenFuture<'_> {
+        let token = self();
+        Box::pin(async move { Ok(token) })
+    }
+}
+
+/// 通过异步闭包获取访问令牌的提供者
+pub struct AsyncTokenProvider<F> {
+    /// 获取令牌的闭包
+    fetch: F,
+}
+
+impl<F, Fut> AsyncTokenProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<String>>> + Send + 'static,
+{
+    /// 创建提供者
+    ///
+    /// # 参数
+    /// * `fetch`: 每次需要令牌时调用的异步闭包
+    pub fn new(fetch: F) -> Self {
+        Self { fetch }
+    }
+}
+
+impl<F, Fut> TokenProvider for AsyncTokenProvider<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<String>>> + Send + 'static,
+{
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin((self.fetch)())
+    }
+}
+
+/// 将事件 WebSocket 连接 URL 中的 `access_token` 查询参数替换为指定的令牌
+///
+/// # 参数
+/// * `url`: 原始的连接 URL
+/// * `token`: 新的令牌，为 `None` 时移除该参数
+#[cfg(feature = "websocket")]
+pub(crate) fn replace_access_token(url: &str, token: Option<&str>) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "access_token")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.set_query(None);
+    if !pairs.is_empty() || token.is_some() {
+        let mut query = url.query_pairs_mut();
+        query.extend_pairs(pairs);
+        if let Some(token) = token {
+            query.append_pair("access_token", token);
+        }
+    }
+    Ok(url.into())
+}
+
+impl MilkyClient {
+    /// 设置访问令牌的提供者，此后通信方式中设置的访问令牌将不再使用
+    ///
+    /// # 参数
+    /// * `provider`: 访问令牌的提供者
+    pub fn with_token_provider(mut self, provider: impl TokenProvider + 'static) -> Self {
+        self.token_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// 获取当前用于 API 请求的访问令牌
+    pub(crate) async fn current_token(&self) -> Result<Option<String>> {
+        match &self.token_provider {
+            Some(provider) => provider.token().await,
+            None => Ok(self.access_token.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use crate::types::communication::{Communication, WebSocketConfig};
+    use axum::Router;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use serde_json::{Value, json};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_token_rotation() {
+        let app = Router::new().route(
+            "/api/get_login_info",
+            post(|headers: HeaderMap| async move {
+                let auth = headers["authorization"].to_str().unwrap().to_string();
+                axum::Json(json!({"status": "ok", "retcode": 0, "data": auth}))
+            }),
+        );
+        let addr = test_util::serve(app).await;
+        let (client, _rx) = test_util::client_for(Communication::WebSocket(WebSocketConfig::new(
+            format!("ws://{addr}"),
+            Some("initial".to_string()),
+        )));
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = Arc::clone(&counter);
+        let client = client.with_token_provider(AsyncTokenProvider::new(move || {
+            let n = counter_clone.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(Some(format!("token-{n}"))) }
+        }));
+
+        for expected in ["Bearer token-0", "Bearer token-1"] {
+            let auth: Value = client
+                .send_request("get_login_info", json!({}))
+                .await
+                .unwrap();
+            assert_eq!(auth, expected);
+        }
+
+        #[cfg(feature = "websocket")]
+        {
+            let url = "ws://127.0.0.1/event?access_token=initial&foo=bar";
+            assert_eq!(
+                replace_access_token(url, Some("a&b")).unwrap(),
+                "ws://127.0.0.1/event?foo=bar&access_token=a%26b"
+            );
+            assert_eq!(
+                replace_access_token(url, None).unwrap(),
+                "ws://127.0.0.1/event?foo=bar"
+            );
+        }
+    }
+}
@@ -0,0 +1,592 @@
+//! WebHook 事件接收服务器
+//!
+//! 以 WebHook 方式接收事件时，[`MilkyClient::connect_events`] 会在
+//! [`WebHookConfig`](crate::types::communication::WebHookConfig) 中设置的 `host:port` 上监听，
+//! 并在 `path`（默认 `/webhook`）上接收服务端推送的事件。端口设置为 `0` 时由系统分配，
+//! 实际监听的地址可以通过 [`MilkyClient::webhook_local_addr`] 获取，便于在容器等环境中运行。
+//!
+//! 设置了访问令牌（或 [`TokenProvider`]）时，服务器会按照 Milky 协议的约定校验请求头中的
+//! `Authorization: Bearer {access_token}`，令牌缺失或不匹配的请求会以 `401 Unauthorized` 拒绝，
+//! 不会作为事件处理。
+//!
+//! 启用 `webhook-tls` 特性并通过 [`WebHookConfig::with_tls`](crate::types::communication::WebHookConfig::with_tls)
+//! 设置证书与私钥后，服务器会直接以 HTTPS 接收事件。
+//!
+//! 面向公网时还可以在配置中限制来源 IP、请求体大小与同时处理的请求数量，
+//! 避免异常或恶意的请求耗尽机器人进程的资源。
+//!
+//! 已经运行了自己的 axum 服务时，可以通过 [`MilkyClient::webhook_router`] 获取接收事件的路由并合并到已有的服务中，
+//! 此时无需调用 [`MilkyClient::connect_events`]：
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! # async fn run(client: MilkyClient) -> milky_rust_sdk::Result<()> {
+//! let app = axum::Router::new()
+//!     .route("/health", axum::routing::get(|| async { "ok" }))
+//!     .merge(client.webhook_router()?);
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+//! // 需要限制来源 IP 时，以 `SocketAddr` 提供连接信息
+//! axum::serve(
+//!     listener,
+//!     app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "webhook-tls")]
+pub(crate) mod tls;
+
+use crate::client::{MilkyClient, TokenProvider};
+use crate::error::{MilkyError, Result};
+use crate::logger::redact::LogPayload;
+use crate::logger::{debug, warn};
+use crate::types::communication::{Communication, WebHookConfig};
+use crate::types::message::OriginalMessage;
+
+use axum::Router;
+use axum::extract::connect_info::Connected;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::serve::IncomingStream;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 以 HTTPS 接收事件时完成 TLS 握手的接收器
+#[cfg(feature = "webhook-tls")]
+pub(crate) type WebhookTlsAcceptor = tokio_rustls::TlsAcceptor;
+
+/// 未启用 `webhook-tls` 特性时无法构造，只以明文 HTTP 接收事件
+#[cfg(not(feature = "webhook-tls"))]
+pub(crate) type WebhookTlsAcceptor = Infallible;
+
+/// 校验 WebHook 请求的访问令牌
+#[derive(Clone)]
+pub(crate) struct WebhookAuth {
+    /// 通信方式中设置的访问令牌
+    access_token: Option<String>,
+    /// 访问令牌的提供者，设置时优先使用
+    token_provider: Option<Arc<dyn TokenProvider>>,
+}
+
+impl WebhookAuth {
+    /// 判断请求是否携带了正确的访问令牌
+    ///
+    /// # 参数
+    /// * `headers`: 请求头
+    ///
+    /// # 返回
+    /// 未设置访问令牌，或请求携带的令牌与之相同时返回 `true`
+    pub(crate) async fn verify(&self, headers: &HeaderMap) -> bool {
+        let expected = match &self.token_provider {
+            Some(provider) => match provider.token().await {
+                Ok(token) => token,
+                Err(e) => {
+                    warn!("获取访问令牌失败，拒绝 WebHook 请求: {e}");
+                    return false;
+                }
+            },
+            None => self.access_token.clone(),
+        };
+        let Some(expected) = expected else {
+            return true;
+        };
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+    }
+}
+
+/// WebHook 请求的来源地址
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeerAddr(pub(crate) SocketAddr);
+
+impl Connected<IncomingStream<'_, TcpListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+#[cfg(feature = "webhook-tls")]
+impl Connected<IncomingStream<'_, tls::TlsListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, tls::TlsListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+/// 请求的来源 IP，无法得知时为 `None`
+///
+/// 优先读取 SDK 自身服务器提供的 [`PeerAddr`]，挂载到外部服务时读取以 `SocketAddr` 提供的连接信息。
+pub(crate) struct RemoteIp(Option<IpAddr>);
+
+impl<S: Send + Sync> FromRequestParts<S> for RemoteIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let extensions = &parts.extensions;
+        let ip = extensions
+            .get::<ConnectInfo<PeerAddr>>()
+            .map(|ConnectInfo(PeerAddr(addr))| addr.ip())
+            .or_else(|| {
+                extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            });
+        Ok(Self(ip))
+    }
+}
+
+/// WebHook 请求的来源、并发限制与访问令牌校验，在读取请求体之前进行
+#[derive(Clone)]
+pub(crate) struct WebhookGuard {
+    /// 允许的来源 IP，为空时不限制
+    allowed_ips: Arc<[IpAddr]>,
+    /// 限制同时处理的请求数量的信号量
+    concurrency: Arc<Semaphore>,
+    /// 访问令牌的校验器
+    auth: WebhookAuth,
+}
+
+impl WebhookGuard {
+    /// 根据 WebHook 配置创建
+    ///
+    /// # 参数
+    /// * `config`: WebHook 配置
+    /// * `auth`: 访问令牌的校验器
+    pub(crate) fn new(config: &WebHookConfig, auth: WebhookAuth) -> Self {
+        Self {
+            allowed_ips: config
+                .allowed_ips
+                .iter()
+                .map(IpAddr::to_canonical)
+                .collect(),
+            concurrency: Arc::new(Semaphore::new(
+                config
+                    .max_concurrent_requests
+                    .unwrap_or(Semaphore::MAX_PERMITS),
+            )),
+            auth,
+        }
+    }
+
+    /// 判断来源 IP 是否被允许，设置了允许列表但无法得知来源时不允许
+    pub(crate) fn is_allowed(&self, ip: Option<IpAddr>) -> bool {
+        self.allowed_ips.is_empty()
+            || ip.is_some_and(|ip| self.allowed_ips.contains(&ip.to_canonical()))
+    }
+
+    /// 占用一个并发名额，处理完成后释放
+    ///
+    /// # 返回
+    /// 名额已满时返回 `None`
+    pub(crate) fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.concurrency).try_acquire_owned().ok()
+    }
+}
+
+/// 读取 WebHook 配置中的证书与私钥
+///
+/// # 返回
+/// 未设置证书时返回 `Ok(None)`；只设置了证书或私钥之一、文件无效，
+/// 或未启用 `webhook-tls` 特性时返回 [`MilkyError::Config`]
+#[cfg(feature = "webhook-tls")]
+pub(crate) fn tls_acceptor(config: &WebHookConfig) -> Result<Option<WebhookTlsAcceptor>> {
+    match (&config.tls_cert, &config.tls_key) {
+        (None, None) => Ok(None),
+        (Some(cert), Some(key)) => tls::tls_acceptor(cert, key).map(Some),
+        _ => Err(incomplete_tls_config()),
+    }
+}
+
+/// 读取 WebHook 配置中的证书与私钥，参见启用 `webhook-tls` 特性时的同名函数
+#[cfg(not(feature = "webhook-tls"))]
+pub(crate) fn tls_acceptor(config: &WebHookConfig) -> Result<Option<WebhookTlsAcceptor>> {
+    match (&config.tls_cert, &config.tls_key) {
+        (None, None) => Ok(None),
+        (Some(_), Some(_)) => Err(MilkyError::Config(
+            "未启用 `webhook-tls` 特性，无法以 HTTPS 接收 WebHook 事件".to_string(),
+        )),
+        _ => Err(incomplete_tls_config()),
+    }
+}
+
+/// 在已绑定的监听器上运行 WebHook 事件接收服务器，直到 `shutdown` 完成
+///
+/// # 参数
+/// * `listener`: 已绑定的监听器
+/// * `app`: 接收事件的路由
+/// * `tls_acceptor`: 设置时以 HTTPS 接收事件
+/// * `shutdown`: 完成后开始平滑关闭服务器
+pub(crate) async fn serve(
+    listener: TcpListener,
+    app: Router,
+    tls_acceptor: Option<WebhookTlsAcceptor>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let app = app.into_make_service_with_connect_info::<PeerAddr>();
+    match tls_acceptor {
+        #[cfg(feature = "webhook-tls")]
+        Some(acceptor) => {
+            axum::serve(tls::TlsListener::new(listener, acceptor)?, app)
+                .with_graceful_shutdown(shutdown)
+                .await
+        }
+        #[cfg(not(feature = "webhook-tls"))]
+        Some(never) => match never {},
+        None => {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown)
+                .await
+        }
+    }
+}
+
+/// 只设置了证书或私钥之一时的错误
+fn incomplete_tls_config() -> MilkyError {
+    MilkyError::Config("WebHook 的 `tls_cert` 与 `tls_key` 需要同时设置".to_string())
+}
+
+/// 在读取请求体之前检查请求的中间件
+///
+/// 依次检查来源 IP、并发数量与访问令牌，分别以 `403 Forbidden`、`503 Service Unavailable`
+/// 与 `401 Unauthorized` 拒绝。并发名额在请求处理完成后释放。
+async fn admit(
+    State(guard): State<WebhookGuard>,
+    RemoteIp(ip): RemoteIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = ip.map_or_else(|| "未知地址".to_string(), |ip| ip.to_string());
+    if !guard.is_allowed(ip) {
+        warn!("拒绝了来自 {peer} 的 WebHook 请求，该地址不在允许列表中");
+        return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+    }
+    let Some(_permit) = guard.try_acquire() else {
+        warn!("同时处理的 WebHook 请求过多，拒绝了来自 {peer} 的请求");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Too many concurrent requests",
+        )
+            .into_response();
+    };
+    if !guard.auth.verify(request.headers()).await {
+        warn!("拒绝了来自 {peer} 的访问令牌无效的 WebHook 请求");
+        return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response();
+    }
+    next.run(request).await
+}
+
+/// 检查 WebHook 的路由路径，避免 axum 在注册路由时 panic
+///
+/// 路径需要以 `/` 开头，并且不能包含 axum 用于路径参数的 `{`、`}`
+fn validate_path(path: &str) -> Result<()> {
+    if !path.starts_with('/') {
+        return Err(MilkyError::Config(format!(
+            "WebHook 的路由路径需要以 `/` 开头: {path}"
+        )));
+    }
+    if path.contains(['{', '}']) {
+        return Err(MilkyError::Config(format!(
+            "WebHook 的路由路径不能包含 `{{` 或 `}}`: {path}"
+        )));
+    }
+    Ok(())
+}
+
+/// 以与内容无关的耗时比较两个字节串，避免通过响应时间推测令牌
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl MilkyClient {
+    /// 获取 WebHook 事件接收服务器实际监听的地址
+    ///
+    /// # 返回
+    /// 服务器正在运行时返回监听的地址，尚未启动或已关闭时返回 `None`
+    pub fn webhook_local_addr(&self) -> Option<SocketAddr> {
+        *self.webhook_addr.lock().unwrap()
+    }
+
+    /// 创建接收 WebHook 事件的路由，可以合并到已有的 axum 服务中
+    ///
+    /// 路由路径、来源 IP、请求体大小与并发限制均取自创建客户端时的 WebHook 配置，
+    /// 并同样校验访问令牌。挂载到外部服务时不会启动 SDK 自身的事件接收服务器。
+    ///
+    /// # 返回
+    /// 成功则返回路由；客户端不是以 WebHook 方式通信，或路由路径无效时返回 [`MilkyError::Config`]
+    pub fn webhook_router(&self) -> Result<Router> {
+        match &self.comm_type {
+            Communication::WebHook(config) => self.build_webhook_router(config),
+            Communication::WebSocket(_) => Err(MilkyError::Config(
+                "客户端以 WebSocket 方式通信，无法创建 WebHook 路由".to_string(),
+            )),
+        }
+    }
+
+    /// 根据 WebHook 配置创建接收事件的路由
+    ///
+    /// # 返回
+    /// 路由路径无效时返回 [`MilkyError::Config`]
+    pub(crate) fn build_webhook_router(&self, config: &WebHookConfig) -> Result<Router> {
+        validate_path(&config.path)?;
+        let event_sender = self.event_sender.clone();
+        let event_broadcast = self.event_broadcast.clone();
+        let event_stats = Arc::clone(&self.event_stats);
+        let event_decoder = Arc::clone(&self.event_decoder);
+        let webhook_guard = WebhookGuard::new(config, self.webhook_auth());
+
+        let handler = move |payload: String| {
+            let event_sender = event_sender.clone();
+            let event_broadcast = event_broadcast.clone();
+            let event_stats = Arc::clone(&event_stats);
+            let event_decoder = Arc::clone(&event_decoder);
+            let handle = async move {
+                debug!("WebHook 接收到 payload: {}", LogPayload(&payload));
+                if let Err(e) = Self::handle_event_message(
+                    OriginalMessage::WebHook(payload),
+                    &event_sender,
+                    &event_broadcast,
+                    &event_stats,
+                    &event_decoder,
+                )
+                .await
+                {
+                    warn!("处理 WebHook 事件消息时出错: {e:?}");
+                    // 返回一个错误响应给调用方
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to process webhook: {e:?}"),
+                    )
+                } else {
+                    // 返回成功响应
+                    (StatusCode::OK, "Webhook received successfully".to_string())
+                }
+            };
+            #[cfg(feature = "tracing")]
+            let handle = tracing::Instrument::instrument(handle, tracing::info_span!("webhook"));
+            handle
+        };
+
+        // 在读取请求体之前检查来源、并发数量与访问令牌，未通过检查的请求不会被读取与解析
+        let mut router = Router::new()
+            .route(&config.path, post(handler))
+            .route_layer(middleware::from_fn_with_state(webhook_guard, admit));
+        if let Some(limit) = config.max_body_size {
+            router = router.layer(DefaultBodyLimit::max(limit));
+        }
+        Ok(router)
+    }
+
+    /// 创建校验 WebHook 请求访问令牌的校验器
+    pub(crate) fn webhook_auth(&self) -> WebhookAuth {
+        WebhookAuth {
+            access_token: self.access_token.clone(),
+            token_provider: self.token_provider.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::MilkyError;
+    use crate::test_util;
+    use crate::types::communication::{Communication, WebHookConfig};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_custom_path() {
+        let (client, mut rx) = test_util::client_for(Communication::WebHook(
+            test_util::webhook_config().with_path("milky/events"),
+        ));
+        client.connect_events().await.unwrap();
+        let addr = client.webhook_local_addr().unwrap();
+
+        let http = reqwest::Client::new();
+        let event = json!({
+            "time": 1,
+            "self_id": 10000,
+            "event_type": "bot_offline",
+            "data": {"reason": "test"},
+        });
+        let default_path = http
+            .post(format!("http://{addr}/webhook"))
+            .json(&event)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(default_path.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let custom_path = http
+            .post(format!("http://{addr}/milky/events"))
+            .json(&event)
+            .send()
+            .await
+            .unwrap();
+        assert!(custom_path.status().is_success());
+        assert_eq!(rx.recv().await.unwrap().time, 1);
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_path() {
+        let mut config = test_util::webhook_config();
+        config.path = "webhook".to_string();
+        let (client, _rx) = test_util::client_for(Communication::WebHook(config.clone()));
+        assert!(matches!(
+            client.webhook_router(),
+            Err(MilkyError::Config(_))
+        ));
+        assert!(matches!(
+            client.connect_events().await,
+            Err(MilkyError::Config(_))
+        ));
+
+        config.path = "/{id}".to_string();
+        let (client, _rx) = test_util::client_for(Communication::WebHook(config));
+        assert!(matches!(
+            client.webhook_router(),
+            Err(MilkyError::Config(_))
+        ));
+
+        let config: WebHookConfig = toml::from_str(
+            r#"
+            port = 0
+            path = "milky/events"
+            http_endpoint = "http://127.0.0.1:3000"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.path, "/milky/events");
+    }
+
+    #[tokio::test]
+    async fn test_verify_access_token() {
+        let mut config = test_util::webhook_config();
+        config.access_token = Some("secret".to_string());
+        let (client, mut rx) = test_util::client_for(Communication::WebHook(config));
+        client.connect_events().await.unwrap();
+        let url = format!("http://{}/webhook", client.webhook_local_addr().unwrap());
+
+        let http = reqwest::Client::new();
+        let event = json!({
+            "time": 2,
+            "self_id": 10000,
+            "event_type": "bot_offline",
+            "data": {"reason": "test"},
+        });
+        for token in [None, Some("wrong")] {
+            let mut request = http.post(&url).json(&event);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        }
+        // 在读取请求体之前校验令牌，内容无效的请求同样返回 401
+        let response = http
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(vec![0xff, 0xfe])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = http
+            .post(&url)
+            .bearer_auth("secret")
+            .json(&event)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(rx.recv().await.unwrap().time, 2);
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_allowed_ips_and_body_limit() {
+        let event = json!({
+            "time": 4,
+            "self_id": 10000,
+            "event_type": "bot_offline",
+            "data": {"reason": "test"},
+        });
+        let config = test_util::webhook_config();
+        let http = reqwest::Client::new();
+
+        let (client, _rx) = test_util::client_for(Communication::WebHook(
+            config
+                .clone()
+                .with_allowed_ips(["10.0.0.1".parse().unwrap()]),
+        ));
+        client.connect_events().await.unwrap();
+        let url = format!("http://{}/webhook", client.webhook_local_addr().unwrap());
+        let response = http.post(&url).json(&event).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+        // 来源检查在读取请求体之前进行，内容无效的请求同样返回 403
+        let response = http.post(&url).body("not json").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+        client.shutdown().await;
+
+        let (client, mut rx) = test_util::client_for(Communication::WebHook(
+            config
+                .with_allowed_ips(["127.0.0.1".parse().unwrap()])
+                .with_max_body_size(256),
+        ));
+        client.connect_events().await.unwrap();
+        let url = format!("http://{}/webhook", client.webhook_local_addr().unwrap());
+        let large = json!({"padding": "x".repeat(1024)});
+        let response = http.post(&url).json(&large).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+        let response = http.post(&url).json(&event).send().await.unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(rx.recv().await.unwrap().time, 4);
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_mount_router() {
+        let (client, mut rx) =
+            test_util::client_for(Communication::WebHook(test_util::webhook_config()));
+        let app = axum::Router::new()
+            .route("/health", axum::routing::get(|| async { "ok" }))
+            .merge(client.webhook_router().unwrap());
+        let addr = test_util::serve(app).await;
+
+        let http = reqwest::Client::new();
+        let health = http
+            .get(format!("http://{addr}/health"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(health.text().await.unwrap(), "ok");
+        let response = http
+            .post(format!("http://{addr}/webhook"))
+            .json(&json!({
+                "time": 5,
+                "self_id": 10000,
+                "event_type": "bot_offline",
+                "data": {"reason": "test"},
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(rx.recv().await.unwrap().time, 5);
+    }
+}
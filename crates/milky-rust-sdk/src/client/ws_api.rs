@@ -0,0 +1,225 @@
+//! 通过事件 WebSocket 连接调用 API
+//!
+//! 默认情况下 API 调用通过 HTTP 发送。通过 [`MilkyClient::with_api_transport`] 选择
+//! [`ApiTransport::WebSocket`] 后，API 请求会以带有 `echo` 字段的 JSON 文本帧在事件 WebSocket 连接上发送，
+//! 服务端返回的同样带有该 `echo` 的响应帧会被交给对应的调用方，多个调用可以同时进行。
+//! 这样无需单独开放 HTTP 端口，所有通信都经由同一个连接完成。
+//!
+//! 该模式需要先调用 [`MilkyClient::connect_events`] 建立连接，连接断开期间的调用会返回
+//! [`MilkyError::NotConnected`]，等待响应期间连接断开的调用同样返回该错误。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+use crate::logger::debug;
+use crate::runtime;
+use crate::types::common::{ApiRequest, ApiResponse};
+use crate::utils::json;
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+/// API 调用使用的传输方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiTransport {
+    /// 通过 HTTP POST 调用 API
+    #[default]
+    Http,
+    /// 通过事件 WebSocket 连接调用 API
+    WebSocket,
+}
+
+/// 等待响应的调用，以 `echo` 为键
+type PendingCalls = Mutex<HashMap<String, oneshot::Sender<ApiResponse<Value>>>>;
+
+/// 在事件 WebSocket 连接上复用的 API 调用
+#[derive(Default)]
+pub(crate) struct WsApi {
+    /// 当前连接的写入端，连接断开时为 `None`
+    outgoing: Mutex<Option<mpsc::UnboundedSender<WsMessage>>>,
+    /// 等待响应的调用
+    pending: PendingCalls,
+}
+
+/// 调用结束（包括被取消）时移除等待中的调用
+struct PendingGuard<'a> {
+    /// 等待响应的调用
+    pending: &'a PendingCalls,
+    /// 本次调用的 `echo`
+    echo: &'a str,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(self.echo);
+    }
+}
+
+impl WsApi {
+    /// 在新建立的连接上启用 API 调用
+    ///
+    /// # 参数
+    /// * `writer`: 连接的写入端
+    pub(crate) fn attach(&self, writer: mpsc::UnboundedSender<WsMessage>) {
+        *self.outgoing.lock().unwrap() = Some(writer);
+    }
+
+    /// 连接断开后停止发送，并让所有等待中的调用返回 [`MilkyError::NotConnected`]
+    pub(crate) fn detach(&self) {
+        self.outgoing.lock().unwrap().take();
+        self.pending.lock().unwrap().clear();
+    }
+
+    /// 尝试将收到的文本帧作为 API 响应交给对应的调用方
+    ///
+    /// # 返回
+    /// 文本帧是带有 `echo` 的 API 响应时返回 `true`，否则返回 `false`，应继续作为事件处理
+    pub(crate) fn dispatch(&self, text: &str) -> bool {
+        // 事件不带有 `echo` 字段，先按文本排除，避免每个事件都被完整解析两次
+        if !text.contains("\"echo\"") {
+            return false;
+        }
+        let Ok(response) = json::from_str::<ApiResponse<Value>>(text) else {
+            return false;
+        };
+        let Some(echo) = &response.echo else {
+            return false;
+        };
+        match self.pending.lock().unwrap().remove(echo) {
+            Some(tx) => {
+                let _ = tx.send(response);
+            }
+            None => debug!("丢弃 echo 为 {echo} 的 API 响应，对应的调用已结束"),
+        }
+        true
+    }
+
+    /// 发送 API 请求并等待响应
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `params`: 请求参数
+    /// * `timeout`: 等待响应的超时时间，为 `None` 时一直等待
+    pub(crate) async fn call(
+        &self,
+        action: &str,
+        params: &Value,
+        timeout: Option<Duration>,
+    ) -> Result<ApiResponse<Value>> {
+        let echo = uuid::Uuid::new_v4().to_string();
+        let request = serde_json::to_string(&ApiRequest {
+            action: action.to_string(),
+            params,
+            echo: Some(echo.clone()),
+        })?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(echo.clone(), tx);
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            echo: &echo,
+        };
+        let sent = self
+            .outgoing
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|outgoing| outgoing.send(WsMessage::text(request)).is_ok());
+        if !sent {
+            return Err(MilkyError::NotConnected);
+        }
+
+        let response = match timeout {
+            Some(timeout) => runtime::timeout(timeout, rx)
+                .await
+                .map_err(|_| MilkyError::Timeout)?,
+            None => rx.await,
+        }
+        .map_err(|_| MilkyError::NotConnected)?;
+        if response.echo.as_deref() != Some(echo.as_str()) {
+            return Err(MilkyError::EchoMismatch);
+        }
+        Ok(response)
+    }
+}
+
+impl MilkyClient {
+    /// 设置 API 调用使用的传输方式，默认通过 HTTP 调用
+    ///
+    /// # 参数
+    /// * `transport`: 传输方式
+    pub fn with_api_transport(mut self, transport: ApiTransport) -> Self {
+        self.ws_api = match transport {
+            ApiTransport::Http => None,
+            ApiTransport::WebSocket => Some(Arc::new(WsApi::default())),
+        };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, WsServer};
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_ws_api_call() {
+        let server = WsServer::bind().await;
+        let addr = server.addr();
+        tokio::spawn(async move {
+            let mut socket = server.accept().await;
+            while let Some(Ok(WsMessage::Text(text))) = socket.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let response = json!({
+                    "status": "ok",
+                    "retcode": 0,
+                    "data": {"action": request["action"]},
+                    "echo": request["echo"],
+                });
+                let _ = socket.send(WsMessage::text(response.to_string())).await;
+            }
+        });
+
+        let (client, _rx) = test_util::client(addr);
+        let client = client.with_api_transport(ApiTransport::WebSocket);
+
+        let result = client
+            .send_request::<_, Value>("get_login_info", json!({}))
+            .await;
+        assert!(matches!(result, Err(MilkyError::NotConnected)));
+
+        client.connect_events().await.unwrap();
+        let (first, second) = tokio::join!(
+            client.send_request::<_, Value>("get_login_info", json!({})),
+            client.send_request::<_, Value>("get_friend_list", json!({})),
+        );
+        assert_eq!(first.unwrap(), json!({"action": "get_login_info"}));
+        assert_eq!(second.unwrap(), json!({"action": "get_friend_list"}));
+        assert_eq!(
+            client
+                .call_raw("get_custom_face_url_list", json!({}))
+                .await
+                .unwrap(),
+            json!({"action": "get_custom_face_url_list"})
+        );
+
+        let result = client
+            .send_request::<_, i64>("get_group_info", json!({"group_id": 123}))
+            .await;
+        match result {
+            Err(MilkyError::Decode {
+                action, request, ..
+            }) => {
+                assert_eq!(action, "get_group_info");
+                assert_eq!(request, r#"{"group_id":123}"#);
+            }
+            other => panic!("{other:?}"),
+        }
+        client.shutdown().await;
+    }
+}
@@ -0,0 +1,578 @@
+//! 从 TOML 配置文件加载客户端与机器人框架的配置。
+//!
+//! 本模块提供了 [`Config`] 结构体，用于描述与服务端的通信方式、访问令牌、命令前缀、
+//! 启用的插件以及按群覆盖的配置项，避免在用户代码中硬编码接入点等信息。
+//!
+//! 配置文件中的字符串值支持环境变量插值，插值在 TOML 解析之后进行，
+//! 因此环境变量的值不会被当作 TOML 语法解释，注释中的 `${...}` 也不会被处理：
+//! - `${VAR}`：替换为环境变量 `VAR` 的值，若未设置则返回错误
+//! - `${VAR:-default}`：若环境变量 `VAR` 未设置或为空，则使用 `default`
+//! - `$$`：转义为字面量 `$`
+//!
+//! 如果需要在运行时修改过滤规则、命令开关或频率限制，可以使用 [`SharedConfig`]，
+//! 它支持手动调用 [`SharedConfig::reload`] 或通过 [`SharedConfig::watch`] 监视配置文件变化，
+//! 在不断开事件连接、不重启机器人的情况下应用新配置：
+//! - 过滤规则与禁用的命令由 [`EventDispatcher::with_config`](crate::framework::EventDispatcher::with_config) 在分发事件前检查
+//! - 频率限制应用于 [`SharedConfig::rate_limiter`] 返回的限速器，将其设置到客户端后即可生效
+//! - 访问令牌可以通过将 [`SharedConfig`] 作为客户端的 [`TokenProvider`] 进行轮换
+//!
+//! ```no_run
+//! use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::config::SharedConfig;
+//! use std::time::Duration;
+//!
+//! # fn run() -> milky_rust_sdk::Result<()> {
+//! let shared = SharedConfig::load("vivian.toml")?;
+//! let (tx, rx) = tokio::sync::mpsc::channel(100);
+//! let client = MilkyClient::new(shared.current().communication(), tx)?
+//!     .with_rate_limiter(shared.rate_limiter())
+//!     .with_token_provider(shared.clone());
+//! shared.watch(Duration::from_secs(5));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 一个典型的配置文件如下：
+//!
+//! ```toml
+//! [communication]
+//! mode = "websocket"
+//! ws_endpoint = "ws://127.0.0.1:3000"
+//! access_token = "${MILKY_TOKEN}"
+//!
+//! [bot]
+//! command_prefix = "/"
+//! plugins = ["echo", "welcome"]
+//! disabled_commands = ["debug"]
+//!
+//! [bot.filter]
+//! blocked_users = [10001]
+//!
+//! [bot.rate_limit]
+//! max_messages = 20
+//! per_seconds = 60
+//!
+//! [groups.123456]
+//! command_prefix = "!"
+//! plugins = ["echo"]
+//! ```
+
+use crate::client::{RateLimit, RateLimiter, TokenFuture, TokenProvider};
+use crate::error::{MilkyError, Result};
+use crate::logger::{error, info, warn};
+use crate::runtime::{self, JoinHandle};
+use crate::types::communication::Communication;
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+/// 客户端与机器人框架的完整配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// 与服务端的通信方式及其配置
+    pub communication: Communication,
+    /// 机器人的全局配置
+    #[serde(default)]
+    pub bot: BotConfig,
+    /// 按群号覆盖的配置项
+    #[serde(default)]
+    pub groups: HashMap<i64, GroupConfig>,
+}
+
+/// 机器人的全局配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct BotConfig {
+    /// 命令前缀，默认为 `/`
+    #[serde(default = "default_command_prefix")]
+    pub command_prefix: String,
+    /// 启用的插件名称列表
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    /// 禁用的命令名称列表
+    #[serde(default)]
+    pub disabled_commands: Vec<String>,
+    /// 消息过滤规则
+    #[serde(default)]
+    pub filter: FilterConfig,
+    /// 发送消息的频率限制，未设置则不限制
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            command_prefix: default_command_prefix(),
+            plugins: Vec::new(),
+            disabled_commands: Vec::new(),
+            filter: FilterConfig::default(),
+            rate_limit: None,
+        }
+    }
+}
+
+/// 消息过滤规则
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FilterConfig {
+    /// 忽略来自这些用户的消息
+    #[serde(default)]
+    pub blocked_users: Vec<i64>,
+    /// 忽略来自这些群组的消息
+    #[serde(default)]
+    pub blocked_groups: Vec<i64>,
+}
+
+/// 发送消息的频率限制
+///
+/// 限额分别作用于 [`MESSAGE_ACTIONS`] 中的每个发送消息的操作。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimitConfig {
+    /// 时间窗口内允许发送的最大消息数量
+    pub max_messages: u32,
+    /// 时间窗口长度（秒）
+    pub per_seconds: u64,
+}
+
+impl RateLimitConfig {
+    /// 转换为限速器使用的 [`RateLimit`]
+    pub fn to_rate_limit(&self) -> RateLimit {
+        RateLimit::new(self.max_messages, Duration::from_secs(self.per_seconds))
+    }
+}
+
+/// 受 [`RateLimitConfig`] 限制的发送消息的 API 操作
+pub const MESSAGE_ACTIONS: [&str; 3] = [
+    "send_private_message",
+    "send_group_message",
+    "send_temp_message",
+];
+
+/// 针对单个群组覆盖的配置项
+///
+/// 未设置的字段将回退到 [`BotConfig`] 中的全局配置。
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GroupConfig {
+    /// 该群使用的命令前缀
+    pub command_prefix: Option<String>,
+    /// 该群启用的插件名称列表
+    pub plugins: Option<Vec<String>>,
+    /// 该群额外禁用的命令名称列表
+    #[serde(default)]
+    pub disabled_commands: Vec<String>,
+}
+
+/// 辅助函数，用于 `serde` 的 `default` 属性，返回默认的命令前缀
+fn default_command_prefix() -> String {
+    "/".to_string()
+}
+
+impl Config {
+    /// 从指定路径的 TOML 文件加载配置
+    ///
+    /// # 参数
+    /// * `path`: 配置文件路径，例如 `vivian.toml`
+    ///
+    /// # 返回
+    /// 成功则返回解析后的 [`Config`]
+    /// 如果文件无法读取、环境变量插值失败或内容格式不正确，则返回错误
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        content.parse()
+    }
+
+    /// 获取与服务端的通信方式，可直接传给 [`MilkyClient::new`](crate::MilkyClient::new)
+    pub fn communication(&self) -> Communication {
+        self.communication.clone()
+    }
+
+    /// 获取指定群组实际生效的命令前缀
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    pub fn command_prefix_for(&self, group_id: i64) -> &str {
+        self.groups
+            .get(&group_id)
+            .and_then(|g| g.command_prefix.as_deref())
+            .unwrap_or(&self.bot.command_prefix)
+    }
+
+    /// 获取指定群组实际启用的插件列表
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    pub fn plugins_for(&self, group_id: i64) -> &[String] {
+        self.groups
+            .get(&group_id)
+            .and_then(|g| g.plugins.as_deref())
+            .unwrap_or(&self.bot.plugins)
+    }
+
+    /// 判断指定插件在某个群组中是否启用
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    /// * `plugin`: 插件名称
+    pub fn is_plugin_enabled(&self, group_id: i64, plugin: &str) -> bool {
+        self.plugins_for(group_id).iter().any(|p| p == plugin)
+    }
+
+    /// 判断指定命令是否启用
+    ///
+    /// # 参数
+    /// * `group_id`: 群号，私聊场景下传入 `None`
+    /// * `command`: 命令名称（不含前缀）
+    pub fn is_command_enabled(&self, group_id: Option<i64>, command: &str) -> bool {
+        let disabled_globally = self.bot.disabled_commands.iter().any(|c| c == command);
+        let disabled_in_group = group_id
+            .and_then(|id| self.groups.get(&id))
+            .is_some_and(|g| g.disabled_commands.iter().any(|c| c == command));
+        !disabled_globally && !disabled_in_group
+    }
+
+    /// 判断来自指定用户（及群组）的消息是否应被过滤
+    ///
+    /// # 参数
+    /// * `group_id`: 消息所在的群号，私聊场景下传入 `None`
+    /// * `user_id`: 消息发送者的QQ号
+    pub fn is_blocked(&self, group_id: Option<i64>, user_id: i64) -> bool {
+        let filter = &self.bot.filter;
+        filter.blocked_users.contains(&user_id)
+            || group_id.is_some_and(|id| filter.blocked_groups.contains(&id))
+    }
+}
+
+/// 可在运行时重新加载的共享配置
+///
+/// 内部通过 `tokio::sync::watch` 通道保存最新的配置快照，克隆后的实例共享同一份配置。
+/// 除访问令牌外，通信方式相关的配置在客户端创建后无法变更，重新加载时会保留旧值并输出警告，
+/// 其余配置（过滤规则、命令开关、频率限制等）会立即生效。
+///
+/// 作为客户端的 [`TokenProvider`] 使用时，每次请求与重新连接都会使用当前配置中的访问令牌。
+#[derive(Clone)]
+pub struct SharedConfig {
+    /// 配置文件路径
+    path: PathBuf,
+    /// 保存最新配置快照的 watch 通道发送端
+    sender: Arc<watch::Sender<Arc<Config>>>,
+    /// 按配置中的频率限制调整限额的限速器
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl SharedConfig {
+    /// 从指定路径的 TOML 文件加载配置，并记录路径以便后续重新加载
+    ///
+    /// # 参数
+    /// * `path`: 配置文件路径
+    ///
+    /// # 返回
+    /// 成功则返回 [`SharedConfig`]，否则返回加载配置时的错误
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let config = Config::from_file(&path)?;
+        let rate_limiter = Arc::new(RateLimiter::new());
+        apply_rate_limit(&rate_limiter, config.bot.rate_limit.as_ref());
+        let (sender, _) = watch::channel(Arc::new(config));
+        Ok(Self {
+            path,
+            sender: Arc::new(sender),
+            rate_limiter,
+        })
+    }
+
+    /// 获取当前生效的配置快照
+    pub fn current(&self) -> Arc<Config> {
+        self.sender.borrow().clone()
+    }
+
+    /// 获取按配置中的频率限制调整限额的限速器
+    ///
+    /// 通过 [`MilkyClient::with_rate_limiter`](crate::MilkyClient::with_rate_limiter) 设置到客户端后，
+    /// 重新加载配置时新的频率限制会立即生效。
+    pub fn rate_limiter(&self) -> Arc<RateLimiter> {
+        Arc::clone(&self.rate_limiter)
+    }
+
+    /// 订阅配置变更，每次重新加载成功后接收端都会收到新的配置快照
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.sender.subscribe()
+    }
+
+    /// 重新读取配置文件并应用新配置
+    ///
+    /// # 返回
+    /// 成功则返回 `Ok(())`；如果新配置无法加载，则返回错误，且当前配置保持不变
+    pub fn reload(&self) -> Result<()> {
+        let mut config = Config::from_file(&self.path)?;
+        let current = self.current();
+        let mut communication = current.communication.clone();
+        *communication.access_token_mut() = config.communication.access_token().map(str::to_string);
+        if communication != config.communication {
+            warn!(
+                "通信方式配置的变更需要重新创建客户端后才能生效，本次重新加载将忽略访问令牌以外的部分"
+            );
+        }
+        if communication.access_token() != current.communication.access_token() {
+            info!("访问令牌已更新");
+        }
+        config.communication = communication;
+        apply_rate_limit(&self.rate_limiter, config.bot.rate_limit.as_ref());
+        self.sender.send_replace(Arc::new(config));
+        info!("配置已从 {} 重新加载", self.path.display());
+        Ok(())
+    }
+
+    /// 在后台定期检查配置文件的修改时间，文件变化时自动重新加载
+    ///
+    /// # 参数
+    /// * `interval`: 检查间隔
+    ///
+    /// # 返回
+    /// 后台任务的 `JoinHandle`，中止该任务即可停止监视
+    pub fn watch(&self, interval: Duration) -> JoinHandle<()> {
+        let shared = self.clone();
+        runtime::spawn(async move {
+            let mut last_modified = modified_time(&shared.path);
+            let mut ticker = runtime::interval(interval);
+            loop {
+                ticker.tick().await;
+                let modified = modified_time(&shared.path);
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+                if let Err(e) = shared.reload() {
+                    error!("重新加载配置文件失败，继续使用当前配置: {e}");
+                }
+            }
+        })
+    }
+}
+
+impl TokenProvider for SharedConfig {
+    fn token(&self) -> TokenFuture<'_> {
+        let token = self
+            .current()
+            .communication
+            .access_token()
+            .map(str::to_string);
+        Box::pin(async move { Ok(token) })
+    }
+}
+
+/// 将配置中的频率限制应用到限速器中发送消息的操作上，未设置时取消限制
+fn apply_rate_limit(limiter: &RateLimiter, config: Option<&RateLimitConfig>) {
+    let limit = config.map(RateLimitConfig::to_rate_limit);
+    for action in MESSAGE_ACTIONS {
+        limiter.set_action(action, limit);
+    }
+}
+
+/// 获取文件的最后修改时间，文件不存在或无法读取时返回 `None`
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl FromStr for Config {
+    type Err = MilkyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut table: toml::Table =
+            toml::from_str(s).map_err(|e| MilkyError::Config(e.to_string()))?;
+        for (_, value) in table.iter_mut() {
+            interpolate_value(value)?;
+        }
+        // 插值后的表重新序列化时，字符串中的引号、换行等字符会被正确转义
+        let content = toml::to_string(&table).map_err(|e| MilkyError::Config(e.to_string()))?;
+        toml::from_str(&content).map_err(|e| MilkyError::Config(e.to_string()))
+    }
+}
+
+/// 对 TOML 值中的所有字符串递归进行环境变量插值
+fn interpolate_value(value: &mut toml::Value) -> Result<()> {
+    match value {
+        toml::Value::String(s) => *s = interpolate_env(s)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                interpolate_value(item)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                interpolate_value(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 将字符串中的 `${VAR}` / `${VAR:-default}` 替换为对应的环境变量值
+fn interpolate_env(input: &str) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        if let Some(stripped) = rest.strip_prefix('$') {
+            output.push('$');
+            rest = stripped;
+            continue;
+        }
+
+        let Some(body) = rest.strip_prefix('{') else {
+            output.push('$');
+            continue;
+        };
+        let end = body
+            .find('}')
+            .ok_or_else(|| MilkyError::Config("环境变量插值缺少右花括号 `}`".to_string()))?;
+        let expr = &body[..end];
+        rest = &body[end + 1..];
+
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        match (env::var(name), default) {
+            (Ok(value), Some(default)) if value.is_empty() => output.push_str(default),
+            (Ok(value), _) => output.push_str(&value),
+            (Err(_), Some(default)) => output.push_str(default),
+            (Err(_), None) => {
+                return Err(MilkyError::Config(format!("环境变量 `{name}` 未设置")));
+            }
+        }
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_with_group_overrides() {
+        let config: Config = r#"
+            [communication]
+            mode = "webhook"
+            port = 8080
+            http_endpoint = "http://127.0.0.1:3000"
+
+            [bot]
+            plugins = ["echo", "welcome"]
+
+            [groups.123456]
+            command_prefix = "!"
+            plugins = ["echo"]
+        "#
+        .parse()
+        .unwrap();
+
+        match &config.communication {
+            Communication::WebHook(wh) => {
+                assert_eq!(wh.host, "127.0.0.1");
+                assert_eq!(wh.port, 8080);
+                assert_eq!(wh.path, "/webhook");
+                assert!(wh.access_token.is_none());
+            }
+            _ => panic!("通信方式应该是 WebHook"),
+        }
+        assert_eq!(config.command_prefix_for(123456), "!");
+        assert_eq!(config.command_prefix_for(654321), "/");
+        assert!(config.is_plugin_enabled(654321, "welcome"));
+        assert!(!config.is_plugin_enabled(123456, "welcome"));
+    }
+
+    #[test]
+    fn test_reload_keeps_communication() {
+        let path = env::temp_dir().join(format!("vivian-reload-{}.toml", std::process::id()));
+        let write = |endpoint: &str, token: &str, disabled: &str| {
+            let content = format!(
+                "[communication]\nmode = \"websocket\"\nws_endpoint = \"{endpoint}\"\n\
+                 access_token = \"{token}\"\n\
+                 [bot]\ndisabled_commands = [\"{disabled}\"]\n\
+                 [bot.rate_limit]\nmax_messages = 1\nper_seconds = 60\n"
+            );
+            std::fs::write(&path, content).unwrap();
+        };
+
+        write("ws://127.0.0.1:3000", "old", "debug");
+        let shared = SharedConfig::load(&path).unwrap();
+        assert!(!shared.current().is_command_enabled(None, "debug"));
+
+        write("ws://127.0.0.1:4000", "new", "echo");
+        shared.reload().unwrap();
+        let config = shared.current();
+        assert!(config.is_command_enabled(None, "debug"));
+        assert!(!config.is_command_enabled(Some(1), "echo"));
+        match &config.communication {
+            Communication::WebSocket(ws) => {
+                assert_eq!(ws.ws_endpoint, "ws://127.0.0.1:3000");
+                assert_eq!(ws.access_token.as_deref(), Some("new"));
+            }
+            _ => panic!("通信方式应该是 WebSocket"),
+        }
+        let token = futures_util::FutureExt::now_or_never(shared.token()).unwrap();
+        assert_eq!(token.unwrap().as_deref(), Some("new"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        // SAFETY: 测试中仅设置本测试独占的环境变量
+        unsafe { env::set_var("VIVIAN_TEST_TOKEN", "secret") };
+
+        assert_eq!(
+            interpolate_env("token = \"${VIVIAN_TEST_TOKEN}\"").unwrap(),
+            "token = \"secret\""
+        );
+        assert_eq!(
+            interpolate_env("${VIVIAN_TEST_UNSET:-fallback} $$HOME $x").unwrap(),
+            "fallback $HOME $x"
+        );
+        assert!(interpolate_env("${VIVIAN_TEST_UNSET}").is_err());
+        assert!(interpolate_env("${VIVIAN_TEST_TOKEN").is_err());
+    }
+
+    #[test]
+    fn test_interpolate_parsed_values() {
+        // SAFETY: 测试中仅设置本测试独占的环境变量
+        unsafe {
+            env::set_var(
+                "VIVIAN_TEST_INJECT",
+                "secret\"\n[bot]\ncommand_prefix = \"#",
+            )
+        };
+
+        let config: Config = r#"
+            # 注释中的 ${VIVIAN_TEST_COMMENT_UNSET} 不会被插值
+            [communication]
+            mode = "websocket"
+            ws_endpoint = "ws://127.0.0.1:3000"
+            access_token = "${VIVIAN_TEST_INJECT}"
+        "#
+        .parse()
+        .unwrap();
+
+        match &config.communication {
+            Communication::WebSocket(ws) => assert_eq!(
+                ws.access_token.as_deref(),
+                Some("secret\"\n[bot]\ncommand_prefix = \"#")
+            ),
+            _ => panic!("通信方式应该是 WebSocket"),
+        }
+        assert_eq!(config.bot.command_prefix, "/");
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("redacted"));
+    }
+}
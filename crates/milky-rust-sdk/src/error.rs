@@ -4,8 +4,13 @@
 //! 以及特定于本应用程序逻辑的自定义错误。
 //! 同时，提供了一个统一的 [`Result<T>`] 类型别名，以便在整个库中方便地使用。
 
+pub mod kind;
+
+pub use kind::MilkyApiErrorKind;
+
 use reqwest;
 use thiserror::Error;
+#[cfg(feature = "websocket")]
 use tokio_tungstenite::tungstenite;
 
 /// `MilkyClient` 操作中可能发生的错误枚举。
@@ -16,6 +21,7 @@ use tokio_tungstenite::tungstenite;
 pub enum MilkyError {
     /// WebSocket 通信过程中发生的错误。
     /// 通常由底层的 `tokio-tungstenite` 库引发。
+    #[cfg(feature = "websocket")]
     #[error("WebSocket 错误: {0}")]
     WebSocket(#[from] Box<tungstenite::Error>),
 
@@ -41,12 +47,29 @@ pub enum MilkyError {
 
     /// API 请求失败，通常表示服务器成功处理了请求但返回了一个业务逻辑上的错误。
     /// 例如，权限不足、参数错误等。
-    #[error("API 请求失败: {message}")]
+    #[error("API {action} 调用失败: {message}")]
     ApiError {
+        /// 调用失败的 API 操作名称。
+        action: String,
         /// 来自服务器的错误描述信息。
         message: String,
         /// 来自服务器的特定返回码（retcode），有助于定位具体错误原因。
         retcode: Option<i64>,
+        /// 根据返回码与错误描述得出的错误类别。
+        kind: MilkyApiErrorKind,
+    },
+
+    /// API 调用成功，但响应中的数据无法解析为预期的类型。
+    /// 通常表示协议端返回的数据与协议定义不一致。
+    #[error("API {action} 的响应数据无法解析: {source}（请求参数: {request}）")]
+    Decode {
+        /// 返回该响应的 API 操作名称。
+        action: String,
+        /// 脱敏并截断后的请求参数。
+        request: String,
+        /// 解析时发生的错误。
+        #[source]
+        source: serde_json::Error,
     },
 
     /// HTTP API 请求返回了非成功状态码（例如 4xx, 5xx）。
@@ -68,6 +91,11 @@ pub enum MilkyError {
     #[error("响应超时")]
     Timeout,
 
+    /// 操作被调用方主动取消。
+    /// 例如，下载进度回调要求中止下载。
+    #[error("操作已取消")]
+    Cancelled,
+
     /// 收到了非预期的响应类型。
     /// 例如，期望一个特定的JSON结构但收到了其他格式。
     #[error("收到了非预期的响应类型")]
@@ -83,12 +111,169 @@ pub enum MilkyError {
     #[error("HTTP 请求错误: {0}")]
     Reqwest(#[from] reqwest::Error),
 
+    /// 下载的文件与协议端报告的哈希值不一致。
+    #[error("文件哈希不匹配: 期望 {expected}，实际 {actual}")]
+    HashMismatch {
+        /// 协议端报告的哈希值
+        expected: String,
+        /// 实际计算得到的哈希值
+        actual: String,
+    },
+
+    /// 下载的文件与协议端报告的大小不一致。
+    #[error("文件大小不匹配: 期望 {expected} 字节，实际 {actual} 字节")]
+    SizeMismatch {
+        /// 协议端报告的文件大小
+        expected: u64,
+        /// 实际写入的字节数
+        actual: u64,
+    },
+
+    /// API 请求被拦截器（`client::Interceptor`）拒绝。
+    #[error("API {action} 的请求被拦截器拒绝: {reason}")]
+    Rejected {
+        /// 被拒绝的 API 操作名称
+        action: String,
+        /// 拒绝的原因
+        reason: String,
+    },
+
+    /// 客户端集合（`client::ClientPool`）中没有指定的账号。
+    #[error("客户端集合中没有账号 {0}")]
+    AccountNotFound(i64),
+
+    /// 事件接收在未被关闭的情况下结束，例如连接断开后不再重连，或 WebHook 服务器出错。
+    #[error("事件接收已结束: {0}")]
+    Disconnected(String),
+
+    /// 配置文件读取、环境变量插值或解析失败时发生的错误。
+    #[error("配置错误: {0}")]
+    Config(String),
+
     #[error("内部错误: {0}")]
     Internal(String),
 }
 
+impl MilkyError {
+    /// 创建 [`MilkyError::ApiError`]，并根据返回码与错误描述得出错误类别
+    ///
+    /// # 参数
+    /// * `action`: 调用失败的 API 操作名称
+    /// * `message`: 服务端返回的错误描述
+    /// * `retcode`: 服务端返回的返回码
+    pub fn api_error(
+        action: impl Into<String>,
+        message: impl Into<String>,
+        retcode: Option<i64>,
+    ) -> Self {
+        let message = message.into();
+        MilkyError::ApiError {
+            action: action.into(),
+            kind: MilkyApiErrorKind::classify(retcode, &message),
+            message,
+            retcode,
+        }
+    }
+
+    /// 获取出错的 API 操作名称
+    ///
+    /// # 返回
+    /// 是 [`MilkyError::ApiError`]、[`MilkyError::Decode`] 或 [`MilkyError::Rejected`] 时返回操作名称，否则返回 `None`
+    pub fn action(&self) -> Option<&str> {
+        match self {
+            MilkyError::ApiError { action, .. }
+            | MilkyError::Decode { action, .. }
+            | MilkyError::Rejected { action, .. } => Some(action),
+            _ => None,
+        }
+    }
+
+    /// 获取 API 调用失败的类别
+    ///
+    /// # 返回
+    /// 是 [`MilkyError::ApiError`] 时返回其类别，否则返回 `None`
+    pub fn api_error_kind(&self) -> Option<MilkyApiErrorKind> {
+        match self {
+            MilkyError::ApiError { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// 判断错误是否为稍后重试可能成功的临时性失败
+    ///
+    /// 包括连接失败、连接断开、请求超时、HTTP 5xx，以及协议端返回的频率限制与内部错误。
+    /// 自动重试（[`RetryPolicy`](crate::client::RetryPolicy)）与文件夹下载的重试都以此为依据，
+    /// 应用也可以据此决定是否将失败的任务重新放回队列。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "websocket")]
+            MilkyError::WebSocket(_) => true,
+            #[cfg(not(target_arch = "wasm32"))]
+            MilkyError::Reqwest(e) => e.is_connect() || e.is_timeout(),
+            // 浏览器的 fetch 不区分连接失败，发送失败都视为临时性失败
+            #[cfg(target_arch = "wasm32")]
+            MilkyError::Reqwest(e) => e.is_request() || e.is_timeout(),
+            MilkyError::HttpApiError { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            MilkyError::ApiError { kind, .. } => matches!(
+                kind,
+                MilkyApiErrorKind::RateLimited | MilkyApiErrorKind::Internal
+            ),
+            MilkyError::NotConnected | MilkyError::Timeout => true,
+            _ => false,
+        }
+    }
+
+    /// 判断错误是否为重试也不会成功的永久性失败
+    ///
+    /// 包括参数错误、权限不足、对象不存在、协议端不支持该操作、响应数据无法解析、被拦截器拒绝以及配置错误等。
+    /// 与 [`is_retryable`](Self::is_retryable) 并不互补：例如被取消的操作或 I/O 错误两者都不是，需要由调用方自行判断。
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            MilkyError::HttpApiError { status, .. } => {
+                status.is_client_error() && *status != reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            MilkyError::ApiError { kind, .. } => matches!(
+                kind,
+                MilkyApiErrorKind::BadRequest
+                    | MilkyApiErrorKind::Unauthorized
+                    | MilkyApiErrorKind::PermissionDenied
+                    | MilkyApiErrorKind::NotFound
+                    | MilkyApiErrorKind::Unsupported
+            ),
+            MilkyError::UrlParse(_)
+            | MilkyError::UnsupportedScheme(_)
+            | MilkyError::Decode { .. }
+            | MilkyError::Rejected { .. }
+            | MilkyError::Config(_) => true,
+            _ => false,
+        }
+    }
+}
+
 /// 一个统一的 `Result` 类型别名，用于 `MilkyClient` 的所有操作。
 ///
 /// 它简化了函数签名，其中 `T` 是成功情况下的返回值类型，
 /// 错误类型固定为 [`MilkyError`]。
 pub type Result<T> = std::result::Result<T, MilkyError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_and_permanent() {
+        let rate_limited = MilkyError::api_error("send_group_message", "操作过于频繁", Some(1));
+        assert!(rate_limited.is_retryable());
+        assert!(!rate_limited.is_permanent());
+
+        let denied = MilkyError::api_error("set_group_name", "", Some(-403));
+        assert!(!denied.is_retryable());
+        assert!(denied.is_permanent());
+
+        assert!(MilkyError::Timeout.is_retryable());
+        assert!(!MilkyError::Cancelled.is_retryable());
+        assert!(!MilkyError::Cancelled.is_permanent());
+    }
+}
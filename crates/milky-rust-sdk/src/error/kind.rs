@@ -0,0 +1,86 @@
+//! 按类别区分 API 调用失败的原因
+
+/// API 调用失败的类别，由 [`MilkyError::ApiError`](crate::MilkyError::ApiError) 中的 `retcode` 与错误描述得出
+///
+/// Milky 协议端以负数的 `retcode` 表示协议端自身判定的错误，数值与相近含义的 HTTP 状态码相同；
+/// 正数的 `retcode` 则由 QQ 后端返回，没有统一的含义，只能根据错误描述粗略判断是否为频率限制或风控。
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MilkyApiErrorKind {
+    /// 请求参数不正确（`-400`）
+    BadRequest,
+    /// 访问令牌无效（`-401`）
+    Unauthorized,
+    /// 机器人没有执行该操作的权限，例如不是群管理员（`-403`）
+    PermissionDenied,
+    /// 操作的对象不存在，例如群、好友或消息不存在（`-404`）
+    NotFound,
+    /// 调用过于频繁（`-429`）
+    RateLimited,
+    /// 账号被风控，操作被 QQ 后端拒绝
+    RiskControlled,
+    /// 协议端内部错误（`-500`）
+    Internal,
+    /// 协议端不支持该操作（`-501`）
+    Unsupported,
+    /// 无法归类的错误
+    Other,
+}
+
+impl MilkyApiErrorKind {
+    /// 根据 `retcode` 与错误描述判断错误类别
+    ///
+    /// # 参数
+    /// * `retcode`: 服务端返回的返回码
+    /// * `message`: 服务端返回的错误描述
+    pub fn classify(retcode: Option<i64>, message: &str) -> Self {
+        match retcode {
+            Some(-400) => return Self::BadRequest,
+            Some(-401) => return Self::Unauthorized,
+            Some(-403) => return Self::PermissionDenied,
+            Some(-404) => return Self::NotFound,
+            Some(-429) => return Self::RateLimited,
+            Some(-500) => return Self::Internal,
+            Some(-501) => return Self::Unsupported,
+            _ => {}
+        }
+        let message = message.to_lowercase();
+        let contains_any = |keywords: &[&str]| keywords.iter().any(|k| message.contains(k));
+        if contains_any(&["风控", "risk"]) {
+            Self::RiskControlled
+        } else if contains_any(&["频繁", "频率", "too frequent", "rate limit"]) {
+            Self::RateLimited
+        } else if contains_any(&["权限", "permission"]) {
+            Self::PermissionDenied
+        } else if contains_any(&["不存在", "not found"]) {
+            Self::NotFound
+        } else {
+            Self::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            MilkyApiErrorKind::classify(Some(-403), "任意描述"),
+            MilkyApiErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            MilkyApiErrorKind::classify(Some(120), "发送失败，账号可能被风控"),
+            MilkyApiErrorKind::RiskControlled
+        );
+        assert_eq!(
+            MilkyApiErrorKind::classify(Some(1), "操作过于频繁"),
+            MilkyApiErrorKind::RateLimited
+        );
+        assert_eq!(
+            MilkyApiErrorKind::classify(None, "未知错误"),
+            MilkyApiErrorKind::Other
+        );
+    }
+}
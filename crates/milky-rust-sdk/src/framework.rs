@@ -0,0 +1,27 @@
+//! 构建机器人时常用的上层工具
+//!
+//! 本模块在 [`MilkyClient`](crate::MilkyClient) 之上提供了一些与具体业务无关的通用组件，
+//! 例如在事件处理函数之间共享状态的 [`Context`]、收集多步骤输入的 [`Form`]，
+//! 向多个群组广播公告的 [`Announcer`]、带确认流程的群管理命令 [`AdminToolkit`]，
+//! 基于 [`Storage`] 持久化的按群功能开关 [`FeatureFlags`]、用于平滑重启的 [`Lifecycle`]，
+//! 自动保存上传文件的 [`FileBackup`]，以及按事件类型分发事件的 [`EventHandler`]。
+
+pub mod admin;
+pub mod announcer;
+pub mod feature;
+pub mod file_backup;
+pub mod form;
+pub mod handler;
+pub mod lifecycle;
+pub mod state;
+pub mod storage;
+
+pub use admin::{AdminCommand, AdminToolkit};
+pub use announcer::{Announcer, DeliveryReport, SkipReason};
+pub use feature::FeatureFlags;
+pub use file_backup::{FileBackup, FileSource, SavedFile};
+pub use form::{Form, FormAnswers, FormOutcome, FormSession, FormSnapshot, FormStep};
+pub use handler::{EventDispatcher, EventHandler};
+pub use lifecycle::Lifecycle;
+pub use state::{Context, TypeMap};
+pub use storage::{JsonFileStorage, MemoryStorage, Storage};
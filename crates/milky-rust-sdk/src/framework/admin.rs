@@ -0,0 +1,475 @@
+//! 开箱即用的群管理命令
+//!
+//! [`AdminToolkit`] 提供了禁言、解除禁言、踢出、设置头衔和批量撤回等常用群管理命令，
+//! 并在执行前检查发送者是否为群主或管理员，再通过 [`Form`] 向发送者发起一次“确认?”交互，
+//! 只有在收到确认回复后才会真正调用对应的群组API。
+//!
+//! 支持的命令（以默认前缀 `/` 为例，目标用户可以使用 @ 或直接填写QQ号）：
+//! - `/mute @用户 10m`：禁言，时长单位支持 `s`、`m`、`h`、`d`，省略单位时视为分钟
+//! - `/unmute @用户`：解除禁言
+//! - `/kick @用户`：踢出群聊
+//! - `/title @用户 头衔`：设置专属头衔
+//! - `/recall 5 [@用户]`：撤回最近的若干条消息（最多 100 条），可选只撤回指定用户的消息
+//!
+//! 未收到回复的确认会在超时后被清理，不会一直占用内存。
+
+use crate::MilkyClient;
+use crate::api::group::Mute;
+use crate::error::Result;
+use crate::framework::form::{Form, FormSession, FormStep};
+use crate::logger::warn;
+use crate::utils::get_plain_text_from_segments;
+
+use milky_types::MessageEvent;
+use milky_types::common::MessageScene;
+use milky_types::group::GroupRole;
+use milky_types::message::in_coming::IncomingSegment;
+use milky_types::message::out_going::{OutgoingSegment, TextData};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 批量撤回时单次最多查询的历史消息数量
+const RECALL_HISTORY_LIMIT: i32 = 30;
+
+/// 批量撤回时最多撤回的消息数量
+const MAX_RECALL_COUNT: u32 = 100;
+
+/// 批量撤回时最多查询的历史消息页数，避免只撤回指定成员的消息时无限翻页
+const MAX_RECALL_PAGES: usize = 20;
+
+/// 视为确认的回复文本
+const CONFIRM_REPLIES: &[&str] = &["确认", "是", "y", "yes"];
+
+/// 解析后的群管理命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+    /// 禁言指定成员
+    Mute {
+        /// 目标成员QQ号
+        user_id: i64,
+        /// 禁言时长（秒）
+        duration: i64,
+    },
+    /// 解除指定成员的禁言
+    Unmute {
+        /// 目标成员QQ号
+        user_id: i64,
+    },
+    /// 将指定成员踢出群聊
+    Kick {
+        /// 目标成员QQ号
+        user_id: i64,
+    },
+    /// 设置指定成员的专属头衔
+    SetTitle {
+        /// 目标成员QQ号
+        user_id: i64,
+        /// 新的专属头衔
+        title: String,
+    },
+    /// 撤回最近的若干条消息
+    RecallLast {
+        /// 只撤回该成员的消息，`None` 表示不限发送者
+        user_id: Option<i64>,
+        /// 撤回的消息数量
+        count: u32,
+    },
+}
+
+impl AdminCommand {
+    /// 从消息段中解析群管理命令
+    ///
+    /// # 参数
+    /// * `segments`: 收到的消息段
+    /// * `prefix`: 命令前缀，例如 `/`
+    ///
+    /// # 返回
+    /// - 消息不是群管理命令时返回 `None`
+    /// - 命令格式错误时返回 `Some(Err(用法提示))`
+    /// - 解析成功时返回 `Some(Ok(命令))`
+    pub fn parse(
+        segments: &[IncomingSegment],
+        prefix: &str,
+    ) -> Option<std::result::Result<Self, String>> {
+        let mention = segments.iter().find_map(|segment| match segment {
+            IncomingSegment::Mention { user_id } => Some(*user_id),
+            _ => None,
+        });
+        let text = get_plain_text_from_segments(segments);
+        let body = text.trim().strip_prefix(prefix)?;
+        let mut args = body.split_whitespace();
+        let name = args.next()?;
+        let args: Vec<&str> = args.collect();
+
+        // 未使用 @ 时，第一个参数视为目标QQ号
+        let mut rest = args.as_slice();
+        let target = mention.or_else(|| {
+            let id = rest.first()?.parse().ok()?;
+            rest = &rest[1..];
+            Some(id)
+        });
+
+        let command = match name {
+            "mute" => {
+                let usage = "用法: mute <@用户|QQ号> <时长>";
+                match (target, rest.first().and_then(|d| parse_duration(d))) {
+                    (Some(user_id), Some(duration)) => Ok(AdminCommand::Mute {
+                        user_id,
+                        duration: duration.as_secs() as i64,
+                    }),
+                    _ => Err(usage.to_string()),
+                }
+            }
+            "unmute" => target
+                .map(|user_id| AdminCommand::Unmute { user_id })
+                .ok_or_else(|| "用法: unmute <@用户|QQ号>".to_string()),
+            "kick" => target
+                .map(|user_id| AdminCommand::Kick { user_id })
+                .ok_or_else(|| "用法: kick <@用户|QQ号>".to_string()),
+            "title" => match target {
+                Some(user_id) => Ok(AdminCommand::SetTitle {
+                    user_id,
+                    title: rest.join(" "),
+                }),
+                None => Err("用法: title <@用户|QQ号> [头衔]".to_string()),
+            },
+            "recall" => {
+                // recall 的第一个参数是条数，因此重新从原始参数中解析
+                let count = args
+                    .first()
+                    .and_then(|c| c.parse().ok())
+                    .filter(|c| (1..=MAX_RECALL_COUNT).contains(c));
+                let user_id = mention.or_else(|| args.get(1).and_then(|id| id.parse().ok()));
+                count
+                    .map(|count| AdminCommand::RecallLast { user_id, count })
+                    .ok_or_else(|| {
+                        format!("用法: recall <条数(1-{MAX_RECALL_COUNT})> [@用户|QQ号]")
+                    })
+            }
+            _ => return None,
+        };
+        Some(command)
+    }
+
+    /// 命令的可读描述，用于确认提示
+    fn describe(&self) -> String {
+        match self {
+            AdminCommand::Mute { user_id, duration } => {
+                format!("禁言 {user_id} {duration} 秒")
+            }
+            AdminCommand::Unmute { user_id } => format!("解除 {user_id} 的禁言"),
+            AdminCommand::Kick { user_id } => format!("将 {user_id} 踢出群聊"),
+            AdminCommand::SetTitle { user_id, title } => {
+                format!("将 {user_id} 的专属头衔设置为「{title}」")
+            }
+            AdminCommand::RecallLast {
+                user_id: Some(user_id),
+                count,
+            } => format!("撤回 {user_id} 最近的 {count} 条消息"),
+            AdminCommand::RecallLast {
+                user_id: None,
+                count,
+            } => format!("撤回最近的 {count} 条消息"),
+        }
+    }
+}
+
+/// 解析形如 `30s`、`10m`、`2h`、`1d` 的时长，省略单位时视为分钟
+///
+/// 单位无效或时长超出范围时返回 `None`
+fn parse_duration(input: &str) -> Option<Duration> {
+    let (number, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => input.split_at(pos),
+        None => (input, "m"),
+    };
+    let number: u64 = number.parse().ok()?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return None,
+    };
+    let seconds = number.checked_mul(unit_secs)?;
+    // 禁言时长以 i64 秒传递给协议端
+    i64::try_from(seconds).ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// 等待确认中的命令
+struct PendingCommand {
+    /// 待执行的命令
+    command: AdminCommand,
+    /// 发出命令的消息序列号，批量撤回时只撤回更早的消息
+    origin_seq: i64,
+    /// 确认交互的会话
+    session: FormSession,
+}
+
+/// 带权限检查与确认流程的群管理命令工具箱
+pub struct AdminToolkit {
+    /// 用于调用群组API的客户端
+    client: Arc<MilkyClient>,
+    /// 命令前缀
+    prefix: String,
+    /// 确认交互使用的表单
+    confirm_form: Form,
+    /// 以 (群号, 发送者QQ号) 为键的待确认命令
+    pending: HashMap<(i64, i64), PendingCommand>,
+}
+
+impl AdminToolkit {
+    /// 创建群管理命令工具箱，默认命令前缀为 `/`，确认超时时间为 30 秒
+    ///
+    /// # 参数
+    /// * `client`: 用于调用群组API的客户端
+    pub fn new(client: Arc<MilkyClient>) -> Self {
+        Self {
+            client,
+            prefix: "/".to_string(),
+            confirm_form: confirm_form(Duration::from_secs(30)),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// 设置命令前缀
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 设置等待确认的超时时间
+    pub fn confirm_timeout(mut self, timeout: Duration) -> Self {
+        self.confirm_form = confirm_form(timeout);
+        self
+    }
+
+    /// 处理一条收到的消息
+    ///
+    /// 非群消息、非管理命令且不属于任何确认流程的消息会被忽略。
+    ///
+    /// # 参数
+    /// * `event`: 收到的消息事件
+    ///
+    /// # 返回
+    /// 消息被工具箱处理（发起确认、完成确认或提示错误）时返回 `Ok(true)`，否则返回 `Ok(false)`
+    pub async fn handle(&mut self, event: &MessageEvent) -> Result<bool> {
+        let MessageEvent::Group(group_msg) = event else {
+            return Ok(false);
+        };
+        let group_id = group_msg.group.group_id;
+        let sender_id = group_msg.message.sender_id;
+        let key = (group_id, sender_id);
+        let pending = self.pending.remove(&key);
+        // 清理其他成员超时未回复的确认
+        self.pending
+            .retain(|_, pending| !pending.session.is_expired());
+
+        if let Some(mut pending) = pending {
+            let reply = match pending.session.handle(event) {
+                FormStep::Completed(answers) => {
+                    let answer = answers.get("confirm").unwrap_or_default();
+                    if CONFIRM_REPLIES.contains(&answer.to_lowercase().as_str()) {
+                        match self
+                            .execute(group_id, &pending.command, pending.origin_seq)
+                            .await
+                        {
+                            Ok(()) => format!("已{}", pending.command.describe()),
+                            Err(e) => {
+                                warn!("执行群管理命令失败: {e}");
+                                format!("执行失败: {e}")
+                            }
+                        }
+                    } else {
+                        "已取消".to_string()
+                    }
+                }
+                FormStep::Expired => "确认超时，已取消".to_string(),
+                _ => "已取消".to_string(),
+            };
+            self.reply(group_id, reply).await?;
+            return Ok(true);
+        }
+
+        let command = match AdminCommand::parse(&group_msg.message.segments, &self.prefix) {
+            None => return Ok(false),
+            Some(Err(usage)) => {
+                self.reply(group_id, usage).await?;
+                return Ok(true);
+            }
+            Some(Ok(command)) => command,
+        };
+        if !matches!(
+            group_msg.group_member.role,
+            GroupRole::Owner | GroupRole::Admin
+        ) {
+            self.reply(
+                group_id,
+                "权限不足，仅群主或管理员可以使用该命令".to_string(),
+            )
+            .await?;
+            return Ok(true);
+        }
+
+        let session = self
+            .confirm_form
+            .start(MessageScene::Group, group_id, sender_id);
+        let prompt = format!(
+            "确认{}? 回复“确认”继续，回复其他内容取消",
+            command.describe()
+        );
+        self.pending.insert(
+            key,
+            PendingCommand {
+                command,
+                origin_seq: group_msg.message.message_seq,
+                session,
+            },
+        );
+        self.reply(group_id, prompt).await?;
+        Ok(true)
+    }
+
+    /// 调用群组API执行命令
+    async fn execute(&self, group_id: i64, command: &AdminCommand, origin_seq: i64) -> Result<()> {
+        match command {
+            AdminCommand::Mute { user_id, duration } => {
+                self.client
+                    .mute_group_member(group_id, *user_id, Mute::for_seconds(*duration as u64))
+                    .await
+            }
+            AdminCommand::Unmute { user_id } => {
+                self.client
+                    .mute_group_member(group_id, *user_id, Mute::unmute())
+                    .await
+            }
+            AdminCommand::Kick { user_id } => {
+                self.client
+                    .kick_group_member(group_id, *user_id, Some(false))
+                    .await
+            }
+            AdminCommand::SetTitle { user_id, title } => {
+                self.client
+                    .set_group_member_special_title(group_id, *user_id, title.clone())
+                    .await
+            }
+            AdminCommand::RecallLast { user_id, count } => {
+                let count = *count as usize;
+                let mut targets: Vec<i64> = Vec::new();
+                let mut start_seq = origin_seq;
+                for _ in 0..MAX_RECALL_PAGES {
+                    let history = self
+                        .client
+                        .get_history_messages(
+                            MessageScene::Group,
+                            group_id,
+                            Some(start_seq),
+                            Some(RECALL_HISTORY_LIMIT),
+                        )
+                        .await?;
+                    let mut page: Vec<i64> = history
+                        .messages
+                        .iter()
+                        .filter(|m| m.message_seq < origin_seq)
+                        .filter(|m| user_id.is_none_or(|id| m.sender_id == id))
+                        .map(|m| m.message_seq)
+                        .filter(|seq| !targets.contains(seq))
+                        .collect();
+                    page.sort_unstable_by(|a, b| b.cmp(a));
+                    targets.extend(page);
+                    match history.next_message_seq {
+                        Some(next) if targets.len() < count && next < start_seq => {
+                            start_seq = next;
+                        }
+                        _ => break,
+                    }
+                }
+                for message_seq in targets.into_iter().take(count) {
+                    self.client
+                        .recall_group_message(group_id, message_seq)
+                        .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 在群内回复一段文本
+    async fn reply(&self, group_id: i64, text: String) -> Result<()> {
+        let message = vec![OutgoingSegment::Text(TextData { text })];
+        self.client.send_group_message(group_id, message).await?;
+        Ok(())
+    }
+}
+
+/// 创建只有一个确认问题的表单
+fn confirm_form(timeout: Duration) -> Form {
+    Form::new()
+        .question("confirm", "确认?")
+        .timeout(timeout)
+        .cancel_keyword(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> IncomingSegment {
+        IncomingSegment::Text {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("10"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1w"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("18446744073709551615d"), None);
+        assert_eq!(parse_duration("9223372036854775807m"), None);
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let segments = vec![
+            text("/mute "),
+            IncomingSegment::Mention { user_id: 10001 },
+            text(" 10m"),
+        ];
+        assert_eq!(
+            AdminCommand::parse(&segments, "/"),
+            Some(Ok(AdminCommand::Mute {
+                user_id: 10001,
+                duration: 600,
+            }))
+        );
+
+        assert_eq!(
+            AdminCommand::parse(&[text("/title 10001 最强王者")], "/"),
+            Some(Ok(AdminCommand::SetTitle {
+                user_id: 10001,
+                title: "最强王者".to_string(),
+            }))
+        );
+        assert_eq!(
+            AdminCommand::parse(&[text("/recall 5 10001")], "/"),
+            Some(Ok(AdminCommand::RecallLast {
+                user_id: Some(10001),
+                count: 5,
+            }))
+        );
+        assert!(matches!(
+            AdminCommand::parse(&[text("/recall 500")], "/"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            AdminCommand::parse(&[text("/kick")], "/"),
+            Some(Err(_))
+        ));
+        assert_eq!(AdminCommand::parse(&[text("/help")], "/"), None);
+        assert_eq!(AdminCommand::parse(&[text("mute 10001 1m")], "/"), None);
+    }
+}
@@ -0,0 +1,245 @@
+//! 面向多个群组的公告广播器
+//!
+//! [`Announcer`] 用于将同一条消息依次发送到多个群组，适合在数十个群中运行同一个机器人的场景。
+//! 它会：
+//! - 在两次发送之间等待固定间隔，避免触发平台风控
+//! - 遵守每个群每天的发送上限
+//! - 在夜间免打扰时段内跳过发送
+//! - 对网络错误、服务端 5xx 等临时性失败进行有限次数的重试
+//!
+//! 每次广播结束后返回一份 [`DeliveryReport`]，列出成功、跳过和失败的群组。
+
+use crate::MilkyClient;
+use crate::error::MilkyError;
+use crate::logger::{info, warn};
+use crate::runtime;
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use milky_types::message::out_going::OutgoingSegment;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 群组被跳过发送的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// 当前处于免打扰时段
+    QuietHours,
+    /// 该群今日的发送次数已达到上限
+    DailyCapReached,
+}
+
+/// 单次广播的投递报告
+#[derive(Debug, Default)]
+pub struct DeliveryReport {
+    /// 发送成功的群组及对应的消息序列号
+    pub delivered: Vec<(i64, i64)>,
+    /// 被跳过的群组及跳过原因
+    pub skipped: Vec<(i64, SkipReason)>,
+    /// 重试后仍发送失败的群组及最后一次的错误
+    pub failed: Vec<(i64, MilkyError)>,
+}
+
+impl DeliveryReport {
+    /// 是否所有目标群组都发送成功
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// 公告广播器
+pub struct Announcer {
+    /// 用于发送消息的客户端
+    client: Arc<MilkyClient>,
+    /// 两次发送之间的间隔
+    send_interval: Duration,
+    /// 每个群每天最多发送的公告数量，`None` 表示不限制
+    daily_cap: Option<u32>,
+    /// 免打扰时段的起止时间（本地时间），允许跨越午夜
+    quiet_hours: Option<(NaiveTime, NaiveTime)>,
+    /// 临时性失败的最大重试次数
+    max_retries: u32,
+    /// 首次重试前的等待时间，之后每次翻倍
+    retry_delay: Duration,
+    /// 每个群当天已发送的数量
+    sent_today: Mutex<HashMap<i64, (NaiveDate, u32)>>,
+}
+
+impl Announcer {
+    /// 创建一个新的公告广播器
+    ///
+    /// 默认发送间隔为 1 秒，不限制每日发送量，无免打扰时段，临时性失败最多重试 2 次。
+    ///
+    /// # 参数
+    /// * `client`: 用于发送消息的客户端
+    pub fn new(client: Arc<MilkyClient>) -> Self {
+        Self {
+            client,
+            send_interval: Duration::from_secs(1),
+            daily_cap: None,
+            quiet_hours: None,
+            max_retries: 2,
+            retry_delay: Duration::from_secs(2),
+            sent_today: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 设置两次发送之间的间隔
+    pub fn send_interval(mut self, interval: Duration) -> Self {
+        self.send_interval = interval;
+        self
+    }
+
+    /// 设置每个群每天最多发送的公告数量
+    pub fn daily_cap(mut self, cap: u32) -> Self {
+        self.daily_cap = Some(cap);
+        self
+    }
+
+    /// 设置免打扰时段（本地时间），例如 `23:00` 至 `08:00`
+    pub fn quiet_hours(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.quiet_hours = Some((start, end));
+        self
+    }
+
+    /// 设置临时性失败的重试策略
+    ///
+    /// # 参数
+    /// * `max_retries`: 最大重试次数
+    /// * `retry_delay`: 首次重试前的等待时间，之后每次翻倍
+    pub fn retry(mut self, max_retries: u32, retry_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// 将消息依次发送到目标群组
+    ///
+    /// # 参数
+    /// * `message`: 要发送的消息内容
+    /// * `group_ids`: 目标群号列表
+    ///
+    /// # 返回
+    /// 本次广播的 [`DeliveryReport`]
+    pub async fn broadcast(
+        &self,
+        message: Vec<OutgoingSegment>,
+        group_ids: &[i64],
+    ) -> DeliveryReport {
+        let mut report = DeliveryReport::default();
+        let mut is_first_send = true;
+
+        for &group_id in group_ids {
+            if let Some((start, end)) = self.quiet_hours
+                && in_quiet_hours(Local::now().time(), start, end)
+            {
+                report.skipped.push((group_id, SkipReason::QuietHours));
+                continue;
+            }
+            if !self.try_reserve(group_id) {
+                report.skipped.push((group_id, SkipReason::DailyCapReached));
+                continue;
+            }
+            if !is_first_send {
+                runtime::sleep(self.send_interval).await;
+            }
+            is_first_send = false;
+
+            match self.send_with_retry(group_id, message.clone()).await {
+                Ok(message_seq) => report.delivered.push((group_id, message_seq)),
+                Err(e) => {
+                    warn!("向群 {group_id} 发送公告失败: {e}");
+                    self.release(group_id);
+                    report.failed.push((group_id, e));
+                }
+            }
+        }
+
+        info!(
+            "公告广播完成: 成功 {} 个，跳过 {} 个，失败 {} 个",
+            report.delivered.len(),
+            report.skipped.len(),
+            report.failed.len()
+        );
+        report
+    }
+
+    /// 发送消息，遇到临时性失败时按指数退避重试
+    async fn send_with_retry(
+        &self,
+        group_id: i64,
+        message: Vec<OutgoingSegment>,
+    ) -> crate::Result<i64> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            match self
+                .client
+                .send_group_message(group_id, message.clone())
+                .await
+            {
+                Ok(resp) => return Ok(resp.message_seq),
+                Err(e) if attempt < self.max_retries && e.is_retryable() => {
+                    attempt += 1;
+                    warn!("向群 {group_id} 发送公告失败，{delay:?} 后进行第 {attempt} 次重试: {e}");
+                    runtime::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 为群组占用一次当日发送额度，额度已满时返回 `false`
+    fn try_reserve(&self, group_id: i64) -> bool {
+        let today = Local::now().date_naive();
+        let mut sent = self.sent_today.lock().unwrap();
+        let entry = sent.entry(group_id).or_insert((today, 0));
+        if entry.0 != today {
+            *entry = (today, 0);
+        }
+        if self.daily_cap.is_some_and(|cap| entry.1 >= cap) {
+            return false;
+        }
+        entry.1 += 1;
+        true
+    }
+
+    /// 发送失败时归还占用的发送额度
+    fn release(&self, group_id: i64) {
+        if let Some(entry) = self.sent_today.lock().unwrap().get_mut(&group_id) {
+            entry.1 = entry.1.saturating_sub(1);
+        }
+    }
+}
+
+/// 判断时间是否处于免打扰时段内，支持跨越午夜的时段
+fn in_quiet_hours(now: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_in_quiet_hours() {
+        // 跨越午夜的时段
+        assert!(in_quiet_hours(time(23, 30), time(23, 0), time(8, 0)));
+        assert!(in_quiet_hours(time(3, 0), time(23, 0), time(8, 0)));
+        assert!(!in_quiet_hours(time(8, 0), time(23, 0), time(8, 0)));
+        assert!(!in_quiet_hours(time(12, 0), time(23, 0), time(8, 0)));
+
+        // 同一天内的时段
+        assert!(in_quiet_hours(time(13, 0), time(12, 0), time(14, 0)));
+        assert!(!in_quiet_hours(time(14, 0), time(12, 0), time(14, 0)));
+    }
+}
@@ -0,0 +1,261 @@
+//! 按群启用或禁用的功能开关
+//!
+//! [`FeatureFlags`] 记录每个群组对各项功能（插件或命令）的启用状态，并通过 [`Storage`] 持久化，
+//! 使同一个机器人实例可以在不同的群中提供不同的功能。未单独设置的群组使用创建时给定的默认值。
+//!
+//! 群主或管理员可以在群内使用以下命令管理功能开关（以默认前缀 `/` 为例）：
+//! - `/feature enable welcome`：启用 `welcome` 功能
+//! - `/feature disable welcome`：禁用 `welcome` 功能
+//! - `/feature list`：列出本群已知功能的启用状态
+
+use crate::error::Result;
+use crate::framework::storage::Storage;
+use crate::utils::get_plain_text_from_segments;
+
+use milky_types::MessageEvent;
+use milky_types::group::GroupRole;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+/// 按群持久化的功能开关
+pub struct FeatureFlags<S: Storage> {
+    /// 持久化存储
+    storage: S,
+    /// 各功能的默认启用状态
+    defaults: BTreeMap<String, bool>,
+    /// 已从存储中加载的群组开关
+    cache: Mutex<HashMap<i64, BTreeMap<String, bool>>>,
+    /// 串行化开关的修改，避免并发的读取-修改-写入相互覆盖
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl<S: Storage> FeatureFlags<S> {
+    /// 创建功能开关管理器
+    ///
+    /// # 参数
+    /// * `storage`: 用于持久化开关状态的存储
+    /// * `defaults`: 各功能的名称及其默认启用状态，未在此列出的功能默认启用
+    pub fn new<I, K>(storage: S, defaults: I) -> Self
+    where
+        I: IntoIterator<Item = (K, bool)>,
+        K: Into<String>,
+    {
+        Self {
+            storage,
+            defaults: defaults.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+            cache: Mutex::new(HashMap::new()),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 判断功能在指定群组中是否启用
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    /// * `feature`: 功能名称
+    pub async fn is_enabled(&self, group_id: i64, feature: &str) -> Result<bool> {
+        let overrides = self.load(group_id).await?;
+        Ok(overrides
+            .get(feature)
+            .or_else(|| self.defaults.get(feature))
+            .copied()
+            .unwrap_or(true))
+    }
+
+    /// 设置功能在指定群组中的启用状态并持久化
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    /// * `feature`: 功能名称
+    /// * `enabled`: 是否启用
+    pub async fn set(&self, group_id: i64, feature: &str, enabled: bool) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let mut overrides = self.load(group_id).await?;
+        overrides.insert(feature.to_string(), enabled);
+        self.storage
+            .set(&storage_key(group_id), serde_json::to_string(&overrides)?)
+            .await?;
+        self.cache.lock().unwrap().insert(group_id, overrides);
+        Ok(())
+    }
+
+    /// 列出指定群组中所有已知功能的启用状态
+    ///
+    /// # 参数
+    /// * `group_id`: 群号
+    pub async fn list(&self, group_id: i64) -> Result<BTreeMap<String, bool>> {
+        let mut features = self.defaults.clone();
+        features.extend(self.load(group_id).await?);
+        Ok(features)
+    }
+
+    /// 处理群内的 `feature` 管理命令
+    ///
+    /// # 参数
+    /// * `event`: 收到的消息事件
+    /// * `prefix`: 命令前缀，例如 `/`
+    ///
+    /// # 返回
+    /// 消息是 `feature` 命令时返回 `Ok(Some(回复文本))`，调用方应将其发送到群内；否则返回 `Ok(None)`
+    pub async fn handle_command(
+        &self,
+        event: &MessageEvent,
+        prefix: &str,
+    ) -> Result<Option<String>> {
+        let MessageEvent::Group(group_msg) = event else {
+            return Ok(None);
+        };
+        let text = get_plain_text_from_segments(&group_msg.message.segments);
+        let Some(body) = text.trim().strip_prefix(prefix) else {
+            return Ok(None);
+        };
+        let mut args = body.split_whitespace();
+        if args.next() != Some("feature") {
+            return Ok(None);
+        }
+        if !matches!(
+            group_msg.group_member.role,
+            GroupRole::Owner | GroupRole::Admin
+        ) {
+            return Ok(Some("权限不足，仅群主或管理员可以管理功能开关".to_string()));
+        }
+
+        let group_id = group_msg.group.group_id;
+        let reply = match (args.next(), args.next()) {
+            (Some("enable"), Some(feature)) => {
+                self.set(group_id, feature, true).await?;
+                format!("已在本群启用 {feature}")
+            }
+            (Some("disable"), Some(feature)) => {
+                self.set(group_id, feature, false).await?;
+                format!("已在本群禁用 {feature}")
+            }
+            (Some("list"), None) => {
+                let features = self.list(group_id).await?;
+                if features.is_empty() {
+                    "本群没有已知的功能".to_string()
+                } else {
+                    features
+                        .iter()
+                        .map(|(name, enabled)| {
+                            format!("{name}: {}", if *enabled { "启用" } else { "禁用" })
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+            _ => "用法: feature <enable|disable> <功能名> 或 feature list".to_string(),
+        };
+        Ok(Some(reply))
+    }
+
+    /// 读取指定群组的开关设置，优先使用缓存
+    async fn load(&self, group_id: i64) -> Result<BTreeMap<String, bool>> {
+        if let Some(overrides) = self.cache.lock().unwrap().get(&group_id) {
+            return Ok(overrides.clone());
+        }
+        let overrides = match self.storage.get(&storage_key(group_id)).await? {
+            Some(value) => serde_json::from_str(&value)?,
+            None => BTreeMap::new(),
+        };
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(group_id, overrides.clone());
+        Ok(overrides)
+    }
+}
+
+/// 分发器检查功能开关时使用的接口，使 [`EventDispatcher`](crate::framework::EventDispatcher)
+/// 不必携带存储的类型参数
+pub(crate) trait FeatureGate: Send + Sync {
+    /// 判断功能在指定群组中是否启用
+    fn is_enabled<'a>(
+        &'a self,
+        group_id: i64,
+        feature: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<bool>> + Send + 'a>>;
+}
+
+impl<S: Storage> FeatureGate for FeatureFlags<S> {
+    fn is_enabled<'a>(
+        &'a self,
+        group_id: i64,
+        feature: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<bool>> + Send + 'a>> {
+        Box::pin(FeatureFlags::is_enabled(self, group_id, feature))
+    }
+}
+
+/// 群组开关在存储中的键
+fn storage_key(group_id: i64) -> String {
+    format!("feature:{group_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::storage::MemoryStorage;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_feature_flags() {
+        let flags = FeatureFlags::new(MemoryStorage::new(), [("welcome", false), ("echo", true)]);
+        assert!(!flags.is_enabled(1, "welcome").await.unwrap());
+        assert!(flags.is_enabled(1, "unknown").await.unwrap());
+
+        flags.set(1, "welcome", true).await.unwrap();
+        flags.set(1, "echo", false).await.unwrap();
+        assert!(flags.is_enabled(1, "welcome").await.unwrap());
+        assert!(!flags.is_enabled(2, "welcome").await.unwrap());
+
+        // 重新创建管理器后仍能从存储中读取开关状态
+        let flags = FeatureFlags::new(flags.storage, [("welcome", false)]);
+        assert!(!flags.is_enabled(1, "echo").await.unwrap());
+        assert_eq!(
+            flags.list(1).await.unwrap(),
+            BTreeMap::from([("echo".to_string(), false), ("welcome".to_string(), true)])
+        );
+    }
+
+    /// 每次读写前都让出执行权的存储，使并发的修改交错执行
+    #[derive(Default)]
+    struct YieldingStorage(MemoryStorage);
+
+    impl Storage for YieldingStorage {
+        async fn get(&self, key: &str) -> Result<Option<String>> {
+            tokio::task::yield_now().await;
+            self.0.get(key).await
+        }
+
+        async fn set(&self, key: &str, value: String) -> Result<()> {
+            tokio::task::yield_now().await;
+            self.0.set(key, value).await
+        }
+
+        async fn remove(&self, key: &str) -> Result<()> {
+            self.0.remove(key).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_set() {
+        let storage = Arc::new(YieldingStorage::default());
+        let flags = Arc::new(FeatureFlags::new(Arc::clone(&storage), [("f0", true)]));
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                let flags = Arc::clone(&flags);
+                tokio::spawn(async move { flags.set(1, &format!("f{i}"), false).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        // 每次修改都基于上一次写入的结果，存储中保留了全部开关
+        let reloaded = FeatureFlags::new(storage, [("f0", true)]);
+        assert_eq!(reloaded.list(1).await.unwrap().len(), 16);
+    }
+}
@@ -0,0 +1,272 @@
+//! 自动保存好友与群文件的备份管线
+//!
+//! [`FileBackup`] 处理 [`FriendFileUpload`](EventKind::FriendFileUpload) 与
+//! [`GroupFileUpload`](EventKind::GroupFileUpload) 事件：获取文件的下载链接，
+//! 将文件保存到指定目录，并在保存成功后调用注册的回调。目录结构如下：
+//! - 好友文件：`<目录>/friend/<好友QQ号>/<文件名>`
+//! - 群文件：`<目录>/group/<群号>/<文件名>`
+//!
+//! 好友文件会使用事件中的 TriSHA1 哈希值进行校验，群文件上传事件不含哈希值，只校验文件大小。
+//! 同名文件已存在时，新文件会被命名为 `名称 (1).扩展名` 等，文件名在下载前即以独占方式创建，
+//! 同时保存多个同名文件也不会相互覆盖。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! # use milky_rust_sdk::framework::FileBackup;
+//! # use std::sync::Arc;
+//! # async fn run(client: Arc<MilkyClient>, mut rx: tokio::sync::mpsc::Receiver<milky_types::Event>) {
+//! let backup = FileBackup::new(client, "./backup")
+//!     .on_saved(|saved| println!("已保存 {}", saved.path.display()));
+//! while let Some(event) = rx.recv().await {
+//!     if let Err(e) = backup.handle_event(&event).await {
+//!         eprintln!("保存文件失败: {e}");
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::MilkyClient;
+use crate::api::download::DownloadedFile;
+use crate::error::{MilkyError, Result};
+use crate::logger::info;
+
+use milky_types::{Event, EventKind};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 文件的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSource {
+    /// 好友聊天中上传的文件
+    Friend {
+        /// 好友的QQ号
+        user_id: i64,
+    },
+    /// 群聊中上传的文件
+    Group {
+        /// 群号
+        group_id: i64,
+        /// 上传者的QQ号
+        user_id: i64,
+    },
+}
+
+/// 已保存到本地的文件
+#[derive(Debug, Clone)]
+pub struct SavedFile {
+    /// 文件的来源
+    pub source: FileSource,
+    /// 文件ID
+    pub file_id: String,
+    /// 上传时的文件名
+    pub file_name: String,
+    /// 保存到的本地路径
+    pub path: PathBuf,
+    /// 下载结果
+    pub download: DownloadedFile,
+}
+
+/// 文件保存成功后的回调
+type SavedCallback = Arc<dyn Fn(&SavedFile) + Send + Sync>;
+
+/// 自动保存上传文件的备份管线
+pub struct FileBackup {
+    /// 用于获取下载链接的客户端
+    client: Arc<MilkyClient>,
+    /// 保存文件的根目录
+    dir: PathBuf,
+    /// 是否保存机器人自己上传的文件
+    include_self: bool,
+    /// 文件保存成功后的回调
+    on_saved: Option<SavedCallback>,
+}
+
+impl FileBackup {
+    /// 创建备份管线
+    ///
+    /// 默认不保存机器人自己上传的好友文件。
+    ///
+    /// # 参数
+    /// * `client`: 用于获取下载链接的客户端
+    /// * `dir`: 保存文件的根目录，不存在时会自动创建
+    pub fn new(client: Arc<MilkyClient>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            dir: dir.into(),
+            include_self: false,
+            on_saved: None,
+        }
+    }
+
+    /// 设置是否保存机器人自己上传的好友文件
+    pub fn include_self(mut self, include: bool) -> Self {
+        self.include_self = include;
+        self
+    }
+
+    /// 设置文件保存成功后的回调
+    pub fn on_saved<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&SavedFile) + Send + Sync + 'static,
+    {
+        self.on_saved = Some(Arc::new(callback));
+        self
+    }
+
+    /// 处理一个事件，若为文件上传事件则保存对应的文件
+    ///
+    /// # 参数
+    /// * `event`: 收到的事件
+    ///
+    /// # 返回
+    /// 保存成功则返回 [`SavedFile`]；事件不是文件上传事件或被忽略时返回 `None`
+    pub async fn handle_event(&self, event: &Event) -> Result<Option<SavedFile>> {
+        let (source, file_id, file_name, file_size, url, file_hash) = match &event.kind {
+            EventKind::FriendFileUpload {
+                user_id,
+                file_id,
+                file_name,
+                file_size,
+                file_hash,
+                is_self,
+            } => {
+                if *is_self && !self.include_self {
+                    return Ok(None);
+                }
+                let url = self
+                    .client
+                    .get_private_file_download_url(*user_id, file_id.clone(), file_hash.clone())
+                    .await?
+                    .download_url;
+                let source = FileSource::Friend { user_id: *user_id };
+                (source, file_id, file_name, file_size, url, Some(file_hash))
+            }
+            EventKind::GroupFileUpload {
+                group_id,
+                user_id,
+                file_id,
+                file_name,
+                file_size,
+            } => {
+                let url = self
+                    .client
+                    .get_group_file_download_url(*group_id, file_id.clone())
+                    .await?
+                    .download_url;
+                let source = FileSource::Group {
+                    group_id: *group_id,
+                    user_id: *user_id,
+                };
+                (source, file_id, file_name, file_size, url, None)
+            }
+            _ => return Ok(None),
+        };
+
+        let dir = self.dir.join(source_dir(&source));
+        tokio::fs::create_dir_all(&dir).await?;
+        let path = reserve_path(&dir, &sanitize_file_name(file_name)).await?;
+        let download = match file_hash {
+            Some(hash) => self.client.download_verified(&url, &path, hash).await?,
+            None => self.client.download_to(&url, &path).await?,
+        };
+        // 协议端未报告大小时不做校验
+        if let Ok(expected) = u64::try_from(*file_size)
+            && expected > 0
+            && download.size != expected
+        {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Err(MilkyError::SizeMismatch {
+                expected,
+                actual: download.size,
+            });
+        }
+        info!("已保存文件 {file_name} 至 {}", path.display());
+
+        let saved = SavedFile {
+            source,
+            file_id: file_id.clone(),
+            file_name: file_name.clone(),
+            path,
+            download,
+        };
+        if let Some(callback) = &self.on_saved {
+            callback(&saved);
+        }
+        Ok(Some(saved))
+    }
+}
+
+/// 文件来源对应的子目录
+fn source_dir(source: &FileSource) -> PathBuf {
+    match source {
+        FileSource::Friend { user_id } => Path::new("friend").join(user_id.to_string()),
+        FileSource::Group { group_id, .. } => Path::new("group").join(group_id.to_string()),
+    }
+}
+
+/// 将文件名中的路径分隔符替换为 `_`，避免写到目标目录以外
+fn sanitize_file_name(name: &str) -> String {
+    let name = name.replace(['/', '\\'], "_");
+    match name.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => name,
+    }
+}
+
+/// 在目录中以独占方式创建一个尚不存在的文件并返回其路径，重名时依次追加 ` (1)`、` (2)` 等
+///
+/// 检查与创建是同一个原子操作，同时保存的同名文件会得到不同的路径。
+async fn reserve_path(dir: &Path, file_name: &str) -> io::Result<PathBuf> {
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (file_name, String::new()),
+    };
+    for n in 0u32.. {
+        let path = match n {
+            0 => dir.join(file_name),
+            n => dir.join(format!("{stem} ({n}){ext}")),
+        };
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_target_path() {
+        assert_eq!(sanitize_file_name("../../etc/passwd"), ".._.._etc_passwd");
+        assert_eq!(sanitize_file_name(".."), "_");
+
+        let dir = std::env::temp_dir().join(format!("milky-backup-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        assert_eq!(
+            reserve_path(&dir, "a.txt").await.unwrap(),
+            dir.join("a.txt")
+        );
+        tokio::fs::write(dir.join("a (1).txt"), b"2").await.unwrap();
+        assert_eq!(
+            reserve_path(&dir, "a.txt").await.unwrap(),
+            dir.join("a (2).txt")
+        );
+
+        // 同时保存的同名文件不会得到相同的路径
+        let paths = futures_util::future::join_all((0..8).map(|_| reserve_path(&dir, "b"))).await;
+        let paths: std::collections::HashSet<_> =
+            paths.into_iter().map(|path| path.unwrap()).collect();
+        assert_eq!(paths.len(), 8);
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
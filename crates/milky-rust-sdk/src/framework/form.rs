@@ -0,0 +1,616 @@
+//! 交互式多步骤表单
+//!
+//! [`Form`] 定义了一组按顺序提出的问题，每个问题可以附带校验函数。
+//! 调用 [`Form::start`] 会针对某个用户创建一个 [`FormSession`]，之后将收到的消息事件依次交给
+//! [`FormSession::handle`]，根据返回的 [`FormStep`] 决定下一步操作（发送下一道问题、提示重新输入、
+//! 完成或超时），最终得到包含所有回答的 [`FormAnswers`]。
+//!
+//! 会话只处理来自同一场景、同一会话对象、同一发送者的消息，其他消息会返回 [`FormStep::Ignored`]，
+//! 因此可以直接在现有的事件循环中使用，常用于注册、举报等需要收集多项信息的场景。
+//!
+//! 也可以调用 [`Form::run`]，由表单自己通过 [`MilkyClient::wait_for`] 的订阅等待回答、发送问题与校验提示，
+//! 并将回答转换为以问题的键为字段名的类型：
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! use milky_rust_sdk::framework::form::{Form, FormOutcome};
+//! use milky_types::common::MessageScene;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Registration {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! # async fn run(client: MilkyClient) -> milky_rust_sdk::Result<()> {
+//! let form = Form::new()
+//!     .question("name", "请输入昵称")
+//!     .question_with("age", "请输入年龄", |s| {
+//!         s.parse::<u32>().map(|_| ()).map_err(|_| "年龄必须是数字".to_string())
+//!     });
+//! match form.run::<Registration>(&client, MessageScene::Friend, 10001, 10001).await? {
+//!     FormOutcome::Completed(registration) => println!("{} 已注册", registration.name),
+//!     FormOutcome::Cancelled | FormOutcome::Expired => {}
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::MilkyClient;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::client::subscribe::recv_matching;
+use crate::error::{MilkyError, Result};
+use crate::utils::get_plain_text_from_segments;
+
+use milky_types::MessageEvent;
+use milky_types::common::MessageScene;
+use milky_types::message::out_going::{OutgoingSegment, TextData};
+use serde::de::value::MapDeserializer;
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, forward_to_deserialize_any};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 问题回答的校验函数，校验失败时返回提示给用户的错误信息
+pub type Validator = Arc<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// 表单中的单个问题
+#[derive(Clone)]
+struct Question {
+    /// 回答在 [`FormAnswers`] 中的键
+    key: String,
+    /// 发送给用户的问题文本
+    prompt: String,
+    /// 可选的校验函数
+    validator: Option<Validator>,
+}
+
+/// 多步骤表单的定义
+///
+/// 表单定义本身不包含任何会话状态，可以在多个用户之间复用。
+#[derive(Clone)]
+pub struct Form {
+    /// 按顺序提出的问题列表
+    questions: Vec<Question>,
+    /// 等待每个问题回答的超时时间
+    timeout: Duration,
+    /// 用户发送该文本时取消表单
+    cancel_keyword: Option<String>,
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self {
+            questions: Vec::new(),
+            timeout: Duration::from_secs(60),
+            cancel_keyword: Some("取消".to_string()),
+        }
+    }
+}
+
+impl Form {
+    /// 创建一个空表单，默认每个问题的超时时间为 60 秒，取消关键字为 `取消`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个不做校验的问题
+    ///
+    /// # 参数
+    /// * `key`: 回答在 [`FormAnswers`] 中的键
+    /// * `prompt`: 发送给用户的问题文本
+    pub fn question(mut self, key: impl Into<String>, prompt: impl Into<String>) -> Self {
+        self.questions.push(Question {
+            key: key.into(),
+            prompt: prompt.into(),
+            validator: None,
+        });
+        self
+    }
+
+    /// 添加一个带校验函数的问题
+    ///
+    /// # 参数
+    /// * `key`: 回答在 [`FormAnswers`] 中的键
+    /// * `prompt`: 发送给用户的问题文本
+    /// * `validator`: 校验函数，返回 `Err(提示)` 时会要求用户重新回答
+    pub fn question_with<F>(
+        mut self,
+        key: impl Into<String>,
+        prompt: impl Into<String>,
+        validator: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        self.questions.push(Question {
+            key: key.into(),
+            prompt: prompt.into(),
+            validator: Some(Arc::new(validator)),
+        });
+        self
+    }
+
+    /// 设置等待每个问题回答的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置取消关键字，传入 `None` 表示不允许取消
+    pub fn cancel_keyword(mut self, keyword: Option<String>) -> Self {
+        self.cancel_keyword = keyword;
+        self
+    }
+
+    /// 针对指定用户开始填写表单
+    ///
+    /// # 参数
+    /// * `message_scene`: 填写表单所在的消息场景
+    /// * `peer_id`: 好友QQ号或群号
+    /// * `user_id`: 填写表单的用户QQ号
+    ///
+    /// # 返回
+    /// 新的 [`FormSession`]，通过 [`FormSession::prompt`] 获取第一道问题
+    pub fn start(&self, message_scene: MessageScene, peer_id: i64, user_id: i64) -> FormSession {
+        FormSession {
+            form: self.clone(),
+            message_scene,
+            peer_id,
+            user_id,
+            current: 0,
+            answers: HashMap::new(),
+            deadline: Instant::now() + self.timeout,
+        }
+    }
+
+    /// 向用户依次发送问题并等待回答，直到表单完成、被取消或超时
+    ///
+    /// 回答通过事件订阅获取，需要已调用 [`connect_events`](MilkyClient::connect_events)。
+    /// 回答未通过校验时发送校验提示，并等待用户重新回答当前问题。
+    ///
+    /// # 参数
+    /// * `client`: 用于发送问题与接收回答的客户端
+    /// * `message_scene`: 填写表单所在的消息场景
+    /// * `peer_id`: 好友QQ号或群号
+    /// * `user_id`: 填写表单的用户QQ号
+    ///
+    /// # 返回
+    /// 表单的结果，完成时包含按 [`FormAnswers::deserialize`] 转换后的回答；
+    /// 发送消息失败或回答无法转换为 `T` 时返回错误
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub async fn run<T: DeserializeOwned>(
+        &self,
+        client: &MilkyClient,
+        message_scene: MessageScene,
+        peer_id: i64,
+        user_id: i64,
+    ) -> Result<FormOutcome<T>> {
+        use milky_types::EventKind;
+
+        // 在发送问题前订阅，避免错过用户的回答
+        let mut events = client.subscribe();
+        let mut session = self.start(message_scene, peer_id, user_id);
+        let Some(prompt) = session.prompt() else {
+            return FormAnswers::default()
+                .deserialize()
+                .map(FormOutcome::Completed);
+        };
+        session.send_text(client, prompt).await?;
+
+        loop {
+            let timeout = session.deadline.saturating_duration_since(Instant::now());
+            let event = match recv_matching(&mut events, timeout, |event| {
+                matches!(&event.kind, EventKind::MessageReceive { message } if session.matches(message))
+            })
+            .await
+            {
+                Ok(event) => event,
+                Err(MilkyError::Timeout) => return Ok(FormOutcome::Expired),
+                Err(e) => return Err(e),
+            };
+            let EventKind::MessageReceive { message } = &event.kind else {
+                continue;
+            };
+            match session.handle(message) {
+                FormStep::Ignored => {}
+                FormStep::Invalid(text) | FormStep::Next(text) => {
+                    session.send_text(client, &text).await?;
+                }
+                FormStep::Completed(answers) => {
+                    return answers.deserialize().map(FormOutcome::Completed);
+                }
+                FormStep::Cancelled => return Ok(FormOutcome::Cancelled),
+                FormStep::Expired => return Ok(FormOutcome::Expired),
+            }
+        }
+    }
+
+    /// 从快照恢复填写中的表单，当前问题的超时时间会重新计算
+    ///
+    /// # 参数
+    /// * `snapshot`: 通过 [`FormSession::snapshot`] 保存的快照，必须来自同一个表单定义
+    pub fn resume(&self, snapshot: FormSnapshot) -> FormSession {
+        FormSession {
+            form: self.clone(),
+            message_scene: snapshot.message_scene,
+            peer_id: snapshot.peer_id,
+            user_id: snapshot.user_id,
+            current: snapshot.current,
+            answers: snapshot.answers,
+            deadline: Instant::now() + self.timeout,
+        }
+    }
+}
+
+/// 表单会话的可序列化快照，用于在重启之间保存填写进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormSnapshot {
+    /// 会话所在的消息场景
+    pub message_scene: MessageScene,
+    /// 好友QQ号或群号
+    pub peer_id: i64,
+    /// 填写表单的用户QQ号
+    pub user_id: i64,
+    /// 当前问题的下标
+    pub current: usize,
+    /// 已收集的回答
+    pub answers: HashMap<String, String>,
+}
+
+/// [`FormSession::handle`] 处理一条消息后的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormStep {
+    /// 消息不属于该会话，未做任何处理
+    Ignored,
+    /// 回答未通过校验，包含提示给用户的错误信息，需要重新回答当前问题
+    Invalid(String),
+    /// 回答已接受，包含下一道问题的文本
+    Next(String),
+    /// 所有问题均已回答
+    Completed(FormAnswers),
+    /// 用户主动取消了表单
+    Cancelled,
+    /// 等待回答超时
+    Expired,
+}
+
+/// [`Form::run`] 的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormOutcome<T> {
+    /// 所有问题均已回答，包含转换后的回答
+    Completed(T),
+    /// 用户主动取消了表单
+    Cancelled,
+    /// 等待回答超时
+    Expired,
+}
+
+/// 单个用户填写表单的会话状态
+pub struct FormSession {
+    /// 表单定义
+    form: Form,
+    /// 会话所在的消息场景
+    message_scene: MessageScene,
+    /// 好友QQ号或群号
+    peer_id: i64,
+    /// 填写表单的用户QQ号
+    user_id: i64,
+    /// 当前问题的下标
+    current: usize,
+    /// 已收集的回答
+    answers: HashMap<String, String>,
+    /// 当前问题的回答截止时间
+    deadline: Instant,
+}
+
+impl FormSession {
+    /// 获取当前需要回答的问题文本，所有问题均已回答时返回 `None`
+    pub fn prompt(&self) -> Option<&str> {
+        self.form
+            .questions
+            .get(self.current)
+            .map(|q| q.prompt.as_str())
+    }
+
+    /// 保存当前的填写进度，之后可通过 [`Form::resume`] 恢复
+    pub fn snapshot(&self) -> FormSnapshot {
+        FormSnapshot {
+            message_scene: self.message_scene,
+            peer_id: self.peer_id,
+            user_id: self.user_id,
+            current: self.current,
+            answers: self.answers.clone(),
+        }
+    }
+
+    /// 当前问题是否已等待超时
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// 判断消息事件是否属于该会话
+    pub fn matches(&self, event: &MessageEvent) -> bool {
+        let message = event.base_message();
+        message.message_scene == self.message_scene
+            && message.peer_id == self.peer_id
+            && message.sender_id == self.user_id
+    }
+
+    /// 处理一条收到的消息
+    ///
+    /// # 参数
+    /// * `event`: 收到的消息事件
+    ///
+    /// # 返回
+    /// 处理结果 [`FormStep`]，调用方应根据结果向用户发送提示
+    pub fn handle(&mut self, event: &MessageEvent) -> FormStep {
+        if !self.matches(event) {
+            return FormStep::Ignored;
+        }
+        if self.is_expired() {
+            return FormStep::Expired;
+        }
+        let text = get_plain_text_from_segments(&event.base_message().segments);
+        self.answer(text.trim())
+    }
+
+    /// 以纯文本的形式回答当前问题
+    fn answer(&mut self, text: &str) -> FormStep {
+        if self.form.cancel_keyword.as_deref() == Some(text) {
+            return FormStep::Cancelled;
+        }
+        let Some(question) = self.form.questions.get(self.current) else {
+            return FormStep::Completed(FormAnswers(self.answers.clone()));
+        };
+        if let Some(validator) = &question.validator
+            && let Err(reason) = validator(text)
+        {
+            self.deadline = Instant::now() + self.form.timeout;
+            return FormStep::Invalid(reason);
+        }
+
+        self.answers.insert(question.key.clone(), text.to_string());
+        self.current += 1;
+        self.deadline = Instant::now() + self.form.timeout;
+        match self.prompt() {
+            Some(prompt) => FormStep::Next(prompt.to_string()),
+            None => FormStep::Completed(FormAnswers(std::mem::take(&mut self.answers))),
+        }
+    }
+
+    /// 在会话所在的场景中发送一段文本，通常用于发送问题或校验提示
+    ///
+    /// # 参数
+    /// * `client`: 用于发送消息的客户端
+    /// * `text`: 要发送的文本
+    pub async fn send_text(&self, client: &MilkyClient, text: &str) -> Result<()> {
+        let message = vec![OutgoingSegment::Text(TextData {
+            text: text.to_string(),
+        })];
+        match self.message_scene {
+            MessageScene::Friend => {
+                client.send_private_message(self.peer_id, message).await?;
+            }
+            MessageScene::Group => {
+                client.send_group_message(self.peer_id, message).await?;
+            }
+            MessageScene::Temp => {
+                return Err(MilkyError::Internal(
+                    "暂不支持在临时会话中发送表单".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 表单填写完成后收集到的所有回答
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormAnswers(HashMap<String, String>);
+
+impl FormAnswers {
+    /// 获取指定问题的原始回答文本
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// 将指定问题的回答解析为具体类型，回答不存在或解析失败时返回 `None`
+    pub fn parse<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+
+    /// 将所有回答转换为以问题的键为字段名的类型
+    ///
+    /// 数字与布尔值字段会从回答文本解析，枚举按变体名称匹配，`Option` 字段在没有对应回答时为 `None`。
+    ///
+    /// # 返回
+    /// 缺少字段或回答无法解析为字段的类型时返回错误
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        let answers = self
+            .0
+            .iter()
+            .map(|(key, value)| (key.as_str(), AnswerDeserializer(value)));
+        T::deserialize(MapDeserializer::new(answers))
+            .map_err(|e| MilkyError::Internal(format!("无法转换表单的回答: {e}")))
+    }
+
+    /// 获取所有回答组成的映射
+    pub fn into_inner(self) -> HashMap<String, String> {
+        self.0
+    }
+}
+
+/// 按字段的类型解析单个回答文本的反序列化器
+struct AnswerDeserializer<'a>(&'a str);
+
+/// 为数字与布尔值类型生成先解析回答文本再交给访问器的方法
+macro_rules! parse_answer {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> std::result::Result<V::Value, Self::Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(Unexpected::Str(self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for AnswerDeserializer<'de> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_answer! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, de::value::Error> for AnswerDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use milky_types::friend::Friend;
+    use milky_types::message::in_coming::{FriendMessage, IncomingMessage, IncomingSegment};
+
+    fn friend_text(sender_id: i64, text: &str) -> MessageEvent {
+        MessageEvent::Friend(FriendMessage {
+            message: IncomingMessage {
+                peer_id: sender_id,
+                message_seq: 1,
+                sender_id,
+                time: 0,
+                segments: vec![IncomingSegment::Text {
+                    text: text.to_string(),
+                }],
+                message_scene: MessageScene::Friend,
+            },
+            friend: Friend::default(),
+        })
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Registration {
+        name: String,
+        age: u32,
+        email: Option<String>,
+    }
+
+    /// 昵称无法解析为数字
+    #[derive(Debug, Deserialize)]
+    struct BadRegistration {
+        #[allow(dead_code)]
+        name: u32,
+    }
+
+    #[test]
+    fn test_form_flow() {
+        let form = Form::new().question("name", "请输入昵称").question_with(
+            "age",
+            "请输入年龄",
+            |s| {
+                s.parse::<u32>()
+                    .map(|_| ())
+                    .map_err(|_| "年龄必须是数字".to_string())
+            },
+        );
+        let mut session = form.start(MessageScene::Friend, 10001, 10001);
+        assert_eq!(session.prompt(), Some("请输入昵称"));
+
+        assert_eq!(
+            session.handle(&friend_text(10002, "路人")),
+            FormStep::Ignored
+        );
+        assert_eq!(
+            session.handle(&friend_text(10001, " 小明 ")),
+            FormStep::Next("请输入年龄".to_string())
+        );
+        assert_eq!(
+            session.handle(&friend_text(10001, "十八")),
+            FormStep::Invalid("年龄必须是数字".to_string())
+        );
+        match session.handle(&friend_text(10001, "18")) {
+            FormStep::Completed(answers) => {
+                assert_eq!(answers.get("name"), Some("小明"));
+                assert_eq!(answers.parse::<u32>("age"), Some(18));
+
+                let registration: Registration = answers.deserialize().unwrap();
+                assert_eq!(
+                    registration,
+                    Registration {
+                        name: "小明".to_string(),
+                        age: 18,
+                        email: None,
+                    }
+                );
+                assert!(answers.deserialize::<BadRegistration>().is_err());
+            }
+            step => panic!("表单应该已完成: {step:?}"),
+        }
+    }
+
+    #[test]
+    fn test_form_cancel_and_expire() {
+        let form = Form::new().question("reason", "请输入举报理由");
+        let mut session = form.start(MessageScene::Friend, 10001, 10001);
+        assert_eq!(
+            session.handle(&friend_text(10001, "取消")),
+            FormStep::Cancelled
+        );
+
+        let form = form.timeout(Duration::ZERO);
+        let mut session = form.start(MessageScene::Friend, 10001, 10001);
+        assert_eq!(
+            session.handle(&friend_text(10001, "刷屏")),
+            FormStep::Expired
+        );
+    }
+}
@@ -0,0 +1,899 @@
+//! 按事件类型分发的事件处理器
+//!
+//! 实现 [`EventHandler`] 中关心的方法，再交给 [`EventDispatcher`] 运行，
+//! 即可按 [`EventKind`] 将事件路由到对应的方法，而不必在自己的事件循环中编写庞大的 `match`。
+//! 未实现的方法默认不做任何处理。
+//!
+//! 通过 [`EventDispatcher::with_config`] 设置 [`SharedConfig`] 后，分发器会在调用处理器之前丢弃
+//! 被过滤的用户或群组发送的消息，以及使用了被禁用命令的消息，重新加载配置后立即按新规则生效；
+//! 通过 [`EventDispatcher::with_feature_flags`] 设置 [`FeatureFlags`] 后，在本群被禁用的功能对应的命令也会被丢弃。
+//!
+//! ```no_run
+//! use milky_rust_sdk::framework::{Context, EventDispatcher, EventHandler};
+//! use milky_rust_sdk::prelude::*;
+//!
+//! struct Bot;
+//!
+//! impl EventHandler for Bot {
+//!     async fn on_message(&self, _ctx: &Context, message: MessageEvent) {
+//!         println!("收到来自 {} 的消息", message.base_message().sender_id);
+//!     }
+//!
+//!     async fn on_group_member_increase(
+//!         &self,
+//!         _ctx: &Context,
+//!         group_id: i64,
+//!         user_id: i64,
+//!         _operator_id: Option<i64>,
+//!         _invitor_id: Option<i64>,
+//!     ) {
+//!         println!("{user_id} 加入了群 {group_id}");
+//!     }
+//! }
+//!
+//! # async fn run(ctx: Context, events: tokio::sync::mpsc::Receiver<Event>) {
+//! EventDispatcher::new(ctx, Bot).run(events).await;
+//! # }
+//! ```
+
+use crate::config::SharedConfig;
+use crate::framework::feature::{FeatureFlags, FeatureGate};
+use crate::framework::state::Context;
+use crate::framework::storage::Storage;
+use crate::logger::warn;
+use crate::runtime;
+use crate::utils::get_plain_text_from_segments;
+
+use milky_types::common::MessageScene;
+use milky_types::message::reaction::Reaction;
+use milky_types::{Event, EventKind, MessageEvent};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// 事件处理器，每个方法对应一种 [`EventKind`]
+///
+/// 方法的参数即对应事件的各个字段，部分事件的字段较多，因此允许较长的参数列表。
+#[allow(clippy::too_many_arguments)]
+pub trait EventHandler: Send + Sync + 'static {
+    /// 收到任意事件时最先调用，之后再调用对应类型的方法
+    fn on_event(&self, ctx: &Context, event: &Event) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, event);
+        async {}
+    }
+
+    /// 机器人离线
+    fn on_bot_offline(&self, ctx: &Context, reason: String) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, reason);
+        async {}
+    }
+
+    /// 收到好友、群或临时会话消息
+    fn on_message(&self, ctx: &Context, message: MessageEvent) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, message);
+        async {}
+    }
+
+    /// 消息被撤回
+    fn on_message_recall(
+        &self,
+        ctx: &Context,
+        message_scene: MessageScene,
+        peer_id: i64,
+        message_seq: i64,
+        sender_id: i64,
+        operator_id: i64,
+        display_suffix: String,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx,
+            message_scene,
+            peer_id,
+            message_seq,
+            sender_id,
+            operator_id,
+            display_suffix,
+        );
+        async {}
+    }
+
+    /// 收到好友请求
+    fn on_friend_request(
+        &self,
+        ctx: &Context,
+        initiator_id: String,
+        initiator_uid: i64,
+        comment: String,
+        via: String,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, initiator_id, initiator_uid, comment, via);
+        async {}
+    }
+
+    /// 收到入群申请
+    fn on_group_join_request(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        notification_seq: i64,
+        is_filtered: bool,
+        initiator_id: i64,
+        comment: String,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx,
+            group_id,
+            notification_seq,
+            is_filtered,
+            initiator_id,
+            comment,
+        );
+        async {}
+    }
+
+    /// 群成员邀请他人入群
+    fn on_group_invited_join_request(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        notification_seq: i64,
+        initiator_id: i64,
+        target_user_id: i64,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx,
+            group_id,
+            notification_seq,
+            initiator_id,
+            target_user_id,
+        );
+        async {}
+    }
+
+    /// 机器人被邀请入群
+    fn on_group_invitation(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        invitation_seq: i64,
+        initiator_id: i64,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, invitation_seq, initiator_id);
+        async {}
+    }
+
+    /// 好友戳一戳
+    fn on_friend_nudge(
+        &self,
+        ctx: &Context,
+        user_id: i64,
+        is_self_send: bool,
+        is_self_receive: bool,
+        display_action: String,
+        display_suffix: String,
+        display_action_img_url: String,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx,
+            user_id,
+            is_self_send,
+            is_self_receive,
+            display_action,
+            display_suffix,
+            display_action_img_url,
+        );
+        async {}
+    }
+
+    /// 好友文件上传
+    fn on_friend_file_upload(
+        &self,
+        ctx: &Context,
+        user_id: i64,
+        file_id: String,
+        file_name: String,
+        file_size: i64,
+        file_hash: String,
+        is_self: bool,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx, user_id, file_id, file_name, file_size, file_hash, is_self,
+        );
+        async {}
+    }
+
+    /// 群管理员变更
+    fn on_group_admin_change(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        operator_id: i64,
+        is_set: bool,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, operator_id, is_set);
+        async {}
+    }
+
+    /// 群精华消息变更
+    fn on_group_essence_message_change(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        message_seq: i64,
+        is_set: bool,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, message_seq, is_set);
+        async {}
+    }
+
+    /// 群成员增加
+    fn on_group_member_increase(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        operator_id: Option<i64>,
+        invitor_id: Option<i64>,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, operator_id, invitor_id);
+        async {}
+    }
+
+    /// 群成员减少
+    fn on_group_member_decrease(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        operator_id: Option<i64>,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, operator_id);
+        async {}
+    }
+
+    /// 群名称变更
+    fn on_group_name_change(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        group_new_name: String,
+        operator_id: i64,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, group_new_name, operator_id);
+        async {}
+    }
+
+    /// 群消息表态变更
+    fn on_group_message_reaction(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        message_seq: i64,
+        face_id: Reaction,
+        is_add: bool,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, message_seq, face_id, is_add);
+        async {}
+    }
+
+    /// 群成员禁言或解除禁言
+    fn on_group_mute(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        operator_id: i64,
+        duration: i32,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, operator_id, duration);
+        async {}
+    }
+
+    /// 群全员禁言或解除全员禁言
+    fn on_group_whole_mute(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        operator_id: i64,
+        is_mute: bool,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, operator_id, is_mute);
+        async {}
+    }
+
+    /// 群戳一戳
+    fn on_group_nudge(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        sender_id: i64,
+        receiver_id: i64,
+        display_action: String,
+        display_suffix: String,
+        display_action_img_url: String,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (
+            ctx,
+            group_id,
+            sender_id,
+            receiver_id,
+            display_action,
+            display_suffix,
+            display_action_img_url,
+        );
+        async {}
+    }
+
+    /// 群文件上传
+    fn on_group_file_upload(
+        &self,
+        ctx: &Context,
+        group_id: i64,
+        user_id: i64,
+        file_id: String,
+        file_name: String,
+        file_size: i64,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, group_id, user_id, file_id, file_name, file_size);
+        async {}
+    }
+
+    /// SDK 尚未定义的事件类型
+    fn on_unknown_event(
+        &self,
+        ctx: &Context,
+        event_type: String,
+        data: Value,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (ctx, event_type, data);
+        async {}
+    }
+}
+
+/// 将事件分发给 [`EventHandler`] 的分发器
+pub struct EventDispatcher<H: EventHandler> {
+    /// 传递给处理器的上下文
+    ctx: Context,
+    /// 事件处理器
+    handler: Arc<H>,
+    /// 分发前丢弃消息的规则
+    filter: Arc<Filter>,
+}
+
+impl<H: EventHandler> EventDispatcher<H> {
+    /// 创建分发器
+    ///
+    /// # 参数
+    /// * `ctx`: 传递给处理器的上下文
+    /// * `handler`: 事件处理器
+    pub fn new(ctx: Context, handler: H) -> Self {
+        Self {
+            ctx,
+            handler: Arc::new(handler),
+            filter: Arc::new(Filter::default()),
+        }
+    }
+
+    /// 按配置中的过滤规则与禁用的命令丢弃消息
+    ///
+    /// 来自 `bot.filter` 中用户或群组的消息，以及以命令前缀开头、命令名称被禁用的消息不会交给处理器。
+    ///
+    /// # 参数
+    /// * `config`: 共享配置，重新加载后分发器使用新的规则
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        Arc::make_mut(&mut self.filter).config = Some(config);
+        self
+    }
+
+    /// 按群功能开关丢弃群消息
+    ///
+    /// 群消息以命令前缀开头时，命令名称即功能名称，该功能在本群被禁用时消息不会交给处理器。
+    /// 命令前缀取自 [`with_config`](Self::with_config) 设置的配置，未设置时为 `/`；
+    /// `feature` 管理命令本身始终会被分发。
+    ///
+    /// # 参数
+    /// * `flags`: 功能开关，通常与处理 `feature` 命令的处理器共享
+    pub fn with_feature_flags<S: Storage + 'static>(mut self, flags: Arc<FeatureFlags<S>>) -> Self {
+        Arc::make_mut(&mut self.filter).features = Some(flags);
+        self
+    }
+
+    /// 持续从事件通道中取出事件，并在独立的任务中分发，直到通道关闭
+    ///
+    /// 每个事件都在新的任务中处理，耗时较长的处理不会阻塞后续事件。
+    pub async fn run(self, mut events: mpsc::Receiver<Event>) {
+        while let Some(event) = events.recv().await {
+            let ctx = self.ctx.clone();
+            let handler = Arc::clone(&self.handler);
+            let filter = Arc::clone(&self.filter);
+            runtime::spawn(async move {
+                if !filter.is_filtered(&event).await {
+                    dispatch(&*handler, &ctx, event).await;
+                }
+            });
+        }
+    }
+
+    /// 在当前任务中分发单个事件，处理完成后返回
+    pub async fn dispatch(&self, event: Event) {
+        if self.filter.is_filtered(&event).await {
+            return;
+        }
+        dispatch(&*self.handler, &self.ctx, event).await;
+    }
+}
+
+/// 分发前丢弃消息的规则
+#[derive(Clone, Default)]
+struct Filter {
+    /// 提供过滤规则与命令开关的配置
+    config: Option<SharedConfig>,
+    /// 按群的功能开关
+    features: Option<Arc<dyn FeatureGate>>,
+}
+
+impl Filter {
+    /// 判断事件是否应被丢弃
+    async fn is_filtered(&self, event: &Event) -> bool {
+        let EventKind::MessageReceive { message } = &event.kind else {
+            return false;
+        };
+        let config = self.config.as_ref().map(SharedConfig::current);
+        let base = message.base_message();
+        let group_id = matches!(message, MessageEvent::Group(_)).then_some(base.peer_id);
+        if config
+            .as_ref()
+            .is_some_and(|config| config.is_blocked(group_id, base.sender_id))
+        {
+            return true;
+        }
+
+        let prefix = match (&config, group_id) {
+            (Some(config), Some(group_id)) => config.command_prefix_for(group_id),
+            (Some(config), None) => &config.bot.command_prefix,
+            (None, _) => "/",
+        };
+        let text = get_plain_text_from_segments(&base.segments);
+        let Some(command) = command_name(&text, prefix) else {
+            return false;
+        };
+        if config
+            .as_ref()
+            .is_some_and(|config| !config.is_command_enabled(group_id, command))
+        {
+            return true;
+        }
+        match (&self.features, group_id) {
+            (Some(features), Some(group_id)) if command != "feature" => {
+                match features.is_enabled(group_id, command).await {
+                    Ok(enabled) => !enabled,
+                    Err(e) => {
+                        warn!("读取群 {group_id} 的功能开关失败，按启用处理: {e}");
+                        false
+                    }
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 从消息文本中取出命令名称，文本不以命令前缀开头时返回 `None`
+fn command_name<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.trim_start()
+        .strip_prefix(prefix)?
+        .split_whitespace()
+        .next()
+}
+
+/// 按事件类型调用处理器中对应的方法
+async fn dispatch<H: EventHandler>(handler: &H, ctx: &Context, event: Event) {
+    handler.on_event(ctx, &event).await;
+    match event.kind {
+        EventKind::BotOffline { reason } => handler.on_bot_offline(ctx, reason).await,
+        EventKind::MessageReceive { message } => handler.on_message(ctx, message).await,
+        EventKind::MessageRecall {
+            message_scene,
+            peer_id,
+            message_seq,
+            sender_id,
+            operator_id,
+            display_suffix,
+        } => {
+            handler
+                .on_message_recall(
+                    ctx,
+                    message_scene,
+                    peer_id,
+                    message_seq,
+                    sender_id,
+                    operator_id,
+                    display_suffix,
+                )
+                .await
+        }
+        EventKind::FriendRequest {
+            initiator_id,
+            initiator_uid,
+            comment,
+            via,
+        } => {
+            handler
+                .on_friend_request(ctx, initiator_id, initiator_uid, comment, via)
+                .await
+        }
+        EventKind::GroupJoinRequest {
+            group_id,
+            notification_seq,
+            is_filtered,
+            initiator_id,
+            comment,
+        } => {
+            handler
+                .on_group_join_request(
+                    ctx,
+                    group_id,
+                    notification_seq,
+                    is_filtered,
+                    initiator_id,
+                    comment,
+                )
+                .await
+        }
+        EventKind::GroupInvitedJoinRequest {
+            group_id,
+            notification_seq,
+            initiator_id,
+            target_user_id,
+        } => {
+            handler
+                .on_group_invited_join_request(
+                    ctx,
+                    group_id,
+                    notification_seq,
+                    initiator_id,
+                    target_user_id,
+                )
+                .await
+        }
+        EventKind::GroupInvitation {
+            group_id,
+            invitation_seq,
+            initiator_id,
+        } => {
+            handler
+                .on_group_invitation(ctx, group_id, invitation_seq, initiator_id)
+                .await
+        }
+        EventKind::FriendNudge {
+            user_id,
+            is_self_send,
+            is_self_receive,
+            display_action,
+            display_suffix,
+            display_action_img_url,
+        } => {
+            handler
+                .on_friend_nudge(
+                    ctx,
+                    user_id,
+                    is_self_send,
+                    is_self_receive,
+                    display_action,
+                    display_suffix,
+                    display_action_img_url,
+                )
+                .await
+        }
+        EventKind::FriendFileUpload {
+            user_id,
+            file_id,
+            file_name,
+            file_size,
+            file_hash,
+            is_self,
+        } => {
+            handler
+                .on_friend_file_upload(
+                    ctx, user_id, file_id, file_name, file_size, file_hash, is_self,
+                )
+                .await
+        }
+        EventKind::GroupAdminChange {
+            group_id,
+            user_id,
+            operator_id,
+            is_set,
+        } => {
+            handler
+                .on_group_admin_change(ctx, group_id, user_id, operator_id, is_set)
+                .await
+        }
+        EventKind::GroupEssenceMessageChange {
+            group_id,
+            message_seq,
+            is_set,
+        } => {
+            handler
+                .on_group_essence_message_change(ctx, group_id, message_seq, is_set)
+                .await
+        }
+        EventKind::GroupMemberIncrease {
+            group_id,
+            user_id,
+            operator_id,
+            invitor_id,
+        } => {
+            handler
+                .on_group_member_increase(ctx, group_id, user_id, operator_id, invitor_id)
+                .await
+        }
+        EventKind::GroupMemberDecrease {
+            group_id,
+            user_id,
+            operator_id,
+        } => {
+            handler
+                .on_group_member_decrease(ctx, group_id, user_id, operator_id)
+                .await
+        }
+        EventKind::GroupNameChange {
+            group_id,
+            group_new_name,
+            operator_id,
+        } => {
+            handler
+                .on_group_name_change(ctx, group_id, group_new_name, operator_id)
+                .await
+        }
+        EventKind::GroupMessageReaction {
+            group_id,
+            user_id,
+            message_seq,
+            face_id,
+            is_add,
+        } => {
+            handler
+                .on_group_message_reaction(ctx, group_id, user_id, message_seq, face_id, is_add)
+                .await
+        }
+        EventKind::GroupMute {
+            group_id,
+            user_id,
+            operator_id,
+            duration,
+        } => {
+            handler
+                .on_group_mute(ctx, group_id, user_id, operator_id, duration)
+                .await
+        }
+        EventKind::GroupWholeMute {
+            group_id,
+            operator_id,
+            is_mute,
+        } => {
+            handler
+                .on_group_whole_mute(ctx, group_id, operator_id, is_mute)
+                .await
+        }
+        EventKind::GroupNudge {
+            group_id,
+            sender_id,
+            receiver_id,
+            display_action,
+            display_suffix,
+            display_action_img_url,
+        } => {
+            handler
+                .on_group_nudge(
+                    ctx,
+                    group_id,
+                    sender_id,
+                    receiver_id,
+                    display_action,
+                    display_suffix,
+                    display_action_img_url,
+                )
+                .await
+        }
+        EventKind::GroupFileUpload {
+            group_id,
+            user_id,
+            file_id,
+            file_name,
+            file_size,
+        } => {
+            handler
+                .on_group_file_upload(ctx, group_id, user_id, file_id, file_name, file_size)
+                .await
+        }
+        EventKind::Unknown { event_type, data } => {
+            handler.on_unknown_event(ctx, event_type, data).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::storage::MemoryStorage;
+    use crate::test_util::{self, OFFLINE};
+    use milky_types::message::in_coming::{GroupMessage, IncomingMessage, IncomingSegment};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl EventHandler for Recorder {
+        async fn on_event(&self, _ctx: &Context, event: &Event) {
+            self.0.lock().unwrap().push(format!("event {}", event.time));
+        }
+
+        async fn on_message(&self, _ctx: &Context, message: MessageEvent) {
+            let text = get_plain_text_from_segments(&message.base_message().segments);
+            self.0.lock().unwrap().push(format!("message {text}"));
+        }
+
+        async fn on_bot_offline(&self, _ctx: &Context, reason: String) {
+            self.0.lock().unwrap().push(format!("offline {reason}"));
+        }
+
+        async fn on_group_member_increase(
+            &self,
+            _ctx: &Context,
+            group_id: i64,
+            user_id: i64,
+            _operator_id: Option<i64>,
+            _invitor_id: Option<i64>,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("increase {group_id} {user_id}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let (client, _rx) = test_util::client(OFFLINE);
+        let ctx = Context::new(Arc::new(client));
+        let dispatcher = EventDispatcher::new(ctx, Recorder::default());
+
+        let event = |time, kind| Event {
+            time,
+            self_id: 10000,
+            kind,
+        };
+        dispatcher
+            .dispatch(event(
+                1,
+                EventKind::BotOffline {
+                    reason: "kicked".to_string(),
+                },
+            ))
+            .await;
+        dispatcher
+            .dispatch(event(
+                2,
+                EventKind::GroupMemberIncrease {
+                    group_id: 100,
+                    user_id: 200,
+                    operator_id: None,
+                    invitor_id: None,
+                },
+            ))
+            .await;
+        dispatcher
+            .dispatch(event(
+                3,
+                EventKind::GroupWholeMute {
+                    group_id: 100,
+                    operator_id: 1,
+                    is_mute: true,
+                },
+            ))
+            .await;
+
+        assert_eq!(
+            *dispatcher.handler.0.lock().unwrap(),
+            [
+                "event 1",
+                "offline kicked",
+                "event 2",
+                "increase 100 200",
+                "event 3"
+            ]
+        );
+    }
+
+    /// 构造群 100 中的一条文本消息事件
+    fn group_message(sender_id: i64, text: &str) -> Event {
+        Event {
+            time: 1,
+            self_id: 10000,
+            kind: EventKind::MessageReceive {
+                message: MessageEvent::Group(GroupMessage {
+                    message: IncomingMessage {
+                        peer_id: 100,
+                        sender_id,
+                        segments: vec![IncomingSegment::Text {
+                            text: text.to_string(),
+                        }],
+                        message_scene: MessageScene::Group,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_config() {
+        let path =
+            std::env::temp_dir().join(format!("vivian-dispatch-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[communication]\nmode = \"websocket\"\nws_endpoint = \"ws://127.0.0.1:3000\"\n\
+             [bot]\ndisabled_commands = [\"debug\"]\n\
+             [bot.filter]\nblocked_users = [666]\n\
+             [groups.100]\ncommand_prefix = \"!\"\n",
+        )
+        .unwrap();
+        let config = SharedConfig::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let (client, _rx) = test_util::client_for(config.current().communication());
+        let ctx = Context::new(Arc::new(client));
+        let dispatcher = EventDispatcher::new(ctx, Recorder::default()).with_config(config);
+
+        dispatcher.dispatch(group_message(666, "hello")).await;
+        dispatcher.dispatch(group_message(1, "!debug on")).await;
+        dispatcher.dispatch(group_message(1, "/debug on")).await;
+        dispatcher.dispatch(group_message(1, "!echo hi")).await;
+
+        assert_eq!(
+            *dispatcher.handler.0.lock().unwrap(),
+            [
+                "event 1",
+                "message /debug on",
+                "event 1",
+                "message !echo hi"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_feature_flags() {
+        let flags = Arc::new(FeatureFlags::new(
+            MemoryStorage::new(),
+            [("welcome", false)],
+        ));
+        flags.set(200, "welcome", true).await.unwrap();
+
+        let (client, _rx) = test_util::client(OFFLINE);
+        let ctx = Context::new(Arc::new(client));
+        let dispatcher =
+            EventDispatcher::new(ctx, Recorder::default()).with_feature_flags(Arc::clone(&flags));
+
+        dispatcher.dispatch(group_message(1, "/welcome")).await;
+        dispatcher
+            .dispatch(group_message(1, "/feature enable welcome"))
+            .await;
+        flags.set(100, "welcome", true).await.unwrap();
+        dispatcher.dispatch(group_message(1, "/welcome")).await;
+
+        assert_eq!(
+            *dispatcher.handler.0.lock().unwrap(),
+            [
+                "event 1",
+                "message /feature enable welcome",
+                "event 1",
+                "message /welcome"
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! 平滑重启与状态交接
+//!
+//! [`Lifecycle`] 负责在进程关闭与重新启动之间交接运行时状态：
+//! - 通过 [`Lifecycle::spawn`] 启动的事件处理任务会被追踪，[`Lifecycle::shutdown`] 会停止接收新任务并等待它们执行完毕
+//! - 关闭前通过 [`Lifecycle::save`] 将待发送的回复、进行中的 [`FormSession`](crate::framework::FormSession)
+//!   快照等状态写入 [`Storage`]，下次启动时通过 [`Lifecycle::restore`] 取回
+//!
+//! 这样重启机器人时不会丢失排队中的回复或正在进行的对话。
+
+use crate::error::Result;
+use crate::framework::storage::Storage;
+use crate::logger::{info, warn};
+use crate::runtime::{self, JoinHandle};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// 进行中任务的计数
+#[derive(Default)]
+struct InFlight {
+    /// 进行中的任务数量
+    count: AtomicUsize,
+    /// 任务数量归零时发出通知
+    idle: Notify,
+}
+
+/// 任务结束时自动减少计数的守卫
+struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// 机器人进程的生命周期管理器
+pub struct Lifecycle<S: Storage> {
+    /// 用于交接状态的存储
+    storage: S,
+    /// 进行中的事件处理任务
+    in_flight: Arc<InFlight>,
+    /// 是否已开始关闭
+    shutting_down: AtomicBool,
+}
+
+impl<S: Storage> Lifecycle<S> {
+    /// 创建生命周期管理器
+    ///
+    /// # 参数
+    /// * `storage`: 用于在重启之间交接状态的存储，需要使用持久化的实现（如 [`JsonFileStorage`](crate::framework::JsonFileStorage)）
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            in_flight: Arc::new(InFlight::default()),
+            shutting_down: AtomicBool::new(false),
+        }
+    }
+
+    /// 启动一个受追踪的事件处理任务
+    ///
+    /// # 参数
+    /// * `future`: 要执行的任务
+    ///
+    /// # 返回
+    /// 任务的 [`JoinHandle`]；已开始关闭时不再接收新任务，返回 `None`
+    pub fn spawn<F>(&self, future: F) -> Option<JoinHandle<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        if self.is_shutting_down() {
+            return None;
+        }
+        self.in_flight.count.fetch_add(1, Ordering::AcqRel);
+        let guard = InFlightGuard(self.in_flight.clone());
+        Some(runtime::spawn(async move {
+            let _guard = guard;
+            future.await
+        }))
+    }
+
+    /// 当前进行中的任务数量
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.count.load(Ordering::Acquire)
+    }
+
+    /// 是否已开始关闭
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+
+    /// 停止接收新任务，并等待进行中的任务执行完毕
+    ///
+    /// # 参数
+    /// * `timeout`: 最长等待时间
+    ///
+    /// # 返回
+    /// 所有任务均在超时前结束时返回 `true`
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.shutting_down.store(true, Ordering::Release);
+        info!("正在关闭，等待 {} 个进行中的任务结束", self.in_flight());
+
+        let drained = runtime::timeout(timeout, async {
+            loop {
+                let idle = self.in_flight.idle.notified();
+                if self.in_flight() == 0 {
+                    break;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok();
+
+        if !drained {
+            warn!("等待超时，仍有 {} 个任务未结束", self.in_flight());
+        }
+        drained
+    }
+
+    /// 保存需要交接给下次启动的状态
+    ///
+    /// # 参数
+    /// * `name`: 状态名称
+    /// * `state`: 要保存的状态
+    pub async fn save<T: Serialize>(&self, name: &str, state: &T) -> Result<()> {
+        self.storage
+            .set(&storage_key(name), serde_json::to_string(state)?)
+            .await
+    }
+
+    /// 取回上次关闭前保存的状态，取回后会从存储中删除
+    ///
+    /// # 参数
+    /// * `name`: 状态名称
+    ///
+    /// # 返回
+    /// 上次保存的状态；没有保存过时返回 `Ok(None)`
+    pub async fn restore<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let key = storage_key(name);
+        let Some(value) = self.storage.get(&key).await? else {
+            return Ok(None);
+        };
+        let state = serde_json::from_str(&value)?;
+        self.storage.remove(&key).await?;
+        Ok(Some(state))
+    }
+}
+
+/// 交接状态在存储中的键
+fn storage_key(name: &str) -> String {
+    format!("handover:{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::Form;
+    use crate::framework::storage::MemoryStorage;
+    use milky_types::common::MessageScene;
+
+    #[tokio::test]
+    async fn test_shutdown_and_handover() {
+        let lifecycle = Lifecycle::new(MemoryStorage::new());
+        lifecycle
+            .spawn(tokio::time::sleep(Duration::from_millis(50)))
+            .unwrap();
+        assert_eq!(lifecycle.in_flight(), 1);
+        assert!(lifecycle.shutdown(Duration::from_secs(1)).await);
+        assert_eq!(lifecycle.in_flight(), 0);
+        assert!(lifecycle.spawn(async {}).is_none());
+
+        let form = Form::new()
+            .question("name", "请输入昵称")
+            .question("age", "请输入年龄");
+        let session = form.start(MessageScene::Group, 1, 2);
+        lifecycle
+            .save("forms", &vec![session.snapshot()])
+            .await
+            .unwrap();
+
+        let snapshots: Vec<_> = lifecycle.restore("forms").await.unwrap().unwrap();
+        let session = form.resume(snapshots.into_iter().next().unwrap());
+        assert_eq!(session.prompt(), Some("请输入昵称"));
+        assert!(
+            lifecycle
+                .restore::<Vec<i64>>("forms")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
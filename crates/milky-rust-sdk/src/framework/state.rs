@@ -0,0 +1,129 @@
+//! 为事件处理函数提供按类型注入的共享状态
+//!
+//! [`TypeMap`] 以类型为键保存任意 `Send + Sync` 的值，每种类型最多保存一个实例。
+//! [`Context`] 将客户端与 [`TypeMap`] 组合在一起，在启动时初始化一次后即可克隆并传递给各个处理函数，
+//! 处理函数通过 `ctx.data::<Database>()` 获取依赖，而无需使用全局变量或 `lazy_static`。
+//!
+//! 保存在 [`TypeMap`] 中的值在初始化后是只读的，如果需要修改，请保存 `Mutex<T>` 或 `RwLock<T>` 等类型。
+
+use crate::MilkyClient;
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 以类型为键的异构容器
+#[derive(Default)]
+pub struct TypeMap {
+    /// 类型ID到值的映射
+    inner: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl TypeMap {
+    /// 创建一个空的 `TypeMap`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 插入一个值，如果该类型已存在旧值，则返回旧值
+    ///
+    /// # 参数
+    /// * `value`: 要插入的值
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.inner
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok().map(|old| *old))
+    }
+
+    /// 获取指定类型的值的引用，不存在则返回 `None`
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.inner
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// 获取指定类型的值的可变引用，不存在则返回 `None`
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.inner
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// 移除并返回指定类型的值
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.inner
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok().map(|value| *value))
+    }
+
+    /// 判断是否包含指定类型的值
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.inner.contains_key(&TypeId::of::<T>())
+    }
+}
+
+/// 传递给事件处理函数的上下文
+///
+/// 克隆 `Context` 只会增加内部 `Arc` 的引用计数，因此可以放心地在任务之间传递。
+#[derive(Clone)]
+pub struct Context {
+    /// 与服务端交互的客户端
+    client: Arc<MilkyClient>,
+    /// 启动时初始化的共享状态
+    data: Arc<TypeMap>,
+}
+
+impl Context {
+    /// 创建一个不包含任何共享状态的上下文
+    ///
+    /// # 参数
+    /// * `client`: 与服务端交互的客户端
+    pub fn new(client: Arc<MilkyClient>) -> Self {
+        Self::with_data(client, TypeMap::new())
+    }
+
+    /// 使用已初始化的共享状态创建上下文
+    ///
+    /// # 参数
+    /// * `client`: 与服务端交互的客户端
+    /// * `data`: 启动时初始化的共享状态
+    pub fn with_data(client: Arc<MilkyClient>, data: TypeMap) -> Self {
+        Self {
+            client,
+            data: Arc::new(data),
+        }
+    }
+
+    /// 获取客户端实例
+    pub fn client(&self) -> &Arc<MilkyClient> {
+        &self.client
+    }
+
+    /// 获取指定类型的共享状态，未注入该类型时返回 `None`
+    pub fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.data.get::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Database(&'static str);
+
+    #[test]
+    fn test_type_map() {
+        let mut map = TypeMap::new();
+        assert!(map.insert(Database("sqlite")).is_none());
+        assert!(map.insert(42_u32).is_none());
+
+        assert_eq!(map.get::<Database>(), Some(&Database("sqlite")));
+        assert_eq!(map.insert(Database("postgres")), Some(Database("sqlite")));
+
+        *map.get_mut::<u32>().unwrap() += 1;
+        assert_eq!(map.remove::<u32>(), Some(43));
+        assert!(!map.contains::<u32>());
+        assert!(map.get::<String>().is_none());
+    }
+}
@@ -0,0 +1,167 @@
+//! 持久化存储的抽象
+//!
+//! [`Storage`] 是一个简单的异步键值存储接口，值统一使用字符串保存（通常是 JSON 文本）。
+//! 框架中需要持久化的组件（如 [`FeatureFlags`](crate::framework::FeatureFlags)）都基于该接口实现，
+//! 用户可以为数据库、Redis 等后端实现该 trait 来替换内置实现。多个组件需要共用同一个存储时，可以传入 `Arc<S>`。
+//!
+//! 内置实现：
+//! - [`MemoryStorage`]：保存在内存中，进程退出后丢失，适合测试
+//! - [`JsonFileStorage`]：保存在单个 JSON 文件中，每次写入都会先写入临时文件再替换原文件，写入中断时不会损坏已有数据
+
+use crate::error::{MilkyError, Result};
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// 异步键值存储接口
+pub trait Storage: Send + Sync {
+    /// 读取指定键的值，键不存在时返回 `Ok(None)`
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    /// 写入指定键的值，已存在时覆盖
+    fn set(&self, key: &str, value: String) -> impl Future<Output = Result<()>> + Send;
+
+    /// 删除指定键，键不存在时不做任何操作
+    fn remove(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<S: Storage> Storage for Arc<S> {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<String>>> + Send {
+        (**self).get(key)
+    }
+
+    fn set(&self, key: &str, value: String) -> impl Future<Output = Result<()>> + Send {
+        (**self).set(key, value)
+    }
+
+    fn remove(&self, key: &str) -> impl Future<Output = Result<()>> + Send {
+        (**self).remove(key)
+    }
+}
+
+/// 保存在内存中的存储实现
+#[derive(Default)]
+pub struct MemoryStorage {
+    /// 键值数据
+    data: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryStorage {
+    /// 创建一个空的内存存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<()> {
+        self.data.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// 保存在单个 JSON 文件中的存储实现
+pub struct JsonFileStorage {
+    /// JSON 文件路径
+    path: PathBuf,
+    /// 文件内容在内存中的副本
+    data: Mutex<HashMap<String, String>>,
+    /// 保证同一时间只有一次写回，避免较早的快照覆盖较新的快照
+    flush_lock: tokio::sync::Mutex<()>,
+}
+
+impl JsonFileStorage {
+    /// 打开指定路径的 JSON 文件，文件不存在时视为空存储
+    ///
+    /// # 参数
+    /// * `path`: JSON 文件路径
+    ///
+    /// # 返回
+    /// 成功则返回 [`JsonFileStorage`]；文件存在但无法读取或格式不正确时返回错误
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let data = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(MilkyError::Io(e)),
+        };
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+            flush_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// 将内存中的数据写入同目录下的临时文件，再重命名为目标文件
+    async fn flush(&self) -> Result<()> {
+        let _guard = self.flush_lock.lock().await;
+        let content = serde_json::to_string_pretty(&*self.data.lock().unwrap())?;
+        let mut temp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_name);
+        tokio::fs::write(&temp_path, content).await?;
+        tokio::fs::rename(&temp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonFileStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<()> {
+        self.data.lock().unwrap().insert(key.to_string(), value);
+        self.flush().await
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let removed = self.data.lock().unwrap().remove(key).is_some();
+        if removed {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_json_file_storage() {
+        let dir = std::env::temp_dir().join(format!("vivian-storage-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.json");
+        let storage = Arc::new(JsonFileStorage::open(&path).unwrap());
+
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                let storage = Arc::clone(&storage);
+                tokio::spawn(async move { storage.set(&format!("key{i}"), i.to_string()).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        storage.remove("key0").await.unwrap();
+
+        let reopened = JsonFileStorage::open(&path).unwrap();
+        assert_eq!(reopened.get("key0").await.unwrap(), None);
+        assert_eq!(reopened.get("key15").await.unwrap().as_deref(), Some("15"));
+        assert_eq!(reopened.data.lock().unwrap().len(), 15);
+        assert!(!dir.join("data.json.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
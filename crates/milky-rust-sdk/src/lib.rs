@@ -8,20 +8,86 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! Milky 协议的 Rust SDK
+//!
+//! 默认启用 `websocket`、`webhook`、`logger` 与 `native-tls` 特性。可以关闭默认特性后按需启用：
+//! - `client`: HTTP API 客户端，不包含事件接收
+//! - `websocket`: 通过 WebSocket 接收事件，依赖 `tokio-tungstenite`
+//! - `webhook`: 通过 WebHook 接收事件，依赖 `axum`
+//! - `webhook-tls`: 以 HTTPS 接收 WebHook 事件，依赖 `tokio-rustls`
+//! - `logger`: 内置的日志记录器 [`logger::init_logger`]
+//! - `tracing`: 通过 `tracing` 输出日志，并为 API 请求、事件分发与 WebHook 请求创建 span
+//! - `otel`: 为 API 调用创建 OpenTelemetry 风格的 client span，并注入 W3C Trace Context 请求头，隐含 `tracing`
+//! - `types-only`: 只提供 [`prelude`] 中的类型定义，不依赖 `reqwest`、`tokio` 等网络相关的库
+//! - `native-tls`（默认）/ `rustls`: HTTP 请求与 WebSocket 连接使用的 TLS 实现，两者保持一致。
+//!   `rustls` 不依赖 OpenSSL，适合静态链接的 musl 构建；都未启用时无法连接 `https`/`wss` 地址
+//! - `smol`: 在 smol 运行时中运行后台任务与定时器，默认使用 tokio，参见 [`runtime`] 模块
+//! - `image`: 发送前使用 `image` 库缩小或重新压缩过大的图片
+//! - `ffmpeg`: 调用系统中的 `ffmpeg` 将语音转码为 amr，并提供基于 `ffmpeg` 的图片处理器
+//! - `wasm`: 编译到 `wasm32-unknown-unknown` 时提供基于 `fetch` 与浏览器 WebSocket 的 `wasm::WasmClient`，
+//!   需要关闭默认特性
+//! - `schemars`: 为事件、消息段与 API 类型实现 `JsonSchema`，通过 `api::schema` 导出 JSON Schema
+//! - `chrono`: 为事件、消息等类型中的时间戳提供返回 `DateTime<Utc>` 的 `*_utc` 方法
+//! - `simd-json`: 使用 `simd-json` 解析事件与 API 响应，是否更快取决于 CPU 与负载，启用前应先测量
+//!
+//! 例如只通过 WebSocket 接收事件的机器人可以这样声明依赖，从而不编译 `axum`：
+//!
+//! ```toml
+//! milky-rust-sdk = { version = "1", default-features = false, features = ["websocket", "logger", "native-tls"] }
+//! ```
+
+#[cfg(all(target_arch = "wasm32", feature = "client"))]
+compile_error!(
+    "milky-rust-sdk 的 `client` 特性依赖 tokio 与 axum 的原生实现，不支持 wasm32 目标；\
+     在浏览器中请关闭默认特性并启用 `wasm` 特性，使用 `wasm::WasmClient`"
+);
+
+#[cfg(any(feature = "client", all(feature = "wasm", target_arch = "wasm32")))]
 pub mod api;
+#[cfg(feature = "client")]
+pub mod blocking;
+#[cfg(all(feature = "websocket", feature = "webhook"))]
+pub mod bridge;
+#[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "client")]
+pub mod config;
+#[cfg(any(feature = "client", feature = "wasm"))]
 pub mod error;
+#[cfg(feature = "client")]
+pub mod framework;
+#[cfg(any(feature = "client", feature = "logger", feature = "wasm"))]
 pub mod logger;
+#[cfg(feature = "client")]
+pub mod media;
+#[cfg(feature = "otel")]
+pub mod otel;
+#[cfg(feature = "client")]
+pub mod runtime;
+#[cfg(feature = "client")]
+pub mod stats;
+#[cfg(all(test, feature = "client"))]
+mod test_util;
+#[cfg(any(feature = "client", feature = "wasm"))]
 pub mod types;
+#[cfg(feature = "client")]
 pub mod utils;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
 
-pub use client::MilkyClient;
-pub use error::{MilkyError, Result};
+#[cfg(feature = "client")]
+pub use client::{MilkyClient, MilkyClientBuilder};
+#[cfg(feature = "client")]
+pub use config::Config;
+#[cfg(any(feature = "client", feature = "wasm"))]
+pub use error::{MilkyApiErrorKind, MilkyError, Result};
+#[cfg(feature = "client")]
 pub use types::communication::{Communication, WebHookConfig, WebSocketConfig};
 
 pub mod prelude {
     pub use milky_types::common::*;
     pub use milky_types::{Event, EventKind, MessageEvent};
+
     pub use milky_types::friend::*;
     pub use milky_types::group::*;
     pub use milky_types::message::in_coming::*;
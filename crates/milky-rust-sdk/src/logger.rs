@@ -3,14 +3,103 @@
 //! 本模块提供了一个函数 [`init_logger`]，用于设置一个全局的日志记录器。
 //! 这个记录器会以特定的彩色格式输出日志，包括时间戳、日志级别、日志来源模块（target）以及日志消息本身。
 //! 日志级别可以通过环境变量 `RUST_LOG` 或函数参数进行配置。
+//!
+//! 需要将日志接入 Loki、ELK 等日志系统时，可以通过 [`init_logger_with_format`] 选择 [`LogFormat::Json`]，
+//! 每条日志输出为一行 JSON；也可以不修改代码，在运行时设置环境变量 `MILKY_LOG_FORMAT=json`，
+//! [`init_logger`] 会据此选择输出格式。使用 `log` 的键值对语法记录的字段（例如 `info!(peer_id = 10001; "...")`）
+//! 会写入 `fields` 对象中，SDK 发送 API 请求时的日志会带有 `action` 字段。
+//!
+//! 需要保留日志文件时，可以通过 [`init_logger_with_file`] 将日志同时写入按大小或日期轮转的文件，参见 [`file`] 模块。
+//!
+//! 排查请求或响应的序列化问题时，可以通过 [`body`] 模块按采样率记录脱敏后的完整 JSON。
+//! 日志中的访问令牌总是会被隐去，事件原始内容的输出方式参见 [`redact`] 模块。
+//!
+//! 启用 `tracing` 特性后，SDK 内部改为通过 `tracing` 输出事件，并为 API 请求、WebSocket 事件读取循环
+//! 与 WebHook 请求创建 span，便于关联同一请求产生的日志。未设置 `tracing` 订阅者时，这些事件仍会转发给
+//! `log`，因此 [`init_logger`] 依然可用。需要接入 OpenTelemetry 时可启用 `otel` 特性，参见 `otel` 模块。
+//!
+//! 日志记录器需要启用 `logger` 特性（默认启用）。关闭该特性后，SDK 仍会通过 `log` 输出日志，
+//! 可以自行选择其他的日志实现。
+
+#[cfg(feature = "client")]
+pub mod body;
+#[cfg(feature = "logger")]
+pub mod file;
+#[cfg(any(feature = "client", all(feature = "wasm", target_arch = "wasm32")))]
+pub mod redact;
 
+#[cfg(feature = "client")]
+pub use body::BodyLogConfig;
+#[cfg(feature = "logger")]
+pub use file::{FileLogConfig, Rotation};
+#[cfg(any(feature = "client", all(feature = "wasm", target_arch = "wasm32")))]
+pub use redact::{PayloadLogging, redact_url, set_payload_logging};
+
+#[cfg(feature = "logger")]
 use ansi_term::Colour;
+#[cfg(feature = "logger")]
 use chrono::Local;
-use log::{Level, LevelFilter};
+#[cfg(feature = "logger")]
+use log::kv::{Key, Value, VisitSource};
+#[cfg(feature = "logger")]
+use log::{Level, LevelFilter, Record};
+#[cfg(feature = "logger")]
 use pretty_env_logger::env_logger::fmt::Color as EnvColor;
+#[cfg(feature = "logger")]
 use pretty_env_logger::formatted_builder;
+#[cfg(feature = "logger")]
+use serde_json::{Map, json};
+#[cfg(feature = "logger")]
 use std::env;
+#[cfg(feature = "logger")]
 use std::io::Write;
+#[cfg(feature = "logger")]
+use std::str::FromStr;
+
+#[cfg(all(feature = "client", not(feature = "tracing")))]
+pub(crate) use log::{debug, error, info, warn};
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, warn};
+
+/// 输出带结构化字段的调试日志，字段在 JSON 格式的日志中会单独输出
+///
+/// `log` 以 `;`、`tracing` 以 `,` 分隔字段与消息，这里统一写作 `debug_fields!(key = value; "消息")`。
+#[cfg(feature = "client")]
+macro_rules! debug_fields {
+    ($($key:ident = $value:expr),+; $($arg:tt)+) => {{
+        #[cfg(not(feature = "tracing"))]
+        log::debug!($($key = $value),+; $($arg)+);
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($key = $value),+, $($arg)+);
+    }};
+}
+#[cfg(feature = "client")]
+pub(crate) use debug_fields;
+
+/// 日志的输出格式
+#[cfg(feature = "logger")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 带颜色的单行文本，适合在终端中阅读
+    #[default]
+    Pretty,
+    /// 每条日志一行 JSON，包含 `timestamp`、`level`、`target`、`message` 与 `fields`
+    Json,
+}
+
+#[cfg(feature = "logger")]
+impl FromStr for LogFormat {
+    type Err = String;
+
+    /// 解析 `pretty` 或 `json`，不区分大小写
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => Err(format!("未知的日志格式: {other}，可选值为 pretty 或 json")),
+        }
+    }
+}
 
 /// 初始化自定义格式的日志记录器。
 ///
@@ -31,11 +120,86 @@ use std::io::Write;
 /// 2. `filter` 参数 (如果提供了 `Some(LevelFilter)`)。
 /// 3. 默认级别 `LevelFilter::Info` (如果 `RUST_LOG` 未设置且 `filter` 参数为 `None`)。
 ///
+/// 设置了环境变量 `MILKY_LOG_FORMAT` 时按其值（`pretty` 或 `json`）选择输出格式，无法识别时使用彩色格式。
+///
 /// # 参数
 /// * `filter`: 可选的日志级别过滤器 (`LevelFilter`)。如果为 `None` 且 `RUST_LOG` 环境变量未设置，则默认使用 `LevelFilter::Info`。
+#[cfg(feature = "logger")]
 pub fn init_logger(filter: Option<LevelFilter>) {
+    let format = env::var("MILKY_LOG_FORMAT")
+        .ok()
+        .and_then(|format| format.parse().ok())
+        .unwrap_or_default();
+    init_logger_with_format(filter, format);
+}
+
+/// 以指定的输出格式初始化日志记录器。
+///
+/// 日志过滤级别的规则与 [`init_logger`] 相同。
+///
+/// # 参数
+/// * `filter`: 可选的日志级别过滤器 (`LevelFilter`)
+/// * `format`: 日志的输出格式
+#[cfg(feature = "logger")]
+pub fn init_logger_with_format(filter: Option<LevelFilter>, format: LogFormat) {
+    logger_builder(filter, format).init();
+}
+
+/// 初始化日志记录器，并同时将日志写入按设置轮转的文件。
+///
+/// 日志过滤级别的规则与 [`init_logger`] 相同，写入文件的日志不含终端颜色。
+///
+/// # 参数
+/// * `filter`: 可选的日志级别过滤器 (`LevelFilter`)
+/// * `format`: 日志的输出格式，同时用于终端与文件
+/// * `file`: 日志文件的设置
+///
+/// # 返回
+/// 无法创建日志目录或打开日志文件时返回错误
+#[cfg(feature = "logger")]
+pub fn init_logger_with_file(
+    filter: Option<LevelFilter>,
+    format: LogFormat,
+    file: FileLogConfig,
+) -> std::io::Result<()> {
+    let file = file::RollingFile::open(file)?;
+    let mut builder = logger_builder(filter, format);
+    builder.target(pretty_env_logger::env_logger::Target::Pipe(Box::new(
+        file::TeeWriter::new(file),
+    )));
+    builder.init();
+    Ok(())
+}
+
+/// 按输出格式与过滤级别创建日志记录器的构建器
+#[cfg(feature = "logger")]
+fn logger_builder(
+    filter: Option<LevelFilter>,
+    format: LogFormat,
+) -> pretty_env_logger::env_logger::Builder {
     let mut builder = formatted_builder();
 
+    match format {
+        LogFormat::Pretty => init_pretty_format(&mut builder),
+        LogFormat::Json => {
+            builder.format(|buf, record| writeln!(buf, "{}", json_line(record)));
+        }
+    }
+
+    if env::var("RUST_LOG").is_err() {
+        if let Some(f) = filter {
+            builder.filter(None, f);
+        } else {
+            builder.filter(None, LevelFilter::Info);
+        }
+    }
+
+    builder
+}
+
+/// 设置带颜色的文本格式：`[MM-DD HH:MM:SS] [级别] [模块路径] > 消息内容`
+#[cfg(feature = "logger")]
+fn init_pretty_format(builder: &mut pretty_env_logger::env_logger::Builder) {
     builder.format(|buf, record| {
         let level_str = match record.level() {
             Level::Error => Colour::Red.paint("[ERROR]").to_string(),
@@ -57,14 +221,77 @@ pub fn init_logger(filter: Option<LevelFilter>) {
             record.args()
         )
     });
+}
 
-    if env::var("RUST_LOG").is_err() {
-        if let Some(f) = filter {
-            builder.filter(None, f);
+/// 将日志记录格式化为一行 JSON
+#[cfg(feature = "logger")]
+fn json_line(record: &Record) -> String {
+    let mut fields = FieldCollector(Map::new());
+    let _ = record.key_values().visit(&mut fields);
+    json!({
+        "timestamp": Local::now().to_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+        "fields": fields.0,
+    })
+    .to_string()
+}
+
+/// 收集日志记录中的键值对字段
+#[cfg(feature = "logger")]
+struct FieldCollector(Map<String, serde_json::Value>);
+
+#[cfg(feature = "logger")]
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = if let Some(v) = value.to_i64() {
+            json!(v)
+        } else if let Some(v) = value.to_u64() {
+            json!(v)
+        } else if let Some(v) = value.to_f64() {
+            json!(v)
+        } else if let Some(v) = value.to_bool() {
+            json!(v)
         } else {
-            builder.filter(None, LevelFilter::Info);
-        }
+            json!(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
     }
+}
 
-    builder.init();
+#[cfg(all(test, feature = "logger"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!(" pretty ".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_line() {
+        let fields: &[(&str, Value)] = &[
+            ("action", Value::from("send_group_message")),
+            ("peer_id", Value::from(123456_i64)),
+        ];
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("milky_rust_sdk::client")
+                .args(format_args!("请求失败"))
+                .key_values(&fields)
+                .build(),
+        );
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "milky_rust_sdk::client");
+        assert_eq!(value["message"], "请求失败");
+        assert_eq!(value["fields"]["action"], "send_group_message");
+        assert_eq!(value["fields"]["peer_id"], 123456);
+    }
 }
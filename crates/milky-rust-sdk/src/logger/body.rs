@@ -0,0 +1,128 @@
+//! 按采样率或指定操作记录 API 请求与响应的完整 JSON
+//!
+//! 用于在生产环境中排查序列化不匹配等问题。通过 [`MilkyClient::with_body_logging`] 启用后，
+//! 被选中的 API 调用会以 `info` 级别输出请求参数与响应内容。输出前会自动脱敏：
+//! - 键名包含 `token`、`password`、`secret`、`cookie` 等字样的字段会被替换为 `<redacted>`
+//! - `base64://` 开头的字符串只保留长度信息，不输出具体内容
+
+use crate::client::MilkyClient;
+use crate::logger::info;
+use crate::logger::redact::redact;
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 请求与响应内容记录的设置
+#[derive(Debug, Clone, Default)]
+pub struct BodyLogConfig {
+    /// 随机记录的调用比例，取值 0 到 1
+    sample_rate: f64,
+    /// 总是记录的 API 操作
+    actions: HashSet<String>,
+}
+
+impl BodyLogConfig {
+    /// 创建一个不记录任何调用的设置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置按比例记录的调用，例如 `0.01` 表示每 100 次调用记录 1 次
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 总是记录指定 API 操作的调用，例如 `send_group_message`
+    pub fn action(mut self, action: impl Into<String>) -> Self {
+        self.actions.insert(action.into());
+        self
+    }
+}
+
+/// 根据设置决定是否记录调用并输出脱敏后的内容
+pub(crate) struct BodyLogger {
+    /// 记录设置
+    config: BodyLogConfig,
+    /// 已经过采样判断的调用次数
+    sampled_calls: AtomicU64,
+}
+
+impl BodyLogger {
+    /// 创建记录器
+    pub(crate) fn new(config: BodyLogConfig) -> Self {
+        Self {
+            config,
+            sampled_calls: AtomicU64::new(0),
+        }
+    }
+
+    /// 判断本次调用是否需要记录
+    ///
+    /// 采样是均匀的：采样率为 `r` 时，每 `1/r` 次调用恰好记录一次。
+    pub(crate) fn should_log(&self, action: &str) -> bool {
+        if self.config.actions.contains(action) {
+            return true;
+        }
+        let rate = self.config.sample_rate;
+        if rate <= 0.0 {
+            return false;
+        }
+        let n = self.sampled_calls.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * rate).floor() > (n * rate).floor()
+    }
+
+    /// 输出脱敏后的请求参数
+    pub(crate) fn log_request(&self, action: &str, params: &Value) {
+        info!("API 请求 {action}: {}", redact(params));
+    }
+
+    /// 输出脱敏后的响应内容
+    pub(crate) fn log_response(&self, action: &str, response: &Value) {
+        info!("API 响应 {action}: {}", redact(response));
+    }
+}
+
+impl MilkyClient {
+    /// 启用 API 请求与响应内容的记录
+    ///
+    /// # 参数
+    /// * `config`: 记录哪些调用的设置
+    pub fn with_body_logging(mut self, config: BodyLogConfig) -> Self {
+        self.body_logger = Some(BodyLogger::new(config));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_and_sample() {
+        let params = json!({
+            "group_id": 123,
+            "access_token": "abc",
+            "message": [{"type": "image", "data": {"uri": "base64://aGVsbG8="}}],
+        });
+        assert_eq!(
+            redact(&params),
+            json!({
+                "group_id": 123,
+                "access_token": "<redacted>",
+                "message": [{"type": "image", "data": {"uri": "base64://<已省略 8 字符>"}}],
+            })
+        );
+
+        let logger = BodyLogger::new(
+            BodyLogConfig::new()
+                .sample_rate(0.25)
+                .action("get_login_info"),
+        );
+        assert!(logger.should_log("get_login_info"));
+        let sampled = (0..100).filter(|_| logger.should_log("get_friend_list"));
+        assert_eq!(sampled.count(), 25);
+    }
+}
@@ -0,0 +1,263 @@
+//! 同时将日志写入按大小或日期轮转的文件
+//!
+//! 通过 [`init_logger_with_file`](crate::logger::init_logger_with_file) 初始化日志记录器后，
+//! 每条日志除了输出到标准错误外，还会去掉颜色后追加到 [`FileLogConfig`] 指定的文件中。
+//! 文件超过设置的大小或日期变化时，当前文件会被重命名为带有时间后缀的归档文件，
+//! 并只保留最近的若干个归档文件。
+//!
+//! ```no_run
+//! use milky_rust_sdk::logger::{FileLogConfig, LogFormat, Rotation, init_logger_with_file};
+//!
+//! init_logger_with_file(
+//!     None,
+//!     LogFormat::Pretty,
+//!     FileLogConfig::new("logs", "bot.log")
+//!         .rotation(Rotation::Daily)
+//!         .max_files(7),
+//! )
+//! .expect("无法打开日志文件");
+//! ```
+
+use chrono::{Local, NaiveDate};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 日志文件的轮转方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// 不轮转，始终追加到同一个文件
+    #[default]
+    Never,
+    /// 文件超过指定的字节数后轮转
+    Size(u64),
+    /// 每天轮转一次
+    Daily,
+}
+
+/// 日志文件的设置
+#[derive(Debug, Clone)]
+pub struct FileLogConfig {
+    /// 日志文件所在的目录，不存在时会被创建
+    dir: PathBuf,
+    /// 当前日志文件的文件名
+    file_name: String,
+    /// 轮转方式
+    rotation: Rotation,
+    /// 最多保留的归档文件数量，为 `None` 时不删除
+    max_files: Option<usize>,
+}
+
+impl FileLogConfig {
+    /// 创建不轮转的日志文件设置
+    ///
+    /// # 参数
+    /// * `dir`: 日志文件所在的目录
+    /// * `file_name`: 当前日志文件的文件名，归档文件会在其后加上时间后缀
+    pub fn new(dir: impl Into<PathBuf>, file_name: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            file_name: file_name.into(),
+            rotation: Rotation::Never,
+            max_files: None,
+        }
+    }
+
+    /// 设置轮转方式
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// 设置最多保留的归档文件数量，更早的归档文件会在轮转时被删除
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+}
+
+/// 按设置轮转的日志文件
+pub(crate) struct RollingFile {
+    /// 日志文件的设置
+    config: FileLogConfig,
+    /// 当前打开的文件
+    file: File,
+    /// 当前文件已写入的字节数
+    size: u64,
+    /// 当前文件开始写入的日期
+    opened_on: NaiveDate,
+}
+
+impl RollingFile {
+    /// 打开日志文件，已存在时追加写入
+    pub(crate) fn open(config: FileLogConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let file = Self::open_current(&config)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            config,
+            file,
+            size,
+            opened_on: Local::now().date_naive(),
+        })
+    }
+
+    /// 当前日志文件的路径
+    fn current_path(config: &FileLogConfig) -> PathBuf {
+        config.dir.join(&config.file_name)
+    }
+
+    /// 以追加方式打开当前日志文件
+    fn open_current(config: &FileLogConfig) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::current_path(config))
+    }
+
+    /// 判断写入 `len` 字节前是否需要轮转
+    fn should_rotate(&self, len: usize, today: NaiveDate) -> bool {
+        match self.config.rotation {
+            Rotation::Never => false,
+            Rotation::Size(limit) => self.size > 0 && self.size + len as u64 > limit,
+            Rotation::Daily => today != self.opened_on,
+        }
+    }
+
+    /// 将当前文件重命名为归档文件，打开新的文件并清理多余的归档文件
+    fn rotate(&mut self, today: NaiveDate) -> io::Result<()> {
+        self.file.flush()?;
+        let suffix = match self.config.rotation {
+            Rotation::Daily => self.opened_on.format("%Y-%m-%d").to_string(),
+            _ => Local::now().format("%Y%m%d-%H%M%S%.3f").to_string(),
+        };
+        let archived = self
+            .config
+            .dir
+            .join(format!("{}.{suffix}", self.config.file_name));
+        fs::rename(Self::current_path(&self.config), archived)?;
+        self.file = Self::open_current(&self.config)?;
+        self.size = 0;
+        self.opened_on = today;
+        if let Some(max_files) = self.config.max_files {
+            prune_archives(&self.config.dir, &self.config.file_name, max_files)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = Local::now().date_naive();
+        if self.should_rotate(buf.len(), today) {
+            self.rotate(today)?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 删除最早的归档文件，只保留最近的 `max_files` 个
+fn prune_archives(dir: &Path, file_name: &str, max_files: usize) -> io::Result<()> {
+    let prefix = format!("{file_name}.");
+    let mut archives: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|entry| entry.path())
+        .collect();
+    // 时间后缀按字典序排列即为时间顺序
+    archives.sort();
+    let excess = archives.len().saturating_sub(max_files);
+    for path in &archives[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 将日志同时写入标准错误与日志文件，写入文件前去掉终端颜色
+pub(crate) struct TeeWriter {
+    /// 日志文件
+    file: RollingFile,
+}
+
+impl TeeWriter {
+    /// 创建写入器
+    pub(crate) fn new(file: RollingFile) -> Self {
+        Self { file }
+    }
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        // 写入文件失败时不影响终端输出
+        let _ = self.file.write_all(&strip_ansi(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        self.file.flush()
+    }
+}
+
+/// 去掉 ANSI 颜色控制序列
+fn strip_ansi(buf: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut bytes = buf.iter().copied();
+    while let Some(byte) = bytes.next() {
+        if byte == 0x1b {
+            // 跳过 `ESC [ ... m` 形式的控制序列
+            for byte in bytes.by_ref() {
+                if byte.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(byte);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rotation() {
+        let dir = std::env::temp_dir().join(format!("milky-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let config = FileLogConfig::new(&dir, "bot.log")
+            .rotation(Rotation::Size(16))
+            .max_files(2);
+        let mut file = RollingFile::open(config).unwrap();
+        for line in [
+            "first line 01\n",
+            "second line 2\n",
+            "third line 03\n",
+            "fourth line 4\n",
+        ] {
+            file.write_all(line.as_bytes()).unwrap();
+            // 保证归档文件的时间后缀互不相同
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let current = fs::read_to_string(dir.join("bot.log")).unwrap();
+        assert_eq!(current, "fourth line 4\n");
+        let archives = fs::read_dir(&dir).unwrap().count() - 1;
+        assert_eq!(archives, 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        let colored = format!("{} > 消息", ansi_term::Colour::Red.paint("[ERROR]"));
+        assert_eq!(strip_ansi(colored.as_bytes()), "[ERROR] > 消息".as_bytes());
+    }
+}
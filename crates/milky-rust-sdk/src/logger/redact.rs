@@ -0,0 +1,209 @@
+//! 输出日志前对访问令牌与消息内容进行脱敏
+//!
+//! SDK 在日志中输出事件 WebSocket 的地址时，会将 `access_token` 等查询参数替换为 `redacted`；
+//! 输出事件的原始内容时，会按 [`set_payload_logging`] 的设置决定输出完整内容、截断、隐去消息内容，
+//! 还是只输出长度，以免聊天内容随调试日志被收集到日志系统中。
+
+use serde_json::Value;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use std::fmt;
+use std::sync::RwLock;
+use url::Url;
+
+/// 键名包含这些字样的字段会被脱敏，不区分大小写
+const SENSITIVE_KEYS: &[&str] = &["token", "password", "secret", "cookie", "authorization"];
+
+/// 这些键下的内容属于消息内容，[`PayloadLogging::Scrubbed`] 时会被隐去
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+const MESSAGE_KEYS: &[&str] = &["segments", "message"];
+
+/// 日志中事件原始内容的输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PayloadLogging {
+    /// 输出脱敏后的完整内容
+    #[default]
+    Full,
+    /// 输出脱敏后的内容，最多保留指定数量的字符
+    Truncate(usize),
+    /// 隐去消息段中的文本、链接等内容，只保留事件的结构
+    Scrubbed,
+    /// 只输出内容的长度
+    Omit,
+}
+
+/// 当前的输出方式
+static PAYLOAD_LOGGING: RwLock<PayloadLogging> = RwLock::new(PayloadLogging::Full);
+
+/// 设置日志中事件原始内容的输出方式，对整个进程生效
+///
+/// # 参数
+/// * `mode`: 输出方式，默认为 [`PayloadLogging::Full`]
+pub fn set_payload_logging(mode: PayloadLogging) {
+    *PAYLOAD_LOGGING.write().unwrap() = mode;
+}
+
+/// 将 URL 中敏感的查询参数与密码替换为 `redacted`
+///
+/// # 返回
+/// 脱敏后的 URL，无法解析时原样返回
+pub fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if parsed.password().is_some() {
+        let _ = parsed.set_password(Some("redacted"));
+    }
+    if parsed.query().is_some() {
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .map(|(key, value)| {
+                let value = if is_sensitive(&key) {
+                    "redacted".to_string()
+                } else {
+                    value.into_owned()
+                };
+                (key.into_owned(), value)
+            })
+            .collect();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    parsed.to_string()
+}
+
+/// 对 JSON 中的敏感字段与 Base64 内容进行脱敏
+pub(crate) fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let value = if is_sensitive(key) {
+                    Value::String("<redacted>".to_string())
+                } else {
+                    redact(value)
+                };
+                (key.clone(), value)
+            })
+            .collect(),
+        Value::Array(items) => items.iter().map(redact).collect(),
+        Value::String(s) => match s.strip_prefix("base64://") {
+            Some(data) => Value::String(format!("base64://<已省略 {} 字符>", data.len())),
+            None => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// 错误信息中请求参数摘要的最大字符数
+const SUMMARY_LIMIT: usize = 200;
+
+/// 生成请求参数的摘要，用于错误信息
+///
+/// # 返回
+/// 脱敏后的 JSON 文本，超过 200 个字符时截断
+pub(crate) fn summarize(value: &Value) -> String {
+    let rendered = redact(value).to_string();
+    if rendered.chars().count() <= SUMMARY_LIMIT {
+        return rendered;
+    }
+    let truncated: String = rendered.chars().take(SUMMARY_LIMIT).collect();
+    format!("{truncated}...")
+}
+
+/// 隐去消息内容，只保留结构与消息段类型
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+fn scrub(value: &Value, in_message: bool) -> Value {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let value = if in_message && key == "type" {
+                    value.clone()
+                } else {
+                    scrub(value, in_message || MESSAGE_KEYS.contains(&key.as_str()))
+                };
+                (key.clone(), value)
+            })
+            .collect(),
+        Value::Array(items) => items.iter().map(|item| scrub(item, in_message)).collect(),
+        Value::String(s) if in_message => {
+            Value::String(format!("<已省略 {} 字符>", s.chars().count()))
+        }
+        _ => value.clone(),
+    }
+}
+
+/// 判断键名是否属于敏感字段
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEYS.iter().any(|k| key.contains(k))
+}
+
+/// 按当前的输出方式格式化事件原始文本，只在日志实际输出时才进行脱敏
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub(crate) struct LogPayload<'a>(pub(crate) &'a str);
+
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+impl fmt::Display for LogPayload<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = *PAYLOAD_LOGGING.read().unwrap();
+        let text = self.0;
+        if mode == PayloadLogging::Omit {
+            return write!(f, "<已省略 {} 字节>", text.len());
+        }
+        let rendered = match serde_json::from_str::<Value>(text).ok() {
+            Some(value) if mode == PayloadLogging::Scrubbed => {
+                scrub(&redact(&value), false).to_string()
+            }
+            Some(value) => redact(&value).to_string(),
+            // 无法解析为 JSON 的文本不包含结构化的令牌字段，原样输出或截断
+            None if mode != PayloadLogging::Scrubbed => text.to_string(),
+            None => "<无法解析的内容>".to_string(),
+        };
+        match mode {
+            PayloadLogging::Truncate(limit) if rendered.chars().count() > limit => {
+                let truncated: String = rendered.chars().take(limit).collect();
+                write!(f, "{truncated}...")
+            }
+            _ => f.write_str(&rendered),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("ws://127.0.0.1:3000/event?access_token=secret&foo=bar"),
+            "ws://127.0.0.1:3000/event?access_token=redacted&foo=bar"
+        );
+        assert_eq!(
+            redact_url("ws://127.0.0.1:3000/event"),
+            "ws://127.0.0.1:3000/event"
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    fn test_scrub_message_contents() {
+        let event = serde_json::json!({
+            "event_type": "message_receive",
+            "data": {
+                "peer_id": 123,
+                "segments": [{"type": "text", "data": {"text": "你好"}}],
+            },
+        });
+        assert_eq!(
+            scrub(&event, false),
+            serde_json::json!({
+                "event_type": "message_receive",
+                "data": {
+                    "peer_id": 123,
+                    "segments": [{"type": "text", "data": {"text": "<已省略 2 字符>"}}],
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! 发送前的媒体预处理
+//!
+//! 本模块提供在发送图片等媒体消息段之前对其进行处理的扩展点：
+//! - 启用 `image` 特性后，[`image`] 模块可以使用 `image` 库自动缩小或重新压缩超出尺寸、体积限制的图片，
+//!   避免因图片过大而被服务端拒绝
+//! - [`transcode`] 模块可以在发送前将 wav、mp3 等格式的语音转码为 silk 或 amr
+
+#[cfg(feature = "image")]
+pub mod image;
+pub mod transcode;
+
+use crate::client::MilkyClient;
+use crate::utils::mime::{HEAD_SIZE, check_media_segment, media_uri, read_media};
+
+use milky_types::common::FileUri;
+use milky_types::message::out_going::OutgoingSegment;
+
+impl MilkyClient {
+    /// 发送前对消息中的媒体消息段进行预处理与检查
+    ///
+    /// 每个媒体文件只读取一次：需要预处理时读取完整内容，否则只读取识别类型所需的头部。
+    pub(crate) async fn prepare_outgoing(
+        &self,
+        mut segments: Vec<OutgoingSegment>,
+    ) -> Vec<OutgoingSegment> {
+        for segment in &mut segments {
+            let Some(uri) = media_uri(segment) else {
+                continue;
+            };
+            let full = match segment {
+                #[cfg(feature = "image")]
+                OutgoingSegment::Image(_) => self.image_preprocessor.is_some(),
+                OutgoingSegment::Record(_) => self.transcoder.is_some(),
+                _ => false,
+            };
+            let Some(data) = read_media(uri, full).await else {
+                continue;
+            };
+
+            let original_head = data[..data.len().min(HEAD_SIZE)].to_vec();
+            match segment {
+                #[cfg(feature = "image")]
+                OutgoingSegment::Image(image) if full => self.preprocess_image(image, data).await,
+                OutgoingSegment::Record(record) if full => {
+                    self.transcode_record(record, data).await
+                }
+                _ => {}
+            }
+            // 预处理后的内容总是以 Base64 形式保存，否则检查原始内容
+            let head = match media_uri(segment) {
+                Some(FileUri::Base64(data)) => data.as_slice(),
+                _ => original_head.as_slice(),
+            };
+            check_media_segment(segment, head);
+        }
+        segments
+    }
+}
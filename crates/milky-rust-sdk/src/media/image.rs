@@ -0,0 +1,301 @@
+//! 发送前的图片预处理
+//!
+//! 通过 [`MilkyClient::with_image_preprocessor`] 设置 [`ImageLimits`] 与 [`ImageProcessor`] 后，
+//! 发送消息时会检查图片消息段中的本地文件与 Base64 内容，超出限制的图片会交给处理器缩小或重新压缩，
+//! 并以 Base64 内容替换原始图片。网络链接不会被下载检查。
+//!
+//! 内置的 [`ResizeImageProcessor`] 使用 [`image`](::image) 库在进程内完成缩放与压缩；
+//! 同时启用 `ffmpeg` 特性后，还可以使用调用系统中 `ffmpeg` 命令的 `FfmpegImageProcessor`。
+//! 也可以基于其他图像库实现 [`ImageProcessor`]。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+use crate::logger::{debug, warn};
+use crate::runtime;
+
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::imageops::FilterType;
+use ::image::{GenericImageView, ImageReader};
+use milky_types::common::FileUri;
+use milky_types::message::out_going::ImageData;
+use std::io::Cursor;
+#[cfg(feature = "ffmpeg")]
+use std::process::Command;
+use std::sync::Arc;
+
+/// 图片的尺寸与体积限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    /// 最大宽度（像素）
+    pub max_width: u32,
+    /// 最大高度（像素）
+    pub max_height: u32,
+    /// 最大体积（字节）
+    pub max_bytes: usize,
+}
+
+impl Default for ImageLimits {
+    /// 默认限制为 4096x4096 像素、10 MiB
+    fn default() -> Self {
+        Self {
+            max_width: 4096,
+            max_height: 4096,
+            max_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl ImageLimits {
+    /// 判断图片是否超出限制
+    ///
+    /// # 参数
+    /// * `data`: 图片的原始字节
+    pub fn exceeds(&self, data: &[u8]) -> bool {
+        if data.len() > self.max_bytes {
+            return true;
+        }
+        image_dimensions(data)
+            .is_some_and(|(width, height)| width > self.max_width || height > self.max_height)
+    }
+}
+
+/// 图片处理器
+///
+/// 处理过程可能较为耗时，会在阻塞线程池中执行。
+pub trait ImageProcessor: Send + Sync {
+    /// 将图片处理为满足限制的新图片
+    ///
+    /// # 参数
+    /// * `data`: 原始图片的字节
+    /// * `limits`: 需要满足的限制
+    ///
+    /// # 返回
+    /// 成功则返回处理后图片的字节
+    fn process(&self, data: &[u8], limits: &ImageLimits) -> Result<Vec<u8>>;
+}
+
+/// 使用 [`image`](::image) 库按比例缩小并重新压缩为 JPEG 的图片处理器
+///
+/// 支持 PNG、JPEG、GIF、BMP 与 WebP，动图只保留第一帧，透明背景会被去除。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResizeImageProcessor;
+
+impl ImageProcessor for ResizeImageProcessor {
+    fn process(&self, data: &[u8], limits: &ImageLimits) -> Result<Vec<u8>> {
+        let image = ::image::load_from_memory(data)
+            .map_err(|e| MilkyError::Internal(format!("无法解码图片: {e}")))?;
+        let (width, height) = image.dimensions();
+        let image = if width > limits.max_width || height > limits.max_height {
+            // 按比例缩放到限制以内
+            image.resize(limits.max_width, limits.max_height, FilterType::Triangle)
+        } else {
+            image
+        };
+        let rgb = image.into_rgb8();
+
+        let mut processed = Vec::new();
+        // 从较高的画质开始，直到体积满足限制
+        for quality in [90, 80, 65, 50, 35] {
+            processed.clear();
+            JpegEncoder::new_with_quality(&mut processed, quality)
+                .encode_image(&rgb)
+                .map_err(|e| MilkyError::Internal(format!("图片压缩失败: {e}")))?;
+            if processed.len() <= limits.max_bytes {
+                break;
+            }
+        }
+        Ok(processed)
+    }
+}
+
+/// 调用 `ffmpeg` 命令缩小并重新压缩为 JPEG 的图片处理器
+#[cfg(feature = "ffmpeg")]
+#[derive(Debug, Clone)]
+pub struct FfmpegImageProcessor {
+    /// `ffmpeg` 可执行文件的路径
+    program: String,
+}
+
+#[cfg(feature = "ffmpeg")]
+impl Default for FfmpegImageProcessor {
+    fn default() -> Self {
+        Self::new("ffmpeg")
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl FfmpegImageProcessor {
+    /// 创建处理器
+    ///
+    /// # 参数
+    /// * `program`: `ffmpeg` 可执行文件的路径，位于 `PATH` 中时可直接传入 `ffmpeg`
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl ImageProcessor for FfmpegImageProcessor {
+    fn process(&self, data: &[u8], limits: &ImageLimits) -> Result<Vec<u8>> {
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let input = dir.join(format!("milky-image-{id}.in"));
+        let output = dir.join(format!("milky-image-{id}.jpg"));
+        std::fs::write(&input, data)?;
+
+        let scale = format!(
+            "scale='min({},iw)':'min({},ih)':force_original_aspect_ratio=decrease",
+            limits.max_width, limits.max_height
+        );
+        let mut result = Err(MilkyError::Internal("图片压缩失败".to_string()));
+        // 从较高的画质开始，直到体积满足限制
+        for quality in [3, 8, 15, 31] {
+            let status = Command::new(&self.program)
+                .args(["-y", "-loglevel", "error", "-i"])
+                .arg(&input)
+                .args(["-vf", &scale, "-q:v", &quality.to_string()])
+                .arg(&output)
+                .status();
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => {
+                    result = Err(MilkyError::Internal(format!("ffmpeg 执行失败: {status}")));
+                    break;
+                }
+                Err(e) => {
+                    result = Err(MilkyError::Io(e));
+                    break;
+                }
+            }
+            let processed = std::fs::read(&output)?;
+            let fits = processed.len() <= limits.max_bytes;
+            result = Ok(processed);
+            if fits {
+                break;
+            }
+        }
+
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+        result
+    }
+}
+
+/// 客户端使用的图片预处理设置
+#[derive(Clone)]
+pub(crate) struct ImagePreprocessor {
+    /// 图片限制
+    limits: ImageLimits,
+    /// 图片处理器
+    processor: Arc<dyn ImageProcessor>,
+}
+
+impl MilkyClient {
+    /// 设置发送前的图片预处理，超出限制的图片会交给处理器处理
+    ///
+    /// # 参数
+    /// * `limits`: 图片的尺寸与体积限制
+    /// * `processor`: 图片处理器，例如 [`ResizeImageProcessor`]
+    pub fn with_image_preprocessor(
+        mut self,
+        limits: ImageLimits,
+        processor: impl ImageProcessor + 'static,
+    ) -> Self {
+        self.image_preprocessor = Some(ImagePreprocessor {
+            limits,
+            processor: Arc::new(processor),
+        });
+        self
+    }
+
+    /// 图片超出限制时进行预处理，处理失败时保留原图并输出警告
+    ///
+    /// # 参数
+    /// * `image`: 图片消息段，处理成功后以 Base64 内容替换原图
+    /// * `data`: 已读取的完整图片内容
+    pub(crate) async fn preprocess_image(&self, image: &mut ImageData, data: Vec<u8>) {
+        let Some(preprocessor) = &self.image_preprocessor else {
+            return;
+        };
+        if !preprocessor.limits.exceeds(&data) {
+            return;
+        }
+
+        let ImagePreprocessor { limits, processor } = preprocessor.clone();
+        let original_size = data.len();
+        let processed = runtime::spawn_blocking(move || processor.process(&data, &limits)).await;
+        match processed {
+            Ok(Ok(processed)) => {
+                debug!(
+                    "图片已压缩: {original_size} 字节 -> {} 字节",
+                    processed.len()
+                );
+                image.uri = FileUri::Base64(processed);
+            }
+            Ok(Err(e)) => warn!("图片预处理失败，将发送原图: {e}"),
+            Err(e) => warn!("图片预处理任务异常，将发送原图: {e}"),
+        }
+    }
+}
+
+/// 从图片头部读取宽高，支持 PNG、JPEG、GIF、BMP 与 WebP，不会解码整张图片
+///
+/// # 参数
+/// * `data`: 图片的原始字节
+///
+/// # 返回
+/// 识别成功则返回 `(宽, 高)`
+pub fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 编码一张纯色的 PNG 图片
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = ::image::RgbImage::from_pixel(width, height, ::image::Rgb([200, 80, 40]));
+        let mut data = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut data), ::image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_image_dimensions_and_limits() {
+        let wide = png(500, 30);
+        assert_eq!(image_dimensions(&wide), Some((500, 30)));
+
+        let limits = ImageLimits {
+            max_width: 100,
+            max_height: 100,
+            max_bytes: 10 * 1024,
+        };
+        assert!(limits.exceeds(&wide));
+        assert!(!limits.exceeds(&png(100, 100)));
+        assert!(!limits.exceeds(b"unknown"));
+    }
+
+    #[test]
+    fn test_resize_image_processor() {
+        let limits = ImageLimits {
+            max_width: 100,
+            max_height: 100,
+            max_bytes: 10 * 1024,
+        };
+        let processed = ResizeImageProcessor
+            .process(&png(500, 30), &limits)
+            .unwrap();
+        assert_eq!(image_dimensions(&processed), Some((100, 6)));
+        assert!(!limits.exceeds(&processed));
+        assert!(ResizeImageProcessor.process(b"unknown", &limits).is_err());
+    }
+}
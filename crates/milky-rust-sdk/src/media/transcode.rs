@@ -0,0 +1,182 @@
+//! 语音消息段的音频转码
+//!
+//! QQ 的语音消息通常需要 silk 或 amr 格式。通过 [`MilkyClient::with_transcoder`] 设置 [`Transcoder`] 后，
+//! 发送消息时若语音消息段的本地文件或 Base64 内容是 wav、mp3 等其他音频格式，会先交给转码器转换，
+//! 并以 Base64 内容替换原始音频。默认不进行任何转码。
+//!
+//! 启用 `ffmpeg` 特性后可使用内置的 [`FfmpegTranscoder`]，它调用系统中的 `ffmpeg` 命令将音频转换为 amr。
+
+use crate::client::MilkyClient;
+use crate::error::Result;
+use crate::logger::{debug, warn};
+use crate::runtime;
+use crate::utils::detect_mime;
+
+use milky_types::common::FileUri;
+use milky_types::message::out_going::RecordData;
+use std::sync::Arc;
+
+/// 无需转码即可发送的音频类型
+const NATIVE_TYPES: &[&str] = &["audio/silk", "audio/amr"];
+
+/// 音频转码器
+///
+/// 转码过程可能较为耗时，会在阻塞线程池中执行。
+pub trait Transcoder: Send + Sync {
+    /// 将音频转换为可以直接发送的格式
+    ///
+    /// # 参数
+    /// * `data`: 原始音频的字节
+    /// * `mime`: 原始音频的 MIME 类型，例如 `audio/wav`
+    ///
+    /// # 返回
+    /// 成功则返回转码后的音频字节；返回 `Ok(None)` 表示保留原始音频
+    fn transcode(&self, data: &[u8], mime: &str) -> Result<Option<Vec<u8>>>;
+}
+
+/// 调用 `ffmpeg` 命令将音频转换为 amr 的转码器
+#[cfg(feature = "ffmpeg")]
+#[derive(Debug, Clone)]
+pub struct FfmpegTranscoder {
+    /// `ffmpeg` 可执行文件的路径
+    program: String,
+}
+
+#[cfg(feature = "ffmpeg")]
+impl Default for FfmpegTranscoder {
+    fn default() -> Self {
+        Self::new("ffmpeg")
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl FfmpegTranscoder {
+    /// 创建转码器
+    ///
+    /// # 参数
+    /// * `program`: `ffmpeg` 可执行文件的路径，位于 `PATH` 中时可直接传入 `ffmpeg`
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl Transcoder for FfmpegTranscoder {
+    fn transcode(&self, data: &[u8], _mime: &str) -> Result<Option<Vec<u8>>> {
+        use crate::error::MilkyError;
+
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let input = dir.join(format!("milky-record-{id}.in"));
+        let output = dir.join(format!("milky-record-{id}.amr"));
+        std::fs::write(&input, data)?;
+
+        let status = std::process::Command::new(&self.program)
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(&input)
+            .args([
+                "-ar",
+                "8000",
+                "-ac",
+                "1",
+                "-c:a",
+                "libopencore_amrnb",
+                "-b:a",
+                "12.2k",
+            ])
+            .arg(&output)
+            .status();
+        let result = match status {
+            Ok(status) if status.success() => std::fs::read(&output).map(Some).map_err(Into::into),
+            Ok(status) => Err(MilkyError::Internal(format!("ffmpeg 执行失败: {status}"))),
+            Err(e) => Err(MilkyError::Io(e)),
+        };
+
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+        result
+    }
+}
+
+impl MilkyClient {
+    /// 设置语音消息段的音频转码器
+    ///
+    /// # 参数
+    /// * `transcoder`: 音频转码器，例如启用 `ffmpeg` 特性后的 `FfmpegTranscoder`
+    pub fn with_transcoder(mut self, transcoder: impl Transcoder + 'static) -> Self {
+        self.transcoder = Some(Arc::new(transcoder));
+        self
+    }
+
+    /// 语音不是可以直接发送的格式时进行转码，转码失败时保留原始音频并输出警告
+    ///
+    /// # 参数
+    /// * `record`: 语音消息段，转码成功后以 Base64 内容替换原始音频
+    /// * `data`: 已读取的完整音频内容
+    pub(crate) async fn transcode_record(&self, record: &mut RecordData, data: Vec<u8>) {
+        let Some(transcoder) = &self.transcoder else {
+            return;
+        };
+        let Some(mime) = detect_mime(&data).filter(|mime| mime.starts_with("audio/")) else {
+            return;
+        };
+        if NATIVE_TYPES.contains(&mime) {
+            return;
+        }
+
+        let transcoder = transcoder.clone();
+        let transcoded = runtime::spawn_blocking(move || transcoder.transcode(&data, mime)).await;
+        match transcoded {
+            Ok(Ok(Some(transcoded))) => {
+                debug!("语音已从 {mime} 转码，大小 {} 字节", transcoded.len());
+                record.uri = FileUri::Base64(transcoded);
+            }
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => warn!("语音转码失败，将发送原始音频: {e}"),
+            Err(e) => warn!("语音转码任务异常，将发送原始音频: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+    use milky_types::message::out_going::OutgoingSegment;
+
+    /// 将任意音频替换为固定内容的转码器
+    struct FakeTranscoder;
+
+    impl Transcoder for FakeTranscoder {
+        fn transcode(&self, _data: &[u8], mime: &str) -> Result<Option<Vec<u8>>> {
+            assert_eq!(mime, "audio/wav");
+            Ok(Some(b"#!AMR\n".to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcode_records() {
+        let (client, _rx) = test_util::client(OFFLINE);
+        let client = client.with_transcoder(FakeTranscoder);
+
+        let record = |data: &[u8]| {
+            OutgoingSegment::Record(RecordData {
+                uri: FileUri::Base64(data.to_vec()),
+            })
+        };
+        let segments = vec![record(b"RIFF\0\0\0\0WAVEfmt "), record(b"#!SILK_V3")];
+        let segments = client.prepare_outgoing(segments).await;
+
+        let uris: Vec<_> = segments
+            .iter()
+            .map(|segment| match segment {
+                OutgoingSegment::Record(record) => record.uri.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(uris[0], FileUri::Base64(b"#!AMR\n".to_vec()));
+        assert_eq!(uris[1], FileUri::Base64(b"#!SILK_V3".to_vec()));
+    }
+}
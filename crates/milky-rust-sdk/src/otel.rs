@@ -0,0 +1,164 @@
+//! API 调用的 OpenTelemetry 风格 span 与链路上下文传播
+//!
+//! 启用 `otel` 特性（同时会启用 `tracing` 特性）后，每次 API 调用都会创建一个 `send_request` span，
+//! 带有 `otel.kind = "client"`、`rpc.method`（API操作名）、`retcode` 与 `latency_ms` 等属性，
+//! 可通过 `tracing-opentelemetry` 等桥接库导出到 OpenTelemetry。
+//!
+//! 同时，客户端会在每个 HTTP 请求中注入 [W3C Trace Context](https://www.w3.org/TR/trace-context/)
+//! 请求头，使 API 调用可以与协议端自身的链路关联。默认的 [`W3cPropagator`] 通过 `tracing-opentelemetry`
+//! 读取当前 span（即该次调用的 `send_request` span）的 OpenTelemetry 上下文，并写入 `traceparent`
+//! 与 `tracestate`；订阅者中没有 `tracing_opentelemetry::OpenTelemetryLayer`，当前 span 没有有效的上下文时不注入请求头。
+//! 需要其他传播格式时可以实现 [`TracePropagator`] 并通过 [`MilkyClient::with_trace_propagator`] 设置。
+
+use crate::client::MilkyClient;
+use crate::error::{MilkyError, Result};
+
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{Instrument, field};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// `traceparent` 请求头的名称
+const TRACEPARENT: &str = "traceparent";
+
+/// `tracestate` 请求头的名称
+const TRACESTATE: &str = "tracestate";
+
+/// 向 API 请求注入链路上下文的传播器
+pub trait TracePropagator: Send + Sync {
+    /// 向即将发送的 HTTP 请求头中写入链路上下文
+    ///
+    /// # 参数
+    /// * `headers`: 请求头，调用时当前 span 为本次 API 调用的 span
+    fn inject(&self, headers: &mut HeaderMap);
+}
+
+/// 以当前 span 的 OpenTelemetry 上下文写入 W3C `traceparent` 与 `tracestate` 请求头的传播器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct W3cPropagator;
+
+impl TracePropagator for W3cPropagator {
+    fn inject(&self, headers: &mut HeaderMap) {
+        let context = tracing::Span::current().context();
+        let span = context.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+        if let Ok(value) = HeaderValue::from_str(&traceparent(span_context)) {
+            headers.insert(TRACEPARENT, value);
+        }
+        let state = span_context.trace_state().header();
+        if !state.is_empty()
+            && let Ok(value) = HeaderValue::from_str(&state)
+        {
+            headers.insert(TRACESTATE, value);
+        }
+    }
+}
+
+/// 生成 `traceparent` 请求头的值
+fn traceparent(span_context: &SpanContext) -> String {
+    format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    )
+}
+
+/// 在 `send_request` span 中执行 API 请求，并在结束后记录返回码与耗时
+pub(crate) async fn instrument_request<R>(
+    action: &str,
+    request: impl Future<Output = Result<R>>,
+) -> Result<R> {
+    let span = tracing::info_span!(
+        "send_request",
+        otel.kind = "client",
+        otel.status_code = field::Empty,
+        rpc.system = "milky",
+        rpc.method = action,
+        peer_id = field::Empty,
+        message_seq = field::Empty,
+        retcode = field::Empty,
+        http.status_code = field::Empty,
+        latency_ms = field::Empty,
+    );
+    let started = Instant::now();
+    let result = request.instrument(span.clone()).await;
+
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    match &result {
+        Ok(_) => {
+            span.record("retcode", 0);
+        }
+        Err(e) => {
+            span.record("otel.status_code", "ERROR");
+            match e {
+                MilkyError::ApiError {
+                    retcode: Some(retcode),
+                    ..
+                } => {
+                    span.record("retcode", retcode);
+                }
+                MilkyError::HttpApiError { status, .. } => {
+                    span.record("http.status_code", status.as_u16());
+                }
+                _ => {}
+            }
+        }
+    }
+    result
+}
+
+impl MilkyClient {
+    /// 设置向 API 请求注入链路上下文的传播器，默认为 [`W3cPropagator`]
+    pub fn with_trace_propagator(mut self, propagator: impl TracePropagator + 'static) -> Self {
+        self.trace_propagator = Arc::new(propagator);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::Context;
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_inject_current_context() {
+        // 没有 OpenTelemetry 上下文时不注入请求头
+        let mut headers = HeaderMap::new();
+        W3cPropagator.inject(&mut headers);
+        assert!(headers.is_empty());
+
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_opentelemetry::layer()
+                .with_tracer(opentelemetry::trace::noop::NoopTracer::new()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let parent = SpanContext::new(
+                TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+                SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::from_key_value([("milky", "1")]).unwrap(),
+            );
+            let span = tracing::info_span!("send_request");
+            let _ = span.set_parent(Context::new().with_remote_span_context(parent));
+            let _entered = span.enter();
+
+            let mut headers = HeaderMap::new();
+            W3cPropagator.inject(&mut headers);
+            assert_eq!(
+                headers[TRACEPARENT],
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+            );
+            assert_eq!(headers[TRACESTATE], "milky=1");
+        });
+    }
+}
@@ -0,0 +1,210 @@
+//! 异步运行时相关的基础操作
+//!
+//! SDK 内部的任务派生、定时器与阻塞任务都通过本模块完成，具体使用的运行时由特性决定：
+//! - 默认使用 tokio
+//! - 启用 `smol` 特性后，后台任务在 smol 的全局执行器中运行，定时器与阻塞任务也由 smol 提供
+//!
+//! 公开接口返回的后台任务句柄统一为 [`JoinHandle`]，不暴露具体运行时的类型。
+//!
+//! HTTP 客户端 reqwest、WebSocket 客户端 tokio-tungstenite 与 WebHook 服务端 axum 的网络 IO 仍依赖 tokio 的反应器。
+//! 启用 `smol` 特性时，SDK 派生的后台任务会通过 `async-compat` 自动获得 tokio 的运行时上下文；
+//! 在应用自己的任务中直接等待 SDK 的异步方法时，需要用 [`Compat`] 包装：
+//!
+//! ```ignore
+//! use milky_rust_sdk::runtime::Compat;
+//!
+//! smol::block_on(Compat::new(async {
+//!     let task = client.connect_events().await?;
+//!     task.await
+//! }))
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "smol")]
+mod smol_backend;
+#[cfg(not(feature = "smol"))]
+mod tokio_backend;
+
+#[cfg(feature = "smol")]
+use smol_backend as backend;
+#[cfg(not(feature = "smol"))]
+use tokio_backend as backend;
+
+#[cfg(feature = "smol")]
+pub use async_compat::Compat;
+
+/// 后台任务的句柄
+///
+/// 等待句柄即可得到任务的返回值；丢弃句柄不会中止任务，需要中止时调用 [`abort`](Self::abort)。
+pub struct JoinHandle<T> {
+    inner: backend::Handle<T>,
+}
+
+impl<T> JoinHandle<T> {
+    /// 中止后台任务，之后等待句柄会得到 [`JoinError::is_cancelled`] 为 `true` 的错误
+    pub fn abort(&self) {
+        self.inner.abort();
+    }
+
+    /// 后台任务是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// 后台任务没有正常结束的原因
+#[derive(Debug)]
+pub struct JoinError {
+    /// 任务是否被中止，否则为执行时发生 panic
+    cancelled: bool,
+}
+
+impl JoinError {
+    /// 任务被中止
+    fn cancelled() -> Self {
+        Self { cancelled: true }
+    }
+
+    /// 任务执行时发生 panic
+    fn panicked() -> Self {
+        Self { cancelled: false }
+    }
+
+    /// 任务是否被 [`JoinHandle::abort`] 中止
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// 任务是否因 panic 而结束
+    pub fn is_panic(&self) -> bool {
+        !self.cancelled
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cancelled {
+            f.write_str("任务已被中止")
+        } else {
+            f.write_str("任务执行时发生 panic")
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+/// 异步任务超过了设置的超时时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Elapsed;
+
+/// 在后台运行一个异步任务
+pub(crate) fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    JoinHandle {
+        inner: backend::spawn(future),
+    }
+}
+
+/// 在专用线程池中运行一个阻塞任务
+pub(crate) fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    JoinHandle {
+        inner: backend::spawn_blocking(f),
+    }
+}
+
+/// 等待指定的时长
+pub(crate) async fn sleep(duration: Duration) {
+    backend::sleep(duration).await;
+}
+
+/// 为异步任务设置超时时间
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    backend::timeout(duration, future).await
+}
+
+/// 按固定间隔触发的定时器
+pub(crate) struct Interval {
+    /// 触发间隔
+    period: Duration,
+    /// 下一次触发的时间
+    next: Instant,
+}
+
+impl Interval {
+    /// 等待下一次触发，错过的触发不会补上
+    pub(crate) async fn tick(&mut self) {
+        let now = Instant::now();
+        if self.next > now {
+            sleep(self.next - now).await;
+        }
+        self.next = self.next.max(now) + self.period;
+    }
+}
+
+/// 创建一个按固定间隔触发的定时器，首次触发是立即的
+pub(crate) fn interval(period: Duration) -> Interval {
+    Interval {
+        period,
+        next: Instant::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_join_handle() {
+        assert_eq!(spawn(async { 1 }).await.unwrap(), 1);
+        assert_eq!(spawn_blocking(|| 2).await.unwrap(), 2);
+
+        let handle = spawn(sleep(Duration::from_secs(10)));
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+
+        let handle = spawn(async { panic!("boom") });
+        assert!(handle.await.unwrap_err().is_panic());
+
+        assert!(
+            timeout(Duration::from_millis(10), sleep(Duration::from_secs(10)))
+                .await
+                .is_err()
+        );
+        let mut ticker = interval(Duration::from_millis(10));
+        let started = Instant::now();
+        ticker.tick().await;
+        ticker.tick().await;
+        assert!(started.elapsed() >= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,92 @@
+//! 基于 smol 的运行时实现
+//!
+//! 任务在 smol 的全局执行器中运行，并通过 [`Compat`] 提供 reqwest、tokio-tungstenite 等库所需的 tokio 运行时上下文。
+//! smol 的任务在句柄被丢弃时会被取消，这里在丢弃句柄时分离任务，与 tokio 的行为保持一致。
+
+use super::{Elapsed, JoinError};
+use async_compat::Compat;
+use futures_util::FutureExt;
+use futures_util::future::{AbortHandle, Abortable, Aborted, Either};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// 任务的输出：被中止时为 `Err(Aborted)`，发生 panic 时为 `Ok(Err(_))`
+type Output<T> = Result<std::thread::Result<T>, Aborted>;
+
+/// smol 任务的句柄
+pub(super) struct Handle<T> {
+    /// 任务本身，只在丢弃句柄时取出
+    task: Option<smol::Task<Output<T>>>,
+    /// 中止任务的句柄
+    abort: AbortHandle,
+}
+
+impl<T> Handle<T> {
+    pub(super) fn abort(&self) {
+        self.abort.abort();
+    }
+
+    pub(super) fn is_finished(&self) -> bool {
+        self.task.as_ref().is_none_or(|task| task.is_finished())
+    }
+}
+
+impl<T> Future for Handle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let task = self.task.as_mut().expect("任务句柄在丢弃前不会被取出");
+        Pin::new(task).poll(cx).map(|output| match output {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(JoinError::panicked()),
+            Err(Aborted) => Err(JoinError::cancelled()),
+        })
+    }
+}
+
+impl<T> Drop for Handle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.detach();
+        }
+    }
+}
+
+pub(super) fn spawn<F>(future: F) -> Handle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (abort, registration) = AbortHandle::new_pair();
+    let future = AssertUnwindSafe(Compat::new(future)).catch_unwind();
+    Handle {
+        task: Some(smol::spawn(Abortable::new(future, registration))),
+        abort,
+    }
+}
+
+pub(super) fn spawn_blocking<F, R>(f: F) -> Handle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    spawn(smol::unblock(f))
+}
+
+pub(super) async fn sleep(duration: Duration) {
+    smol::Timer::after(duration).await;
+}
+
+pub(super) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    let future = std::pin::pin!(future);
+    match futures_util::future::select(future, smol::Timer::after(duration)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
@@ -0,0 +1,63 @@
+//! 基于 tokio 的运行时实现
+
+use super::{Elapsed, JoinError};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// tokio 任务的句柄
+pub(super) struct Handle<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Handle<T> {
+    pub(super) fn abort(&self) {
+        self.0.abort();
+    }
+
+    pub(super) fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+impl<T> Future for Handle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx).map_err(|e| {
+            if e.is_cancelled() {
+                JoinError::cancelled()
+            } else {
+                JoinError::panicked()
+            }
+        })
+    }
+}
+
+pub(super) fn spawn<F>(future: F) -> Handle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    Handle(tokio::spawn(future))
+}
+
+pub(super) fn spawn_blocking<F, R>(f: F) -> Handle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    Handle(tokio::task::spawn_blocking(f))
+}
+
+pub(super) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+pub(super) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Elapsed)
+}
@@ -0,0 +1,179 @@
+//! 按 API 操作统计的调用数据
+//!
+//! 客户端会记录每次 [`MilkyClient::send_request`] 的耗时与是否成功，
+//! 通过 [`MilkyClient::api_stats`] 可以查看每个 API 操作的调用次数、错误率与延迟分位数，
+//! 也可以通过 [`MilkyClient::spawn_api_stats_logger`] 定期在日志中输出统计摘要，用于排查拖慢机器人的接口。
+//!
+//! 调用次数与错误次数从客户端创建起累计；错误率与延迟分位数只基于每个操作最近的若干次调用计算。
+//!
+//! 事件管线的积压情况参见 [`events`] 模块，导出到外部指标系统参见 [`sink`] 模块。
+
+pub mod events;
+pub mod sink;
+
+pub use events::{EventGauges, InstrumentedEvents};
+pub use sink::{MetricsSink, error_code};
+
+use crate::client::MilkyClient;
+use crate::logger::info;
+use crate::runtime::{self, JoinHandle};
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 每个操作保留用于计算错误率与延迟分位数的最近调用数
+const WINDOW_SIZE: usize = 1024;
+
+/// 单个 API 操作的调用统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionStats {
+    /// API操作的名称，例如 `send_group_message`
+    pub action: String,
+    /// 累计调用次数
+    pub calls: u64,
+    /// 累计失败次数
+    pub errors: u64,
+    /// 最近调用的错误率，取值 0 到 1
+    pub error_rate: f64,
+    /// 最近调用耗时的中位数
+    pub p50: Duration,
+    /// 最近调用耗时的第 99 百分位数
+    pub p99: Duration,
+}
+
+/// 单个操作的累计次数与最近调用记录
+#[derive(Default)]
+struct ActionWindow {
+    /// 累计调用次数
+    calls: u64,
+    /// 累计失败次数
+    errors: u64,
+    /// 最近调用的耗时与是否成功
+    recent: VecDeque<(Duration, bool)>,
+}
+
+/// 记录 API 调用数据的统计器
+#[derive(Default)]
+pub(crate) struct ApiStatsRecorder {
+    /// 各操作的统计数据
+    actions: Mutex<HashMap<String, ActionWindow>>,
+}
+
+impl ApiStatsRecorder {
+    /// 记录一次调用
+    pub(crate) fn record(&self, action: &str, latency: Duration, ok: bool) {
+        let mut actions = self.actions.lock().unwrap();
+        let window = match actions.get_mut(action) {
+            Some(window) => window,
+            None => actions.entry(action.to_string()).or_default(),
+        };
+        window.calls += 1;
+        if !ok {
+            window.errors += 1;
+        }
+        if window.recent.len() == WINDOW_SIZE {
+            window.recent.pop_front();
+        }
+        window.recent.push_back((latency, ok));
+    }
+
+    /// 生成所有操作的统计快照，按操作名排序
+    pub(crate) fn snapshot(&self) -> Vec<ActionStats> {
+        let actions = self.actions.lock().unwrap();
+        let mut stats: Vec<_> = actions
+            .iter()
+            .map(|(action, window)| {
+                let mut latencies: Vec<_> = window.recent.iter().map(|(d, _)| *d).collect();
+                latencies.sort_unstable();
+                let failed = window.recent.iter().filter(|(_, ok)| !ok).count();
+                ActionStats {
+                    action: action.clone(),
+                    calls: window.calls,
+                    errors: window.errors,
+                    error_rate: failed as f64 / window.recent.len().max(1) as f64,
+                    p50: percentile(&latencies, 50),
+                    p99: percentile(&latencies, 99),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.action.cmp(&b.action));
+        stats
+    }
+}
+
+/// 计算已排序数据的百分位数（最近秩法）
+pub(crate) fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * p).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+impl MilkyClient {
+    /// 获取每个 API 操作的调用统计
+    ///
+    /// # 返回
+    /// 按操作名排序的 [`ActionStats`] 列表，未调用过的操作不会出现
+    pub fn api_stats(&self) -> Vec<ActionStats> {
+        self.api_stats.snapshot()
+    }
+
+    /// 启动一个后台任务，定期在日志中输出每个 API 操作的调用统计
+    ///
+    /// # 参数
+    /// * `interval`: 输出统计摘要的间隔
+    ///
+    /// # 返回
+    /// 后台任务的句柄，调用 `abort` 即可停止输出；客户端的取消令牌被取消时任务也会结束
+    pub fn spawn_api_stats_logger(&self, interval: Duration) -> JoinHandle<()> {
+        let recorder = Arc::clone(&self.api_stats);
+        let cancel = self.cancel_token.clone();
+        runtime::spawn(async move {
+            let mut ticker = runtime::interval(interval);
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                for stats in recorder.snapshot() {
+                    info!(
+                        "API {}: 调用 {} 次，失败 {} 次，近期错误率 {:.1}%，p50 {:?}，p99 {:?}",
+                        stats.action,
+                        stats.calls,
+                        stats.errors,
+                        stats.error_rate * 100.0,
+                        stats.p50,
+                        stats.p99
+                    );
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let recorder = ApiStatsRecorder::default();
+        for ms in 1..=100 {
+            recorder.record("get_group_info", Duration::from_millis(ms), ms % 10 != 0);
+        }
+        recorder.record("send_group_message", Duration::from_millis(5), true);
+
+        let stats = recorder.snapshot();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].action, "get_group_info");
+        assert_eq!(stats[0].calls, 100);
+        assert_eq!(stats[0].errors, 10);
+        assert!((stats[0].error_rate - 0.1).abs() < f64::EPSILON);
+        assert_eq!(stats[0].p50, Duration::from_millis(50));
+        assert_eq!(stats[0].p99, Duration::from_millis(99));
+        assert_eq!(stats[1].p99, Duration::from_millis(5));
+    }
+}
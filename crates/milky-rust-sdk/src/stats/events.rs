@@ -0,0 +1,342 @@
+//! 事件管线的积压情况
+//!
+//! 客户端在把事件写入事件通道时会记录通道中积压的事件数量。使用 [`MilkyClient::instrument_events`]
+//! 包装事件通道的接收端后，还可以得到事件在通道中等待的时间，以及通过 [`InstrumentedEvents::handle`]
+//! 执行的事件处理函数的耗时。等待时间或处理耗时超过阈值、或通道接近写满时会输出警告，
+//! 以便在事件因积压被延迟之前发现处理能力不足。
+//!
+//! ```no_run
+//! # use milky_rust_sdk::MilkyClient;
+//! # async fn run(client: MilkyClient, rx: tokio::sync::mpsc::Receiver<milky_types::Event>) {
+//! let mut events = client.instrument_events(rx);
+//! while let Some(event) = events.recv().await {
+//!     events.handle(async { /* 处理事件 */ }).await;
+//! }
+//! # }
+//! ```
+
+use crate::client::MilkyClient;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+use crate::client::overflow::{Backlog, OverflowPolicy};
+use crate::logger::warn;
+use crate::stats::{MetricsSink, percentile};
+
+use milky_types::Event;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// 默认的等待时间与处理耗时警告阈值
+const DEFAULT_WARN_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// 保留用于计算处理耗时分位数的最近处理次数
+const HANDLER_WINDOW_SIZE: usize = 1024;
+
+/// 事件管线的积压情况快照
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventGauges {
+    /// 最近一次写入事件后通道中积压的事件数量
+    pub queue_depth: usize,
+    /// 事件通道的容量
+    pub queue_capacity: usize,
+    /// 最近一个被取出的事件在通道中等待的时间
+    pub last_time_in_queue: Duration,
+    /// 事件在通道中等待的最长时间
+    pub max_time_in_queue: Duration,
+    /// 最近事件处理耗时的中位数
+    pub handler_p50: Duration,
+    /// 最近事件处理耗时的第 99 百分位数
+    pub handler_p99: Duration,
+    /// 因事件通道写满而被丢弃的事件总数
+    pub dropped_events: u64,
+}
+
+/// 事件管线的内部状态
+#[derive(Default)]
+struct PipelineState {
+    /// 尚在通道中的事件的写入时间，按写入顺序排列
+    enqueued: VecDeque<Instant>,
+    /// 当前的积压情况
+    gauges: EventGauges,
+    /// 最近的事件处理耗时
+    handler_times: VecDeque<Duration>,
+    /// 是否已经输出过通道接近写满的警告
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    depth_warned: bool,
+    /// 是否已经输出过丢弃事件的警告
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    overflow_warned: bool,
+    /// 事件通道写满时的处理方式
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    overflow: OverflowPolicy,
+    /// 导出事件数据的指标导出器
+    #[cfg_attr(not(any(feature = "websocket", feature = "webhook")), allow(dead_code))]
+    sink: Option<Arc<dyn MetricsSink>>,
+}
+
+/// 记录事件管线积压情况的统计器
+pub(crate) struct EventPipelineRecorder {
+    /// 内部状态
+    state: Mutex<PipelineState>,
+    /// 警告阈值（毫秒）
+    warn_threshold_ms: AtomicU64,
+    /// 通道写满后等待写入的事件
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    backlog: Arc<Mutex<Backlog>>,
+}
+
+impl Default for EventPipelineRecorder {
+    fn default() -> Self {
+        Self {
+            state: Mutex::default(),
+            warn_threshold_ms: AtomicU64::new(DEFAULT_WARN_THRESHOLD.as_millis() as u64),
+            #[cfg(any(feature = "websocket", feature = "webhook"))]
+            backlog: Arc::default(),
+        }
+    }
+}
+
+impl EventPipelineRecorder {
+    /// 按设置的处理方式将事件写入通道，并记录写入后的积压情况
+    ///
+    /// # 返回
+    /// 通道已关闭时返回 `false`，事件因通道写满被丢弃时仍返回 `true`
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub(crate) async fn forward(&self, sender: &mpsc::Sender<Event>, event: Event) -> bool {
+        let (sink, overflow) = {
+            let state = self.state.lock().unwrap();
+            (state.sink.clone(), state.overflow)
+        };
+        // 事件会被移入通道，只在设置了导出器时提前取出事件类型
+        let event_type = sink.as_ref().map(|_| event.kind.event_type().to_string());
+        let now = Instant::now();
+        match overflow {
+            OverflowPolicy::Block => {
+                if sender.send(event).await.is_err() {
+                    return false;
+                }
+            }
+            OverflowPolicy::DropNewest => match sender.try_send(event) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(event)) => {
+                    self.on_dropped(&event);
+                    return true;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => return false,
+            },
+            OverflowPolicy::DropOldest => {
+                if sender.is_closed() {
+                    return false;
+                }
+                if let Some(dropped) = Backlog::push(&self.backlog, sender, event) {
+                    self.on_dropped(&dropped);
+                }
+            }
+        }
+        let depth = sender.max_capacity() - sender.capacity();
+        let capacity = sender.max_capacity();
+
+        let mut state = self.state.lock().unwrap();
+        state.enqueued.push_back(now);
+        // 未使用 `instrument_events` 时不会有出队记录，按实际积压数量丢弃已被取出的事件
+        while state.enqueued.len() > depth {
+            state.enqueued.pop_front();
+        }
+        state.gauges.queue_depth = depth;
+        state.gauges.queue_capacity = capacity;
+        if depth * 5 >= capacity * 4 {
+            if !state.depth_warned {
+                state.depth_warned = true;
+                warn!("事件通道积压了 {depth}/{capacity} 个事件，事件处理可能跟不上接收速度");
+            }
+        } else if depth * 2 < capacity {
+            state.depth_warned = false;
+            state.overflow_warned = false;
+        }
+        drop(state);
+        if let (Some(sink), Some(event_type)) = (sink, event_type) {
+            sink.event_received(&event_type, depth);
+        }
+        true
+    }
+
+    /// 记录一个事件因通道写满被丢弃
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    fn on_dropped(&self, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+        state.gauges.dropped_events += 1;
+        if !state.overflow_warned {
+            state.overflow_warned = true;
+            warn!(
+                "事件通道已满，已累计丢弃 {} 个事件，事件处理跟不上接收速度",
+                state.gauges.dropped_events
+            );
+        }
+        let sink = state.sink.clone();
+        drop(state);
+        if let Some(sink) = sink {
+            sink.event_dropped(event.kind.event_type());
+        }
+    }
+
+    /// 设置事件通道写满时的处理方式
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    pub(crate) fn set_overflow_policy(&self, policy: OverflowPolicy) {
+        self.state.lock().unwrap().overflow = policy;
+    }
+
+    /// 设置导出事件数据的指标导出器
+    pub(crate) fn set_sink(&self, sink: Arc<dyn MetricsSink>) {
+        self.state.lock().unwrap().sink = Some(sink);
+    }
+
+    /// 记录一个事件被取出
+    fn on_dequeue(&self) {
+        let mut state = self.state.lock().unwrap();
+        let Some(enqueued_at) = state.enqueued.pop_front() else {
+            return;
+        };
+        let waited = enqueued_at.elapsed();
+        state.gauges.last_time_in_queue = waited;
+        state.gauges.max_time_in_queue = state.gauges.max_time_in_queue.max(waited);
+        drop(state);
+        if waited > self.warn_threshold() {
+            warn!("事件在通道中等待了 {waited:?} 才被处理");
+        }
+    }
+
+    /// 记录一次事件处理的耗时
+    fn on_handled(&self, elapsed: Duration) {
+        let mut state = self.state.lock().unwrap();
+        if state.handler_times.len() == HANDLER_WINDOW_SIZE {
+            state.handler_times.pop_front();
+        }
+        state.handler_times.push_back(elapsed);
+        drop(state);
+        if elapsed > self.warn_threshold() {
+            warn!("事件处理耗时 {elapsed:?}，超过了警告阈值");
+        }
+    }
+
+    /// 当前的警告阈值
+    fn warn_threshold(&self) -> Duration {
+        Duration::from_millis(self.warn_threshold_ms.load(Ordering::Relaxed))
+    }
+
+    /// 生成积压情况快照
+    pub(crate) fn snapshot(&self) -> EventGauges {
+        let state = self.state.lock().unwrap();
+        let mut handler_times: Vec<_> = state.handler_times.iter().copied().collect();
+        handler_times.sort_unstable();
+        EventGauges {
+            handler_p50: percentile(&handler_times, 50),
+            handler_p99: percentile(&handler_times, 99),
+            ..state.gauges.clone()
+        }
+    }
+}
+
+/// 记录等待时间与处理耗时的事件接收端
+pub struct InstrumentedEvents {
+    /// 事件通道的接收端
+    receiver: mpsc::Receiver<Event>,
+    /// 事件管线统计器
+    recorder: Arc<EventPipelineRecorder>,
+}
+
+impl InstrumentedEvents {
+    /// 接收下一个事件，并记录它在通道中等待的时间
+    ///
+    /// # 返回
+    /// 通道关闭且没有剩余事件时返回 `None`
+    pub async fn recv(&mut self) -> Option<Event> {
+        let event = self.receiver.recv().await?;
+        self.recorder.on_dequeue();
+        Some(event)
+    }
+
+    /// 执行事件处理函数，并记录其耗时
+    ///
+    /// # 参数
+    /// * `handler`: 处理事件的异步任务
+    pub async fn handle<F: Future>(&self, handler: F) -> F::Output {
+        let started = Instant::now();
+        let output = handler.await;
+        self.recorder.on_handled(started.elapsed());
+        output
+    }
+
+    /// 取回原始的事件通道接收端
+    pub fn into_inner(self) -> mpsc::Receiver<Event> {
+        self.receiver
+    }
+}
+
+impl MilkyClient {
+    /// 包装事件通道的接收端，记录事件的等待时间与处理耗时
+    ///
+    /// # 参数
+    /// * `receiver`: 创建客户端时传入的事件通道对应的接收端
+    pub fn instrument_events(&self, receiver: mpsc::Receiver<Event>) -> InstrumentedEvents {
+        InstrumentedEvents {
+            receiver,
+            recorder: Arc::clone(&self.event_stats),
+        }
+    }
+
+    /// 设置事件等待时间与处理耗时的警告阈值，默认为 5 秒
+    pub fn with_event_lag_warning(self, threshold: Duration) -> Self {
+        self.event_stats
+            .warn_threshold_ms
+            .store(threshold.as_millis() as u64, Ordering::Relaxed);
+        self
+    }
+
+    /// 获取事件管线当前的积压情况
+    pub fn event_gauges(&self) -> EventGauges {
+        self.event_stats.snapshot()
+    }
+}
+
+#[cfg(all(test, any(feature = "websocket", feature = "webhook")))]
+mod tests {
+    use super::*;
+    use milky_types::EventKind;
+
+    #[tokio::test]
+    async fn test_event_gauges() {
+        let event = Event {
+            time: 0,
+            self_id: 10000,
+            kind: EventKind::BotOffline {
+                reason: String::new(),
+            },
+        };
+        let recorder = Arc::new(EventPipelineRecorder::default());
+        let (tx, rx) = mpsc::channel(4);
+        for _ in 0..3 {
+            assert!(recorder.forward(&tx, event.clone()).await);
+        }
+        let gauges = recorder.snapshot();
+        assert_eq!((gauges.queue_depth, gauges.queue_capacity), (3, 4));
+
+        let mut events = InstrumentedEvents {
+            receiver: rx,
+            recorder: Arc::clone(&recorder),
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        events.recv().await.unwrap();
+        events
+            .handle(tokio::time::sleep(Duration::from_millis(10)))
+            .await;
+
+        let gauges = recorder.snapshot();
+        assert!(gauges.last_time_in_queue >= Duration::from_millis(20));
+        assert_eq!(gauges.max_time_in_queue, gauges.last_time_in_queue);
+        assert!(gauges.handler_p50 >= Duration::from_millis(10));
+        assert_eq!(recorder.state.lock().unwrap().enqueued.len(), 2);
+    }
+}
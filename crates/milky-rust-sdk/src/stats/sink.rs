@@ -0,0 +1,161 @@
+//! 将调用与事件数据导出到外部的指标系统
+//!
+//! 通过 [`MilkyClient::with_metrics_sink`] 设置 [`MetricsSink`] 后，客户端会在每次 API 调用结束、
+//! 每个事件写入事件通道时调用对应的方法，便于在生产环境中通过 Prometheus 等系统监控机器人。
+//! SDK 不依赖具体的指标库，下面的导出器只在内存中计数，接入 `metrics` 等门面时在对应的方法中转发即可：
+//!
+//! ```
+//! use milky_rust_sdk::MilkyError;
+//! use milky_rust_sdk::stats::{MetricsSink, error_code};
+//! use std::collections::HashMap;
+//! use std::sync::Mutex;
+//! use std::time::Duration;
+//!
+//! #[derive(Default)]
+//! struct Counters(Mutex<HashMap<String, u64>>);
+//!
+//! impl Counters {
+//!     fn increment(&self, key: String) {
+//!         *self.0.lock().unwrap().entry(key).or_default() += 1;
+//!     }
+//! }
+//!
+//! impl MetricsSink for Counters {
+//!     fn api_call(&self, action: &str, _latency: Duration, error: Option<&MilkyError>) {
+//!         let code = error.map_or_else(|| "ok".to_string(), error_code);
+//!         self.increment(format!("milky_api_calls_total{{action={action},code={code}}}"));
+//!     }
+//!
+//!     fn event_received(&self, event_type: &str, _queue_depth: usize) {
+//!         self.increment(format!("milky_events_total{{kind={event_type}}}"));
+//!     }
+//! }
+//! ```
+
+use crate::client::MilkyClient;
+use crate::error::MilkyError;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 接收调用与事件数据的指标导出器
+///
+/// 各方法都在请求或事件处理的路径上同步调用，应只做计数等轻量操作。
+pub trait MetricsSink: Send + Sync {
+    /// 一次 API 调用结束，每次重试都会单独调用
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称
+    /// * `latency`: 本次调用的耗时
+    /// * `error`: 调用失败时的错误，成功时为 `None`
+    fn api_call(&self, action: &str, latency: Duration, error: Option<&MilkyError>) {
+        let _ = (action, latency, error);
+    }
+
+    /// 一个事件写入了事件通道
+    ///
+    /// # 参数
+    /// * `event_type`: 事件类型，例如 `message_receive`
+    /// * `queue_depth`: 写入后通道中积压的事件数量
+    fn event_received(&self, event_type: &str, queue_depth: usize) {
+        let _ = (event_type, queue_depth);
+    }
+
+    /// 一个事件因事件通道写满被丢弃，参见 [`OverflowPolicy`](crate::client::OverflowPolicy)
+    ///
+    /// # 参数
+    /// * `event_type`: 被丢弃的事件的类型
+    fn event_dropped(&self, event_type: &str) {
+        let _ = event_type;
+    }
+}
+
+/// 获取错误的简短代码，用作指标的标签
+///
+/// # 返回
+/// API 错误返回 `retcode`，HTTP 错误返回状态码，其他错误返回错误种类，例如 `timeout`
+pub fn error_code(error: &MilkyError) -> String {
+    match error {
+        MilkyError::ApiError {
+            retcode: Some(retcode),
+            ..
+        } => retcode.to_string(),
+        MilkyError::ApiError { retcode: None, .. } => "api".to_string(),
+        MilkyError::HttpApiError { status, .. } => status.as_u16().to_string(),
+        MilkyError::Timeout => "timeout".to_string(),
+        MilkyError::NotConnected => "not_connected".to_string(),
+        MilkyError::Reqwest(_) => "http".to_string(),
+        MilkyError::Decode { .. } => "decode".to_string(),
+        _ => "other".to_string(),
+    }
+}
+
+impl MilkyClient {
+    /// 设置导出调用与事件数据的 [`MetricsSink`]
+    ///
+    /// 需要在调用 [`connect_events`](Self::connect_events) 之前设置。
+    ///
+    /// # 参数
+    /// * `sink`: 指标导出器
+    pub fn with_metrics_sink(mut self, sink: impl MetricsSink + 'static) -> Self {
+        let sink: Arc<dyn MetricsSink> = Arc::new(sink);
+        self.event_stats.set_sink(Arc::clone(&sink));
+        self.metrics_sink = Some(sink);
+        self
+    }
+}
+
+#[cfg(all(test, any(feature = "websocket", feature = "webhook")))]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, OFFLINE};
+    use milky_types::{Event, EventKind};
+    use std::sync::Mutex;
+
+    /// 记录收到的数据的导出器
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<(String, String)>>,
+        events: Mutex<Vec<(String, usize)>>,
+    }
+
+    impl MetricsSink for Arc<Recorder> {
+        fn api_call(&self, action: &str, _latency: Duration, error: Option<&MilkyError>) {
+            let code = error.map_or_else(|| "ok".to_string(), error_code);
+            self.calls.lock().unwrap().push((action.to_string(), code));
+        }
+
+        fn event_received(&self, event_type: &str, queue_depth: usize) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((event_type.to_string(), queue_depth));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_sink() {
+        let (client, tx, _rx) = test_util::client_with_sender(OFFLINE);
+        let recorder = Arc::new(Recorder::default());
+        let client = client.with_metrics_sink(Arc::clone(&recorder));
+
+        assert!(client.get_login_info().await.is_err());
+        assert_eq!(
+            *recorder.calls.lock().unwrap(),
+            [("get_login_info".to_string(), "http".to_string())]
+        );
+
+        let event = Event {
+            time: 1,
+            self_id: 10000,
+            kind: EventKind::BotOffline {
+                reason: String::new(),
+            },
+        };
+        assert!(client.event_stats.forward(&tx, event).await);
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            [("bot_offline".to_string(), 1)]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! 测试中共用的模拟协议端与客户端
+//!
+//! 模拟协议端都监听 `127.0.0.1` 上的随机端口，各个测试之间互不影响。
+
+#[cfg(feature = "tracing")]
+mod spans;
+
+use crate::client::MilkyClient;
+#[cfg(feature = "webhook")]
+use crate::types::communication::WebHookConfig;
+use crate::types::communication::{Communication, WebSocketConfig};
+
+use axum::Router;
+use milky_types::Event;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+#[cfg(feature = "tracing")]
+pub(crate) use spans::SpanRecorder;
+
+/// 没有协议端监听的地址，向它发送的请求总是失败
+pub(crate) const OFFLINE: &str = "127.0.0.1:1";
+
+/// 创建连接到 `addr` 的 WebSocket 通信配置
+pub(crate) fn ws_comm(addr: impl Display) -> Communication {
+    Communication::WebSocket(WebSocketConfig::new(format!("ws://{addr}"), None))
+}
+
+/// 创建监听随机端口、API 请求发往 [`OFFLINE`] 的 WebHook 配置
+#[cfg(feature = "webhook")]
+pub(crate) fn webhook_config() -> WebHookConfig {
+    WebHookConfig::new(None, 0, format!("http://{OFFLINE}"), None)
+}
+
+/// 创建连接到 `addr` 的客户端
+///
+/// # 返回
+/// 客户端与它的事件接收端，接收端需要保留到测试结束，否则事件无法送达
+pub(crate) fn client(addr: impl Display) -> (MilkyClient, mpsc::Receiver<Event>) {
+    client_for(ws_comm(addr))
+}
+
+/// 使用指定的通信配置创建客户端
+///
+/// # 返回
+/// 客户端与它的事件接收端
+pub(crate) fn client_for(comm: Communication) -> (MilkyClient, mpsc::Receiver<Event>) {
+    let (tx, rx) = mpsc::channel(16);
+    (MilkyClient::new(comm, tx).unwrap(), rx)
+}
+
+/// 创建连接到 `addr` 的客户端，同时返回事件通道的发送端，便于测试直接分发事件
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub(crate) fn client_with_sender(
+    addr: impl Display,
+) -> (MilkyClient, mpsc::Sender<Event>, mpsc::Receiver<Event>) {
+    let (tx, rx) = mpsc::channel(16);
+    let client = MilkyClient::new(ws_comm(addr), tx.clone()).unwrap();
+    (client, tx, rx)
+}
+
+/// 在随机端口上启动 HTTP 服务
+///
+/// # 返回
+/// 服务监听的地址
+pub(crate) async fn serve(app: Router) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    addr
+}
+
+/// 接受事件 WebSocket 连接的模拟协议端
+///
+/// 丢弃后不再接受新的连接，已经建立的连接不受影响。
+#[cfg(feature = "websocket")]
+pub(crate) struct WsServer {
+    listener: TcpListener,
+}
+
+#[cfg(feature = "websocket")]
+impl WsServer {
+    /// 在随机端口上开始监听
+    pub(crate) async fn bind() -> Self {
+        Self {
+            listener: TcpListener::bind("127.0.0.1:0").await.unwrap(),
+        }
+    }
+
+    /// 监听的地址
+    pub(crate) fn addr(&self) -> SocketAddr {
+        self.listener.local_addr().unwrap()
+    }
+
+    /// 接受下一个连接并完成 WebSocket 握手
+    pub(crate) async fn accept(&self) -> tokio_tungstenite::WebSocketStream<tokio::net::TcpStream> {
+        let (stream, _) = self.listener.accept().await.unwrap();
+        tokio_tungstenite::accept_async(stream).await.unwrap()
+    }
+}
@@ -0,0 +1,62 @@
+//! 记录 span 字段的 tracing 层
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::Subscriber;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+/// 记录所有 span 及其字段的 tracing 层，克隆的实例共享记录
+#[derive(Clone, Default)]
+pub(crate) struct SpanRecorder {
+    /// 按创建顺序排列的 span
+    spans: Arc<Mutex<Vec<RecordedSpan>>>,
+}
+
+/// [`SpanRecorder`] 记录的 span
+struct RecordedSpan {
+    id: Id,
+    name: &'static str,
+    fields: BTreeMap<&'static str, String>,
+}
+
+impl SpanRecorder {
+    /// 获取最后一个名为 `name` 的 span 中已记录的字段，没有该 span 时返回空映射
+    pub(crate) fn fields(&self, name: &str) -> BTreeMap<&'static str, String> {
+        let spans = self.spans.lock().unwrap();
+        let span = spans.iter().rev().find(|span| span.name == name);
+        span.map(|span| span.fields.clone()).unwrap_or_default()
+    }
+}
+
+impl Visit for RecordedSpan {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields.insert(field.name(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name(), value.to_string());
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SpanRecorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut span = RecordedSpan {
+            id: id.clone(),
+            name: attrs.metadata().name(),
+            fields: BTreeMap::new(),
+        };
+        attrs.record(&mut span);
+        self.spans.lock().unwrap().push(span);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.spans.lock().unwrap();
+        if let Some(span) = spans.iter_mut().rev().find(|span| span.id == *id) {
+            values.record(span);
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod common;
+#[cfg(feature = "client")]
 pub mod communication;
+#[cfg(any(feature = "websocket", feature = "webhook"))]
 pub mod message;
@@ -16,6 +16,9 @@ pub struct ApiResponse<T> {
     /// 响应消息这是一个可选字段，通常在API调用失败时提供错误信息，
     /// 或者在成功时提供一些附加的提示信息
     pub message: Option<String>,
+    /// 通过 WebSocket 调用 API 时，与请求中相同的 `echo` 字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub echo: Option<String>,
 }
 
 /// 通用的API请求结构体
@@ -25,4 +28,7 @@ pub struct ApiRequest<P> {
     pub action: String,
     /// 针对指定 `action` 的具体参数`P` 是参数的数据结构类型。
     pub params: P,
+    /// 通过 WebSocket 调用 API 时用于匹配响应的标识
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub echo: Option<String>,
 }
@@ -1,16 +1,47 @@
 //! 定义与服务端的通信方式
 
+use crate::logger::redact_url;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
 /// 枚举了可以使用的通信方式。
-#[derive(Clone)]
+///
+/// 从配置文件反序列化时，通过 `mode` 字段区分通信方式，可选值为 `websocket` 和 `webhook`。
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "mode")]
 pub enum Communication {
     /// WebSocket
+    #[serde(rename = "websocket")]
     WebSocket(WebSocketConfig),
     /// WebHook
+    #[serde(rename = "webhook")]
     WebHook(WebHookConfig),
 }
 
+impl Communication {
+    /// 获取访问令牌
+    pub fn access_token(&self) -> Option<&str> {
+        match self {
+            Communication::WebSocket(config) => config.access_token.as_deref(),
+            Communication::WebHook(config) => config.access_token.as_deref(),
+        }
+    }
+
+    /// 获取访问令牌的可变引用
+    pub(crate) fn access_token_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Communication::WebSocket(config) => &mut config.access_token,
+            Communication::WebHook(config) => &mut config.access_token,
+        }
+    }
+}
+
 /// WebSocket的配置项
-#[derive(Clone)]
+///
+/// `Debug` 输出中的访问令牌会被隐去。
+#[derive(Clone, PartialEq, Deserialize)]
 pub struct WebSocketConfig {
     /// 服务端的WebSocket 接入点 e.g. `ws://127.0.0.1:3000`。
     pub ws_endpoint: String,
@@ -36,19 +67,75 @@ impl WebSocketConfig {
     }
 }
 
+impl fmt::Debug for WebSocketConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketConfig")
+            .field("ws_endpoint", &redact_url(&self.ws_endpoint))
+            .field("access_token", &redact_token(&self.access_token))
+            .finish()
+    }
+}
+
 /// WebHook的配置项
-#[derive(Clone)]
+///
+/// `Debug` 输出中的访问令牌会被隐去。
+#[derive(Clone, PartialEq, Deserialize)]
 pub struct WebHookConfig {
-    /// http service主机地址，默认 `127.0.0.1`。
+    /// http service监听的地址，默认 `127.0.0.1`，在容器中运行时通常需要设置为 `0.0.0.0`。
+    #[serde(default = "default_host")]
     pub host: String,
-    /// 本机开放http service的端口。
+    /// 本机开放http service的端口，为 `0` 时由系统分配，
+    /// 实际监听的地址可以通过 `MilkyClient::webhook_local_addr` 获取。
     pub port: i32,
+    /// 接收事件的路由路径，默认 `/webhook`，缺少开头的 `/` 时会自动补全。
+    #[serde(
+        default = "default_webhook_path",
+        deserialize_with = "deserialize_webhook_path"
+    )]
+    pub path: String,
+    /// PEM 格式的证书链文件路径，与 `tls_key` 同时设置时以 HTTPS 接收事件（需要启用 `webhook-tls` 特性）。
+    #[serde(default)]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM 格式的私钥文件路径。
+    #[serde(default)]
+    pub tls_key: Option<PathBuf>,
+    /// 允许推送事件的来源 IP，为空时不限制。
+    #[serde(default)]
+    pub allowed_ips: Vec<IpAddr>,
+    /// 请求体的最大字节数，为 `None` 时使用 axum 的默认值（2 MiB）。
+    #[serde(default)]
+    pub max_body_size: Option<usize>,
+    /// 同时处理的请求数量上限，超出时以 `503 Service Unavailable` 拒绝，为 `None` 时不限制。
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
     /// 服务端的Http 接入点 e.g. `http://127.0.0.1:3000`。
     pub http_endpoint: String,
     /// 可选的访问令牌，用于认证。
     pub access_token: Option<String>,
 }
 
+impl fmt::Debug for WebHookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebHookConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("path", &self.path)
+            .field("tls_cert", &self.tls_cert)
+            .field("tls_key", &self.tls_key)
+            .field("allowed_ips", &self.allowed_ips)
+            .field("max_body_size", &self.max_body_size)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("http_endpoint", &redact_url(&self.http_endpoint))
+            .field("access_token", &redact_token(&self.access_token))
+            .finish()
+    }
+}
+
+/// 在 `Debug` 输出中以 `redacted` 代替访问令牌
+fn redact_token(token: &Option<String>) -> Option<&'static str> {
+    token.as_ref().map(|_| "redacted")
+}
+
 impl WebHookConfig {
     /// 创建一个新的 `WebSocketCofig` 实例。
     ///
@@ -67,15 +154,86 @@ impl WebHookConfig {
         http_endpoint: String,
         access_token: Option<String>,
     ) -> Self {
-        let host = match host {
-            Some(_host) => _host,
-            None => "127.0.0.1".to_string(),
-        };
+        let host = host.unwrap_or_else(default_host);
         Self {
             host,
             port,
+            path: default_webhook_path(),
+            tls_cert: None,
+            tls_key: None,
+            allowed_ips: Vec::new(),
+            max_body_size: None,
+            max_concurrent_requests: None,
             http_endpoint,
             access_token,
         }
     }
+
+    /// 设置接收事件的路由路径，默认 `/webhook`
+    ///
+    /// # 参数
+    /// * `path`: 路由路径，缺少开头的 `/` 时会自动补全
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = normalize_webhook_path(path.into());
+        self
+    }
+
+    /// 以 HTTPS 接收事件，需要启用 `webhook-tls` 特性
+    ///
+    /// # 参数
+    /// * `cert`: PEM 格式的证书链文件路径
+    /// * `key`: PEM 格式的私钥文件路径
+    pub fn with_tls(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.tls_cert = Some(cert.into());
+        self.tls_key = Some(key.into());
+        self
+    }
+
+    /// 只接受来自指定 IP 的请求，其他来源的请求以 `403 Forbidden` 拒绝
+    ///
+    /// # 参数
+    /// * `ips`: 允许的来源 IP
+    pub fn with_allowed_ips(mut self, ips: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.allowed_ips = ips.into_iter().collect();
+        self
+    }
+
+    /// 设置请求体的最大字节数，超出时以 `413 Payload Too Large` 拒绝
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// 设置同时处理的请求数量上限
+    pub fn with_max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+}
+
+/// 辅助函数，用于 `serde` 的 `default` 属性，返回默认的主机地址
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// 辅助函数，用于 `serde` 的 `default` 属性，返回默认的 WebHook 路由路径
+fn default_webhook_path() -> String {
+    "/webhook".to_string()
+}
+
+/// 为 WebHook 路由路径补全开头的 `/`
+fn normalize_webhook_path(path: String) -> String {
+    if path.starts_with('/') {
+        path
+    } else {
+        format!("/{path}")
+    }
+}
+
+/// 辅助函数，用于 `serde` 的 `deserialize_with` 属性，读取并补全 WebHook 路由路径
+fn deserialize_webhook_path<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(normalize_webhook_path)
 }
@@ -1,6 +1,9 @@
+#[cfg(feature = "websocket")]
 use tokio_tungstenite::tungstenite::Message as WsMessage;
 
 pub enum OriginalMessage {
+    #[cfg(feature = "websocket")]
     Ws(WsMessage),
-    WebHook(serde_json::Value),
+    #[cfg(feature = "webhook")]
+    WebHook(String),
 }
@@ -1,3 +1,13 @@
+pub mod cache;
+pub mod forward;
+pub mod hash;
+pub(crate) mod json;
+pub mod mime;
+pub mod template;
+
+pub use hash::{TriSha1Hasher, tri_sha1_file};
+pub use mime::{detect_mime, infer_file_name};
+
 use milky_types::message::in_coming::IncomingSegment;
 
 /// 从消息段列表中提取所有文本内容并拼接成一个字符串
@@ -20,3 +30,42 @@ pub fn get_plain_text_from_segments(segments: &[IncomingSegment]) -> String {
         })
         .collect()
 }
+
+/// 从消息段列表中提取所有被提及（@）的用户的QQ号
+///
+/// # 参数
+/// * `segments`: 一个包含 `IncomingSegment` 的向量引用
+///
+/// # 返回
+/// 按出现顺序排列并去重的QQ号列表，提及全体成员不会计入其中
+pub fn extract_mentions(segments: &[IncomingSegment]) -> Vec<i64> {
+    let mut mentions = Vec::new();
+    for segment in segments {
+        if let IncomingSegment::Mention { user_id } = segment
+            && !mentions.contains(user_id)
+        {
+            mentions.push(*user_id);
+        }
+    }
+    mentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_mentions() {
+        let segments = [
+            IncomingSegment::Mention { user_id: 10000 },
+            IncomingSegment::Text {
+                text: " 签到".to_string(),
+            },
+            IncomingSegment::MentionAll {},
+            IncomingSegment::Mention { user_id: 20000 },
+            IncomingSegment::Mention { user_id: 10000 },
+        ];
+        assert_eq!(extract_mentions(&segments), [10000, 20000]);
+        assert!(extract_mentions(&[IncomingSegment::MentionAll {}]).is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! 带过期时间的内存缓存
+//!
+//! [`TtlCache`] 为每个条目单独记录过期时间，读取过期条目时视为不存在。
+//! 条目数量达到上限时，会先清理已过期的条目，仍然不足时淘汰最早过期的条目。
+//! 有效期过长、过期时间超出 [`Instant`] 的表示范围时，条目视为永不过期。
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 带过期时间的内存缓存
+pub struct TtlCache<K, V> {
+    /// 缓存的条目及其过期时间，`None` 表示永不过期
+    entries: Mutex<HashMap<K, (V, Option<Instant>)>>,
+    /// 最多保存的条目数量
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// 创建一个空缓存
+    ///
+    /// # 参数
+    /// * `capacity`: 最多保存的条目数量
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// 读取未过期的条目
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((value, expires_at)) if is_live(*expires_at, Instant::now()) => {
+                Some(value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// 写入条目，已存在时覆盖
+    ///
+    /// # 参数
+    /// * `key`: 键
+    /// * `value`: 值
+    /// * `ttl`: 条目的有效期
+    pub fn insert(&self, key: K, value: V, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (_, expires_at)| is_live(*expires_at, now));
+            if entries.len() >= self.capacity
+                && let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires_at))| (expires_at.is_none(), *expires_at))
+                    .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (value, now.checked_add(ttl)));
+    }
+
+    /// 删除条目
+    pub fn remove(&self, key: &K) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    /// 清空所有条目
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// 条目在 `now` 时是否仍未过期
+fn is_live(expires_at: Option<Instant>, now: Instant) -> bool {
+    expires_at.is_none_or(|expires_at| now < expires_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_cache() {
+        let cache = TtlCache::new(2);
+        cache.insert("a", 1, Duration::from_secs(60));
+        cache.insert("b", 2, Duration::ZERO);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"b"), None);
+
+        cache.insert("b", 2, Duration::from_secs(30));
+        cache.insert("c", 3, Duration::from_secs(90));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"c"), Some(3));
+    }
+
+    #[test]
+    fn test_ttl_overflow() {
+        let cache = TtlCache::new(2);
+        cache.insert("forever", 1, Duration::MAX);
+        assert_eq!(cache.get(&"forever"), Some(1));
+
+        // 永不过期的条目最后被淘汰
+        cache.insert("a", 2, Duration::from_secs(60));
+        cache.insert("b", 3, Duration::from_secs(90));
+        assert_eq!(cache.get(&"forever"), Some(1));
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.get(&"b"), Some(3));
+    }
+}
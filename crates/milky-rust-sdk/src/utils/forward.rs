@@ -0,0 +1,189 @@
+//! 将过长或由多个部分组成的回复打包为合并转发消息
+//!
+//! QQ 中过长的消息会被折叠或拒绝发送，通常的做法是将其作为合并转发消息发送。
+//! [`ForwardPacker`] 会把长文本按行切分为多条转发消息，并以指定的发送者名称与QQ号展示。
+//!
+//! ```
+//! use milky_rust_sdk::utils::forward::ForwardPacker;
+//! use milky_types::message::out_going::OutgoingSegment;
+//!
+//! let packer = ForwardPacker::new(10000, "小助手").threshold(20);
+//! let reply = packer.package(vec![OutgoingSegment::text("很长的帮助信息……".repeat(10))]);
+//! assert!(matches!(reply[..], [OutgoingSegment::Forward(_)]));
+//! ```
+
+use milky_types::message::out_going::{
+    ForwardData, OutgoingForwardMessage, OutgoingSegment, TextData,
+};
+
+/// 默认在文本超过该字符数时打包为合并转发消息
+const DEFAULT_THRESHOLD: usize = 500;
+
+/// 每条转发消息默认的最大字符数
+const DEFAULT_MAX_NODE_LEN: usize = 1000;
+
+/// 将回复打包为合并转发消息的设置
+#[derive(Debug, Clone)]
+pub struct ForwardPacker {
+    /// 转发消息中显示的发送者QQ号
+    user_id: i64,
+    /// 转发消息中显示的发送者名称
+    sender_name: String,
+    /// 文本超过该字符数时打包
+    threshold: usize,
+    /// 每条转发消息的最大字符数
+    max_node_len: usize,
+}
+
+impl ForwardPacker {
+    /// 创建打包设置
+    ///
+    /// # 参数
+    /// * `user_id`: 转发消息中显示的发送者QQ号，通常为机器人自身
+    /// * `sender_name`: 转发消息中显示的发送者名称
+    pub fn new(user_id: i64, sender_name: impl Into<String>) -> Self {
+        Self {
+            user_id,
+            sender_name: sender_name.into(),
+            threshold: DEFAULT_THRESHOLD,
+            max_node_len: DEFAULT_MAX_NODE_LEN,
+        }
+    }
+
+    /// 设置打包的阈值，文本总字符数超过该值时打包，默认为 500
+    pub fn threshold(mut self, chars: usize) -> Self {
+        self.threshold = chars;
+        self
+    }
+
+    /// 设置每条转发消息的最大字符数，默认为 1000
+    pub fn max_node_len(mut self, chars: usize) -> Self {
+        self.max_node_len = chars.max(1);
+        self
+    }
+
+    /// 在回复过长时将其打包为合并转发消息
+    ///
+    /// # 参数
+    /// * `segments`: 原本要发送的消息段
+    ///
+    /// # 返回
+    /// 文本总字符数未超过阈值时原样返回，否则返回只包含一个合并转发消息段的列表
+    pub fn package(&self, segments: Vec<OutgoingSegment>) -> Vec<OutgoingSegment> {
+        let len: usize = segments
+            .iter()
+            .map(|segment| match segment {
+                OutgoingSegment::Text(TextData { text }) => text.chars().count(),
+                _ => 0,
+            })
+            .sum();
+        if len <= self.threshold {
+            return segments;
+        }
+        vec![self.pack([segments])]
+    }
+
+    /// 将多个部分打包为合并转发消息，每个部分为一条转发消息，过长的文本会继续切分
+    ///
+    /// # 参数
+    /// * `parts`: 各部分的消息段
+    pub fn pack(&self, parts: impl IntoIterator<Item = Vec<OutgoingSegment>>) -> OutgoingSegment {
+        let mut messages = Vec::new();
+        for part in parts {
+            let mut current = Vec::new();
+            let mut current_len = 0;
+            for segment in part {
+                let OutgoingSegment::Text(TextData { text }) = segment else {
+                    current.push(segment);
+                    continue;
+                };
+                for chunk in split_text(&text, self.max_node_len) {
+                    let chunk_len = chunk.chars().count();
+                    if current_len + chunk_len > self.max_node_len && !current.is_empty() {
+                        messages.push(self.node(std::mem::take(&mut current)));
+                        current_len = 0;
+                    }
+                    current_len += chunk_len;
+                    current.push(OutgoingSegment::text(chunk));
+                }
+            }
+            if !current.is_empty() {
+                messages.push(self.node(current));
+            }
+        }
+        OutgoingSegment::Forward(ForwardData { messages })
+    }
+
+    /// 将长文本打包为合并转发消息
+    pub fn pack_text(&self, text: &str) -> OutgoingSegment {
+        self.pack([vec![OutgoingSegment::text(text)]])
+    }
+
+    /// 创建一条转发消息
+    fn node(&self, segments: Vec<OutgoingSegment>) -> OutgoingForwardMessage {
+        OutgoingForwardMessage {
+            user_id: self.user_id,
+            sender_name: self.sender_name.clone(),
+            segments,
+        }
+    }
+}
+
+/// 将文本切分为不超过 `max_len` 个字符的片段，尽量在换行处切分
+fn split_text(text: &str, max_len: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for line in text.split_inclusive('\n') {
+        let line_len = line.chars().count();
+        if current_len + line_len > max_len && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if line_len > max_len {
+            // 单行过长，只能按字符切分
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(max_len) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        current.push_str(line);
+        current_len += line_len;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_long_text() {
+        let packer = ForwardPacker::new(10000, "小助手")
+            .threshold(5)
+            .max_node_len(4);
+        let short = vec![OutgoingSegment::text("你好")];
+        assert_eq!(packer.package(short).len(), 1);
+
+        let OutgoingSegment::Forward(ForwardData { messages }) =
+            packer.pack_text("一二\n三四\n五六七八九")
+        else {
+            panic!("应当打包为合并转发消息");
+        };
+        let texts: Vec<String> = messages
+            .iter()
+            .flat_map(|message| &message.segments)
+            .map(|segment| match segment {
+                OutgoingSegment::Text(TextData { text }) => text.clone(),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(texts, ["一二\n", "三四\n", "五六七八", "九"]);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].sender_name, "小助手");
+    }
+}
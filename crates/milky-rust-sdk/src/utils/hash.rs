@@ -0,0 +1,183 @@
+//! 文件哈希相关的工具函数
+//!
+//! QQ 的文件接口使用 TriSHA1 作为文件哈希（如 [`FriendFileUpload`](milky_types::EventKind::FriendFileUpload)
+//! 事件与文件消息段中的 `file_hash` 字段）：
+//! - 文件不超过 30 MiB 时，即为整个文件的 SHA1
+//! - 文件超过 30 MiB 时，取开头、中间、结尾各 10 MiB 的数据，再追加 8 字节小端序的文件大小，对拼接结果计算 SHA1
+
+use crate::error::Result;
+
+use sha1::{Digest, Sha1};
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// 每个采样块的大小
+const CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 以流的方式计算 TriSHA1 哈希值
+///
+/// 需要预先知道数据的总大小，之后按顺序调用 [`TriSha1Hasher::update`] 传入全部数据。
+pub struct TriSha1Hasher {
+    /// 数据总大小
+    total: u64,
+    /// 已传入的数据量
+    offset: u64,
+    /// 计算哈希的方式
+    state: State,
+}
+
+/// TriSHA1 的两种计算方式
+enum State {
+    /// 小文件直接计算整体 SHA1
+    Whole(Sha1),
+    /// 大文件收集三个采样块
+    Sampled(Vec<u8>),
+}
+
+impl TriSha1Hasher {
+    /// 创建哈希计算器
+    ///
+    /// # 参数
+    /// * `total`: 数据的总字节数
+    pub fn new(total: u64) -> Self {
+        let state = if total <= CHUNK_SIZE * 3 {
+            State::Whole(Sha1::new())
+        } else {
+            State::Sampled(vec![0; (CHUNK_SIZE * 3) as usize])
+        };
+        Self {
+            total,
+            offset: 0,
+            state,
+        }
+    }
+
+    /// 按顺序传入下一段数据
+    pub fn update(&mut self, data: &[u8]) {
+        self.write_at(self.offset, data);
+        self.offset += data.len() as u64;
+    }
+
+    /// 计算最终的哈希值
+    ///
+    /// # 返回
+    /// 小写十六进制的 TriSHA1 哈希值
+    pub fn finalize(self) -> String {
+        let digest = match self.state {
+            State::Whole(hasher) => hasher.finalize(),
+            State::Sampled(mut samples) => {
+                samples.extend_from_slice(&self.total.to_le_bytes());
+                Sha1::digest(&samples)
+            }
+        };
+        to_hex(&digest)
+    }
+
+    /// 传入位于原始数据指定位置的一段数据
+    fn write_at(&mut self, offset: u64, data: &[u8]) {
+        let total = self.total;
+        match &mut self.state {
+            State::Whole(hasher) => hasher.update(data),
+            State::Sampled(samples) => {
+                let starts = sample_starts(total);
+                let end = offset + data.len() as u64;
+                for (index, start) in starts.into_iter().enumerate() {
+                    let from = offset.max(start);
+                    let to = end.min(start + CHUNK_SIZE);
+                    if from >= to {
+                        continue;
+                    }
+                    let dst = index as u64 * CHUNK_SIZE + (from - start);
+                    samples[dst as usize..(dst + to - from) as usize]
+                        .copy_from_slice(&data[(from - offset) as usize..(to - offset) as usize]);
+                }
+            }
+        }
+    }
+}
+
+/// 大文件中三个采样块的起始位置
+fn sample_starts(total: u64) -> [u64; 3] {
+    [0, total / 2 - CHUNK_SIZE / 2, total - CHUNK_SIZE]
+}
+
+/// 计算本地文件的 TriSHA1 哈希值
+///
+/// 对于超过 30 MiB 的文件只会读取三个采样块，不会读取整个文件。
+///
+/// # 参数
+/// * `path`: 文件路径
+///
+/// # 返回
+/// 成功则返回小写十六进制的 TriSHA1 哈希值
+pub async fn tri_sha1_file(path: impl AsRef<Path>) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let total = file.metadata().await?.len();
+    let mut hasher = TriSha1Hasher::new(total);
+
+    if let State::Whole(_) = hasher.state {
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+    } else {
+        let mut buf = vec![0; CHUNK_SIZE as usize];
+        for start in sample_starts(total) {
+            file.seek(SeekFrom::Start(start)).await?;
+            file.read_exact(&mut buf).await?;
+            hasher.write_at(start, &buf);
+        }
+    }
+    Ok(hasher.finalize())
+}
+
+/// 将字节转换为小写十六进制字符串
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_data_is_plain_sha1() {
+        let mut hasher = TriSha1Hasher::new(11);
+        hasher.update(b"hello ");
+        hasher.update(b"milky");
+        assert_eq!(
+            hasher.finalize(),
+            "22c6a4035980f804df84f429c2e8d56cc4939938"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_data_is_sampled() {
+        let total = CHUNK_SIZE * 3 + 12345;
+        let data: Vec<u8> = (0..total).map(|i| (i % 251) as u8).collect();
+
+        let mut expected = Vec::new();
+        let mid = (total / 2 - CHUNK_SIZE / 2) as usize;
+        expected.extend_from_slice(&data[..CHUNK_SIZE as usize]);
+        expected.extend_from_slice(&data[mid..mid + CHUNK_SIZE as usize]);
+        expected.extend_from_slice(&data[(total - CHUNK_SIZE) as usize..]);
+        expected.extend_from_slice(&total.to_le_bytes());
+        let expected = to_hex(&Sha1::digest(&expected));
+
+        let mut hasher = TriSha1Hasher::new(total);
+        for chunk in data.chunks(1024 * 1024 + 7) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize(), expected);
+
+        let path = std::env::temp_dir().join(format!("milky-trisha1-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, &data).await.unwrap();
+        assert_eq!(tri_sha1_file(&path).await.unwrap(), expected);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
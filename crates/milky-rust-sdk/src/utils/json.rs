@@ -0,0 +1,67 @@
+//! 解析事件与 API 响应的 JSON
+//!
+//! 默认使用 serde_json。启用 `simd-json` 特性后改用 simd-json 解析，它需要可修改的输入，
+//! 因此解析文本时会先复制一次。是否更快取决于 CPU 与事件的内容，启用前应先在实际负载下测量。
+//! 两种实现的错误都以 [`serde_json::Error`] 返回，调用方无需区分。
+
+use serde::de::DeserializeOwned;
+
+/// 从文本解析
+#[cfg(any(feature = "websocket", feature = "webhook"))]
+pub(crate) fn from_str<T: DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    #[cfg(feature = "simd-json")]
+    {
+        from_vec(text.as_bytes().to_vec())
+    }
+    #[cfg(not(feature = "simd-json"))]
+    {
+        serde_json::from_str(text)
+    }
+}
+
+/// 从字节解析，启用 `simd-json` 特性时直接在 `bytes` 上原地解析，无需再复制
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_vec<T: DeserializeOwned>(mut bytes: Vec<u8>) -> serde_json::Result<T> {
+    simd_json::serde::from_slice(&mut bytes)
+        .map_err(<serde_json::Error as serde::de::Error>::custom)
+}
+
+/// 从字节解析，启用 `simd-json` 特性时直接在 `bytes` 上原地解析，无需再复制
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_vec<T: DeserializeOwned>(bytes: Vec<u8>) -> serde_json::Result<T> {
+    serde_json::from_slice(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::common::ApiResponse;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_from_vec() {
+        let text =
+            r#"{"status":"ok","retcode":0,"data":{"nested":[1,2.5,null,"测试"]},"echo":"7"}"#;
+        let response: ApiResponse<Value> = from_vec(text.as_bytes().to_vec()).unwrap();
+        assert_eq!(
+            response.data,
+            Some(json!({"nested": [1, 2.5, null, "测试"]}))
+        );
+        assert_eq!(response.echo.as_deref(), Some("7"));
+
+        assert!(from_vec::<Value>(b"{".to_vec()).is_err());
+    }
+
+    #[cfg(any(feature = "websocket", feature = "webhook"))]
+    #[test]
+    fn test_from_str() {
+        use milky_types::{Event, EventKind};
+
+        let text =
+            r#"{"time":1,"self_id":10000,"event_type":"bot_offline","data":{"reason":"测试"}}"#;
+        let event: Event = from_str(text).unwrap();
+        assert!(matches!(event.kind, EventKind::BotOffline { reason } if reason == "测试"));
+
+        assert!(from_str::<Event>("{").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! 文件类型识别与文件名推断
+//!
+//! 通过文件头部的魔数识别常见的图片、语音、视频与文档格式，
+//! 用于在上传时推断合适的文件名，以及在发送图片、语音、视频消息段前检查媒体类型是否受支持。
+
+use crate::logger::warn;
+
+use milky_types::common::FileUri;
+use milky_types::message::out_going::OutgoingSegment;
+use tokio::io::AsyncReadExt;
+
+/// 识别文件类型所需读取的头部字节数
+pub(crate) const HEAD_SIZE: usize = 16;
+
+/// 图片消息段支持的类型
+const IMAGE_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/bmp",
+];
+
+/// 语音消息段支持的类型
+const RECORD_TYPES: &[&str] = &[
+    "audio/amr",
+    "audio/silk",
+    "audio/mpeg",
+    "audio/wav",
+    "audio/ogg",
+    "audio/flac",
+];
+
+/// 视频消息段支持的类型
+const VIDEO_TYPES: &[&str] = &["video/mp4"];
+
+/// 根据文件头部的魔数识别文件类型
+///
+/// # 参数
+/// * `data`: 文件开头的若干字节，至少需要 16 字节才能识别所有格式
+///
+/// # 返回
+/// 识别成功则返回 MIME 类型，例如 `image/png`；无法识别时返回 `None`
+pub fn detect_mime(data: &[u8]) -> Option<&'static str> {
+    let mime = match data {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => "image/webp",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'A',
+            b'V',
+            b'E',
+            ..,
+        ] => "audio/wav",
+        [b'B', b'M', ..] => "image/bmp",
+        [b'#', b'!', b'A', b'M', b'R', ..] => "audio/amr",
+        [b'#', b'!', b'S', b'I', b'L', b'K', ..]
+        | [0x02, b'#', b'!', b'S', b'I', b'L', b'K', ..] => "audio/silk",
+        [b'I', b'D', b'3', ..] | [0xFF, 0xFB | 0xF3 | 0xF2, ..] => "audio/mpeg",
+        [b'O', b'g', b'g', b'S', ..] => "audio/ogg",
+        [b'f', b'L', b'a', b'C', ..] => "audio/flac",
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => "video/mp4",
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        [b'P', b'K', 0x03, 0x04, ..] => "application/zip",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// 获取 MIME 类型对应的常用扩展名
+pub fn extension_for(mime: &str) -> Option<&'static str> {
+    let ext = match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/bmp" => "bmp",
+        "audio/amr" => "amr",
+        "audio/silk" => "silk",
+        "audio/mpeg" => "mp3",
+        "audio/wav" => "wav",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "video/mp4" => "mp4",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        _ => return None,
+    };
+    Some(ext)
+}
+
+/// 读取文件头部用于识别类型，网络链接不会被下载，返回 `None`
+async fn read_head(uri: &FileUri) -> Option<Vec<u8>> {
+    read_media(uri, false).await
+}
+
+/// 读取媒体文件的内容，网络链接不会被下载，返回 `None`
+///
+/// # 参数
+/// * `uri`: 文件的 URI
+/// * `full`: 是否读取完整内容，否则只读取识别类型所需的头部
+pub(crate) async fn read_media(uri: &FileUri, full: bool) -> Option<Vec<u8>> {
+    match uri {
+        FileUri::Path(path) if full => match tokio::fs::read(path).await {
+            Ok(data) => Some(data),
+            Err(e) => {
+                warn!("读取文件 {} 失败: {e}", path.display());
+                None
+            }
+        },
+        FileUri::Path(path) => {
+            let mut file = tokio::fs::File::open(path).await.ok()?;
+            let mut head = Vec::with_capacity(HEAD_SIZE);
+            (&mut file)
+                .take(HEAD_SIZE as u64)
+                .read_to_end(&mut head)
+                .await
+                .ok()?;
+            Some(head)
+        }
+        FileUri::Base64(data) if full => Some(data.clone()),
+        FileUri::Base64(data) => Some(data[..data.len().min(HEAD_SIZE)].to_vec()),
+        FileUri::Url(_) => None,
+    }
+}
+
+/// 识别文件的 MIME 类型，网络链接无法识别
+///
+/// # 参数
+/// * `uri`: 文件的 URI
+pub async fn detect_uri_mime(uri: &FileUri) -> Option<&'static str> {
+    detect_mime(&read_head(uri).await?)
+}
+
+/// 为要上传的文件推断文件名
+///
+/// 优先使用本地路径或网络链接中的文件名；没有可用的文件名时，根据识别出的文件类型生成 `file.<扩展名>`。
+///
+/// # 参数
+/// * `uri`: 文件的 URI
+///
+/// # 返回
+/// 推断出的文件名，无法识别类型时返回 `file`
+pub async fn infer_file_name(uri: &FileUri) -> String {
+    let name = match uri {
+        FileUri::Path(path) => path.file_name().and_then(|name| name.to_str()),
+        FileUri::Url(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back()),
+        FileUri::Base64(_) => None,
+    }
+    .filter(|name| !name.is_empty());
+    if let Some(name) = name
+        && (name.contains('.') || matches!(uri, FileUri::Url(_)))
+    {
+        return name.to_string();
+    }
+
+    let ext = detect_uri_mime(uri).await.and_then(extension_for);
+    match (name, ext) {
+        (Some(name), Some(ext)) => format!("{name}.{ext}"),
+        (Some(name), None) => name.to_string(),
+        (None, Some(ext)) => format!("file.{ext}"),
+        (None, None) => "file".to_string(),
+    }
+}
+
+/// 检查消息中的图片、语音、视频消息段是否使用了受支持的媒体类型，不受支持时输出警告
+///
+/// 仅检查本地文件与 Base64 内容，网络链接不会被下载检查。
+///
+/// # 参数
+/// * `segments`: 待发送的消息段
+pub async fn check_media_segments(segments: &[OutgoingSegment]) {
+    for segment in segments {
+        let Some(uri) = media_uri(segment) else {
+            continue;
+        };
+        if let Some(head) = read_head(uri).await {
+            check_media_segment(segment, &head);
+        }
+    }
+}
+
+/// 获取图片、语音、视频消息段的文件 URI，其他消息段返回 `None`
+pub(crate) fn media_uri(segment: &OutgoingSegment) -> Option<&FileUri> {
+    match segment {
+        OutgoingSegment::Image(data) => Some(&data.uri),
+        OutgoingSegment::Record(data) => Some(&data.uri),
+        OutgoingSegment::Video(data) => Some(&data.uri),
+        _ => None,
+    }
+}
+
+/// 根据已读取的文件头部检查媒体消息段的类型是否受支持，不受支持时输出警告
+///
+/// # 参数
+/// * `segment`: 媒体消息段，其他消息段不做检查
+/// * `head`: 文件开头的若干字节
+pub(crate) fn check_media_segment(segment: &OutgoingSegment, head: &[u8]) {
+    let (kind, supported) = match segment {
+        OutgoingSegment::Image(_) => ("图片", IMAGE_TYPES),
+        OutgoingSegment::Record(_) => ("语音", RECORD_TYPES),
+        OutgoingSegment::Video(_) => ("视频", VIDEO_TYPES),
+        _ => return,
+    };
+    match detect_mime(head) {
+        Some(mime) if supported.contains(&mime) => {}
+        Some(mime) => warn!("{kind}消息段的文件类型 {mime} 可能不受支持"),
+        None => warn!("无法识别{kind}消息段的文件类型，发送可能失败"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn test_detect_and_infer() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        assert_eq!(detect_mime(&png), Some("image/png"));
+        assert_eq!(detect_mime(b"#!SILK_V3"), Some("audio/silk"));
+        assert_eq!(detect_mime(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(detect_mime(b"hello"), None);
+
+        assert_eq!(infer_file_name(&FileUri::Base64(png)).await, "file.png");
+        assert_eq!(
+            infer_file_name(&FileUri::Base64(b"hi".to_vec())).await,
+            "file"
+        );
+        assert_eq!(
+            infer_file_name(&FileUri::Path(PathBuf::from("/tmp/报告.pdf"))).await,
+            "报告.pdf"
+        );
+        assert_eq!(
+            infer_file_name(&FileUri::parse("https://example.com/a/b.zip?x=1").unwrap()).await,
+            "b.zip"
+        );
+    }
+}
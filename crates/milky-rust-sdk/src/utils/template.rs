@@ -0,0 +1,277 @@
+//! 将消息模板字符串解析为消息段
+//!
+//! 模板由普通文本与方括号中的标记组成，适合写在配置文件中、由用户编辑的回复内容：
+//!
+//! | 标记 | 消息段 |
+//! | --- | --- |
+//! | `[at:12345]`、`[at:all]` | 提及某人、提及全体成员 |
+//! | `[face:14]` | QQ表情 |
+//! | `[reply:100]` | 回复指定序列号的消息 |
+//! | `[img:file:///x.png]`、`[image:https://…]` | 图片 |
+//! | `[record:…]`、`[video:…]` | 语音、视频 |
+//!
+//! 媒体标记中的地址需要是 `file://`、`http(s)://` 或 `base64://` URI。模板通常由用户编辑，
+//! 因此默认不接受不带协议的路径，避免 `[img:/etc/shadow]` 之类的模板读取任意本地文件；
+//! 通过 [`TemplateParser::with_media_dir`] 指定媒体目录后，不带协议的地址按该目录下的相对路径解析。
+//!
+//! 文本与标记中的 `\[`、`\]` 与 `\\` 分别表示 `[`、`]` 与 `\`，例如 `[img:file:///a\]b.png]`。
+//!
+//! 同时兼容 CQ 码写法，例如 `[CQ:at,qq=12345]`、`[CQ:image,file=https://…]`，
+//! 参数值中的 `&#91;`、`&#93;`、`&#44;` 与 `&amp;` 会被还原。
+//!
+//! ```
+//! use milky_rust_sdk::utils::template::parse_template;
+//!
+//! let segments = parse_template("你好 [at:12345]，看看这个 [img:https://example.com/a.png]").unwrap();
+//! assert_eq!(segments.len(), 4);
+//! ```
+
+use milky_types::common::FileUri;
+use milky_types::message::out_going::OutgoingSegment;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// 模板解析失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    /// 出错的标记在模板中的字节位置
+    pub position: usize,
+    /// 错误描述
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "消息模板第 {} 字节处有误: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// 以默认设置将消息模板解析为消息段，参见 [`TemplateParser::parse`]
+pub fn parse_template(template: &str) -> Result<Vec<OutgoingSegment>, TemplateError> {
+    TemplateParser::new().parse(template)
+}
+
+/// 消息模板解析器
+#[derive(Debug, Clone, Default)]
+pub struct TemplateParser {
+    /// 不带协议的媒体地址所在的目录
+    media_dir: Option<PathBuf>,
+}
+
+impl TemplateParser {
+    /// 创建解析器，默认只接受带有协议的媒体地址
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 允许不带协议的媒体地址，并将其解析为指定目录中的文件
+    ///
+    /// # 参数
+    /// * `dir`: 媒体目录，地址为绝对路径或包含 `..` 时仍会被拒绝
+    pub fn with_media_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.media_dir = Some(dir.into());
+        self
+    }
+
+    /// 将消息模板解析为消息段
+    ///
+    /// # 参数
+    /// * `template`: 消息模板，格式参见 [模块文档](self)
+    ///
+    /// # 返回
+    /// 按顺序排列的消息段，相邻的文本会合并为一个文本消息段；标记格式不正确时返回 [`TemplateError`]
+    pub fn parse(&self, template: &str) -> Result<Vec<OutgoingSegment>, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+        loop {
+            let position = template.len() - chars.as_str().len();
+            let Some(c) = chars.next() else {
+                break;
+            };
+            match c {
+                '\\' => push_escaped(&mut text, chars.next()),
+                '[' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('\\') => push_escaped(&mut tag, chars.next()),
+                            Some(c) => tag.push(c),
+                            None => {
+                                return Err(TemplateError {
+                                    position,
+                                    message: "标记缺少结尾的 `]`".to_string(),
+                                });
+                            }
+                        }
+                    }
+                    let segment = self
+                        .parse_tag(&tag)
+                        .map_err(|message| TemplateError { position, message })?;
+                    if !text.is_empty() {
+                        segments.push(OutgoingSegment::text(std::mem::take(&mut text)));
+                    }
+                    segments.push(segment);
+                }
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(OutgoingSegment::text(text));
+        }
+        Ok(segments)
+    }
+
+    /// 解析方括号中的标记
+    fn parse_tag(&self, tag: &str) -> Result<OutgoingSegment, String> {
+        if let Some(code) = tag.strip_prefix("CQ:") {
+            return self.parse_cq_code(code);
+        }
+        let (kind, value) = tag
+            .split_once(':')
+            .ok_or_else(|| format!("标记 `[{tag}]` 缺少 `:`"))?;
+        self.build_segment(kind, value.trim())
+    }
+
+    /// 解析 CQ 码，例如 `at,qq=12345`
+    fn parse_cq_code(&self, code: &str) -> Result<OutgoingSegment, String> {
+        let mut parts = code.split(',');
+        let kind = parts.next().unwrap_or_default();
+        let params: Vec<(&str, String)> = parts
+            .filter_map(|part| part.split_once('='))
+            .map(|(key, value)| (key, unescape_cq(value)))
+            .collect();
+        let param = |keys: &[&str]| {
+            params
+                .iter()
+                .find(|(key, _)| keys.contains(key))
+                .map(|(_, value)| value.as_str())
+                .ok_or_else(|| format!("CQ 码 `{kind}` 缺少参数 `{}`", keys[0]))
+        };
+        match kind {
+            "at" => self.build_segment("at", param(&["qq"])?),
+            "face" => self.build_segment("face", param(&["id"])?),
+            "reply" => self.build_segment("reply", param(&["id", "seq"])?),
+            "image" | "record" | "video" => self.build_segment(kind, param(&["file", "url"])?),
+            _ => Err(format!("不支持的 CQ 码类型: {kind}")),
+        }
+    }
+
+    /// 根据标记类型与参数创建消息段
+    fn build_segment(&self, kind: &str, value: &str) -> Result<OutgoingSegment, String> {
+        let number = |value: &str| {
+            value
+                .parse::<i64>()
+                .map_err(|_| format!("`{kind}` 的参数 `{value}` 不是有效的数字"))
+        };
+        match kind {
+            "at" if value == "all" => Ok(OutgoingSegment::at_all()),
+            "at" => Ok(OutgoingSegment::at(number(value)?)),
+            "face" => Ok(OutgoingSegment::face(value)),
+            "reply" => Ok(OutgoingSegment::reply(number(value)?)),
+            "img" | "image" => Ok(OutgoingSegment::image(self.parse_uri(value)?)),
+            "record" => Ok(OutgoingSegment::record(self.parse_uri(value)?)),
+            "video" => Ok(OutgoingSegment::video(self.parse_uri(value)?)),
+            _ => Err(format!("不支持的标记类型: {kind}")),
+        }
+    }
+
+    /// 解析媒体地址，不带协议的地址只在设置了媒体目录时视为其中的文件
+    fn parse_uri(&self, value: &str) -> Result<FileUri, String> {
+        if value.is_empty() {
+            return Err("媒体地址不能为空".to_string());
+        }
+        if value.contains("://") {
+            return FileUri::parse(value).map_err(|e| e.to_string());
+        }
+        let dir = self
+            .media_dir
+            .as_ref()
+            .ok_or_else(|| format!("媒体地址 `{value}` 缺少协议，本地文件请使用 `file://` URI"))?;
+        let path = Path::new(value);
+        let relative = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !relative {
+            return Err(format!("媒体地址 `{value}` 不在媒体目录中"));
+        }
+        Ok(FileUri::Path(dir.join(path)))
+    }
+}
+
+/// 写入 `\` 之后的字符，`[`、`]` 与 `\` 去掉转义，其他字符保留 `\`
+fn push_escaped(out: &mut String, next: Option<char>) {
+    match next {
+        Some(escaped @ ('[' | ']' | '\\')) => out.push(escaped),
+        Some(other) => {
+            out.push('\\');
+            out.push(other);
+        }
+        None => out.push('\\'),
+    }
+}
+
+/// 还原 CQ 码参数中的转义字符
+fn unescape_cq(value: &str) -> String {
+    value
+        .replace("&#91;", "[")
+        .replace("&#93;", "]")
+        .replace("&#44;", ",")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_template() {
+        let segments = parse_template(
+            r"hello [at:12345] see [img:file:///x.png]\[ok\] [CQ:at,qq=all][CQ:reply,id=7]",
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&segments).unwrap(),
+            json!([
+                {"type": "text", "data": {"text": "hello "}},
+                {"type": "mention", "data": {"user_id": 12345}},
+                {"type": "text", "data": {"text": " see "}},
+                {"type": "image", "data": {"uri": "file:///x.png", "sub_type": "normal"}},
+                {"type": "text", "data": {"text": "[ok] "}},
+                {"type": "mention_all", "data": null},
+                {"type": "reply", "data": {"message_seq": 7}},
+            ])
+        );
+
+        let error = parse_template("你好 [at:abc]").unwrap_err();
+        assert_eq!(error.position, "你好 ".len());
+        assert!(parse_template("[at:1").is_err());
+        assert!(parse_template("[dice:1]").is_err());
+    }
+
+    #[test]
+    fn test_media_paths() {
+        // 默认拒绝不带协议的本地路径
+        assert!(parse_template("[img:/etc/shadow]").is_err());
+        assert!(parse_template("[CQ:image,file=a.png]").is_err());
+
+        let parser = TemplateParser::new().with_media_dir("/srv/media");
+        let segments = parser.parse(r"[img:cats/a\]b.png]").unwrap();
+        assert!(matches!(
+            &segments[..],
+            [OutgoingSegment::Image(image)]
+                if image.uri == FileUri::Path(PathBuf::from("/srv/media/cats/a]b.png"))
+        ));
+        assert!(parser.parse("[img:/etc/shadow]").is_err());
+        assert!(parser.parse("[img:../secret.png]").is_err());
+        assert!(parser.parse("[img:cats/../../secret.png]").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! 在浏览器（`wasm32-unknown-unknown`）中调用 API 与接收事件
+//!
+//! [`MilkyClient`](crate::MilkyClient) 依赖 tokio 与 axum 的原生实现，无法编译到 wasm32。
+//! 关闭默认特性并启用 `wasm` 特性后，可以使用本模块的 [`WasmClient`]：API 请求通过浏览器的 `fetch` 发送，
+//! 事件通过浏览器的 `WebSocket` 接收，适合浏览器中的管理面板或 Tauri 前端直接连接 Milky 协议端。
+//!
+//! ```toml
+//! milky-rust-sdk = { version = "1", default-features = false, features = ["wasm"] }
+//! ```
+//!
+//! [`api`](crate::api) 模块中不依赖本地文件系统的 API 方法（发送消息、群管理、获取好友列表等）同样可以在
+//! `WasmClient` 上调用；文件下载、上传与头像缓存等功能只在原生平台可用。
+//!
+//! 浏览器的 `WebSocket` 无法设置请求头，连接事件流时访问令牌通过 `access_token` 查询参数传递。
+//!
+//! ```ignore
+//! use futures_util::StreamExt;
+//! use milky_rust_sdk::wasm::WasmClient;
+//!
+//! let client = WasmClient::new("http://127.0.0.1:3000", Some("token".to_string()))?;
+//! let info = client.get_login_info().await?;
+//! let mut events = client.connect_events()?;
+//! while let Some(event) = events.next().await {
+//!     let event = event?;
+//!     // ...
+//! }
+//! ```
+
+use crate::error::{MilkyError, Result};
+use crate::logger::redact::summarize;
+use crate::types::common::ApiResponse;
+
+use futures_util::{Stream, StreamExt};
+use gloo_net::websocket::Message;
+use gloo_net::websocket::futures::WebSocket;
+use milky_types::Event;
+use milky_types::common::FileUri;
+use milky_types::message::out_going::OutgoingSegment;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use url::Url;
+
+/// 运行在浏览器中的 Milky 客户端
+#[derive(Clone)]
+pub struct WasmClient {
+    /// 通过 `fetch` 发送请求的 HTTP 客户端
+    http_client: reqwest::Client,
+    /// API请求的基础URL，例如 `http://127.0.0.1:8080/api/`
+    api_base_url: Url,
+    /// 事件WebSocket连接的URL，例如 `ws://127.0.0.1:8080/event`
+    event_ws_url: Url,
+    /// 访问令牌
+    access_token: Option<String>,
+}
+
+impl WasmClient {
+    /// 创建一个新的 `WasmClient` 实例
+    ///
+    /// # 参数
+    /// * `endpoint`: 协议端的地址，例如 `http://127.0.0.1:3000`，也可以使用 `ws://` 或 `wss://` 地址
+    /// * `access_token`: 可选的访问令牌，用于认证
+    ///
+    /// # 返回
+    /// 成功则返回 `WasmClient`；如果URL解析失败或协议不受支持，则返回错误
+    pub fn new(endpoint: &str, access_token: Option<String>) -> Result<Self> {
+        let endpoint = Url::parse(endpoint)?;
+        let (http_scheme, ws_scheme) = match endpoint.scheme() {
+            "http" | "ws" => ("http", "ws"),
+            "https" | "wss" => ("https", "wss"),
+            scheme => return Err(MilkyError::UnsupportedScheme(scheme.to_string())),
+        };
+
+        let mut api_base_url = endpoint.clone();
+        api_base_url
+            .set_scheme(http_scheme)
+            .map_err(|_| MilkyError::UrlParse(url::ParseError::InvalidPort))?;
+        api_base_url.set_path("api/");
+
+        let mut event_ws_url = endpoint;
+        event_ws_url
+            .set_scheme(ws_scheme)
+            .map_err(|_| MilkyError::UrlParse(url::ParseError::InvalidPort))?;
+        event_ws_url.set_path("event");
+        if let Some(token) = &access_token {
+            event_ws_url
+                .query_pairs_mut()
+                .append_pair("access_token", token);
+        }
+
+        Ok(Self {
+            http_client: reqwest::Client::new(),
+            api_base_url,
+            event_ws_url,
+            access_token,
+        })
+    }
+
+    /// 发送 API 请求
+    ///
+    /// # 参数
+    /// * `action`: API 操作名称，例如 `get_login_info`
+    /// * `params`: 请求参数
+    ///
+    /// # 返回
+    /// 成功则返回解析后的响应数据；请求失败或协议端返回错误时返回 [`MilkyError`]
+    pub async fn send_request<P: Serialize, R: DeserializeOwned>(
+        &self,
+        action: &str,
+        params: P,
+    ) -> Result<R> {
+        let params = serde_json::to_value(params)?;
+        let mut request_builder = self.http_client.post(self.api_base_url.join(action)?);
+        if let Some(token) = &self.access_token {
+            request_builder = request_builder.bearer_auth(token);
+        }
+
+        let http_response = request_builder.json(&params).send().await?;
+        let status = http_response.status();
+        if status != StatusCode::OK {
+            let error_message = http_response
+                .text()
+                .await
+                .unwrap_or("未知的 HTTP 错误".to_string());
+            return Err(MilkyError::HttpApiError {
+                status,
+                message: error_message,
+            });
+        }
+
+        let api_resp = http_response.json::<ApiResponse<Value>>().await?;
+        if api_resp.status == "ok" && api_resp.retcode == 0 {
+            let data = api_resp.data.unwrap_or(Value::Null);
+            serde_json::from_value(data).map_err(|source| MilkyError::Decode {
+                action: action.to_string(),
+                request: summarize(&params),
+                source,
+            })
+        } else {
+            Err(MilkyError::api_error(
+                action,
+                api_resp
+                    .message
+                    .unwrap_or_else(|| "未知的 API 错误".to_string()),
+                Some(api_resp.retcode),
+            ))
+        }
+    }
+
+    /// 连接事件 WebSocket
+    ///
+    /// # 返回
+    /// 成功则返回按顺序产出事件的 [`EventStream`]；地址无效等原因导致浏览器无法创建连接时返回错误
+    pub fn connect_events(&self) -> Result<EventStream> {
+        let socket = WebSocket::open(self.event_ws_url.as_str())
+            .map_err(|e| MilkyError::Internal(format!("无法创建事件 WebSocket 连接: {e}")))?;
+        Ok(EventStream { socket })
+    }
+}
+
+impl WasmClient {
+    /// 发送前对消息中的媒体消息段进行预处理，浏览器中无法读取本地文件，消息段原样发送
+    pub(crate) async fn prepare_outgoing(
+        &self,
+        segments: Vec<OutgoingSegment>,
+    ) -> Vec<OutgoingSegment> {
+        segments
+    }
+}
+
+/// 根据文件 URI 推断文件名，浏览器中无法读取文件内容，只使用路径或链接中的最后一段
+pub(crate) async fn infer_file_name(uri: &FileUri) -> String {
+    let name = match uri {
+        FileUri::Path(path) => path.file_name().and_then(|name| name.to_str()),
+        FileUri::Url(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back()),
+        FileUri::Base64(_) => None,
+    };
+    name.filter(|name| !name.is_empty())
+        .unwrap_or("file")
+        .to_string()
+}
+
+/// 通过浏览器的 `WebSocket` 接收的事件流
+///
+/// 连接关闭后流结束，连接出错时产出 [`MilkyError::Disconnected`]。
+/// 无法解析的消息产出 [`MilkyError::Json`]，不影响后续事件的接收。
+pub struct EventStream {
+    /// 事件 WebSocket 连接
+    socket: WebSocket,
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let message = match self.socket.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(message))) => message,
+            Poll::Ready(Some(Err(e))) => {
+                return Poll::Ready(Some(Err(MilkyError::Disconnected(e.to_string()))));
+            }
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => return Poll::Pending,
+        };
+        let event = match message {
+            Message::Text(text) => serde_json::from_str(&text),
+            Message::Bytes(bytes) => serde_json::from_slice(&bytes),
+        };
+        Poll::Ready(Some(event.map_err(MilkyError::from)))
+    }
+}
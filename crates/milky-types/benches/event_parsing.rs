@@ -0,0 +1,71 @@
+//! 对比解析大量群消息事件时 [`Event`] 与 [`EventRef`] 的内存分配次数与耗时
+//!
+//! 运行 `cargo bench -p milky-types --bench event_parsing`
+
+use milky_types::Event;
+use milky_types::borrowed::EventRef;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// 统计内存分配次数的分配器
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// 一次突发中的群消息数量
+const BURST: usize = 10_000;
+
+/// 构造第 `seq` 条群消息事件的文本
+fn group_message(seq: usize) -> String {
+    format!(
+        r#"{{"time":1630483200,"self_id":10000,"event_type":"message_receive","data":{{"peer_id":123456,"message_seq":{seq},"sender_id":987654321,"time":1630483200,"segments":[{{"type":"reply","data":{{"message_seq":1}}}},{{"type":"text","data":{{"text":"这是一条用于测试解析性能的群消息，编号 {seq}"}}}},{{"type":"image","data":{{"resource_id":"resource-{seq}","temp_url":"https://example.com/image/{seq}.png","width":640,"height":480,"summary":"[图片]","sub_type":"normal"}}}}],"message_scene":"group","group":{{"group_id":123456,"group_name":"测试群","member_count":100,"max_member_count":500}},"group_member":{{"user_id":987654321,"nickname":"测试成员","sex":"female","group_id":123456,"card":"","title":"","level":1,"role":"member","join_time":1630000000,"last_sent_time":1630483200}}}}}}"#
+    )
+}
+
+/// 解析整个突发，返回分配次数与耗时
+fn measure(frames: &[String], parse: impl Fn(&str)) -> (usize, Duration) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for frame in frames {
+        parse(frame);
+    }
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - before,
+        start.elapsed(),
+    )
+}
+
+fn main() {
+    let frames: Vec<String> = (0..BURST).map(group_message).collect();
+
+    let owned = measure(&frames, |text| {
+        black_box(serde_json::from_str::<Event>(text).unwrap());
+    });
+    let borrowed = measure(&frames, |text| {
+        let event = EventRef::from_str(text).unwrap();
+        black_box(event.message().unwrap().unwrap());
+    });
+
+    for (name, (allocations, elapsed)) in [("Event", owned), ("EventRef", borrowed)] {
+        println!(
+            "{name:<10} {BURST} 条群消息: {allocations:>8} 次分配 ({:.1} 次/条), {elapsed:?}",
+            allocations as f64 / BURST as f64
+        );
+    }
+}
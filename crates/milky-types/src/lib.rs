@@ -1,7 +1,20 @@
+//! Milky 协议的事件、消息与 API 数据类型
+//!
+//! 本 crate 只依赖 `serde`、`serde_json`、`base64` 与 `url`，不包含任何网络或运行时相关的代码，
+//! 可以直接编译到 `wasm32-unknown-unknown` 等目标，在浏览器面板或 Tauri 前端中解析 Milky 事件、构造请求参数。
+//!
+//! 启用 `chrono` 特性后，可以通过 [`time`] 模块提供的方法以 `DateTime<Utc>` 读取各类型中的时间戳。
+//! 启用 `schemars` 特性后，可以通过 [`schema`] 模块导出各类型的 JSON Schema。
+
 mod types;
 
+pub use types::borrowed;
 pub use types::common;
 pub use types::event::{Event, EventKind, MessageEvent};
 pub use types::friend;
 pub use types::group;
 pub use types::message;
+#[cfg(feature = "schemars")]
+pub use types::schema;
+#[cfg(feature = "chrono")]
+pub use types::time;
@@ -0,0 +1,532 @@
+//! 借用原始 JSON 文本的事件与消息段视图
+//!
+//! [`Event`] 会把每个字符串复制到新分配的内存中，群消息密集时这部分开销在解析中占了大头。
+//! 只需要检查事件类型、读取消息文本等场景可以改用 [`EventRef`]：它直接借用 WebSocket 文本帧中的字符串，
+//! `data` 部分保留为未解析的原始 JSON，需要时再通过 [`EventRef::message`] 解析为借用的
+//! [`IncomingMessageRef`]，或通过 [`EventRef::to_event`] 解析为完整的 [`Event`]。
+//!
+//! 包含转义字符的字符串无法直接借用，此时会退回为复制后的 [`Cow::Owned`]。
+//!
+//! ```
+//! use milky_types::borrowed::{EventRef, IncomingSegmentRef};
+//!
+//! let text = r#"{
+//!     "time": 1,
+//!     "self_id": 10000,
+//!     "event_type": "message_receive",
+//!     "data": {
+//!         "peer_id": 123,
+//!         "message_seq": 1,
+//!         "sender_id": 456,
+//!         "time": 1,
+//!         "segments": [{"type": "text", "data": {"text": "你好"}}],
+//!         "message_scene": "group"
+//!     }
+//! }"#;
+//! let event = EventRef::from_str(text).unwrap();
+//! let message = event.message().unwrap().unwrap();
+//! assert!(matches!(&message.segments[0], IncomingSegmentRef::Text { text } if text == "你好"));
+//! ```
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::value::RawValue;
+
+use crate::types::{
+    common::MessageScene,
+    event::Event,
+    message::in_coming::{IncomingMessage, IncomingSegment},
+    tagged::{self, AdjacentlyTagged},
+};
+
+/// 借用原始文本的事件
+///
+/// 只解析事件的公共字段，`data` 部分保留为原始 JSON
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct EventRef<'a> {
+    /// 事件发生的Unix时间戳（秒）
+    pub time: i64,
+    /// 机器人自身的 QQ 号
+    pub self_id: i64,
+    /// 事件类型，与 [`EventKind::event_type`](crate::EventKind::event_type) 相同
+    pub event_type: &'a str,
+    /// 未解析的事件数据
+    #[serde(borrow)]
+    pub data: &'a RawValue,
+}
+
+impl<'a> EventRef<'a> {
+    /// 从事件的 JSON 文本解析
+    ///
+    /// # 参数
+    /// * `text`: 事件的 JSON 文本，解析结果借用其中的字符串
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &'a str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    /// 是否为消息事件
+    pub fn is_message(&self) -> bool {
+        self.event_type == "message_receive"
+    }
+
+    /// 将消息事件的数据解析为借用的消息
+    ///
+    /// # 返回
+    /// 不是消息事件时返回 `None`，否则返回解析结果
+    pub fn message(&self) -> Option<serde_json::Result<IncomingMessageRef<'a>>> {
+        self.is_message()
+            .then(|| serde_json::from_str(self.data.get()))
+    }
+
+    /// 解析为完整的 [`Event`]
+    pub fn to_event(&self) -> serde_json::Result<Event> {
+        #[derive(serde::Serialize)]
+        struct Envelope<'b> {
+            time: i64,
+            self_id: i64,
+            event_type: &'b str,
+            data: &'b RawValue,
+        }
+        // 借用的字段重新组装为原始文本后交给 `Event` 的反序列化逻辑，保证两者的结果一致
+        let text = serde_json::to_string(&Envelope {
+            time: self.time,
+            self_id: self.self_id,
+            event_type: self.event_type,
+            data: self.data,
+        })?;
+        serde_json::from_str(&text)
+    }
+}
+
+/// 借用原始文本的接收消息，对应 [`IncomingMessage`]
+///
+/// 群、好友与群成员等附加信息不会被解析
+#[derive(Deserialize, Debug, Clone)]
+pub struct IncomingMessageRef<'a> {
+    /// 消息的接收方ID，可以是好友QQ号或群号
+    pub peer_id: i64,
+    /// 消息的序列号，用于唯一标识一条消息
+    pub message_seq: i64,
+    /// 消息发送者的QQ号
+    pub sender_id: i64,
+    /// 消息发送的Unix时间戳（单位：秒）
+    pub time: i64,
+    /// 组成消息内容的实际数据段列表
+    #[serde(borrow)]
+    pub segments: Vec<IncomingSegmentRef<'a>>,
+    /// 消息场景的类型标识符
+    pub message_scene: MessageScene,
+}
+
+impl IncomingMessageRef<'_> {
+    /// 复制为拥有所有权的 [`IncomingMessage`]
+    pub fn into_owned(self) -> IncomingMessage {
+        IncomingMessage {
+            peer_id: self.peer_id,
+            message_seq: self.message_seq,
+            sender_id: self.sender_id,
+            time: self.time,
+            segments: self
+                .segments
+                .into_iter()
+                .map(IncomingSegmentRef::into_owned)
+                .collect(),
+            message_scene: self.message_scene,
+        }
+    }
+}
+
+/// 借用原始文本的消息段，对应 [`IncomingSegment`]
+///
+/// 与 [`IncomingSegment`] 相同，未定义的消息段类型会被解析为 [`IncomingSegmentRef::Unknown`]。
+#[derive(Deserialize, Debug, Clone)]
+#[serde(
+    remote = "Self",
+    rename_all = "snake_case",
+    tag = "type",
+    content = "data"
+)]
+pub enum IncomingSegmentRef<'a> {
+    /// 文本消息段
+    Text {
+        /// 实际的文本内容
+        #[serde(borrow)]
+        text: Cow<'a, str>,
+    },
+
+    /// 提及（@）某人的消息段
+    Mention {
+        /// 被提及用户的QQ号
+        user_id: i64,
+    },
+
+    /// 提及（@）全体成员的消息段
+    MentionAll {},
+
+    /// QQ表情消息段
+    Face {
+        /// QQ表情的内置ID
+        #[serde(borrow)]
+        face_id: Cow<'a, str>,
+    },
+
+    /// 回复消息段，用于引用之前的某条消息
+    Reply {
+        /// 被回复（引用）的消息的序列号
+        message_seq: i64,
+    },
+
+    /// 图片消息段
+    Image {
+        /// 图片的资源ID
+        #[serde(borrow)]
+        resource_id: Cow<'a, str>,
+        /// 临时URL
+        #[serde(borrow)]
+        temp_url: Cow<'a, str>,
+        /// 图片宽度
+        width: i32,
+        /// 图片高度
+        height: i32,
+        /// 图片的预览文本
+        #[serde(borrow)]
+        summary: Cow<'a, str>,
+        /// 图片的子类型
+        #[serde(borrow)]
+        sub_type: Cow<'a, str>,
+    },
+
+    /// 语音消息段
+    Record {
+        /// 语音的资源ID
+        #[serde(borrow)]
+        resource_id: Cow<'a, str>,
+        /// 临时URL
+        #[serde(borrow)]
+        temp_url: Cow<'a, str>,
+        /// 语音的时长（单位：秒）
+        duration: i32,
+    },
+
+    /// 视频消息段
+    Video {
+        /// 视频的资源ID
+        #[serde(borrow)]
+        resource_id: Cow<'a, str>,
+        /// 临时URL
+        #[serde(borrow)]
+        temp_url: Cow<'a, str>,
+        /// 视频宽度
+        width: i32,
+        /// 视频高度
+        height: i32,
+        /// 视频时长（单位：秒）
+        duration: i32,
+    },
+
+    /// 文件消息段
+    File {
+        /// 文件 ID
+        #[serde(borrow)]
+        file_id: Cow<'a, str>,
+        /// 文件名称
+        #[serde(borrow)]
+        file_name: Cow<'a, str>,
+        /// 文件大小（字节）
+        file_size: i64,
+        /// 文件的 TriSHA1 哈希值，仅在私聊文件中存在
+        #[serde(default)]
+        file_hash: Option<String>,
+    },
+
+    /// 合并转发消息段
+    Forward {
+        /// 合并转发消息的ID
+        #[serde(borrow)]
+        forward_id: Cow<'a, str>,
+    },
+
+    /// 商城表情（大表情）消息段
+    MarketFace {
+        /// 商城表情的图片URL
+        #[serde(borrow)]
+        url: Cow<'a, str>,
+    },
+
+    /// 轻应用（小程序、小游戏卡片等）消息段
+    LightApp {
+        /// 小程序的名称
+        #[serde(borrow)]
+        app_name: Cow<'a, str>,
+        /// 小程序的JSON数据负载
+        #[serde(borrow)]
+        json_payload: Cow<'a, str>,
+    },
+
+    /// XML 卡片消息段
+    #[serde(rename = "xml")]
+    XML {
+        /// XML消息的服务ID
+        service_id: i32,
+        /// XML数据的字符串负载
+        #[serde(borrow)]
+        xml_payload: Cow<'a, str>,
+    },
+
+    /// 位置分享消息段
+    Location {
+        /// 纬度
+        latitude: f64,
+        /// 经度
+        longitude: f64,
+        /// 位置的名称
+        #[serde(borrow)]
+        title: Cow<'a, str>,
+        /// 位置的详细地址
+        #[serde(borrow)]
+        address: Cow<'a, str>,
+    },
+
+    /// 推荐好友或群组的名片分享消息段
+    #[serde(rename = "contact")]
+    ContactShare {
+        /// 名片的类型，`friend` 为好友名片，`group` 为群名片
+        scene: MessageScene,
+        /// 好友的QQ号或群号
+        peer_id: i64,
+    },
+
+    /// 未知类型的消息段，保留原始的类型与数据
+    #[serde(skip)]
+    Unknown {
+        /// 消息段类型
+        r#type: &'a str,
+        /// 消息段的原始数据
+        data: &'a RawValue,
+    },
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for IncomingSegmentRef<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        tagged::deserialize(deserializer)
+    }
+}
+
+impl<'de: 'a, 'a> AdjacentlyTagged<'de> for IncomingSegmentRef<'a> {
+    const NAME: &'static str = "IncomingSegmentRef";
+    const TAG: &'static str = "type";
+    const CONTENT: &'static str = "data";
+
+    type Tag = &'a str;
+    type Content = &'a RawValue;
+
+    fn deserialize_known<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        IncomingSegmentRef::deserialize(deserializer)
+    }
+
+    fn deserialize_content(tag: &str, content: Option<&'a RawValue>) -> serde_json::Result<Self> {
+        tagged::deserialize_buffered(tag, content)
+    }
+
+    fn unknown(r#type: &'a str, data: Option<&'a RawValue>) -> Self {
+        IncomingSegmentRef::Unknown {
+            r#type,
+            data: data.unwrap_or(RawValue::NULL),
+        }
+    }
+}
+
+impl IncomingSegmentRef<'_> {
+    /// 复制为拥有所有权的 [`IncomingSegment`]
+    pub fn into_owned(self) -> IncomingSegment {
+        match self {
+            Self::Text { text } => IncomingSegment::Text { text: text.into() },
+            Self::Mention { user_id } => IncomingSegment::Mention { user_id },
+            Self::MentionAll {} => IncomingSegment::MentionAll {},
+            Self::Face { face_id } => IncomingSegment::Face {
+                face_id: face_id.into(),
+            },
+            Self::Reply { message_seq } => IncomingSegment::Reply { message_seq },
+            Self::Image {
+                resource_id,
+                temp_url,
+                width,
+                height,
+                summary,
+                sub_type,
+            } => IncomingSegment::Image {
+                resource_id: resource_id.into(),
+                temp_url: temp_url.into(),
+                width,
+                height,
+                summary: summary.into(),
+                sub_type: sub_type.into(),
+            },
+            Self::Record {
+                resource_id,
+                temp_url,
+                duration,
+            } => IncomingSegment::Record {
+                resource_id: resource_id.into(),
+                temp_url: temp_url.into(),
+                duration,
+            },
+            Self::Video {
+                resource_id,
+                temp_url,
+                width,
+                height,
+                duration,
+            } => IncomingSegment::Video {
+                resource_id: resource_id.into(),
+                temp_url: temp_url.into(),
+                width,
+                height,
+                duration,
+            },
+            Self::File {
+                file_id,
+                file_name,
+                file_size,
+                file_hash,
+            } => IncomingSegment::File {
+                file_id: file_id.into(),
+                file_name: file_name.into(),
+                file_size,
+                file_hash,
+            },
+            Self::Forward { forward_id } => IncomingSegment::Forward {
+                forward_id: forward_id.into(),
+            },
+            Self::MarketFace { url } => IncomingSegment::MarketFace { url: url.into() },
+            Self::LightApp {
+                app_name,
+                json_payload,
+            } => IncomingSegment::LightApp {
+                app_name: app_name.into(),
+                json_payload: json_payload.into(),
+            },
+            Self::XML {
+                service_id,
+                xml_payload,
+            } => IncomingSegment::XML {
+                service_id,
+                xml_payload: xml_payload.into(),
+            },
+            Self::Location {
+                latitude,
+                longitude,
+                title,
+                address,
+            } => IncomingSegment::Location {
+                latitude,
+                longitude,
+                title: title.into(),
+                address: address.into(),
+            },
+            Self::ContactShare { scene, peer_id } => {
+                IncomingSegment::ContactShare { scene, peer_id }
+            }
+            Self::Unknown { r#type, data } => IncomingSegment::Unknown {
+                r#type: r#type.to_string(),
+                // RawValue 中总是合法的 JSON，只有嵌套层数超出限制时才会解析失败
+                data: serde_json::from_str(data.get()).unwrap_or_default(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::EventKind;
+
+    const GROUP_MESSAGE: &str = r#"{
+        "time": 1630483200,
+        "self_id": 1234567890,
+        "event_type": "message_receive",
+        "data": {
+            "peer_id": 123456,
+            "message_seq": 12345,
+            "sender_id": 987654321,
+            "time": 1630483200,
+            "segments": [
+                {"type": "text", "data": {"text": "你好"}},
+                {"data": {"text": "第二行\n"}, "type": "text"},
+                {"type": "mention", "data": {"user_id": 1}},
+                {"type": "dice", "data": {"value": 6}}
+            ],
+            "message_scene": "group",
+            "group": {
+                "group_id": 123456,
+                "group_name": "测试群",
+                "member_count": 100,
+                "max_member_count": 500
+            },
+            "group_member": {
+                "user_id": 987654321,
+                "nickname": "测试成员",
+                "sex": "female",
+                "group_id": 123456,
+                "card": "",
+                "title": "",
+                "level": 1,
+                "role": "member",
+                "join_time": 1630000000,
+                "last_sent_time": 1630483200
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_borrowed_segments_match_owned() {
+        let event = EventRef::from_str(GROUP_MESSAGE).unwrap();
+        let message = event.message().unwrap().unwrap();
+        // 不含转义字符的字符串直接借用原始文本，含转义字符时退回为复制
+        assert!(matches!(
+            &message.segments[0],
+            IncomingSegmentRef::Text {
+                text: Cow::Borrowed("你好")
+            }
+        ));
+        assert!(matches!(
+            &message.segments[1],
+            IncomingSegmentRef::Text { text: Cow::Owned(text) } if text == "第二行\n"
+        ));
+
+        assert!(matches!(
+            &message.segments[3],
+            IncomingSegmentRef::Unknown { r#type: "dice", data } if data.get() == r#"{"value": 6}"#
+        ));
+
+        let owned = event.to_event().unwrap();
+        let EventKind::MessageReceive { message: expected } = &owned.kind else {
+            panic!("反序列化结果应该是消息事件");
+        };
+        assert_eq!(&message.into_owned(), expected.base_message());
+        assert_eq!(owned, serde_json::from_str::<Event>(GROUP_MESSAGE).unwrap());
+    }
+
+    #[test]
+    fn test_borrowed_xml_segment() {
+        let segment: IncomingSegmentRef = serde_json::from_str(
+            r#"{"type": "xml", "data": {"service_id": 35, "xml_payload": "<msg/>"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            segment,
+            IncomingSegmentRef::XML {
+                service_id: 35,
+                xml_payload: Cow::Borrowed("<msg/>")
+            }
+        ));
+    }
+}
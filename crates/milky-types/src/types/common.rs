@@ -1,9 +1,16 @@
 //! 定义了与API通信时通用的请求和响应数据结构
 
-use serde::{Deserialize, Serialize};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use url::Url;
 
 /// 通用的API响应结构体
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ApiResponse<T> {
     /// 响应状态通常为 "ok" 表示成功，或 "failed" 表示失败。
     pub status: String,
@@ -17,6 +24,7 @@ pub struct ApiResponse<T> {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Platform {
     Windows,
@@ -31,6 +39,7 @@ pub enum Platform {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Sex {
     Male,
@@ -40,6 +49,7 @@ pub enum Sex {
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum MessageScene {
     /// 好友消息场景
@@ -53,6 +63,7 @@ pub enum MessageScene {
 
 /// 请求状态
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum RequestState {
     /// 等待处理
@@ -65,3 +76,211 @@ pub enum RequestState {
     /// 已忽略
     Ignored,
 }
+
+/// 文件的统一资源标识符，用于图片、语音、视频消息段以及文件上传等接口
+///
+/// 序列化时会自动转换为协议要求的 URI 字符串：
+/// - [`FileUri::Path`] 转换为 `file:///path/to/file`，相对路径会基于当前工作目录转换为绝对路径，特殊字符会自动进行百分号编码
+/// - [`FileUri::Url`] 原样输出 `http(s)://` 链接
+/// - [`FileUri::Base64`] 对数据进行 Base64 编码后输出 `base64://<BASE64编码的数据>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileUri {
+    /// 本地文件路径
+    Path(PathBuf),
+    /// 网络链接，仅支持 `http` 与 `https`
+    Url(Url),
+    /// 文件的原始内容
+    Base64(Vec<u8>),
+}
+
+/// 解析或转换 [`FileUri`] 失败时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileUriError {
+    /// URI 使用了不支持的协议
+    UnsupportedScheme(String),
+    /// URI 格式不正确
+    Invalid(String),
+    /// `base64://` 中的数据无法解码
+    InvalidBase64(String),
+}
+
+impl fmt::Display for FileUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedScheme(scheme) => write!(f, "不支持的 URI 协议: {scheme}"),
+            Self::Invalid(reason) => write!(f, "URI 格式不正确: {reason}"),
+            Self::InvalidBase64(reason) => write!(f, "Base64 数据无法解码: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for FileUriError {}
+
+impl FileUri {
+    /// 解析 `file://`、`http(s)://` 或 `base64://` 格式的 URI 字符串
+    ///
+    /// # 参数
+    /// * `uri`: URI 字符串
+    ///
+    /// # 返回
+    /// 成功则返回对应的 [`FileUri`]；协议不受支持或格式不正确时返回 [`FileUriError`]
+    pub fn parse(uri: &str) -> Result<Self, FileUriError> {
+        if let Some(data) = uri.strip_prefix("base64://") {
+            return BASE64_STANDARD
+                .decode(data)
+                .map(Self::Base64)
+                .map_err(|e| FileUriError::InvalidBase64(e.to_string()));
+        }
+        let url = Url::parse(uri).map_err(|e| FileUriError::Invalid(e.to_string()))?;
+        match url.scheme() {
+            "http" | "https" => Ok(Self::Url(url)),
+            "file" => url_to_path(&url)
+                .map(Self::Path)
+                .ok_or_else(|| FileUriError::Invalid(format!("无效的本地文件路径: {uri}"))),
+            scheme => Err(FileUriError::UnsupportedScheme(scheme.to_string())),
+        }
+    }
+
+    /// 转换为协议要求的 URI 字符串
+    ///
+    /// # 返回
+    /// 成功则返回 URI 字符串；本地路径无法转换为绝对路径时返回 [`FileUriError`]
+    pub fn to_uri_string(&self) -> Result<String, FileUriError> {
+        match self {
+            Self::Path(path) => path_to_url(path).map(String::from).ok_or_else(|| {
+                FileUriError::Invalid(format!("无效的本地文件路径: {}", path.display()))
+            }),
+            Self::Url(url) => Ok(url.to_string()),
+            Self::Base64(data) => Ok(format!("base64://{}", BASE64_STANDARD.encode(data))),
+        }
+    }
+}
+
+/// 将 `file://` URL 转换为本地路径
+#[cfg(any(unix, windows, target_os = "wasi"))]
+fn url_to_path(url: &Url) -> Option<PathBuf> {
+    url.to_file_path().ok()
+}
+
+/// 将 `file://` URL 转换为协议端上的路径
+///
+/// `wasm32-unknown-unknown` 等没有本地文件系统的平台上，路径只会原样传给协议端，按 Unix 风格的绝对路径处理。
+#[cfg(not(any(unix, windows, target_os = "wasi")))]
+fn url_to_path(url: &Url) -> Option<PathBuf> {
+    let path = percent_encoding::percent_decode_str(url.path())
+        .decode_utf8()
+        .ok()?;
+    Some(PathBuf::from(path.into_owned()))
+}
+
+/// 将本地路径转换为 `file://` URL，相对路径基于当前工作目录
+#[cfg(any(unix, windows, target_os = "wasi"))]
+fn path_to_url(path: &Path) -> Option<Url> {
+    std::path::absolute(path)
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+}
+
+/// 将协议端上的绝对路径转换为 `file://` URL，没有本地文件系统的平台上不支持相对路径
+#[cfg(not(any(unix, windows, target_os = "wasi")))]
+fn path_to_url(path: &Path) -> Option<Url> {
+    let path = path.to_str().filter(|path| path.starts_with('/'))?;
+    let mut url = Url::parse("file:///").ok()?;
+    url.set_path(path);
+    Some(url)
+}
+
+impl FromStr for FileUri {
+    type Err = FileUriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<PathBuf> for FileUri {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for FileUri {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for FileUri {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Base64(data)
+    }
+}
+
+impl TryFrom<Url> for FileUri {
+    type Error = FileUriError;
+
+    fn try_from(url: Url) -> Result<Self, Self::Error> {
+        Self::parse(url.as_str())
+    }
+}
+
+impl Serialize for FileUri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let uri = self.to_uri_string().map_err(ser::Error::custom)?;
+        serializer.serialize_str(&uri)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileUri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uri = String::deserialize(deserializer)?;
+        Self::parse(&uri).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for FileUri {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "FileUri".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "`file://`、`http(s)://` 或 `base64://` 格式的文件 URI",
+            "type": "string",
+            "pattern": "^(file|https?|base64)://",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri_round_trip() {
+        let uri = FileUri::Path(PathBuf::from("/tmp/图片 1.png"));
+        let encoded = uri.to_uri_string().unwrap();
+        assert_eq!(encoded, "file:///tmp/%E5%9B%BE%E7%89%87%201.png");
+        assert_eq!(FileUri::parse(&encoded).unwrap(), uri);
+
+        let uri = FileUri::Base64(b"milky".to_vec());
+        assert_eq!(
+            serde_json::to_string(&uri).unwrap(),
+            "\"base64://bWlsa3k=\""
+        );
+        assert_eq!(
+            serde_json::from_str::<FileUri>("\"base64://bWlsa3k=\"").unwrap(),
+            uri
+        );
+
+        let uri = FileUri::parse("https://example.com/a.png").unwrap();
+        assert_eq!(uri.to_uri_string().unwrap(), "https://example.com/a.png");
+
+        assert_eq!(
+            FileUri::parse("ftp://example.com/a.png"),
+            Err(FileUriError::UnsupportedScheme("ftp".to_string()))
+        );
+        assert!(FileUri::parse("/tmp/a.png").is_err());
+    }
+}
@@ -3,6 +3,8 @@
 use crate::types::{
     common::MessageScene,
     message::in_coming::{FriendMessage, GroupMessage, IncomingMessage, TempMessage},
+    message::reaction::Reaction,
+    tagged::{self, AdjacentlyTagged},
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -11,6 +13,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 /// 每个事件都有一个时间戳、接收该事件的机器人实例的ID，
 /// 以及一个详细说明事件性质的特定 [`EventKind`]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Event {
     /// 事件发生的Unix时间戳（秒）
     pub time: i64,
@@ -22,8 +25,16 @@ pub struct Event {
 }
 
 /// 枚举可以接收到的不同类型的事件
+///
+/// 协议端新增、尚未在此定义的事件类型会被解析为 [`EventKind::Unknown`]，而不会导致整个事件解析失败。
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "snake_case", tag = "event_type", content = "data")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(
+    remote = "Self",
+    rename_all = "snake_case",
+    tag = "event_type",
+    content = "data"
+)]
 pub enum EventKind {
     /// 机器人离线事件
     BotOffline {
@@ -198,8 +209,8 @@ pub enum EventKind {
         user_id: i64,
         /// 被表态的消息的序列号
         message_seq: i64,
-        /// 表态表情/face的ID
-        face_id: String,
+        /// 表态的表情回应
+        face_id: Reaction,
         /// 是否为添加，`false` 表示取消回应
         is_add: bool,
     },
@@ -255,6 +266,100 @@ pub enum EventKind {
         /// 上传文件的大小（字节）
         file_size: i64,
     },
+
+    /// 未知类型的事件，保留原始的事件类型与数据
+    #[serde(skip)]
+    Unknown {
+        /// 事件类型
+        event_type: String,
+        /// 事件的原始数据
+        data: serde_json::Value,
+    },
+}
+
+impl Serialize for EventKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        match self {
+            EventKind::Unknown { event_type, data } => {
+                let mut state = serializer.serialize_struct("EventKind", 2)?;
+                state.serialize_field("event_type", event_type)?;
+                state.serialize_field("data", data)?;
+                state.end()
+            }
+            _ => EventKind::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EventKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        tagged::deserialize(deserializer)
+    }
+}
+
+impl<'de> AdjacentlyTagged<'de> for EventKind {
+    const NAME: &'static str = "EventKind";
+    const TAG: &'static str = "event_type";
+    const CONTENT: &'static str = "data";
+
+    type Tag = String;
+    type Content = serde_json::Value;
+
+    fn deserialize_known<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        EventKind::deserialize(deserializer)
+    }
+
+    fn deserialize_content(
+        tag: &str,
+        content: Option<serde_json::Value>,
+    ) -> serde_json::Result<Self> {
+        tagged::deserialize_buffered(tag, content)
+    }
+
+    fn unknown(event_type: String, data: Option<serde_json::Value>) -> Self {
+        EventKind::Unknown {
+            event_type,
+            data: data.unwrap_or_default(),
+        }
+    }
+}
+
+impl EventKind {
+    /// 获取事件类型，与序列化时的 `event_type` 字段相同，例如 `message_receive`
+    pub fn event_type(&self) -> &str {
+        match self {
+            EventKind::BotOffline { .. } => "bot_offline",
+            EventKind::MessageReceive { .. } => "message_receive",
+            EventKind::MessageRecall { .. } => "message_recall",
+            EventKind::FriendRequest { .. } => "friend_request",
+            EventKind::GroupJoinRequest { .. } => "group_join_request",
+            EventKind::GroupInvitedJoinRequest { .. } => "group_invited_join_request",
+            EventKind::GroupInvitation { .. } => "group_invitation",
+            EventKind::FriendNudge { .. } => "friend_nudge",
+            EventKind::FriendFileUpload { .. } => "friend_file_upload",
+            EventKind::GroupAdminChange { .. } => "group_admin_change",
+            EventKind::GroupEssenceMessageChange { .. } => "group_essence_message_change",
+            EventKind::GroupMemberIncrease { .. } => "group_member_increase",
+            EventKind::GroupMemberDecrease { .. } => "group_member_decrease",
+            EventKind::GroupNameChange { .. } => "group_name_change",
+            EventKind::GroupMessageReaction { .. } => "group_message_reaction",
+            EventKind::GroupMute { .. } => "group_mute",
+            EventKind::GroupWholeMute { .. } => "group_whole_mute",
+            EventKind::GroupNudge { .. } => "group_nudge",
+            EventKind::GroupFileUpload { .. } => "group_file_upload",
+            EventKind::Unknown { event_type, .. } => event_type,
+        }
+    }
 }
 
 /// 消息事件的包装类型，根据 message_scene 字段自动反序列化为具体的消息类型
@@ -262,6 +367,7 @@ pub enum EventKind {
 /// 使用自定义序列化/反序列化逻辑根据 message_scene 字段选择具体的消息结构，
 /// 保留额外的元信息
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema), schemars(untagged))]
 pub enum MessageEvent {
     /// 好友消息，包含好友的详细信息
     Friend(FriendMessage),
@@ -347,6 +453,54 @@ mod tests {
         group::{Group, GroupMember, GroupRole},
     };
 
+    #[test]
+    fn test_event_type_matches_serialized_tag() {
+        let kind = EventKind::GroupMute {
+            group_id: 123456,
+            user_id: 987654321,
+            operator_id: 10000,
+            duration: 60,
+        };
+        let json = serde_json::to_value(&kind).unwrap();
+        assert_eq!(json["event_type"], kind.event_type());
+    }
+
+    #[test]
+    fn test_unknown_event_roundtrip() {
+        let json =
+            r#"{"time":1,"self_id":10000,"event_type":"group_todo","data":{"group_id":123}}"#;
+        let event: Event = serde_json::from_str(json).unwrap();
+        assert_eq!(event.kind.event_type(), "group_todo");
+        assert!(matches!(
+            &event.kind,
+            EventKind::Unknown { data, .. } if data["group_id"] == 123
+        ));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+
+        let offline: Event = serde_json::from_str(
+            r#"{"time":1,"self_id":10000,"event_type":"bot_offline","data":{"reason":"test"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(offline.kind, EventKind::BotOffline { .. }));
+
+        // 数据先于事件类型出现时同样可以解析
+        let reordered: Event = serde_json::from_str(
+            r#"{"data":{"reason":"test"},"time":1,"event_type":"bot_offline","self_id":10000}"#,
+        )
+        .unwrap();
+        assert_eq!(reordered, offline);
+        // 已定义的事件类型的数据不符合定义时解析失败，而不是当作未知事件
+        assert!(
+            serde_json::from_str::<Event>(
+                r#"{"time":1,"self_id":10000,"event_type":"bot_offline","data":{}}"#
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_serialize_and_deserialize_friend_message() {
         let event = Event {
@@ -6,6 +6,7 @@ use crate::{common::RequestState, types::common::Sex};
 
 /// 代表一个好友的基本信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Friend {
     /// 好友的QQ号
     pub user_id: i64,
@@ -24,6 +25,7 @@ pub struct Friend {
 
 /// 代表一个好友分组的信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FriendCategory {
     /// 好友分组的唯一ID
     pub category_id: i32,
@@ -33,6 +35,7 @@ pub struct FriendCategory {
 
 /// 好友请求实体
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FriendRequest {
     /// 请求发起时的 Unix 时间戳（秒）
     pub time: i64,
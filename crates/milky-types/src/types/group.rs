@@ -8,6 +8,7 @@ use crate::{
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum GroupRole {
     Owner,
@@ -18,6 +19,7 @@ pub enum GroupRole {
 
 /// 代表一个群组的基本信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Group {
     /// 群号
     pub group_id: i64,
@@ -31,6 +33,7 @@ pub struct Group {
 
 /// 代表一个群组成员的详细信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupMember {
     /// 用户QQ号
     pub user_id: i64,
@@ -61,6 +64,7 @@ pub struct GroupMember {
 
 /// 群精华消息
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupEssenceMessage {
     /// 群号
     pub group_id: i64,
@@ -84,6 +88,7 @@ pub struct GroupEssenceMessage {
 
 /// 代表一条群公告的信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupAnnouncement {
     /// 群号
     pub group_id: i64,
@@ -102,6 +107,7 @@ pub struct GroupAnnouncement {
 
 /// 代表群文件系统中的一个文件
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupFile {
     /// 该文件所属群组的唯一标识符（群号）
     pub group_id: i64,
@@ -125,6 +131,7 @@ pub struct GroupFile {
 
 /// 代表群文件系统中的一个文件夹
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupFolder {
     /// 该文件夹所属群组的唯一标识符（群号）
     pub group_id: i64,
@@ -145,6 +152,7 @@ pub struct GroupFolder {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupNotification {
     /// 群号
     pub group_id: i64,
@@ -158,6 +166,7 @@ pub struct GroupNotification {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case", content = "")]
 pub enum GroupNotificationKind {
     JoinRequest {
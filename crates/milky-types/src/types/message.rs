@@ -1,2 +1,4 @@
+pub mod face;
 pub mod in_coming;
 pub mod out_going;
+pub mod reaction;
@@ -0,0 +1,244 @@
+//! 常见 QQ 表情的 ID 与名称
+//!
+//! [`Face`] 的各个常量对应 QQ 内置的系统表情，既可以用于构造待发送的表情消息段，
+//! 也可以在展示接收到的消息时将 `face_id` 转换为可读的名称。
+//!
+//! ```
+//! use milky_types::message::face::Face;
+//! use milky_types::message::out_going::OutgoingSegment;
+//!
+//! let segment = OutgoingSegment::face(Face::DOGE);
+//! assert_eq!(Face::from_id("179"), Some(Face::DOGE));
+//! assert_eq!(Face::DOGE.to_string(), "[doge]");
+//! ```
+
+use std::fmt;
+
+use crate::types::message::out_going::FaceData;
+
+/// QQ 内置的系统表情
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Face {
+    id: &'static str,
+    name: &'static str,
+}
+
+/// 根据 ID 与名称的列表生成 [`Face`] 的常量与 [`Face::ALL`]
+macro_rules! faces {
+    ($($ident:ident = $id:literal, $name:literal;)*) => {
+        impl Face {
+            $(
+                #[doc = concat!("表情「", $name, "」，ID 为 ", $id)]
+                pub const $ident: Face = Face { id: $id, name: $name };
+            )*
+
+            /// 所有已收录的表情，按 ID 排列
+            pub const ALL: &'static [Face] = &[$(Face::$ident),*];
+        }
+    };
+}
+
+faces! {
+    SURPRISED = "0", "惊讶";
+    POUT = "1", "撇嘴";
+    DROOL = "2", "色";
+    DAZE = "3", "发呆";
+    COOL_GUY = "4", "得意";
+    TEARS = "5", "流泪";
+    SHY = "6", "害羞";
+    SILENT = "7", "闭嘴";
+    SLEEP = "8", "睡";
+    CRY = "9", "大哭";
+    AWKWARD = "10", "尴尬";
+    ANGRY = "11", "发怒";
+    NAUGHTY = "12", "调皮";
+    GRIN = "13", "呲牙";
+    SMILE = "14", "微笑";
+    SAD = "15", "难过";
+    COOL = "16", "酷";
+    CRAZY = "18", "抓狂";
+    VOMIT = "19", "吐";
+    CHUCKLE = "20", "偷笑";
+    CUTE = "21", "可爱";
+    EYE_ROLL = "22", "白眼";
+    PROUD = "23", "傲慢";
+    HUNGRY = "24", "饥饿";
+    SLEEPY = "25", "困";
+    SCARED = "26", "惊恐";
+    SWEAT = "27", "流汗";
+    HAN_HAN = "28", "憨笑";
+    RELAXED = "29", "悠闲";
+    STRIVE = "30", "奋斗";
+    CURSE = "31", "咒骂";
+    QUESTION = "32", "疑问";
+    SHUSH = "33", "嘘";
+    DIZZY = "34", "晕";
+    TORMENTED = "35", "折磨";
+    TOASTED = "36", "衰";
+    SKULL = "37", "骷髅";
+    HAMMER = "38", "敲打";
+    BYE = "39", "再见";
+    SHIVER = "41", "发抖";
+    LOVE = "42", "爱情";
+    JUMP = "43", "跳跳";
+    PIG = "46", "猪头";
+    HUG = "49", "拥抱";
+    CAKE = "53", "蛋糕";
+    KNIFE = "56", "刀";
+    POOP = "59", "便便";
+    COFFEE = "60", "咖啡";
+    ROSE = "63", "玫瑰";
+    WILT = "64", "凋谢";
+    HEART = "66", "爱心";
+    BROKEN_HEART = "67", "心碎";
+    SUN = "74", "太阳";
+    MOON = "75", "月亮";
+    THUMBS_UP = "76", "赞";
+    THUMBS_DOWN = "77", "踩";
+    SHAKE_HANDS = "78", "握手";
+    VICTORY = "79", "胜利";
+    BLOW_KISS = "85", "飞吻";
+    WATERMELON = "89", "西瓜";
+    COLD_SWEAT = "96", "冷汗";
+    WIPE_SWEAT = "97", "擦汗";
+    DIG_NOSE = "98", "抠鼻";
+    APPLAUSE = "99", "鼓掌";
+    EMBARRASSED = "100", "糗大了";
+    SMIRK = "101", "坏笑";
+    HUMPH_LEFT = "102", "左哼哼";
+    HUMPH_RIGHT = "103", "右哼哼";
+    YAWN = "104", "哈欠";
+    DESPISE = "105", "鄙视";
+    WRONGED = "106", "委屈";
+    ABOUT_TO_CRY = "107", "快哭了";
+    SLY = "108", "阴险";
+    KISS = "109", "左亲亲";
+    FRIGHTENED = "110", "吓";
+    PITIFUL = "111", "可怜";
+    CLEAVER = "112", "菜刀";
+    BASKETBALL = "114", "篮球";
+    SHOW_LOVE = "116", "示爱";
+    SALUTE = "118", "抱拳";
+    BECKON = "119", "勾引";
+    FIST = "120", "拳头";
+    BAD = "121", "差劲";
+    LOVE_YOU = "122", "爱你";
+    NO = "123", "NO";
+    OK = "124", "OK";
+    CIRCLE = "125", "转圈";
+    WAVE = "129", "挥手";
+    CHEER = "144", "喝彩";
+    LOLLIPOP = "147", "棒棒糖";
+    TEA = "171", "茶";
+    BURST_INTO_TEARS = "173", "泪奔";
+    HELPLESS = "174", "无奈";
+    ACT_CUTE = "175", "卖萌";
+    TANGLED = "176", "小纠结";
+    SPIT_BLOOD = "177", "喷血";
+    SIDE_EYE_SMILE = "178", "斜眼笑";
+    DOGE = "179", "doge";
+    PLEASANT_SURPRISE = "180", "惊喜";
+    HARASS = "181", "骚扰";
+    LAUGH_CRY = "182", "笑哭";
+    MOST_BEAUTIFUL = "183", "我最美";
+    LIKE = "201", "点赞";
+    CHIN_ON_HAND = "212", "托腮";
+    BOBO = "214", "啵啵";
+    NUZZLE = "219", "蹭一蹭";
+    EMBRACE = "222", "抱抱";
+    CLAP = "227", "拍手";
+    ZEN = "232", "佛系";
+    SPRAY_FACE = "240", "喷脸";
+    HAIR_FLIP = "243", "甩头";
+    CHEER_HUG = "246", "加油抱抱";
+    HEADACHE = "262", "脑阔疼";
+    FACEPALM = "264", "捂脸";
+    EYE_BURN = "265", "辣眼睛";
+    OH_YO = "266", "哦哟";
+    BALD = "267", "头秃";
+    QUESTION_FACE = "268", "问号脸";
+    PEEK = "269", "暗中观察";
+    EMM = "270", "emm";
+    EAT_MELON = "271", "吃瓜";
+    HEHE = "272", "呵呵哒";
+    SOUR = "273", "我酸了";
+    WOOF = "277", "汪汪";
+    SALUTE_HAND = "282", "敬礼";
+    EXPRESSIONLESS = "284", "面无表情";
+    SLACK_OFF = "285", "摸鱼";
+    OH = "287", "哦";
+    RUB_KOI = "293", "摸锦鲤";
+    LOOK_FORWARD = "294", "期待";
+    THANKS = "297", "拜谢";
+    AWESOME = "299", "牛啊";
+    MEOW = "307", "喵喵";
+    ANALYZE = "314", "仔细分析";
+    FIGHTING = "315", "加油";
+    ADMIRE = "318", "崇拜";
+    FINGER_HEART = "319", "比心";
+    CELEBRATE = "320", "庆祝";
+    REFUSE = "322", "拒绝";
+    EAT_CANDY = "324", "吃糖";
+    ANGRY_FACE = "326", "生气";
+}
+
+impl Face {
+    /// 表情的内置ID
+    pub const fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// 表情的名称
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 根据表情ID查找已收录的表情
+    ///
+    /// # 参数
+    /// * `face_id`: 表情的内置ID，即 `FaceData::face_id` 或 `IncomingSegment::Face` 中的 `face_id`
+    ///
+    /// # 返回
+    /// 未收录该ID时返回 `None`
+    pub fn from_id(face_id: &str) -> Option<Face> {
+        Self::ALL.iter().find(|face| face.id == face_id).copied()
+    }
+
+    /// 根据表情ID获取表情的名称
+    ///
+    /// # 返回
+    /// 未收录该ID时返回 `None`
+    pub fn name_of(face_id: &str) -> Option<&'static str> {
+        Self::from_id(face_id).map(|face| face.name)
+    }
+}
+
+/// 以 `[名称]` 的形式展示表情，与 QQ 客户端中复制表情得到的文本一致
+impl fmt::Display for Face {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.name)
+    }
+}
+
+impl From<Face> for FaceData {
+    fn from(face: Face) -> Self {
+        FaceData {
+            face_id: face.id.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_table() {
+        assert!(Face::ALL.windows(2).all(|pair| {
+            pair[0].id.parse::<u32>().unwrap() < pair[1].id.parse::<u32>().unwrap()
+        }));
+        assert_eq!(Face::name_of("14"), Some("微笑"));
+        assert_eq!(Face::from_id("99999"), None);
+        assert_eq!(FaceData::from(Face::THUMBS_UP).face_id, "76");
+    }
+}
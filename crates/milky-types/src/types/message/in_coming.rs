@@ -1,11 +1,12 @@
 //! 定义了接收到的各类消息（如私聊、群聊、临时会话消息）及其组成部分（消息段）的数据结构
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::types::{
     common::MessageScene,
     friend::Friend,
     group::{Group, GroupMember},
+    tagged::{self, AdjacentlyTagged},
 };
 
 /// 代表一个通用的接收消息结构
@@ -13,6 +14,7 @@ use crate::types::{
 /// 这是许多具体消息类型（如 [`FriendMessage`], [`GroupMessage`]）的基础，
 /// 包含了消息的共同属性
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct IncomingMessage {
     /// 消息的接收方ID，可以是好友QQ号或群号
     pub peer_id: i64,
@@ -28,10 +30,26 @@ pub struct IncomingMessage {
     pub message_scene: MessageScene,
 }
 
+impl IncomingMessage {
+    /// 判断消息是否提及（@）了指定用户
+    ///
+    /// # 参数
+    /// * `user_id`: 用户的QQ号，判断是否提及机器人时传入机器人自身的QQ号
+    ///
+    /// # 返回
+    /// 消息中包含提及该用户的消息段时返回 `true`，仅提及全体成员时返回 `false`
+    pub fn mentions_user(&self, user_id: i64) -> bool {
+        self.segments.iter().any(
+            |segment| matches!(segment, IncomingSegment::Mention { user_id: id } if *id == user_id),
+        )
+    }
+}
+
 /// 代表接收到的好友消息
 ///
 /// 继承自 [`IncomingMessage`] 并额外包含了好友的详细信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FriendMessage {
     #[serde(flatten)]
     pub message: IncomingMessage,
@@ -43,6 +61,7 @@ pub struct FriendMessage {
 ///
 /// 继承自 [`IncomingMessage`] 并额外包含了群及发送成员的详细信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GroupMessage {
     #[serde(flatten)]
     pub message: IncomingMessage,
@@ -56,6 +75,7 @@ pub struct GroupMessage {
 ///
 /// 继承自 [`IncomingMessage`] 并可能包含临时会话来源群组的信息
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TempMessage {
     #[serde(flatten)]
     pub message: IncomingMessage,
@@ -66,6 +86,7 @@ pub struct TempMessage {
 
 /// 代表接收到的合并转发消息中的单条消息内容
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct IncomingForwardMessage {
     /// 发送者名称
     pub sender_name: String,
@@ -78,8 +99,16 @@ pub struct IncomingForwardMessage {
 }
 
 /// 枚举构成接收消息内容的各种可能的消息段类型
+///
+/// 协议端新增、尚未在此定义的消息段类型会被解析为 [`IncomingSegment::Unknown`]，而不会导致整条消息解析失败。
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(
+    remote = "Self",
+    rename_all = "snake_case",
+    tag = "type",
+    content = "data"
+)]
 pub enum IncomingSegment {
     /// 文本消息段
     Text {
@@ -182,12 +211,100 @@ pub enum IncomingSegment {
     },
 
     /// XML 卡片消息段
+    #[serde(rename = "xml")]
     XML {
         /// XML消息的服务ID
         service_id: i32,
         /// XML数据的字符串负载
         xml_payload: String,
     },
+
+    /// 位置分享消息段
+    Location {
+        /// 纬度
+        latitude: f64,
+        /// 经度
+        longitude: f64,
+        /// 位置的名称
+        title: String,
+        /// 位置的详细地址
+        address: String,
+    },
+
+    /// 推荐好友或群组的名片分享消息段
+    #[serde(rename = "contact")]
+    ContactShare {
+        /// 名片的类型，`friend` 为好友名片，`group` 为群名片
+        scene: MessageScene,
+        /// 好友的QQ号或群号
+        peer_id: i64,
+    },
+
+    /// 未知类型的消息段，保留原始的类型与数据
+    #[serde(skip)]
+    Unknown {
+        /// 消息段类型
+        r#type: String,
+        /// 消息段的原始数据
+        data: serde_json::Value,
+    },
+}
+
+impl Serialize for IncomingSegment {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        match self {
+            IncomingSegment::Unknown { r#type, data } => {
+                let mut state = serializer.serialize_struct("IncomingSegment", 2)?;
+                state.serialize_field("type", r#type)?;
+                state.serialize_field("data", data)?;
+                state.end()
+            }
+            _ => IncomingSegment::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IncomingSegment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        tagged::deserialize(deserializer)
+    }
+}
+
+impl<'de> AdjacentlyTagged<'de> for IncomingSegment {
+    const NAME: &'static str = "IncomingSegment";
+    const TAG: &'static str = "type";
+    const CONTENT: &'static str = "data";
+
+    type Tag = String;
+    type Content = serde_json::Value;
+
+    fn deserialize_known<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        IncomingSegment::deserialize(deserializer)
+    }
+
+    fn deserialize_content(
+        tag: &str,
+        content: Option<serde_json::Value>,
+    ) -> serde_json::Result<Self> {
+        tagged::deserialize_buffered(tag, content)
+    }
+
+    fn unknown(r#type: String, data: Option<serde_json::Value>) -> Self {
+        IncomingSegment::Unknown {
+            r#type,
+            data: data.unwrap_or_default(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -223,4 +340,103 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn test_unknown_segment_keeps_message() {
+        let segments: Vec<IncomingSegment> = serde_json::from_str(
+            r#"[
+                {"type": "text", "data": {"text": "你好"}},
+                {"type": "dice", "data": {"value": 6}},
+                {"type": "xml", "data": {"service_id": 1, "xml_payload": "<msg/>"}},
+                {"data": {"user_id": 1}, "type": "mention"},
+                {"data": {"value": 1}, "type": "rps"}
+            ]"#,
+        )
+        .unwrap();
+        assert!(matches!(&segments[0], IncomingSegment::Text { text } if text == "你好"));
+        assert!(matches!(
+            &segments[1],
+            IncomingSegment::Unknown { r#type, data } if r#type == "dice" && data["value"] == 6
+        ));
+        assert!(matches!(
+            &segments[2],
+            IncomingSegment::XML { service_id: 1, .. }
+        ));
+        // 内容先于类型出现时同样可以解析
+        assert_eq!(segments[3], IncomingSegment::Mention { user_id: 1 });
+        assert!(matches!(&segments[4], IncomingSegment::Unknown { r#type, .. } if r#type == "rps"));
+        assert_eq!(
+            serde_json::to_value(&segments[1]).unwrap(),
+            serde_json::json!({"type": "dice", "data": {"value": 6}})
+        );
+        // 已定义类型的内容不符合定义时解析失败，而不是当作未知类型
+        assert!(
+            serde_json::from_str::<IncomingSegment>(r#"{"type": "mention", "data": {}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_xml_segment() {
+        let raw =
+            serde_json::json!({"type": "xml", "data": {"service_id": 35, "xml_payload": "<msg/>"}});
+        let segment: IncomingSegment = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(
+            segment,
+            IncomingSegment::XML {
+                service_id: 35,
+                xml_payload: "<msg/>".to_string()
+            }
+        );
+        assert_eq!(serde_json::to_value(&segment).unwrap(), raw);
+
+        // 旧的 `x_m_l` 不是协议定义的类型，只能作为未知消息段保留
+        let legacy: IncomingSegment = serde_json::from_value(serde_json::json!(
+            {"type": "x_m_l", "data": {"service_id": 35, "xml_payload": "<msg/>"}}
+        ))
+        .unwrap();
+        assert!(matches!(legacy, IncomingSegment::Unknown { r#type, .. } if r#type == "x_m_l"));
+    }
+
+    #[test]
+    fn test_share_segments() {
+        let raw = serde_json::json!({"type": "location", "data": {
+            "latitude": 39.9087,
+            "longitude": 116.3975,
+            "title": "天安门",
+            "address": "北京市东城区",
+        }});
+        let segment: IncomingSegment = serde_json::from_value(raw.clone()).unwrap();
+        assert!(matches!(&segment, IncomingSegment::Location { title, .. } if title == "天安门"));
+        assert_eq!(serde_json::to_value(&segment).unwrap(), raw);
+
+        let contact: IncomingSegment = serde_json::from_value(serde_json::json!(
+            {"type": "contact", "data": {"scene": "friend", "peer_id": 10000}}
+        ))
+        .unwrap();
+        assert_eq!(
+            contact,
+            IncomingSegment::ContactShare {
+                scene: MessageScene::Friend,
+                peer_id: 10000
+            }
+        );
+    }
+
+    #[test]
+    fn test_mentions() {
+        let message = IncomingMessage {
+            segments: vec![
+                IncomingSegment::Mention { user_id: 10000 },
+                IncomingSegment::Text {
+                    text: " 签到".to_string(),
+                },
+                IncomingSegment::MentionAll {},
+                IncomingSegment::Mention { user_id: 20000 },
+                IncomingSegment::Mention { user_id: 10000 },
+            ],
+            ..Default::default()
+        };
+        assert!(message.mentions_user(10000));
+        assert!(!message.mentions_user(30000));
+    }
 }
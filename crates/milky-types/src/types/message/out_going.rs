@@ -1,9 +1,12 @@
 //! 定义了用于发送消息的各类数据结构，包括消息段和特定的消息格式（如合并转发）
 
+use crate::types::common::{FileUri, MessageScene};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 /// 代表一条待发送的合并转发消息中的单条消息内容
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct OutgoingForwardMessage {
     /// 发送者QQ号
     pub user_id: i64,
@@ -15,6 +18,7 @@ pub struct OutgoingForwardMessage {
 
 /// 枚举构成待发送消息内容的各种可能的消息段类型
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "data")]
 pub enum OutgoingSegment {
     /// 文本消息段
@@ -52,10 +56,168 @@ pub enum OutgoingSegment {
     /// 合并转发消息段
     #[serde(rename = "forward")]
     Forward(ForwardData),
+
+    /// 音乐分享消息段
+    #[serde(rename = "music")]
+    MusicShare(MusicShareData),
+
+    /// 位置分享消息段
+    #[serde(rename = "location")]
+    Location(LocationData),
+
+    /// 推荐好友或群组的名片分享消息段
+    #[serde(rename = "contact")]
+    ContactShare(ContactShareData),
+}
+
+impl OutgoingSegment {
+    /// 创建文本消息段
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(TextData { text: text.into() })
+    }
+
+    /// 创建提及（@）某人的消息段
+    pub fn at(user_id: i64) -> Self {
+        Self::Mention(MentionData { user_id })
+    }
+
+    /// 创建提及（@）全体成员的消息段
+    pub fn at_all() -> Self {
+        Self::MentionAll(MentionAllData)
+    }
+
+    /// 创建QQ表情消息段
+    ///
+    /// # 参数
+    /// * `face`: 表情ID，或 [`Face`](crate::types::message::face::Face) 中的常量
+    pub fn face(face: impl Into<FaceData>) -> Self {
+        Self::Face(face.into())
+    }
+
+    /// 创建回复消息段
+    pub fn reply(message_seq: i64) -> Self {
+        Self::Reply(ReplyData { message_seq })
+    }
+
+    /// 创建普通图片消息段
+    ///
+    /// # 参数
+    /// * `uri`: 图片文件的 URI，详见 [`FileUri`]
+    pub fn image(uri: impl Into<FileUri>) -> Self {
+        Self::Image(ImageData {
+            uri: uri.into(),
+            summary: None,
+            sub_type: "normal".to_string(),
+        })
+    }
+
+    /// 以本地文件创建普通图片消息段
+    pub fn image_from_path(path: impl AsRef<Path>) -> Self {
+        Self::image(path.as_ref())
+    }
+
+    /// 以图片内容创建普通图片消息段，发送时以 Base64 编码
+    pub fn image_from_bytes(data: &[u8]) -> Self {
+        Self::image(data.to_vec())
+    }
+
+    /// 创建语音消息段
+    ///
+    /// # 参数
+    /// * `uri`: 语音文件的 URI，详见 [`FileUri`]
+    pub fn record(uri: impl Into<FileUri>) -> Self {
+        Self::Record(RecordData { uri: uri.into() })
+    }
+
+    /// 以本地文件创建语音消息段
+    pub fn record_from_path(path: impl AsRef<Path>) -> Self {
+        Self::record(path.as_ref())
+    }
+
+    /// 以语音内容创建语音消息段，发送时以 Base64 编码
+    pub fn record_from_bytes(data: &[u8]) -> Self {
+        Self::record(data.to_vec())
+    }
+
+    /// 创建不带封面的视频消息段
+    ///
+    /// # 参数
+    /// * `uri`: 视频文件的 URI，详见 [`FileUri`]
+    pub fn video(uri: impl Into<FileUri>) -> Self {
+        Self::Video(VideoData {
+            uri: uri.into(),
+            thumb_uri: None,
+        })
+    }
+
+    /// 以本地文件创建不带封面的视频消息段
+    pub fn video_from_path(path: impl AsRef<Path>) -> Self {
+        Self::video(path.as_ref())
+    }
+
+    /// 创建音乐分享消息段
+    ///
+    /// # 参数
+    /// * `platform`: 音乐平台
+    /// * `title`: 歌曲标题
+    /// * `url`: 点击卡片后跳转的链接
+    /// * `audio_url`: 音频文件的链接
+    pub fn music_share(
+        platform: MusicPlatform,
+        title: impl Into<String>,
+        url: impl Into<String>,
+        audio_url: impl Into<String>,
+    ) -> Self {
+        Self::MusicShare(MusicShareData {
+            platform,
+            title: title.into(),
+            url: url.into(),
+            audio_url: audio_url.into(),
+            cover_url: None,
+        })
+    }
+
+    /// 创建位置分享消息段
+    ///
+    /// # 参数
+    /// * `latitude`: 纬度
+    /// * `longitude`: 经度
+    /// * `title`: 位置的名称
+    /// * `address`: 位置的详细地址
+    pub fn location(
+        latitude: f64,
+        longitude: f64,
+        title: impl Into<String>,
+        address: impl Into<String>,
+    ) -> Self {
+        Self::Location(LocationData {
+            latitude,
+            longitude,
+            title: title.into(),
+            address: address.into(),
+        })
+    }
+
+    /// 创建推荐好友的名片分享消息段
+    pub fn friend_contact(user_id: i64) -> Self {
+        Self::ContactShare(ContactShareData {
+            scene: MessageScene::Friend,
+            peer_id: user_id,
+        })
+    }
+
+    /// 创建推荐群组的名片分享消息段
+    pub fn group_contact(group_id: i64) -> Self {
+        Self::ContactShare(ContactShareData {
+            scene: MessageScene::Group,
+            peer_id: group_id,
+        })
+    }
 }
 
 /// 待发送的文本消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TextData {
     /// 要发送的实际文本内容
     pub text: String,
@@ -63,6 +225,7 @@ pub struct TextData {
 
 /// 待发送的提及（@）某人的消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MentionData {
     /// 要提及的用户的QQ号
     pub user_id: i64,
@@ -70,31 +233,46 @@ pub struct MentionData {
 
 /// 待发送的提及（@）全体成员的消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MentionAllData;
 
 /// 待发送的QQ表情消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FaceData {
     /// QQ表情的内置ID
     pub face_id: String,
 }
 
+impl From<String> for FaceData {
+    fn from(face_id: String) -> Self {
+        Self { face_id }
+    }
+}
+
+impl From<&str> for FaceData {
+    fn from(face_id: &str) -> Self {
+        Self {
+            face_id: face_id.to_string(),
+        }
+    }
+}
+
 /// 待发送的回复消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ReplyData {
     /// 要回复（引用）的消息的序列号 (`message_seq`)
     pub message_seq: i64,
 }
 
 /// 待发送的图片消息段的具体数据
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ImageData {
     /// 图片文件的统一资源标识符 (URI)
-    /// 支持三种格式:
-    /// - `file:///path/to/image.jpg` (本地文件路径)
-    /// - `http://example.com/image.png` 或 `https://example.com/image.png` (网络URL)
-    /// - `base64://<BASE64编码的图片数据>` (Base64编码的图片内容)
-    pub uri: String,
+    /// 支持本地文件路径、网络URL与Base64编码的内容，详见 [`FileUri`]
+    pub uri: FileUri,
     /// 图片的预览文本（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
@@ -104,33 +282,134 @@ pub struct ImageData {
 }
 
 /// 待发送的语音消息段的具体数据
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RecordData {
     /// 语音文件的统一资源标识符 (URI)
-    /// 支持三种格式:
-    /// - `file:///path/to/image.jpg` (本地文件路径)
-    /// - `http://example.com/image.png` 或 `https://example.com/image.png` (网络URL)
-    /// - `base64://<BASE64编码的图片数据>` (Base64编码的图片内容)
-    pub uri: String,
+    /// 支持本地文件路径、网络URL与Base64编码的内容，详见 [`FileUri`]
+    pub uri: FileUri,
 }
 
 /// 待发送的视频消息段的具体数据
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct VideoData {
     /// 视频文件的统一资源标识符 (URI)
-    /// 支持三种格式:
-    /// - `file:///path/to/image.jpg` (本地文件路径)
-    /// - `http://example.com/image.png` 或 `https://example.com/image.png` (网络URL)
-    /// - `base64://<BASE64编码的图片数据>` (Base64编码的图片内容)
-    pub uri: String,
+    /// 支持本地文件路径、网络URL与Base64编码的内容，详见 [`FileUri`]
+    pub uri: FileUri,
     /// 视频封面图片的URI（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub thumb_uri: Option<String>,
+    pub thumb_uri: Option<FileUri>,
 }
 
 /// 待发送的（已存在的）合并转发消息段的具体数据
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ForwardData {
     /// 合并转发消息段
     pub messages: Vec<OutgoingForwardMessage>,
 }
+
+/// 音乐分享卡片所属的平台
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MusicPlatform {
+    /// QQ音乐
+    #[default]
+    Qq,
+    /// 网易云音乐
+    Netease,
+    /// 酷狗音乐
+    Kugou,
+    /// 酷我音乐
+    Kuwo,
+    /// 自定义平台，卡片内容完全由各字段决定
+    Custom,
+}
+
+/// 待发送的音乐分享消息段的具体数据
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MusicShareData {
+    /// 音乐平台
+    pub platform: MusicPlatform,
+    /// 歌曲标题
+    pub title: String,
+    /// 点击卡片后跳转的链接
+    pub url: String,
+    /// 音频文件的链接
+    pub audio_url: String,
+    /// 封面图片的链接（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_url: Option<String>,
+}
+
+/// 待发送的位置分享消息段的具体数据
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LocationData {
+    /// 纬度
+    pub latitude: f64,
+    /// 经度
+    pub longitude: f64,
+    /// 位置的名称
+    pub title: String,
+    /// 位置的详细地址
+    pub address: String,
+}
+
+/// 待发送的名片分享消息段的具体数据
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ContactShareData {
+    /// 名片的类型，`Friend` 为好友名片，`Group` 为群名片
+    pub scene: MessageScene,
+    /// 好友的QQ号或群号
+    pub peer_id: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_segment_constructors() {
+        assert_eq!(
+            serde_json::to_value(OutgoingSegment::text("你好")).unwrap(),
+            json!({"type": "text", "data": {"text": "你好"}})
+        );
+        assert_eq!(
+            serde_json::to_value(OutgoingSegment::at(10000)).unwrap(),
+            json!({"type": "mention", "data": {"user_id": 10000}})
+        );
+        assert_eq!(
+            serde_json::to_value(OutgoingSegment::group_contact(123456)).unwrap(),
+            json!({"type": "contact", "data": {"scene": "group", "peer_id": 123456}})
+        );
+        assert_eq!(
+            serde_json::to_value(OutgoingSegment::record_from_bytes(b"amr")).unwrap(),
+            json!({"type": "record", "data": {"uri": "base64://YW1y"}})
+        );
+        assert!(matches!(
+            OutgoingSegment::image_from_path("/tmp/a.png"),
+            OutgoingSegment::Image(ImageData { uri: FileUri::Path(_), sub_type, .. }) if sub_type == "normal"
+        ));
+        assert_eq!(
+            serde_json::to_value(OutgoingSegment::music_share(
+                MusicPlatform::Netease,
+                "晴天",
+                "https://music.163.com/song?id=1",
+                "https://music.163.com/song/media/outer/url?id=1",
+            ))
+            .unwrap(),
+            json!({"type": "music", "data": {
+                "platform": "netease",
+                "title": "晴天",
+                "url": "https://music.163.com/song?id=1",
+                "audio_url": "https://music.163.com/song/media/outer/url?id=1",
+            }})
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! 群消息的表情回应
+//!
+//! 协议中表情回应以字符串形式的ID表示，QQ 系统表情使用其表情ID，Emoji 使用其 Unicode 码点的十进制值。
+//! [`Reaction`] 为常用的表情回应提供了具名的变体，其余的ID保存在 [`Reaction::Custom`] 中。
+//!
+//! ```
+//! use milky_types::message::reaction::Reaction;
+//!
+//! assert_eq!(Reaction::ThumbsUp.id(), "76");
+//! assert_eq!(Reaction::from_id("128077"), Reaction::ThumbsUpEmoji);
+//! assert_eq!(Reaction::from_id("424"), Reaction::Custom("424".to_string()));
+//! ```
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::types::message::face::Face;
+
+/// 系统表情的ID远小于该值，不小于该值的ID视为 Emoji 的码点
+const EMOJI_MIN_CODE_POINT: u32 = 0x2000;
+
+/// 群消息的表情回应
+///
+/// 比较与哈希都基于 [`id`](Reaction::id)，ID 相同的 [`Reaction::Custom`] 与具名变体视为相等。
+#[derive(Debug, Clone)]
+pub enum Reaction {
+    /// 赞（系统表情 76）
+    ThumbsUp,
+    /// 爱心（系统表情 66）
+    Heart,
+    /// 玫瑰（系统表情 63）
+    Rose,
+    /// 鼓掌（系统表情 99）
+    Applause,
+    /// 点赞（系统表情 201）
+    Like,
+    /// 笑哭（系统表情 182）
+    LaughCry,
+    /// doge（系统表情 179）
+    Doge,
+    /// 比心（系统表情 319）
+    FingerHeart,
+    /// 庆祝（系统表情 320）
+    Celebrate,
+    /// 👍（Emoji 128077）
+    ThumbsUpEmoji,
+    /// ❤（Emoji 10084）
+    HeartEmoji,
+    /// 😂（Emoji 128514）
+    JoyEmoji,
+    /// 🎉（Emoji 127881）
+    PartyEmoji,
+    /// 🔥（Emoji 128293）
+    FireEmoji,
+    /// 其他表情回应，保存原始的ID
+    Custom(String),
+}
+
+/// 所有具名的表情回应
+const NAMED: [Reaction; 14] = [
+    Reaction::ThumbsUp,
+    Reaction::Heart,
+    Reaction::Rose,
+    Reaction::Applause,
+    Reaction::Like,
+    Reaction::LaughCry,
+    Reaction::Doge,
+    Reaction::FingerHeart,
+    Reaction::Celebrate,
+    Reaction::ThumbsUpEmoji,
+    Reaction::HeartEmoji,
+    Reaction::JoyEmoji,
+    Reaction::PartyEmoji,
+    Reaction::FireEmoji,
+];
+
+impl Reaction {
+    /// 根据ID创建表情回应，未收录的ID保存为 [`Reaction::Custom`]
+    pub fn from_id(id: &str) -> Self {
+        NAMED
+            .iter()
+            .find(|reaction| reaction.id() == id)
+            .cloned()
+            .unwrap_or_else(|| Self::Custom(id.to_string()))
+    }
+
+    /// 表情回应的ID
+    pub fn id(&self) -> &str {
+        match self {
+            Self::ThumbsUp => Face::THUMBS_UP.id(),
+            Self::Heart => Face::HEART.id(),
+            Self::Rose => Face::ROSE.id(),
+            Self::Applause => Face::APPLAUSE.id(),
+            Self::Like => Face::LIKE.id(),
+            Self::LaughCry => Face::LAUGH_CRY.id(),
+            Self::Doge => Face::DOGE.id(),
+            Self::FingerHeart => Face::FINGER_HEART.id(),
+            Self::Celebrate => Face::CELEBRATE.id(),
+            Self::ThumbsUpEmoji => "128077",
+            Self::HeartEmoji => "10084",
+            Self::JoyEmoji => "128514",
+            Self::PartyEmoji => "127881",
+            Self::FireEmoji => "128293",
+            Self::Custom(id) => id,
+        }
+    }
+
+    /// 获取表情回应对应的 QQ 系统表情
+    ///
+    /// # 返回
+    /// 表情回应为 Emoji 或未收录的系统表情时返回 `None`
+    pub fn face(&self) -> Option<Face> {
+        Face::from_id(self.id())
+    }
+}
+
+/// 系统表情以 `[名称]` 的形式展示，Emoji 展示为对应的字符，其余展示为ID
+impl fmt::Display for Reaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(face) = self.face() {
+            return face.fmt(f);
+        }
+        match self
+            .id()
+            .parse::<u32>()
+            .ok()
+            .filter(|code| *code >= EMOJI_MIN_CODE_POINT)
+            .and_then(char::from_u32)
+        {
+            Some(emoji) => write!(f, "{emoji}"),
+            None => f.write_str(self.id()),
+        }
+    }
+}
+
+impl PartialEq for Reaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Eq for Reaction {}
+
+impl Hash for Reaction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+impl From<Face> for Reaction {
+    fn from(face: Face) -> Self {
+        Self::from_id(face.id())
+    }
+}
+
+impl From<&str> for Reaction {
+    fn from(id: &str) -> Self {
+        Self::from_id(id)
+    }
+}
+
+impl From<String> for Reaction {
+    fn from(id: String) -> Self {
+        Self::from_id(&id)
+    }
+}
+
+impl Serialize for Reaction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Reaction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let id = String::deserialize(deserializer)?;
+        Ok(Self::from(id))
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Reaction {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Reaction".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "表情回应的ID，系统表情为表情ID，Emoji 为 Unicode 码点的十进制值",
+            "type": "string",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reaction_round_trip() {
+        for id in ["76", "128077", "424"] {
+            let reaction: Reaction = serde_json::from_value(serde_json::json!(id)).unwrap();
+            assert_eq!(serde_json::to_value(&reaction).unwrap(), id);
+        }
+        assert_eq!(Reaction::from(Face::DOGE), Reaction::Doge);
+        assert_eq!(Reaction::ThumbsUp.to_string(), "[赞]");
+        assert_eq!(Reaction::ThumbsUpEmoji.to_string(), "👍");
+        assert_eq!(Reaction::Custom("424".to_string()).to_string(), "424");
+    }
+
+    #[test]
+    fn test_reaction_eq_by_id() {
+        use std::collections::HashSet;
+
+        let custom = Reaction::Custom("76".to_string());
+        assert_eq!(custom, Reaction::ThumbsUp);
+        assert_ne!(Reaction::ThumbsUp, Reaction::ThumbsUpEmoji);
+
+        let set: HashSet<_> = [custom, Reaction::ThumbsUp].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -1,5 +1,11 @@
+pub mod borrowed;
 pub mod common;
 pub mod event;
 pub mod friend;
 pub mod group;
 pub mod message;
+#[cfg(feature = "schemars")]
+pub mod schema;
+mod tagged;
+#[cfg(feature = "chrono")]
+pub mod time;
@@ -0,0 +1,99 @@
+//! 导出协议类型的 JSON Schema
+//!
+//! 启用 `schemars` 特性后，事件、消息段与各类数据结构都实现了 [`JsonSchema`]。
+//! 其他语言的集成方可以将本模块导出的 Schema 用于校验收发的数据，与 SDK 对协议的理解保持一致。
+//!
+//! 协议端新增、尚未定义的事件与消息段类型在 SDK 中会被解析为 `Unknown`，Schema 中只包含已定义的类型。
+//!
+//! ```no_run
+//! milky_types::schema::dump_schemas("schemas").unwrap();
+//! ```
+
+use crate::types::event::Event;
+use crate::types::friend::{Friend, FriendCategory, FriendRequest};
+use crate::types::group::{
+    Group, GroupAnnouncement, GroupEssenceMessage, GroupFile, GroupFolder, GroupMember,
+    GroupNotification,
+};
+use crate::types::message::in_coming::{IncomingForwardMessage, IncomingMessage, IncomingSegment};
+use crate::types::message::out_going::{OutgoingForwardMessage, OutgoingSegment};
+
+use schemars::{Schema, schema_for};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// 将类型的 Schema 以类型名称为键插入到映射中
+macro_rules! insert_schemas {
+    ($schemas:expr, $($ty:ty),* $(,)?) => {
+        $($schemas.insert(stringify!($ty), schema_for!($ty));)*
+    };
+}
+
+/// 获取协议中各顶层类型的 JSON Schema
+///
+/// # 返回
+/// 以类型名称（例如 `Event`、`OutgoingSegment`）为键的映射，每个 Schema 都是独立完整的，可以单独使用
+pub fn schemas() -> BTreeMap<&'static str, Schema> {
+    let mut schemas = BTreeMap::new();
+    insert_schemas!(
+        schemas,
+        Event,
+        IncomingMessage,
+        IncomingSegment,
+        IncomingForwardMessage,
+        OutgoingSegment,
+        OutgoingForwardMessage,
+        Friend,
+        FriendCategory,
+        FriendRequest,
+        Group,
+        GroupMember,
+        GroupAnnouncement,
+        GroupEssenceMessage,
+        GroupFile,
+        GroupFolder,
+        GroupNotification,
+    );
+    schemas
+}
+
+/// 将 [`schemas`] 中的所有 Schema 写入目录
+///
+/// # 参数
+/// * `dir`: 输出目录，不存在时会自动创建；每个类型写入一个 `<类型名称>.json` 文件
+pub fn dump_schemas(dir: impl AsRef<Path>) -> io::Result<()> {
+    write_schemas(dir, &schemas())
+}
+
+/// 将一组 Schema 写入目录，每个 Schema 写入一个 `<名称>.json` 文件
+///
+/// # 参数
+/// * `dir`: 输出目录，不存在时会自动创建
+/// * `schemas`: 以名称为键的 Schema
+pub fn write_schemas(dir: impl AsRef<Path>, schemas: &BTreeMap<&str, Schema>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    for (name, schema) in schemas {
+        let json = serde_json::to_string_pretty(schema)?;
+        std::fs::write(dir.join(format!("{name}.json")), json + "\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_schema() {
+        let schemas = schemas();
+        let event = serde_json::to_string(&schemas["Event"]).unwrap();
+        assert!(event.contains("message_receive"));
+        assert!(event.contains("group_message_reaction"));
+
+        let segment = serde_json::to_value(&schemas["OutgoingSegment"]).unwrap();
+        assert_eq!(segment["$defs"]["FileUri"]["type"], json!("string"));
+    }
+}
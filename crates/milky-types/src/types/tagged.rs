@@ -0,0 +1,339 @@
+//! 带有未知类型兜底的相邻标签（adjacently tagged）枚举的反序列化
+//!
+//! [`EventKind`](crate::EventKind) 与 [`IncomingSegment`](crate::message::in_coming::IncomingSegment)
+//! 以 `{"<标签>": ..., "<内容>": ...}` 的形式表示，并以 `#[serde(remote = "Self")]` 生成对应的派生实现。
+//! 本模块在一次遍历中读取标签，已知的类型直接交给派生实现解析内容，未知的类型保留原始数据，
+//! 不需要将内容先解析为 [`Value`](serde_json::Value) 再重新构造。
+//!
+//! 判断类型是否已知时，以只包含标签的输入调用派生实现，并通过自定义错误类型的
+//! [`unknown_variant`](de::Error::unknown_variant) 识别未定义的类型，因此无需另外维护类型名称的列表。
+
+use serde::de::value::{BorrowedStrDeserializer, StrDeserializer};
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// 带有未知类型兜底的相邻标签枚举
+pub(crate) trait AdjacentlyTagged<'de>: Sized {
+    /// 类型名称
+    const NAME: &'static str;
+    /// 标签字段名
+    const TAG: &'static str;
+    /// 内容字段名
+    const CONTENT: &'static str;
+
+    /// 未知类型保留的标签
+    type Tag: Deserialize<'de> + AsRef<str>;
+    /// 未知类型，或内容先于标签出现时保留的原始数据
+    type Content: Deserialize<'de>;
+
+    /// 以派生实现解析已知的类型
+    fn deserialize_known<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+
+    /// 从保留的原始数据解析已知的类型，一般直接调用 [`deserialize_buffered`]
+    fn deserialize_content(tag: &str, content: Option<Self::Content>) -> serde_json::Result<Self>;
+
+    /// 构造未知的类型，缺少内容字段时 `content` 为 `None`
+    fn unknown(tag: Self::Tag, content: Option<Self::Content>) -> Self;
+}
+
+/// 反序列化带有未知类型兜底的相邻标签枚举
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: AdjacentlyTagged<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct(T::NAME, &[T::TAG, T::CONTENT], TaggedVisitor(PhantomData))
+}
+
+/// 判断标签是否对应已定义的类型
+fn is_known<'de, T: AdjacentlyTagged<'de>>(tag: &str) -> bool {
+    !matches!(
+        T::deserialize_known(ProbeDeserializer::<T> {
+            tag,
+            marker: PhantomData,
+        }),
+        Err(ProbeError::UnknownVariant)
+    )
+}
+
+/// 读取标签与内容的访问器
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: AdjacentlyTagged<'de>> Visitor<'de> for TaggedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} 与 {} 字段", T::TAG, T::CONTENT)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<T, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut tag: Option<T::Tag> = None;
+        let mut content: Option<T::Content> = None;
+        let mut known: Option<T> = None;
+        while let Some(field) = map.next_key_seed(FieldSeed::<T>(PhantomData))? {
+            match field {
+                Field::Tag => tag = Some(map.next_value()?),
+                Field::Content => match &tag {
+                    Some(tag) if is_known::<T>(tag.as_ref()) => {
+                        known = Some(map.next_value_seed(ContentSeed {
+                            tag: tag.as_ref(),
+                            marker: PhantomData,
+                        })?);
+                    }
+                    // 未知的类型，或内容出现在标签之前时，先保留原始数据
+                    _ => content = Some(map.next_value()?),
+                },
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if let Some(known) = known {
+            return Ok(known);
+        }
+        let tag = tag.ok_or_else(|| de::Error::missing_field(T::TAG))?;
+        if !is_known::<T>(tag.as_ref()) {
+            return Ok(T::unknown(tag, content));
+        }
+        T::deserialize_content(tag.as_ref(), content).map_err(de::Error::custom)
+    }
+}
+
+/// 以标签与先前保留的内容解析已知的类型
+///
+/// # 参数
+/// * `tag`: 标签
+/// * `content`: 保留的内容，缺少内容字段时为 `None`
+pub(crate) fn deserialize_buffered<'de, T, C>(tag: &str, content: Option<C>) -> Result<T, C::Error>
+where
+    T: AdjacentlyTagged<'de>,
+    C: Deserializer<'de>,
+{
+    T::deserialize_known(TaggedDeserializer::<T, C> {
+        tag,
+        content,
+        marker: PhantomData,
+    })
+}
+
+/// 字段名
+enum Field {
+    Tag,
+    Content,
+    Other,
+}
+
+/// 识别字段名
+struct FieldSeed<T>(PhantomData<T>);
+
+impl<'de, T: AdjacentlyTagged<'de>> DeserializeSeed<'de> for FieldSeed<T> {
+    type Value = Field;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Field, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de, T: AdjacentlyTagged<'de>> Visitor<'de> for FieldSeed<T> {
+    type Value = Field;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("字段名")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Field, E> {
+        Ok(if v == T::TAG {
+            Field::Tag
+        } else if v == T::CONTENT {
+            Field::Content
+        } else {
+            Field::Other
+        })
+    }
+}
+
+/// 以派生实现直接从输入中解析已知类型的内容
+struct ContentSeed<'a, T> {
+    tag: &'a str,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: AdjacentlyTagged<'de>> DeserializeSeed<'de> for ContentSeed<'_, T> {
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_known(TaggedDeserializer::<T, D> {
+            tag: self.tag,
+            content: Some(deserializer),
+            marker: PhantomData,
+        })
+    }
+}
+
+/// 向派生实现依次提供标签与内容的反序列化器
+struct TaggedDeserializer<'a, T, D> {
+    tag: &'a str,
+    content: Option<D>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: AdjacentlyTagged<'de>, D: Deserializer<'de>> Deserializer<'de>
+    for TaggedDeserializer<'_, T, D>
+{
+    type Error = D::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(TaggedMap {
+            tag: Some(self.tag),
+            content: self.content,
+            marker: self.marker,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// 先提供标签、再提供内容的映射
+struct TaggedMap<'a, T, D> {
+    tag: Option<&'a str>,
+    content: Option<D>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: AdjacentlyTagged<'de>, D: Deserializer<'de>> MapAccess<'de> for TaggedMap<'_, T, D> {
+    type Error = D::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, D::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let key = if self.tag.is_some() {
+            T::TAG
+        } else if self.content.is_some() {
+            T::CONTENT
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, D::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match (self.tag.take(), self.content.take()) {
+            (Some(tag), content) => {
+                self.content = content;
+                seed.deserialize(StrDeserializer::new(tag))
+            }
+            (None, Some(content)) => seed.deserialize(content),
+            (None, None) => Err(de::Error::custom("映射中没有更多的值")),
+        }
+    }
+}
+
+/// 判断类型是否已知时使用的错误
+#[derive(Debug)]
+enum ProbeError {
+    /// 标签不对应任何已定义的类型
+    UnknownVariant,
+    /// 其他错误，例如缺少内容
+    Other,
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProbeError::UnknownVariant => f.write_str("未定义的类型"),
+            ProbeError::Other => f.write_str("解析失败"),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<M: fmt::Display>(_msg: M) -> Self {
+        ProbeError::Other
+    }
+
+    fn unknown_variant(_variant: &str, _expected: &'static [&'static str]) -> Self {
+        ProbeError::UnknownVariant
+    }
+}
+
+/// 只提供标签的反序列化器，用于判断类型是否已知
+struct ProbeDeserializer<'a, T> {
+    tag: &'a str,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: AdjacentlyTagged<'de>> Deserializer<'de> for ProbeDeserializer<'_, T> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, ProbeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(ProbeMap {
+            tag: Some(self.tag),
+            marker: self.marker,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// 只包含标签的映射，派生实现在读取标签时即可判断类型是否已知
+struct ProbeMap<'a, T> {
+    tag: Option<&'a str>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: AdjacentlyTagged<'de>> MapAccess<'de> for ProbeMap<'_, T> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, ProbeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.tag.is_none() {
+            return Ok(None);
+        }
+        seed.deserialize(BorrowedStrDeserializer::new(T::TAG))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, ProbeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let tag = self.tag.take().ok_or(ProbeError::Other)?;
+        seed.deserialize(StrDeserializer::new(tag))
+    }
+}
@@ -0,0 +1,128 @@
+//! 以 [`chrono`] 的 `DateTime<Utc>` 读取各类型中的时间戳
+//!
+//! 协议中的时间均为 Unix 时间戳（秒），各类型仍以 `i64` 字段保存原始值；
+//! 启用 `chrono` 特性后，可以通过对应的 `*_utc` 方法得到 `DateTime<Utc>`，避免误把秒当作毫秒等问题。
+//! 时间戳超出 `DateTime` 可表示的范围时返回 `None`。
+
+use crate::types::event::Event;
+use crate::types::friend::FriendRequest;
+use crate::types::group::{
+    GroupAnnouncement, GroupEssenceMessage, GroupFile, GroupFolder, GroupMember,
+};
+use crate::types::message::in_coming::{IncomingForwardMessage, IncomingMessage};
+
+use chrono::{DateTime, Utc};
+
+/// 将 Unix 时间戳（秒）转换为 `DateTime<Utc>`
+///
+/// # 返回
+/// 时间戳超出可表示的范围时返回 `None`
+pub fn to_utc(timestamp: i64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(timestamp, 0)
+}
+
+impl Event {
+    /// 事件发生的时间
+    pub fn time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.time)
+    }
+}
+
+impl IncomingMessage {
+    /// 消息发送的时间
+    pub fn time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.time)
+    }
+}
+
+impl IncomingForwardMessage {
+    /// 转发消息的发送时间
+    pub fn time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.time)
+    }
+}
+
+impl FriendRequest {
+    /// 请求发起的时间
+    pub fn time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.time)
+    }
+}
+
+impl GroupMember {
+    /// 加入群组的时间
+    pub fn join_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.join_time)
+    }
+
+    /// 最后发言的时间
+    pub fn last_sent_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.last_sent_time)
+    }
+
+    /// 禁言结束的时间，未被禁言时返回 `None`
+    pub fn shut_up_end_time_utc(&self) -> Option<DateTime<Utc>> {
+        self.shut_up_end_time.and_then(to_utc)
+    }
+}
+
+impl GroupEssenceMessage {
+    /// 消息发送的时间
+    pub fn message_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.message_time)
+    }
+
+    /// 消息被设置为精华的时间
+    pub fn operation_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.operation_time)
+    }
+}
+
+impl GroupAnnouncement {
+    /// 公告发布的时间
+    pub fn time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.time)
+    }
+}
+
+impl GroupFile {
+    /// 文件上传的时间
+    pub fn uploaded_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.uploaded_time)
+    }
+
+    /// 文件过期的时间
+    pub fn expire_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.expire_time)
+    }
+}
+
+impl GroupFolder {
+    /// 文件夹创建的时间
+    pub fn created_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.created_time)
+    }
+
+    /// 文件夹最后修改的时间
+    pub fn last_modified_time_utc(&self) -> Option<DateTime<Utc>> {
+        to_utc(self.last_modified_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_utc() {
+        let file = GroupFile {
+            uploaded_time: 1_630_483_200,
+            ..Default::default()
+        };
+        assert_eq!(
+            file.uploaded_time_utc().unwrap().to_rfc3339(),
+            "2021-09-01T08:00:00+00:00"
+        );
+        assert_eq!(to_utc(i64::MAX), None);
+    }
+}